@@ -0,0 +1,328 @@
+use std::{fmt, sync::Mutex, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    AsyncSmtpTransport, AsyncTransport as _, Message, Tokio1Executor,
+};
+use plexi_core::SignatureResponse;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{cmd::APP_USER_AGENT, exit::usage};
+
+const LAST_ALERT_LOCK_POISONED: &str = "last alert lock poisoned";
+
+const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    Signature,
+    Proof,
+    Stalled,
+}
+
+impl FailureKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Signature => "signature",
+            Self::Proof => "proof",
+            Self::Stalled => "stalled",
+        }
+    }
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Signature => "signature verification failed",
+            Self::Proof => "proof verification failed",
+            Self::Stalled => "namespace stalled",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AlertEvent {
+    namespace: String,
+    epoch: u64,
+    kind: FailureKind,
+    message: String,
+    digest: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_digest: Option<String>,
+    timestamp: u64,
+}
+
+impl AlertEvent {
+    pub fn new(signature: &SignatureResponse, kind: FailureKind, message: &str) -> Self {
+        Self {
+            namespace: signature.namespace().to_string(),
+            epoch: signature.epoch().into(),
+            kind,
+            message: message.to_string(),
+            digest: hex::encode(signature.digest()),
+            previous_digest: signature.previous_digest().map(hex::encode),
+            timestamp: signature.timestamp(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "plexi: {namespace} epoch {epoch}, {kind}: {message}",
+            namespace = self.namespace,
+            epoch = self.epoch,
+            kind = self.kind,
+            message = self.message
+        )
+    }
+
+    fn key(&self) -> (String, u64, FailureKind) {
+        (self.namespace.clone(), self.epoch, self.kind)
+    }
+}
+
+#[async_trait]
+pub trait Notifier: Send + Sync + fmt::Debug {
+    async fn notify(&self, event: &AlertEvent) -> Result<()>;
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum NotifierConfig {
+    Webhook {
+        url: Url,
+    },
+    Slack {
+        webhook_url: Url,
+    },
+    Pagerduty {
+        routing_key: String,
+    },
+    Email {
+        smtp_url: String,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+impl NotifierConfig {
+    pub fn notifier(&self) -> Result<Box<dyn Notifier>> {
+        Ok(match self {
+            Self::Webhook { url } => Box::new(Webhook::new(url.clone())?),
+            Self::Slack { webhook_url } => Box::new(Slack::new(webhook_url.clone())?),
+            Self::Pagerduty { routing_key } => Box::new(PagerDuty::new(routing_key)?),
+            Self::Email { smtp_url, from, to } => Box::new(Email::new(smtp_url, from, to)?),
+        })
+    }
+}
+
+fn http_client() -> Result<Client> {
+    Client::builder()
+        .user_agent(APP_USER_AGENT)
+        .timeout(Duration::from_secs(10))
+        .build()
+        .context("building notifier client")
+}
+
+#[derive(Debug, Default)]
+pub struct Alerts {
+    notifiers: Vec<(Box<dyn Notifier>, Mutex<Option<(String, u64, FailureKind)>>)>,
+}
+
+impl Alerts {
+    pub fn new(notifiers: Vec<Box<dyn Notifier>>) -> Self {
+        Self {
+            notifiers: notifiers
+                .into_iter()
+                .map(|notifier| (notifier, Mutex::new(None)))
+                .collect(),
+        }
+    }
+
+    pub async fn notify(&self, event: &AlertEvent) {
+        let key = event.key();
+        for (notifier, last) in &self.notifiers {
+            if last.lock().expect(LAST_ALERT_LOCK_POISONED).as_ref() == Some(&key) {
+                continue;
+            }
+            match notifier.notify(event).await {
+                Ok(()) => *last.lock().expect(LAST_ALERT_LOCK_POISONED) = Some(key.clone()),
+                Err(e) => tracing::error!("cannot notify {notifier:?}: {e:#}"),
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Webhook {
+    client: Client,
+    url: Url,
+}
+
+impl Webhook {
+    pub fn new(url: Url) -> Result<Self> {
+        Ok(Self {
+            client: http_client()?,
+            url,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for Webhook {
+    async fn notify(&self, event: &AlertEvent) -> Result<()> {
+        self.client
+            .post(self.url.clone())
+            .json(event)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+pub struct Slack {
+    client: Client,
+    webhook_url: Url,
+}
+
+impl fmt::Debug for Slack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the webhook URL is a secret
+        f.debug_struct("Slack").finish_non_exhaustive()
+    }
+}
+
+impl Slack {
+    pub fn new(webhook_url: Url) -> Result<Self> {
+        Ok(Self {
+            client: http_client()?,
+            webhook_url,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for Slack {
+    async fn notify(&self, event: &AlertEvent) -> Result<()> {
+        self.client
+            .post(self.webhook_url.clone())
+            .json(&serde_json::json!({ "text": event.summary() }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+pub struct PagerDuty {
+    client: Client,
+    routing_key: String,
+}
+
+impl fmt::Debug for PagerDuty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PagerDuty").finish_non_exhaustive()
+    }
+}
+
+impl PagerDuty {
+    pub fn new(routing_key: &str) -> Result<Self> {
+        Ok(Self {
+            client: http_client()?,
+            routing_key: routing_key.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for PagerDuty {
+    async fn notify(&self, event: &AlertEvent) -> Result<()> {
+        // the same failure updates its incident rather than opening another one
+        let dedup_key = format!(
+            "plexi/{namespace}/{epoch}/{kind:?}",
+            namespace = event.namespace,
+            epoch = event.epoch,
+            kind = event.kind
+        );
+        let body = serde_json::json!({
+            "routing_key": self.routing_key,
+            "event_action": "trigger",
+            "dedup_key": dedup_key,
+            "payload": {
+                "summary": event.summary(),
+                "source": event.namespace,
+                "severity": "critical",
+                "custom_details": event,
+            },
+        });
+        self.client
+            .post(PAGERDUTY_EVENTS_URL)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+pub struct Email {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl fmt::Debug for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the SMTP URL may hold credentials
+        f.debug_struct("Email")
+            .field("from", &self.from.to_string())
+            .field(
+                "to",
+                &self.to.iter().map(Mailbox::to_string).collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl Email {
+    pub fn new(smtp_url: &str, from: &str, to: &[String]) -> Result<Self> {
+        if to.is_empty() {
+            return Err(usage(anyhow!("email notifier has no recipient")));
+        }
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(smtp_url)
+            .map_err(|e| usage(anyhow!("invalid SMTP URL: {e}")))?
+            .timeout(Some(Duration::from_secs(10)))
+            .build();
+        let mailbox = |address: &str| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|e| usage(anyhow!("invalid email address {address}: {e}")))
+        };
+        Ok(Self {
+            transport,
+            from: mailbox(from)?,
+            to: to.iter().map(|to| mailbox(to)).collect::<Result<_>>()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for Email {
+    async fn notify(&self, event: &AlertEvent) -> Result<()> {
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(event.summary())
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+        let message = message.body(serde_json::to_string_pretty(event)?)?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
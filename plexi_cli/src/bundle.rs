@@ -0,0 +1,80 @@
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use plexi_core::{auditor::KeyInfo, namespaces::NamespaceInfo, SignatureResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::exit::usage;
+
+const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Bundle {
+    version: u32,
+    signature: SignatureResponse,
+    previous_signature: Option<SignatureResponse>,
+    #[serde(default, with = "hex::serde", skip_serializing_if = "Vec::is_empty")]
+    proof: Vec<u8>,
+    keys: Vec<KeyInfo>,
+    namespace: NamespaceInfo,
+}
+
+impl Bundle {
+    pub fn new(
+        signature: SignatureResponse,
+        previous_signature: Option<SignatureResponse>,
+        proof: Option<Vec<u8>>,
+        keys: Vec<KeyInfo>,
+        namespace: NamespaceInfo,
+    ) -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            signature,
+            previous_signature,
+            proof: proof.unwrap_or_default(),
+            keys,
+            namespace,
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let bundle = fs::read_to_string(path)
+            .with_context(|| format!("reading {path}", path = path.display()))
+            .map_err(usage)?;
+        let bundle: Self = serde_json::from_str(&bundle)
+            .with_context(|| format!("parsing {path}", path = path.display()))
+            .map_err(usage)?;
+        if bundle.version != BUNDLE_VERSION {
+            return Err(usage(anyhow!(
+                "bundle version {version} is not supported",
+                version = bundle.version
+            )));
+        }
+        Ok(bundle)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("writing {path}", path = path.display()))
+    }
+
+    pub fn signature(&self) -> &SignatureResponse {
+        &self.signature
+    }
+
+    pub fn previous_signature(&self) -> Option<&SignatureResponse> {
+        self.previous_signature.as_ref()
+    }
+
+    pub fn proof(&self) -> Option<&[u8]> {
+        (!self.proof.is_empty()).then_some(self.proof.as_slice())
+    }
+
+    pub fn keys(&self) -> &[KeyInfo] {
+        &self.keys
+    }
+
+    pub fn namespace(&self) -> &NamespaceInfo {
+        &self.namespace
+    }
+}
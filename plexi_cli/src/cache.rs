@@ -0,0 +1,88 @@
+use std::{
+    collections::BTreeSet,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use plexi_core::Epoch;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct VerifiedEpoch {
+    namespace: String,
+    epoch: u64,
+    #[serde(with = "hex::serde")]
+    digest: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+pub struct VerifiedCache {
+    path: Option<PathBuf>,
+    epochs: BTreeSet<VerifiedEpoch>,
+}
+
+impl VerifiedCache {
+    pub fn default_path() -> Option<PathBuf> {
+        let state_home = env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+            })?;
+        Some(state_home.join("plexi").join("verified-epochs.json"))
+    }
+
+    pub fn open_default() -> Self {
+        let Some(path) = Self::default_path() else {
+            return Self::default();
+        };
+        Self::open(&path).unwrap_or_else(|e| {
+            tracing::warn!("ignoring verified epoch cache: {e:#}");
+            Self::default()
+        })
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        let epochs = if path.exists() {
+            let epochs = fs::read_to_string(path)
+                .with_context(|| format!("reading {path}", path = path.display()))?;
+            serde_json::from_str(&epochs)
+                .with_context(|| format!("parsing {path}", path = path.display()))?
+        } else {
+            BTreeSet::new()
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            epochs,
+        })
+    }
+
+    pub fn contains(&self, namespace: &str, epoch: &Epoch, digest: &[u8]) -> bool {
+        self.epochs.contains(&VerifiedEpoch {
+            namespace: namespace.to_string(),
+            epoch: epoch.into(),
+            digest: digest.to_vec(),
+        })
+    }
+
+    pub fn insert(&mut self, namespace: &str, epoch: &Epoch, digest: &[u8]) -> Result<()> {
+        let inserted = self.epochs.insert(VerifiedEpoch {
+            namespace: namespace.to_string(),
+            epoch: epoch.into(),
+            digest: digest.to_vec(),
+        });
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !inserted {
+            return Ok(());
+        }
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).with_context(|| {
+                format!("creating {directory}", directory = directory.display())
+            })?;
+        }
+        fs::write(path, serde_json::to_string(&self.epochs)?)
+            .with_context(|| format!("writing {path}", path = path.display()))
+    }
+}
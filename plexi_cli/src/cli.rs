@@ -1,7 +1,10 @@
-use std::path::PathBuf;
+use std::{io::IsTerminal as _, path::PathBuf, time::Duration};
 
-use clap::{Parser, Subcommand};
-use plexi_core::Epoch;
+use clap::{Args, ColorChoice, CommandFactory as _, Parser, Subcommand, ValueEnum};
+use plexi_core::{
+    auditor::AkdConfig, namespaces::NamespaceStatus, Ciphersuite, Epoch, MigrationPolicy,
+};
+use reqwest::Url;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -9,6 +12,25 @@ use plexi_core::Epoch;
 pub struct Cli {
     #[clap(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity,
+    /// Profile of the configuration file providing option defaults. Defaults to its default_profile
+    #[arg(long, global = true, env = "PLEXI_PROFILE")]
+    pub profile: Option<String>,
+    /// Configuration file. Defaults to $XDG_CONFIG_HOME/plexi/config.toml or ~/.config/plexi/config.toml
+    #[arg(long, global = true, env = "PLEXI_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// When to color the output (auto, always, never). With auto, output is colored if NO_COLOR is not set and stdout is a terminal
+    #[arg(long, value_enum, global = true, default_value_t = ColorChoice::Auto, env = "PLEXI_COLOR")]
+    pub color: ColorChoice,
+    /// Format of the logs written to stderr (text, json). JSON writes one object per line, for log pipelines
+    #[arg(long, value_enum, global = true, default_value_t = LogFormat::Text, env = "PLEXI_LOG_FORMAT")]
+    pub log_format: LogFormat,
+    /// OTLP/HTTP endpoint to export verification traces to, for instance http://localhost:4318/v1/traces
+    #[cfg(feature = "otel")]
+    #[arg(long, global = true, env = "PLEXI_OTLP_ENDPOINT")]
+    pub otlp_endpoint: Option<String>,
+    /// File to write the output of the command to, in addition to stdout. With -q, the output only goes there
+    #[arg(long, global = true, env = "PLEXI_OUTPUT_FILE")]
+    pub output_file: Option<PathBuf>,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -18,60 +40,806 @@ pub enum Commands {
     /// Information about a given epoch. By default, it retrieves and validates its audit proof
     #[command(verbatim_doc_comment)]
     Audit {
-        /// URL of the auditor
+        /// URL of the auditor. Defaults to the one of the profile
         #[arg(short, long, env = "PLEXI_REMOTE_URL")]
-        remote_url: String,
-        /// Namespace ID
+        remote_url: Vec<String>,
+        /// Namespace ID. Defaults to the one of the profile
         #[arg(short, long, env = "PLEXI_NAMESPACE")]
-        namespace: String,
+        namespace: Option<String>,
         /// Ed25519 public key in hex format.
         #[arg(long, env = "PLEXI_VERIFYING_KEY")]
         verifying_key: Option<String>,
+        /// Signatures to require on epochs signed under two ciphersuites while their namespace migrates (either, both)
+        #[arg(long, default_value_t = MigrationPolicy::Either, env = "PLEXI_MIGRATION_POLICY")]
+        migration_policy: MigrationPolicy,
         /// Height of the epoch to verify. If not set, the latest epoch is verified.
-        #[arg(long)]
+        #[arg(long, conflicts_with = "from")]
         epoch: Option<Epoch>,
+        /// Digest the epoch has to have, in hex format, such as one obtained from the application vendor
+        #[arg(long, conflicts_with_all = ["from", "stdin"])]
+        expected_digest: Option<String>,
+        /// First epoch of a range to verify, with --to. Proofs are verified as a chain from one epoch to the next
+        #[arg(long, requires = "to")]
+        from: Option<Epoch>,
+        /// Last epoch of a range to verify, with --from
+        #[arg(long, requires = "from")]
+        to: Option<Epoch>,
+        /// Number of epochs of a range whose proof is downloaded and verified concurrently
+        #[arg(long, default_value_t = 4, env = "PLEXI_WORKERS")]
+        workers: usize,
+        /// Seconds an epoch of a range may be timestamped after the epoch before. Timestamps always have to be non-decreasing
+        #[arg(long, requires = "from", env = "PLEXI_MAX_TIMESTAMP_GAP")]
+        max_timestamp_gap: Option<u64>,
         /// Enable detailed output
         #[arg(short, long, default_value_t = false, group = "format")]
         long: bool,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, group = "format", env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
         /// Disable signature and proof validation
         #[arg(long, default_value_t = false, env = "PLEXI_VERIFICATION_DISABLED")]
         no_verify: bool,
+        /// Log directory to retrieve proofs from, overriding the one advertised by the namespace
+        #[arg(long, env = "PLEXI_LOG_DIRECTORY")]
+        log_directory: Option<String>,
+        /// Verify proofs again, even those that already verified on this machine
+        #[arg(long, default_value_t = false)]
+        force: bool,
+        /// Audit the namespaces read from stdin, one per line, instead of --namespace. Same as --namespace -
+        #[arg(long, default_value_t = false, conflicts_with_all = ["namespace", "from", "long"])]
+        stdin: bool,
+        /// Audit the latest epoch of every namespace of the auditor, and output a summary table
+        #[arg(long, default_value_t = false, conflicts_with_all = ["namespace", "stdin", "epoch", "expected_digest", "from", "long"])]
+        all_namespaces: bool,
+        /// Only audit namespaces with this status with --all-namespaces (online, initialization, disabled)
+        #[arg(long, requires = "all_namespaces")]
+        status: Option<NamespaceStatus>,
+        #[command(flatten)]
+        freshness: FreshnessArgs,
+        #[command(flatten)]
+        network: NetworkArgs,
     },
     /// List all namespaces
     #[command(verbatim_doc_comment)]
     Ls {
-        /// URL of the auditor
+        /// URL of the auditor. Defaults to the one of the profile
         #[arg(short, long, env = "PLEXI_REMOTE_URL")]
-        remote_url: String,
+        remote_url: Option<String>,
         /// Namespace ID
         #[arg(short, long, env = "PLEXI_NAMESPACE")]
         namespace: Option<String>,
+        /// Only list namespaces with this status (online, initialization, disabled)
+        #[arg(long, conflicts_with = "namespace")]
+        status: Option<NamespaceStatus>,
+        /// Only list namespaces whose name starts with this prefix
+        #[arg(long, conflicts_with = "namespace")]
+        prefix: Option<String>,
+        /// Only list namespaces using this ciphersuite (1: protobuf, 2: bincode, 3: protobuf v2)
+        #[arg(long, conflicts_with = "namespace")]
+        ciphersuite: Option<Ciphersuite>,
         /// Enable detailed output
         #[arg(short, long, default_value_t = false, group = "format")]
         long: bool,
+        /// Output format (text, json, yaml, table)
+        #[arg(short, long, value_enum, default_value_t = ListFormat::Text, group = "format", env = "PLEXI_OUTPUT")]
+        output: ListFormat,
+        #[command(flatten)]
+        network: NetworkArgs,
     },
     #[command(verbatim_doc_comment)]
     LocalAudit {
         /// Ed25519 public key in hex format.
         #[arg(long, env = "PLEXI_VERIFYING_KEY")]
         verifying_key: Option<String>,
+        /// Signatures to require on epochs signed under two ciphersuites while their namespace migrates (either, both)
+        #[arg(long, default_value_t = MigrationPolicy::Either, env = "PLEXI_MIGRATION_POLICY")]
+        migration_policy: MigrationPolicy,
         /// Enable detailed output
         #[arg(short, long, default_value_t = false, group = "format")]
         long: bool,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, group = "format", env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
         /// Disable signature and proof validation
         #[arg(long, default_value_t = false, env = "PLEXI_VERIFICATION_DISABLED")]
         no_verify: bool,
-        /// Path to a file containing an epoch consistency proof
-        /// Format is still ad-hoc, based on AKD
+        /// Path to a file containing an epoch consistency proof, or an http(s):// URL to fetch it from
         #[arg(long, env = "PLEXI_PROOF_PATH")]
-        proof_path: Option<PathBuf>,
-        /// Path to a file containing an epoch to verify
-        /// Format is { ciphersuite, namespace, timestamp, epoch, digest, signature }
+        proof_path: Option<String>,
+        /// AKD configuration of the log the proof comes from (whatsapp_v1, experimental)
+        #[arg(long, default_value_t = AkdConfig::WhatsAppV1, env = "PLEXI_AKD_CONFIG")]
+        akd_config: AkdConfig,
+        /// Digest the epoch has to have, in hex format, such as one obtained from the application vendor
+        #[arg(long)]
+        expected_digest: Option<String>,
+        /// Path to a file containing an epoch to verify, or an http(s):// URL to fetch it from
+        signature_path_or_stdin: Option<String>,
+        #[command(flatten)]
+        network: NetworkArgs,
+    },
+    /// Watch a namespace, verifying its epochs as the auditor signs them
+    #[command(verbatim_doc_comment)]
+    Watch {
+        /// URL of the auditor. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_REMOTE_URL")]
+        remote_url: Option<String>,
+        /// Namespace ID. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_NAMESPACE")]
+        namespace: Option<String>,
+        /// Ed25519 public key in hex format.
+        #[arg(long, env = "PLEXI_VERIFYING_KEY")]
+        verifying_key: Option<String>,
+        /// Signatures to require on epochs signed under two ciphersuites while their namespace migrates (either, both)
+        #[arg(long, default_value_t = MigrationPolicy::Either, env = "PLEXI_MIGRATION_POLICY")]
+        migration_policy: MigrationPolicy,
+        /// Log directory to retrieve proofs from, overriding the one advertised by the namespace
+        #[arg(long, env = "PLEXI_LOG_DIRECTORY")]
+        log_directory: Option<String>,
+        /// Seconds between checks for new epochs
+        #[arg(long, default_value_t = 60, env = "PLEXI_WATCH_INTERVAL")]
+        interval: u64,
+        /// Seconds an epoch may be timestamped after the epoch before. Timestamps always have to be non-decreasing
+        #[arg(long, env = "PLEXI_MAX_TIMESTAMP_GAP")]
+        max_timestamp_gap: Option<u64>,
+        /// File keeping the last verified epoch, to resume watching from it after a restart
+        #[arg(long, env = "PLEXI_WATCH_STATE_PATH")]
+        state_path: Option<PathBuf>,
+        /// URL to POST a JSON event to when an epoch does not verify or the namespace stalls
+        #[arg(long, env = "PLEXI_WEBHOOK_URL")]
+        webhook_url: Option<Url>,
+        /// File to write Prometheus metrics to after each check, for the node_exporter textfile collector
+        #[arg(long, env = "PLEXI_METRICS_TEXTFILE")]
+        metrics_textfile: Option<PathBuf>,
+        /// Output format (text, json, yaml). JSON outputs one line per epoch, and YAML one document per epoch
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        #[command(flatten)]
+        freshness: FreshnessArgs,
+        #[command(flatten)]
+        network: NetworkArgs,
+    },
+    /// Check that every key the auditor advertises descends from a pinned root key
+    #[command(verbatim_doc_comment)]
+    VerifyKeys {
+        /// URL of the auditor. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_REMOTE_URL")]
+        remote_url: Option<String>,
+        /// Ed25519 public key in hex format, trusted as the first key of the auditor
+        #[arg(long, env = "PLEXI_ROOT_KEY")]
+        root_key: String,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        #[command(flatten)]
+        network: NetworkArgs,
+    },
+    /// Diagnose the setup of plexi
+    #[command(verbatim_doc_comment)]
+    Doctor {
+        /// URL of the auditor. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_REMOTE_URL")]
+        remote_url: Option<String>,
+        /// Ed25519 public key in hex format. Defaults to the one of the profile
+        #[arg(long, env = "PLEXI_VERIFYING_KEY")]
+        verifying_key: Option<String>,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        #[command(flatten)]
+        network: NetworkArgs,
+    },
+    /// Compare the signatures of an epoch by two auditors, to detect a split view of the log
+    #[command(verbatim_doc_comment)]
+    Diff {
+        /// URL of an auditor. Has to be given twice
+        #[arg(short, long, required = true)]
+        remote_url: Vec<String>,
+        /// Namespace ID. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_NAMESPACE")]
+        namespace: Option<String>,
+        /// Height of the epoch to compare
+        #[arg(long)]
+        epoch: Option<Epoch>,
+        /// Signatures to require on epochs signed under two ciphersuites while their namespace migrates (either, both)
+        #[arg(long, default_value_t = MigrationPolicy::Either, env = "PLEXI_MIGRATION_POLICY")]
+        migration_policy: MigrationPolicy,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+    },
+    /// Export the signatures of a range of epochs, for offline analysis or archival
+    #[command(verbatim_doc_comment)]
+    Export {
+        /// URL of the auditor. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_REMOTE_URL")]
+        remote_url: Option<String>,
+        /// Namespace ID. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_NAMESPACE")]
+        namespace: Option<String>,
+        /// First epoch of the range to export
+        #[arg(long)]
+        from: Epoch,
+        /// Last epoch of the range to export, included
+        #[arg(long)]
+        to: Epoch,
+        /// Format of the exported signatures (jsonl, csv)
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Jsonl)]
+        format: ExportFormat,
+        #[command(flatten)]
+        network: NetworkArgs,
+    },
+    /// Audit proof utilities
+    Proof {
+        #[command(subcommand)]
+        command: ProofCommands,
+    },
+    /// Offline audit bundles, packaging everything needed to verify an epoch without network access
+    Bundle {
+        #[command(subcommand)]
+        command: BundleCommands,
+    },
+    /// Audit trail of the verifications performed on this machine
+    Log {
+        #[command(subcommand)]
+        command: LogCommands,
+    },
+    /// Create, update, and disable the namespaces of an auditor, with its admin token
+    Namespace {
+        #[command(subcommand)]
+        command: NamespaceCommands,
+    },
+    /// Reports of new epochs, submitted by logs for the auditor to verify and sign
+    Report {
+        #[command(subcommand)]
+        command: ReportCommands,
+    },
+    /// Roots of the namespaces, trusted the first time they are audited
+    Trust {
+        #[command(subcommand)]
+        command: TrustCommands,
+    },
+    /// Sign an epoch as an auditor, and output the signature
+    #[command(verbatim_doc_comment)]
+    Sign {
+        /// Namespace ID
+        #[arg(short, long, env = "PLEXI_NAMESPACE")]
+        namespace: String,
+        /// Height of the epoch to sign
+        #[arg(long)]
+        epoch: Epoch,
+        /// Digest of the epoch, in hex format
+        #[arg(long)]
+        digest: String,
+        /// Ciphersuite of the signature (1: protobuf, 2: bincode, 3: protobuf v2)
+        #[arg(long, default_value = "1")]
+        ciphersuite: Ciphersuite,
+        /// Timestamp of the signature, in milliseconds since UNIX epoch. Defaults to now
+        #[arg(long)]
+        timestamp: Option<u64>,
+        #[command(flatten)]
+        signer: SignerArgs,
+    },
+    /// Check an Ed25519 signature over a signature message, offline
+    #[command(verbatim_doc_comment)]
+    VerifySignature {
+        /// Ed25519 public key in hex format
+        #[arg(long, env = "PLEXI_VERIFYING_KEY")]
+        key: String,
+        /// Path to a file containing the serialized message that was signed, in its canonical encoding
+        #[arg(
+            long,
+            requires = "signature",
+            conflicts_with = "signature_path_or_stdin"
+        )]
+        message: Option<PathBuf>,
+        /// Ed25519 signature over the message, in hex format
+        #[arg(long, requires = "message")]
+        signature: Option<String>,
+        /// Signatures to require on epochs signed under two ciphersuites while their namespace migrates (either, both)
+        #[arg(long, default_value_t = MigrationPolicy::Either, env = "PLEXI_MIGRATION_POLICY")]
+        migration_policy: MigrationPolicy,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        /// Path to a file containing a signature response, when --message is not set
         signature_path_or_stdin: Option<PathBuf>,
     },
+    /// Generate signature test vectors, for implementations of plexi verification in other languages
+    #[command(verbatim_doc_comment)]
+    GenVectors {
+        /// Ciphersuite of the signatures (1: protobuf, 2: bincode, 3: protobuf v2)
+        #[arg(long, default_value = "1")]
+        ciphersuite: Ciphersuite,
+        /// Number of vectors to generate
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+        /// Seed the keys, epochs, timestamps, and digests of the vectors are derived from
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Generate an Ed25519 key pair to sign epochs with
+    #[command(verbatim_doc_comment)]
+    Keygen {
+        /// Path to write the signing key to. Only the current user can read it
+        #[arg(long, env = "PLEXI_SIGNING_KEY_PATH")]
+        signing_key_path: PathBuf,
+        /// Overwrite the signing key file if it exists
+        #[arg(long, default_value_t = false)]
+        force: bool,
+        /// Write the signing key hex encoded, without passphrase, as plexi-server reads it
+        #[arg(long, default_value_t = false)]
+        unencrypted: bool,
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Generate roff man pages from the command line definitions, for packagers to ship
+    #[command(verbatim_doc_comment)]
+    Man {
+        /// Directory to write the pages of plexi and each of its subcommands to
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+impl Commands {
+    pub fn output(&self) -> OutputFormat {
+        match self {
+            Self::Audit { output, .. }
+            | Self::LocalAudit { output, .. }
+            | Self::Watch { output, .. }
+            | Self::Diff { output, .. }
+            | Self::VerifyKeys { output, .. }
+            | Self::VerifySignature { output, .. }
+            | Self::Doctor { output, .. } => *output,
+            Self::Ls {
+                output: ListFormat::Json,
+                ..
+            } => OutputFormat::Json,
+            Self::Ls {
+                output: ListFormat::Yaml,
+                ..
+            } => OutputFormat::Yaml,
+            Self::Proof {
+                command: ProofCommands::Inspect { output, .. },
+            }
+            | Self::Bundle {
+                command: BundleCommands::Verify { output, .. },
+            }
+            | Self::Log {
+                command: LogCommands::Verify { output, .. },
+            }
+            | Self::Report {
+                command: ReportCommands::Submit { output, .. },
+            }
+            | Self::Namespace {
+                command:
+                    NamespaceCommands::Create { output, .. }
+                    | NamespaceCommands::Update { output, .. }
+                    | NamespaceCommands::Disable { output, .. },
+            } => *output,
+            _ => OutputFormat::Text,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Text for humans, detailed with --long
+    #[default]
+    Text,
+    /// JSON, for scripts and dashboards
+    Json,
+    /// YAML, for GitOps pipelines and Kubernetes tooling
+    Yaml,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ListFormat {
+    /// Namespace names, detailed with --long
+    #[default]
+    Text,
+    /// JSON, for scripts and dashboards
+    Json,
+    /// YAML, for GitOps pipelines and Kubernetes tooling
+    Yaml,
+    /// One namespace per line, with its status, ciphersuite, last verified epoch, and root in aligned columns
+    Table,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    /// One JSON signature per line, as served by the auditor
+    #[default]
+    Jsonl,
+    /// One signature per line, with a header, fields hex encoded
+    Csv,
+    /// One RFC 6962 signed tree head per line, as CT logs serve them, with the epoch as tree size
+    Sth,
+}
+
+#[derive(Args)]
+pub struct NetworkArgs {
+    /// Seconds a request may take before it times out, including downloading proofs. Defaults to 60
+    #[arg(long, env = "PLEXI_TIMEOUT")]
+    pub timeout: Option<u64>,
+    /// Number of times a request to the auditor is retried after a timeout, connection failure, or server error
+    #[arg(long, default_value_t = 0, env = "PLEXI_RETRIES")]
+    pub retries: u32,
+    /// Seconds the auditor configuration and namespaces are reused for once fetched, rather than fetched for every epoch. 0 disables caching
+    #[arg(long, default_value_t = 300, env = "PLEXI_CACHE_TTL")]
+    pub cache_ttl: u64,
+    /// Directory to persist cached lookups to
+    #[arg(long, env = "PLEXI_CACHE_DIR")]
+    pub cache_dir: Option<PathBuf>,
+    /// PEM encoded client certificate, to authenticate to the auditor with mTLS. Overrides the one of the profile
+    #[arg(long, env = "PLEXI_CLIENT_CERT", requires = "client_key")]
+    pub client_cert: Option<PathBuf>,
+    /// PEM encoded key of the client certificate
+    #[arg(long, env = "PLEXI_CLIENT_KEY", requires = "client_cert")]
+    pub client_key: Option<PathBuf>,
+    /// DER encoded PKCS#12 archive holding the client certificate and its key, instead of --client-cert and --client-key
+    #[cfg(feature = "native-tls")]
+    #[arg(long, env = "PLEXI_CLIENT_PKCS12", conflicts_with = "client_cert")]
+    pub client_pkcs12: Option<PathBuf>,
+    /// Password of the PKCS#12 archive
+    #[cfg(feature = "native-tls")]
+    #[arg(
+        long,
+        env = "PLEXI_CLIENT_PKCS12_PASSWORD",
+        hide_env_values = true,
+        requires = "client_pkcs12"
+    )]
+    pub client_pkcs12_password: Option<String>,
+}
+
+#[derive(Args)]
+pub struct FreshnessArgs {
+    /// Seconds the latest epoch may be timestamped before now. Older epochs are reported, as the namespace may be stalled
+    #[arg(long, env = "PLEXI_MAX_AGE")]
+    pub max_age: Option<u64>,
+    /// Seconds the clock of this machine may drift from the auditor one
+    #[arg(long, default_value_t = plexi_core::DEFAULT_CLOCK_SKEW.as_secs(), env = "PLEXI_CLOCK_SKEW")]
+    pub clock_skew: u64,
+}
+
+impl Default for FreshnessArgs {
+    fn default() -> Self {
+        Self {
+            max_age: None,
+            clock_skew: plexi_core::DEFAULT_CLOCK_SKEW.as_secs(),
+        }
+    }
+}
+
+impl FreshnessArgs {
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age.map(Duration::from_secs)
+    }
+
+    pub fn clock_skew(&self) -> Duration {
+        Duration::from_secs(self.clock_skew)
+    }
+}
+
+#[derive(Args)]
+pub struct PassphraseArgs {
+    /// Passphrase of the signing key
+    #[arg(long, env = "PLEXI_SIGNING_KEY_PASSPHRASE", hide_env_values = true)]
+    pub passphrase: Option<String>,
+    /// Command printing the passphrase of the signing key, such as the client of a password manager or agent. It is run with sh -c
+    #[arg(long, env = "PLEXI_PASSPHRASE_COMMAND", conflicts_with = "passphrase")]
+    pub passphrase_command: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SignerArgs {
+    /// Path to the Ed25519 key to sign with, encrypted by plexi keygen or hex encoded
+    #[arg(long, env = "PLEXI_SIGNING_KEY_PATH")]
+    pub signing_key_path: Option<PathBuf>,
+    #[command(flatten)]
+    pub passphrase: PassphraseArgs,
+    /// PKCS#11 module of the HSM holding the key, such as /usr/lib/softhsm/libsofthsm2.so
+    #[cfg(feature = "pkcs11")]
+    #[arg(
+        long,
+        env = "PLEXI_PKCS11_MODULE",
+        conflicts_with = "signing_key_path",
+        requires_all = ["pkcs11_token", "pkcs11_key"]
+    )]
+    pub pkcs11_module: Option<PathBuf>,
+    /// Label of the PKCS#11 token holding the key
+    #[cfg(feature = "pkcs11")]
+    #[arg(long, env = "PLEXI_PKCS11_TOKEN")]
+    pub pkcs11_token: Option<String>,
+    /// PIN of the PKCS#11 token user
+    #[cfg(feature = "pkcs11")]
+    #[arg(long, env = "PLEXI_PKCS11_PIN", hide_env_values = true)]
+    pub pkcs11_pin: Option<String>,
+    /// Label of the Ed25519 key pair in the PKCS#11 token
+    #[cfg(feature = "pkcs11")]
+    #[arg(long, env = "PLEXI_PKCS11_KEY")]
+    pub pkcs11_key: Option<String>,
+    /// AWS KMS Ed25519 key ID, ARN, or alias. Region and credentials are read from AWS_* environment variables
+    #[cfg(feature = "kms")]
+    #[arg(long, env = "PLEXI_AWS_KMS_KEY_ID", conflicts_with_all = ["signing_key_path", "gcp_kms_key_version"])]
+    pub aws_kms_key_id: Option<String>,
+    /// Google Cloud KMS Ed25519 key version, as projects/.../cryptoKeyVersions/{version}
+    #[cfg(feature = "kms")]
+    #[arg(
+        long,
+        env = "PLEXI_GCP_KMS_KEY_VERSION",
+        conflicts_with = "signing_key_path"
+    )]
+    pub gcp_kms_key_version: Option<String>,
+    /// PIV slot of the YubiKey holding the key (9a, 9c, 9d, 9e). The YubiKey has to be touched if the key requires it
+    #[cfg(feature = "yubikey")]
+    #[arg(long, env = "PLEXI_YUBIKEY_SLOT", conflicts_with = "signing_key_path")]
+    pub yubikey_slot: Option<plexi_core::signer::PivSlot>,
+    /// PIN of the YubiKey PIV application
+    #[cfg(feature = "yubikey")]
+    #[arg(long, env = "PLEXI_YUBIKEY_PIN", hide_env_values = true)]
+    pub yubikey_pin: Option<String>,
+    /// Path to the YubiKey PKCS#11 module, ykcs11
+    #[cfg(feature = "yubikey")]
+    #[arg(long, env = "PLEXI_YKCS11_MODULE", default_value = plexi_core::signer::DEFAULT_YKCS11_MODULE)]
+    pub ykcs11_module: PathBuf,
+}
+
+#[derive(Subcommand)]
+pub enum ProofCommands {
+    /// Describe an audit proof without verifying it
+    #[command(verbatim_doc_comment)]
+    Inspect {
+        /// AKD configuration of the log the proof comes from (whatsapp_v1, experimental)
+        #[arg(long, default_value_t = AkdConfig::WhatsAppV1, env = "PLEXI_AKD_CONFIG")]
+        akd_config: AkdConfig,
+        /// Name of the proof in the log directory, formatted as epoch/previous_hash/current_hash
+        #[arg(long)]
+        blob: Option<String>,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        /// Path to a file containing an epoch consistency proof
+        proof_path_or_stdin: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LogCommands {
+    /// Check the hash chain of the audit trail
+    #[command(verbatim_doc_comment)]
+    Verify {
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        /// Path to the audit trail. Defaults to $XDG_STATE_HOME/plexi/audit-trail.jsonl or ~/.local/state/plexi/audit-trail.jsonl
+        trail_path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum NamespaceCommands {
+    /// Create a namespace for the auditor to audit, and output it
+    Create {
+        /// URL of the auditor. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_REMOTE_URL")]
+        remote_url: Option<String>,
+        /// Namespace ID. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_NAMESPACE")]
+        namespace: Option<String>,
+        /// Ciphersuite epochs are signed under (1: protobuf, 2: bincode, 3: protobuf v2)
+        #[arg(long, default_value = "1")]
+        ciphersuite: Ciphersuite,
+        /// First epoch of the namespace and its digest in hex format, as epoch/digest. Audits do not go before it
+        #[arg(long)]
+        root: Option<String>,
+        #[command(flatten)]
+        settings: NamespaceSettingsArgs,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        #[command(flatten)]
+        admin: AdminArgs,
+        #[command(flatten)]
+        network: NetworkArgs,
+    },
+    /// Change the log directory, AKD configuration, aliases, or metadata of a namespace, and output it
+    #[command(verbatim_doc_comment)]
+    Update {
+        /// URL of the auditor. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_REMOTE_URL")]
+        remote_url: Option<String>,
+        /// Namespace ID. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_NAMESPACE")]
+        namespace: Option<String>,
+        #[command(flatten)]
+        settings: NamespaceSettingsArgs,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        #[command(flatten)]
+        admin: AdminArgs,
+        #[command(flatten)]
+        network: NetworkArgs,
+    },
+    /// Stop the auditor from auditing a namespace. Its signatures remain served
+    #[command(verbatim_doc_comment)]
+    Disable {
+        /// URL of the auditor. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_REMOTE_URL")]
+        remote_url: Option<String>,
+        /// Namespace ID. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_NAMESPACE")]
+        namespace: Option<String>,
+        /// Disable the namespace without asking for confirmation
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        #[command(flatten)]
+        admin: AdminArgs,
+        #[command(flatten)]
+        network: NetworkArgs,
+    },
+}
+
+#[derive(Args)]
+pub struct NamespaceSettingsArgs {
+    /// Log directory the namespace publishes its proofs to
+    #[arg(long)]
+    pub log_directory: Option<String>,
+    /// AKD configuration of the log (whatsapp_v1, experimental)
+    #[arg(long)]
+    pub akd_config: Option<AkdConfig>,
+    /// Other name the namespace is known by. Can be given several times, and replaces the current aliases
+    #[arg(long = "alias")]
+    pub aliases: Vec<String>,
+    /// Description of the namespace
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Contact of the log operator, such as an email address
+    #[arg(long)]
+    pub contact: Option<String>,
+    /// URL of the policy the log operates under
+    #[arg(long)]
+    pub policy_url: Option<String>,
+}
+
+#[derive(Args)]
+pub struct AdminArgs {
+    /// Bearer token of the auditor admin API
+    #[arg(long, env = "PLEXI_ADMIN_TOKEN", hide_env_values = true)]
+    pub admin_token: String,
+}
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// Submit a report of a new epoch to the auditor, and output the report it queued
+    #[command(verbatim_doc_comment)]
+    Submit {
+        /// URL of the auditor. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_REMOTE_URL")]
+        remote_url: Option<String>,
+        /// Check the ciphersuite, digest, signature, and epoch of the report against the auditor, without submitting it
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// Ed25519 public key of the log provider in hex format, the report signature is checked against with --dry-run
+        #[arg(long, requires = "dry_run", env = "PLEXI_PROVIDER_KEY")]
+        provider_key: Option<String>,
+        /// Bearer token of the auditor admin API, which report submission requires
+        #[arg(
+            long,
+            env = "PLEXI_ADMIN_TOKEN",
+            hide_env_values = true,
+            required_unless_present = "dry_run"
+        )]
+        admin_token: Option<String>,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        #[command(flatten)]
+        network: NetworkArgs,
+        /// Path to a file containing the report
+        report_path_or_stdin: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TrustCommands {
+    /// Forget the trusted root of a namespace
+    #[command(verbatim_doc_comment)]
+    Reset {
+        /// Namespace ID
+        #[arg(short, long, required_unless_present = "all")]
+        namespace: Option<String>,
+        /// Forget the trusted roots of every namespace
+        #[arg(long, conflicts_with = "namespace")]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BundleCommands {
+    /// Package the signature of an epoch and its audit proof into a file
+    #[command(verbatim_doc_comment)]
+    Create {
+        /// URL of the auditor. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_REMOTE_URL")]
+        remote_url: Option<String>,
+        /// Namespace ID. Defaults to the one of the profile
+        #[arg(short, long, env = "PLEXI_NAMESPACE")]
+        namespace: Option<String>,
+        /// Height of the epoch to package
+        #[arg(long)]
+        epoch: Option<Epoch>,
+        /// Log directory to retrieve the proof from, overriding the one advertised by the namespace
+        #[arg(long, env = "PLEXI_LOG_DIRECTORY")]
+        log_directory: Option<String>,
+        #[command(flatten)]
+        network: NetworkArgs,
+        /// Path to write the bundle to
+        bundle_path: PathBuf,
+    },
+    /// Verify the signature and audit proof of a bundle, without network access
+    #[command(verbatim_doc_comment)]
+    Verify {
+        /// Ed25519 public key in hex format.
+        #[arg(long, env = "PLEXI_VERIFYING_KEY")]
+        verifying_key: Option<String>,
+        /// Signatures to require on epochs signed under two ciphersuites while their namespace migrates (either, both)
+        #[arg(long, default_value_t = MigrationPolicy::Either, env = "PLEXI_MIGRATION_POLICY")]
+        migration_policy: MigrationPolicy,
+        /// Enable detailed output
+        #[arg(short, long, default_value_t = false, group = "format")]
+        long: bool,
+        /// Output format (text, json, yaml)
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text, group = "format", env = "PLEXI_OUTPUT")]
+        output: OutputFormat,
+        /// Path to the bundle to verify
+        bundle_path: PathBuf,
+    },
 }
 
 #[allow(dead_code)]
 pub fn build() -> Cli {
     Cli::parse()
 }
+
+pub fn command() -> clap::Command {
+    Cli::command()
+}
+
+pub fn log_level(verbose: &clap_verbosity_flag::Verbosity) -> tracing::level_filters::LevelFilter {
+    use clap_verbosity_flag::LevelFilter as Verbosity;
+    use tracing::level_filters::LevelFilter;
+
+    match verbose.log_level_filter() {
+        Verbosity::Off => LevelFilter::OFF,
+        Verbosity::Error => LevelFilter::ERROR,
+        Verbosity::Warn => LevelFilter::WARN,
+        Verbosity::Info => LevelFilter::INFO,
+        Verbosity::Debug => LevelFilter::DEBUG,
+        Verbosity::Trace => LevelFilter::TRACE,
+    }
+}
+
+pub fn colored(color: ColorChoice) -> bool {
+    match color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        // https://no-color.org: NO_COLOR disables colors when set to a non-empty value
+        ColorChoice::Auto => {
+            let no_color =
+                std::env::var_os("NO_COLOR").is_some_and(|no_color| !no_color.is_empty());
+            !no_color && std::io::stdout().is_terminal()
+        }
+    }
+}
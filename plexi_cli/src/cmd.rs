@@ -1,26 +1,59 @@
 use std::{
     fmt, fs,
-    io::{self, Read},
-    path::PathBuf,
+    io::{self, IsTerminal as _, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
-use akd::local_auditing::AuditBlobName;
 use anyhow::{anyhow, Context, Result};
 use colored::Colorize;
-use log::log_enabled;
+use ed25519_dalek::{Signature, VerifyingKey, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH};
+use futures_util::{stream, StreamExt as _, TryStreamExt as _};
 use plexi_core::{
-    auditor, client::PlexiClient, namespaces::Namespaces, Ciphersuite, Epoch, SignatureResponse,
+    auditor::{
+        self, AkdConfig, AuditProofSource, ChainOutcome, EpochChainVerifier, KeyInfo, ProofId,
+        ProofLimits, VerifyOptions,
+    },
+    client::{store::ProofStore, PlexiClient, ProofResponse},
+    crypto::{constant_time_eq, ed25519_public_key_to_key_id},
+    namespaces::{
+        root_epoch, Namespace, NamespaceFilter, NamespaceInfo, NamespaceMetadata, Namespaces,
+    },
+    signer::{sign_message, Ed25519Signer, Signer},
+    sth::SignedTreeHead,
+    testing, Ciphersuite, Epoch, LastVerifiedEpoch, MigrationPolicy, Report, SignatureMessage,
+    SignatureResponse,
 };
-use reqwest::Url;
+use serde::Serialize;
+use tokio::{task::JoinHandle, time::Duration};
+use tracing::Level;
 
-use crate::print::print_dots;
+use crate::{
+    alert::{AlertEvent, Alerts, FailureKind},
+    bundle::Bundle,
+    cache::VerifiedCache,
+    cli::{
+        self, ExportFormat, FreshnessArgs, ListFormat, NamespaceSettingsArgs, OutputFormat,
+        PassphraseArgs, SignerArgs,
+    },
+    config::Remote,
+    exit::{usage, VerificationFailure},
+    keyfile::{encrypt_signing_key, read_signing_key},
+    metrics::WatchMetrics,
+    print::{print_dots, print_output, print_progress, RangeProgress},
+    trail::{self, AuditTrail, Outcome},
+    trust::TrustStore,
+};
 
-const APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
+pub const APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
 pub fn file_or_stdin(input: Option<PathBuf>) -> Result<Box<dyn io::Read>> {
     let reader: Box<dyn io::Read> = match input {
         Some(path) => Box::new(io::BufReader::new(
-            fs::File::open(path).context("cannot read input file")?,
+            fs::File::open(path)
+                .context("cannot read input file")
+                .map_err(usage)?,
         )),
         None => Box::new(io::BufReader::new(io::stdin())),
     };
@@ -38,53 +71,62 @@ pub fn file_or_stdout(output: Option<PathBuf>) -> Result<Box<dyn io::Write>> {
     Ok(writer)
 }
 
-pub async fn ls(remote_url: &str, namespace: Option<&str>, long: bool) -> Result<String> {
-    let client = PlexiClient::new(Url::parse(remote_url)?, None, Some(APP_USER_AGENT))?;
+pub fn is_url(location: &str) -> bool {
+    location.starts_with("https://") || location.starts_with("http://")
+}
+
+pub enum Input {
+    Stdin,
+    Path(PathBuf),
+    Url(Remote),
+}
+
+impl Input {
+    async fn reader(self) -> Result<Box<dyn io::Read>> {
+        match self {
+            Input::Stdin => file_or_stdin(None),
+            Input::Path(path) => file_or_stdin(Some(path)),
+            Input::Url(remote) => Ok(Box::new(io::Cursor::new(fetch(&remote).await?))),
+        }
+    }
+}
+
+async fn fetch(remote: &Remote) -> Result<Vec<u8>> {
+    let url = reqwest::Url::parse(remote.url()).map_err(usage)?;
+    remote.client()?.fetch(&url).await
+}
+
+pub async fn ls(
+    remote: &Remote,
+    namespace: Option<&str>,
+    filter: &NamespaceFilter,
+    long: bool,
+    output: ListFormat,
+) -> Result<String> {
+    let client = remote.client()?;
 
     let namespaces = if let Some(namespace) = namespace {
         let mut namespaces = Namespaces::new();
         let Some(info) = client.namespace(namespace).await? else {
-            return Err(anyhow!("namespace {namespace} does not exist"));
+            return Err(usage(anyhow!("namespace {namespace} does not exist")));
         };
         namespaces.push(info);
         namespaces
     } else {
-        client.namespaces().await?
+        client.namespaces_matching(filter).await?
     };
+    match output {
+        ListFormat::Json => return serialize_output(OutputFormat::Json, &namespaces),
+        ListFormat::Yaml => return serialize_output(OutputFormat::Yaml, &namespaces),
+        ListFormat::Table => return format_namespace_table(&client, &namespaces).await,
+        ListFormat::Text => {}
+    }
 
     let result: Vec<String> = namespaces
         .iter()
         .map(|info| {
             if long {
-                [
-                    info.name().to_string().as_str(),
-                    format!(
-                        "  {: <11}: {status}",
-                        "Status".bold(),
-                        status = info.status()
-                    )
-                    .as_str(),
-                    format!(
-                        "  {: <11}: {version}",
-                        "Ciphersuite".bold(),
-                        version = format_ciphersuite(&info.signature_version())
-                    )
-                    .as_str(),
-                    format!(
-                        "  {: <11}: {root}",
-                        "Root".bold(),
-                        root = info.root().unwrap_or("-")
-                    )
-                    .as_str(),
-                    format!(
-                        "  {: <11}: {directory}",
-                        "Directory".bold(),
-                        directory = info.log_directory().unwrap_or("-")
-                    )
-                    .as_str(),
-                    "\n",
-                ]
-                .join("\n")
+                format!("{details}\n\n", details = format_namespace_details(info))
             } else {
                 info.name().to_string()
             }
@@ -94,10 +136,287 @@ pub async fn ls(remote_url: &str, namespace: Option<&str>, long: bool) -> Result
     Ok(result.join("\n"))
 }
 
+fn format_namespace_details(info: &NamespaceInfo) -> String {
+    [
+        info.name().to_string().as_str(),
+        format!(
+            "  {: <11}: {status}",
+            "Status".bold(),
+            status = info.status()
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {aliases}",
+            "Aliases".bold(),
+            aliases = if info.aliases().is_empty() {
+                "-".to_string()
+            } else {
+                info.aliases().join(", ")
+            }
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {version}",
+            "Ciphersuite".bold(),
+            version = match info.next_ciphersuite() {
+                Some(next) => format!(
+                    "{current}, migrating to {next}",
+                    current = format_ciphersuite(&info.signature_version()),
+                    next = format_ciphersuite(&next)
+                ),
+                None => format_ciphersuite(&info.signature_version()),
+            }
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {akd_config}",
+            "AKD config".bold(),
+            akd_config = info.akd_config()
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {digest_algorithm}",
+            "Digest".bold(),
+            digest_algorithm = info
+                .digest_algorithm()
+                .map_or("-", |digest_algorithm| digest_algorithm.name())
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {root}",
+            "Root".bold(),
+            root = info.root().unwrap_or("-")
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {history}",
+            "Past roots".bold(),
+            history = if info.root_history().is_empty() {
+                "-".to_string()
+            } else {
+                info.root_history().join(", ")
+            }
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {directory}",
+            "Directory".bold(),
+            directory = info.log_directory().unwrap_or("-")
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {description}",
+            "Description".bold(),
+            description = info.metadata().description().unwrap_or("-")
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {contact}",
+            "Contact".bold(),
+            contact = info.metadata().contact().unwrap_or("-")
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {policy_url}",
+            "Policy URL".bold(),
+            policy_url = info.metadata().policy_url().unwrap_or("-")
+        )
+        .as_str(),
+        format!(
+            "  {: <11}: {cadence}",
+            "Cadence".bold(),
+            cadence = info
+                .metadata()
+                .cadence()
+                .map_or("-".to_string(), |cadence| cadence.to_string())
+        )
+        .as_str(),
+    ]
+    .join("\n")
+}
+
+async fn format_namespace_table(client: &PlexiClient, namespaces: &Namespaces) -> Result<String> {
+    const HEADERS: [&str; 5] = ["Name", "Status", "Ciphersuite", "Last verified", "Root"];
+
+    let mut rows = vec![];
+    for info in namespaces.iter() {
+        let last_verified_epoch = client
+            .last_verified_epoch(info.name())
+            .await?
+            .map_or("-".to_string(), |last_verified_epoch| {
+                last_verified_epoch.epoch().to_string()
+            });
+        rows.push([
+            info.name().to_string(),
+            info.status().to_string(),
+            format_ciphersuite(&info.signature_version()),
+            last_verified_epoch,
+            info.root().unwrap_or("-").to_string(),
+        ]);
+    }
+
+    Ok(format_table(HEADERS, &rows))
+}
+
+fn format_table<const N: usize>(headers: [&str; N], rows: &[[String; N]]) -> String {
+    let mut widths = headers.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let header = headers
+        .iter()
+        .zip(widths)
+        .map(|(header, width)| format!("{: <width$}", header.bold()))
+        .collect::<Vec<_>>()
+        .join("  ");
+    let lines = rows.iter().map(|row| {
+        row.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell: <width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+    });
+    [header]
+        .into_iter()
+        .chain(lines)
+        .map(|line| line.trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_namespace_info(info: &NamespaceInfo, output: OutputFormat) -> Result<String> {
+    Ok(match output {
+        OutputFormat::Json | OutputFormat::Yaml => serialize_output(output, info)?,
+        OutputFormat::Text => format_namespace_details(info),
+    })
+}
+
+fn namespace_metadata(
+    settings: &NamespaceSettingsArgs,
+    current: &NamespaceMetadata,
+) -> NamespaceMetadata {
+    let metadata = NamespaceMetadata::new(
+        settings
+            .description
+            .clone()
+            .or_else(|| current.description().map(str::to_string)),
+        settings
+            .contact
+            .clone()
+            .or_else(|| current.contact().map(str::to_string)),
+        settings
+            .policy_url
+            .clone()
+            .or_else(|| current.policy_url().map(str::to_string)),
+    );
+    match current.cadence() {
+        Some(cadence) => metadata.with_cadence(cadence),
+        None => metadata,
+    }
+}
+
+pub async fn namespace_create(
+    remote: &Remote,
+    namespace: &str,
+    ciphersuite: &Ciphersuite,
+    root: Option<&str>,
+    settings: &NamespaceSettingsArgs,
+    output: OutputFormat,
+) -> Result<String> {
+    if root.is_some_and(|root| root_epoch(root).is_none()) {
+        return Err(usage(anyhow!("root has to be formatted as epoch/digest")));
+    }
+    let mut new = Namespace::new(
+        namespace.to_string(),
+        settings.log_directory.clone(),
+        root.map(str::to_string),
+        None,
+        Some(*ciphersuite),
+    );
+    if let Some(akd_config) = settings.akd_config {
+        new.set_akd_config(akd_config);
+    }
+    new.set_aliases(settings.aliases.clone());
+    new.set_metadata(namespace_metadata(settings, &NamespaceMetadata::default()));
+    new.validate().map_err(usage)?;
+
+    let info = remote.client()?.create_namespace(&new).await?;
+    format_namespace_info(&info, output)
+}
+
+pub async fn namespace_update(
+    remote: &Remote,
+    namespace: &str,
+    settings: &NamespaceSettingsArgs,
+    output: OutputFormat,
+) -> Result<String> {
+    let client = remote.client()?;
+    // the update replaces the namespace, so it starts from its current settings rather than cached ones
+    client.invalidate_namespace(namespace)?;
+    let Some(current) = client.namespace(namespace).await? else {
+        return Err(usage(anyhow!("namespace {namespace} does not exist")));
+    };
+
+    let mut updated = Namespace::new(
+        current.name().to_string(),
+        settings
+            .log_directory
+            .clone()
+            .or_else(|| current.log_directory().map(str::to_string)),
+        current.root().map(str::to_string),
+        Some(current.signature_version()),
+        Some(current.ciphersuite()),
+    );
+    updated.set_akd_config(settings.akd_config.unwrap_or(current.akd_config()));
+    updated.set_digest_algorithm(current.digest_algorithm());
+    updated.set_next_ciphersuite(current.next_ciphersuite());
+    updated.set_aliases(if settings.aliases.is_empty() {
+        current.aliases().to_vec()
+    } else {
+        settings.aliases.clone()
+    });
+    updated.set_metadata(namespace_metadata(settings, current.metadata()));
+    updated.validate().map_err(usage)?;
+
+    let info = client.update_namespace(&updated).await?;
+    format_namespace_info(&info, output)
+}
+
+pub async fn namespace_disable(
+    remote: &Remote,
+    namespace: &str,
+    yes: bool,
+    output: OutputFormat,
+) -> Result<String> {
+    if !yes {
+        if !io::stdin().is_terminal() {
+            return Err(usage(anyhow!(
+                "--yes is required to disable a namespace without a terminal to confirm in"
+            )));
+        }
+        eprint!(
+            "Disabling {namespace} stops {url} from auditing it. Type {namespace} to confirm: ",
+            url = remote.url()
+        );
+        io::stderr().flush()?;
+        let mut confirmation = String::new();
+        io::stdin().read_line(&mut confirmation)?;
+        if confirmation.trim() != namespace {
+            return Err(anyhow!("{namespace} was not disabled"));
+        }
+    }
+
+    let info = remote.client()?.disable_namespace(namespace).await?;
+    format_namespace_info(&info, output)
+}
+
 fn format_ciphersuite(ciphersuite: &Ciphersuite) -> String {
     match ciphersuite {
         Ciphersuite::BincodeEd25519 => "ed25519(bincode)".to_string(),
         Ciphersuite::ProtobufEd25519 => "ed25519(protobuf)".to_string(),
+        Ciphersuite::ProtobufV2Ed25519 => "ed25519(protobuf v2)".to_string(),
         Ciphersuite::Unknown(u) => format!("unknown {u}"),
     }
 }
@@ -108,6 +427,51 @@ enum VerificationStatus {
     Failed(String),
 }
 
+impl VerificationStatus {
+    fn is_failed(&self) -> bool {
+        matches!(self, VerificationStatus::Failed(_))
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            VerificationStatus::Success => serde_json::json!({ "status": "success" }),
+            VerificationStatus::Disabled => serde_json::json!({ "status": "disabled" }),
+            VerificationStatus::Failed(err) => {
+                serde_json::json!({ "status": "failed", "error": err })
+            }
+        }
+    }
+}
+
+impl From<&VerificationStatus> for Outcome {
+    fn from(status: &VerificationStatus) -> Self {
+        match status {
+            VerificationStatus::Success => Outcome::Success,
+            VerificationStatus::Disabled => Outcome::Disabled,
+            VerificationStatus::Failed(err) => Outcome::Failed { error: err.clone() },
+        }
+    }
+}
+
+fn record_verification(
+    trail: &AuditTrail,
+    signature: &SignatureResponse,
+    signature_verification_status: &VerificationStatus,
+    proof_verification_status: &VerificationStatus,
+) {
+    if let Err(e) = trail.record(
+        signature,
+        signature_verification_status.into(),
+        proof_verification_status.into(),
+    ) {
+        tracing::warn!(
+            "cannot record the verification of {namespace} epoch {epoch} in the audit trail: {e:#}",
+            namespace = signature.namespace(),
+            epoch = signature.epoch()
+        );
+    }
+}
+
 impl fmt::Display for VerificationStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
@@ -119,26 +483,218 @@ impl fmt::Display for VerificationStatus {
     }
 }
 
-fn format_audit_response(
-    long: bool,
+fn serialize_output(output: OutputFormat, value: &impl Serialize) -> Result<String> {
+    Ok(match output {
+        // other formats do not end with a newline
+        OutputFormat::Yaml => serde_yaml::to_string(value)?.trim_end().to_string(),
+        OutputFormat::Json | OutputFormat::Text => serde_json::to_string_pretty(value)?,
+    })
+}
+
+fn serialize_line(output: OutputFormat, value: &impl Serialize) -> Result<String> {
+    Ok(match output {
+        OutputFormat::Yaml => format!("---\n{}", serialize_output(output, value)?),
+        OutputFormat::Json | OutputFormat::Text => serde_json::to_string(value)?,
+    })
+}
+
+#[derive(Clone, Copy)]
+enum AuditFormat {
+    Short,
+    Long,
+    Structured(OutputFormat),
+}
+
+impl AuditFormat {
+    fn new(long: bool, output: OutputFormat) -> Self {
+        match output {
+            OutputFormat::Text if long => Self::Long,
+            OutputFormat::Text => Self::Short,
+            OutputFormat::Json | OutputFormat::Yaml => Self::Structured(output),
+        }
+    }
+}
+
+fn summary_status<'a>(
+    signature_verification_status: &'a VerificationStatus,
+    proof_verification_status: &'a VerificationStatus,
+) -> &'a VerificationStatus {
+    match (signature_verification_status, proof_verification_status) {
+        (_, VerificationStatus::Disabled) => signature_verification_status,
+        (VerificationStatus::Failed(_), _) => signature_verification_status,
+        (_, _) => proof_verification_status,
+    }
+}
+
+fn audit_to_json(
     signature: &SignatureResponse,
     signature_verification_status: &VerificationStatus,
     proof_verification_status: &VerificationStatus,
+) -> serde_json::Value {
+    serde_json::json!({
+        "signature": signature,
+        "signature_verification": signature_verification_status.to_json(),
+        "proof_verification": proof_verification_status.to_json(),
+    })
+}
+
+fn check_verification(
+    output: String,
+    signature_failed: bool,
+    proof_failed: bool,
 ) -> Result<String> {
-    if !long {
-        return match (signature_verification_status, proof_verification_status) {
-            (_, VerificationStatus::Disabled) => Ok(signature_verification_status.to_string()),
-            (VerificationStatus::Failed(_), _) => Ok(signature_verification_status.to_string()),
-            (_, _) => Ok(proof_verification_status.to_string()),
-        };
+    match (signature_failed, proof_failed) {
+        (true, _) => Err(VerificationFailure::signature(output).into()),
+        (_, true) => Err(VerificationFailure::proof(output).into()),
+        (_, _) => Ok(output),
+    }
+}
+
+fn check_fresh(output: Result<String>, stale: bool) -> Result<String> {
+    match output {
+        Ok(output) if stale => Err(VerificationFailure::stale(output).into()),
+        output => output,
+    }
+}
+
+fn short_status(
+    signature_verification_status: &VerificationStatus,
+    proof_verification_status: &VerificationStatus,
+    freshness_status: &VerificationStatus,
+) -> String {
+    let status = summary_status(signature_verification_status, proof_verification_status);
+    match freshness_status {
+        VerificationStatus::Failed(e) => format!("{status}, stale - {e}"),
+        _ => status.to_string(),
     }
+}
+
+fn with_freshness(details: String, freshness_status: &VerificationStatus) -> String {
+    match freshness_status {
+        VerificationStatus::Disabled => details,
+        status => format!("{details}\n  {: <22}: {status}", "Freshness".bold()),
+    }
+}
+
+fn format_audit_response(
+    format: AuditFormat,
+    signature: &SignatureResponse,
+    signature_verification_status: &VerificationStatus,
+    proof_verification_status: &VerificationStatus,
+    freshness_status: &VerificationStatus,
+) -> Result<String> {
+    let output = match format {
+        AuditFormat::Structured(output) => {
+            let mut audit = audit_to_json(
+                signature,
+                signature_verification_status,
+                proof_verification_status,
+            );
+            audit["freshness"] = freshness_status.to_json();
+            serialize_output(output, &audit)?
+        }
+        AuditFormat::Short => short_status(
+            signature_verification_status,
+            proof_verification_status,
+            freshness_status,
+        ),
+        AuditFormat::Long => with_freshness(
+            format_audit_details(
+                signature,
+                signature_verification_status,
+                proof_verification_status,
+            )?,
+            freshness_status,
+        ),
+    };
+
+    check_fresh(
+        check_verification(
+            output,
+            signature_verification_status.is_failed(),
+            proof_verification_status.is_failed(),
+        ),
+        freshness_status.is_failed(),
+    )
+}
+
+fn format_range_response(
+    format: AuditFormat,
+    from: &Epoch,
+    to: &Epoch,
+    audits: &[(SignatureResponse, VerificationStatus, VerificationStatus)],
+) -> Result<String> {
+    let signature_failed = audits.iter().any(|(_, status, _)| status.is_failed());
+    let proof_failed = audits.iter().any(|(_, _, status)| status.is_failed());
+    let failed = audits
+        .iter()
+        .filter(|(_, signature_status, proof_status)| {
+            signature_status.is_failed() || proof_status.is_failed()
+        })
+        .count();
+    let verdict = if failed == 0 {
+        VerificationStatus::Success
+    } else {
+        VerificationStatus::Failed(format!(
+            "{failed} of {total} epochs did not verify",
+            total = audits.len()
+        ))
+    };
+
+    let range = format!("Epochs {from}-{to}").bold();
+    let output = match format {
+        AuditFormat::Structured(output) => serialize_output(
+            output,
+            &serde_json::json!({
+                "from": from,
+                "to": to,
+                "epochs": audits
+                    .iter()
+                    .map(|(signature, signature_status, proof_status)| {
+                        audit_to_json(signature, signature_status, proof_status)
+                    })
+                    .collect::<Vec<_>>(),
+                "verification": verdict.to_json(),
+            }),
+        )?,
+        AuditFormat::Short => audits
+            .iter()
+            .map(|(signature, signature_status, proof_status)| {
+                format!(
+                    "{epoch}: {status}",
+                    epoch = signature.epoch(),
+                    status = summary_status(signature_status, proof_status)
+                )
+            })
+            .chain([format!("\n{range}: {verdict}")])
+            .collect::<Vec<_>>()
+            .join("\n"),
+        AuditFormat::Long => audits
+            .iter()
+            .map(|(signature, signature_status, proof_status)| {
+                format_audit_details(signature, signature_status, proof_status)
+            })
+            .chain([Ok(format!("{range}: {verdict}"))])
+            .collect::<Result<Vec<_>>>()?
+            .join("\n\n"),
+    };
 
+    check_verification(output, signature_failed, proof_failed)
+}
+
+fn format_timestamp(timestamp: u64) -> Result<String> {
     let format = time::format_description::parse("[year]-[month]-[day]T[hour]:[minute]:[second]Z")?;
-    let formatted_timestamp =
-        time::OffsetDateTime::from_unix_timestamp((signature.timestamp() / 1000) as i64)?
-            .format(&format)?;
+    Ok(time::OffsetDateTime::from_unix_timestamp((timestamp / 1000) as i64)?.format(&format)?)
+}
+
+fn format_audit_details(
+    signature: &SignatureResponse,
+    signature_verification_status: &VerificationStatus,
+    proof_verification_status: &VerificationStatus,
+) -> Result<String> {
+    let formatted_timestamp = format_timestamp(signature.timestamp())?;
 
-    return Ok([
+    Ok([
         "Namespace",
         format!(
             "  {: <22}: {namespace}",
@@ -149,7 +705,14 @@ fn format_audit_response(
         format!(
             "  {: <22}: {version}",
             "Ciphersuite".bold(),
-            version = format_ciphersuite(signature.version())
+            version = match signature.migration_signature() {
+                Some(migration) => format!(
+                    "{current}, also signed with {next}",
+                    current = format_ciphersuite(signature.version()),
+                    next = format_ciphersuite(migration.version())
+                ),
+                None => format_ciphersuite(signature.version()),
+            }
         )
         .as_str(),
         format!("\nSignature ({timestamp})", timestamp = formatted_timestamp).as_str(),
@@ -184,344 +747,2213 @@ fn format_audit_response(
         )
         .as_str(),
     ]
-    .join("\n"));
+    .join("\n"))
 }
 
-pub async fn audit(
-    namespace: &str,
-    remote_url: &str,
-    long: bool,
-    verify: bool,
-    verifying_key: Option<&str>,
-    epoch: Option<&Epoch>,
-) -> Result<String> {
-    let client = PlexiClient::new(Url::parse(remote_url)?, None, Some(APP_USER_AGENT))?;
-    let epoch = match epoch {
-        Some(epoch) => epoch,
-        None => {
-            let Some(last_verified_epoch) = client.last_verified_epoch(namespace).await? else {
-                return Err(anyhow!(
-                    "namespace {namespace} does not have a latest epoch. Please specify one"
-                ));
-            };
-            &last_verified_epoch.epoch()
-        }
-    };
-    let Some(signature) = client.signature(namespace, epoch).await? else {
-        return Err(anyhow!(
-            "Signature not found for {namespace} at epoch {epoch}"
-        ));
-    };
-
-    // no verification requested, we can stop here
-    if !verify {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Disabled,
-            &VerificationStatus::Disabled,
+async fn check_freshness(
+    client: &PlexiClient,
+    signature: &SignatureResponse,
+    freshness: &FreshnessArgs,
+) -> Result<Option<String>> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+    let cadence = client
+        .namespace(signature.namespace())
+        .await?
+        .and_then(|info| info.metadata().cadence());
+    let fresh = signature
+        .verify_fresh(now, freshness.max_age(), freshness.clock_skew())
+        .and_then(|()| match cadence {
+            Some(cadence) => {
+                cadence.verify_with_skew(signature.timestamp(), now, freshness.clock_skew())
+            }
+            None => Ok(()),
+        });
+    if let Err(e) = fresh {
+        tracing::error!(
+            "{namespace} latest epoch is {epoch}: {e}",
+            namespace = signature.namespace(),
+            epoch = signature.epoch()
         );
+        return Ok(Some(e.to_string()));
     }
+    Ok(None)
+}
 
-    // verify the signature against the log signature
-    let config = client.auditor_config().await?;
-    let verifying_key = match verifying_key {
-        Some(key) => key,
-        None => {
-            let Some(key_id) = signature.key_id() else {
-                return format_audit_response(
-                    long,
-                    &signature,
-                    &VerificationStatus::Failed(
-                        "don't want to implement random key validation".to_string(),
-                    ),
-                    &VerificationStatus::Disabled,
-                );
-            };
-            let Some(key) = config
-                .keys()
-                .iter()
-                .find(|key_info| key_info.key_id() == key_id)
-            else {
-                return format_audit_response(
-                    long,
-                    &signature,
-                    &VerificationStatus::Failed(
-                        "auditor does not have key with key_id".to_string(),
-                    ),
-                    &VerificationStatus::Disabled,
-                );
-            };
+async fn verify_freshness(
+    client: &PlexiClient,
+    signature: &SignatureResponse,
+    freshness: &FreshnessArgs,
+) -> Result<VerificationStatus> {
+    Ok(match check_freshness(client, signature, freshness).await? {
+        Some(stale) => VerificationStatus::Failed(stale),
+        None => VerificationStatus::Success,
+    })
+}
 
-            key.public_key().as_str()
+const CACHE_LOCK_POISONED: &str = "verified epoch cache lock poisoned";
+
+struct EpochVerifier<'a> {
+    client: &'a PlexiClient,
+    namespace: &'a str,
+    verifying_key: Option<&'a str>,
+    migration_policy: MigrationPolicy,
+    log_directory: Option<&'a str>,
+    config: auditor::Configuration,
+    namespace_info: Option<NamespaceInfo>,
+    force: bool,
+    cache: Mutex<VerifiedCache>,
+    trail: AuditTrail,
+    progress: bool,
+}
+
+impl<'a> EpochVerifier<'a> {
+    async fn new(
+        client: &'a PlexiClient,
+        namespace: &'a str,
+        verifying_key: Option<&'a str>,
+        migration_policy: MigrationPolicy,
+        log_directory: Option<&'a str>,
+        force: bool,
+    ) -> Result<Self> {
+        let namespace_info = client.namespace(namespace).await?;
+        if let Some(root) = namespace_info.as_ref().and_then(NamespaceInfo::root) {
+            TrustStore::open_default()?.check(namespace, root)?;
         }
-    };
+        Ok(Self {
+            client,
+            namespace,
+            verifying_key,
+            migration_policy,
+            log_directory,
+            config: client.auditor_config().await?,
+            namespace_info,
+            force,
+            cache: Mutex::new(VerifiedCache::open_default()),
+            trail: AuditTrail::open_default(),
+            progress: true,
+        })
+    }
 
-    let Ok(verifying_key) = hex::decode(verifying_key) else {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Failed("auditor key is not valid hex".to_string()),
-            &VerificationStatus::Disabled,
-        );
-    };
+    fn without_progress(mut self) -> Self {
+        self.progress = false;
+        self
+    }
 
-    if signature.verify(&verifying_key).is_err() {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Failed(
-                "signature does not verify for the auditor key".to_string(),
-            ),
-            &VerificationStatus::Disabled,
+    #[tracing::instrument(skip_all, fields(namespace = self.namespace, epoch = %signature.epoch()))]
+    async fn verify(
+        &self,
+        signature: &SignatureResponse,
+        previous: Option<&SignatureResponse>,
+    ) -> Result<(VerificationStatus, VerificationStatus)> {
+        let (signature_verification_status, proof_verification_status) =
+            self.verify_statuses(signature, previous).await?;
+        record_verification(
+            &self.trail,
+            signature,
+            &signature_verification_status,
+            &proof_verification_status,
         );
+        Ok((signature_verification_status, proof_verification_status))
     }
 
-    // then download the proof and verify it
-    if log_enabled!(log::Level::Error) {
-        eprintln!("Audit proof verification enabled. It can take a few seconds");
-    }
+    async fn verify_statuses(
+        &self,
+        signature: &SignatureResponse,
+        previous: Option<&SignatureResponse>,
+    ) -> Result<(VerificationStatus, VerificationStatus)> {
+        let signature_verification_status = self.verify_signature(signature);
+        if let VerificationStatus::Failed(_) = signature_verification_status {
+            return Ok((signature_verification_status, VerificationStatus::Disabled));
+        }
 
-    let dots_handle = print_dots();
+        // proofs that verified during a previous run are not verified again
+        let (namespace, epoch, digest) = (self.namespace, signature.epoch(), signature.digest());
+        if !self.force
+            && self
+                .cache
+                .lock()
+                .expect(CACHE_LOCK_POISONED)
+                .contains(namespace, epoch, digest)
+        {
+            tracing::info!("proof of {namespace} epoch {epoch} already verified, use --force to verify it again");
+            return Ok((signature_verification_status, VerificationStatus::Success));
+        }
 
-    // given Cloudflare does not expose the proof at the time of writing, uses the log directory and assume it's formatted like what WhatsApp provides
-    let Some(namespace_info) = client.namespace(namespace).await? else {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Failed(format!("namespace {namespace} does not exist")),
-        );
-    };
-    // if the namespace does not have a log directory, it means it does not provide proofs
-    let Some(log_directory) = namespace_info.log_directory() else {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Disabled,
-        );
-    };
+        // then download the proof and verify it
+        if self.progress && tracing::enabled!(Level::ERROR) {
+            eprintln!("Audit proof verification enabled. It can take a few seconds");
+        }
 
-    // TODO: support namespace in the initialisation phase
-    let Some(root) = namespace_info.root() else {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Failed(format!("namespace {namespace} does not have a root")),
-        );
-    };
+        let dots_handle = self.progress.then(print_dots);
+        let proof_verification_status = self
+            .verify_proof(signature, previous, dots_handle.as_ref())
+            .await;
+        if let Some(dots_handle) = dots_handle {
+            dots_handle.abort();
+        }
+        let proof_verification_status = proof_verification_status?;
 
-    // First check if the epoch is the root or before root
-    let (root_epoch, root_digest) = {
-        let root_parts: Vec<&str> = root.split("/").collect();
-        if root_parts.len() != 2 {
-            return format_audit_response(
-                long,
-                &signature,
-                &VerificationStatus::Success,
-                &VerificationStatus::Failed(format!("namespace {namespace} has an invalid root")),
-            );
+        if let VerificationStatus::Success = proof_verification_status {
+            if let Err(e) = self
+                .cache
+                .lock()
+                .expect(CACHE_LOCK_POISONED)
+                .insert(namespace, epoch, digest)
+            {
+                tracing::warn!("cannot cache the verification of {namespace} epoch {epoch}: {e:#}");
+            }
         }
-        let epoch: Epoch = root_parts[0].parse()?;
-        let digest = hex::decode(root_parts[1])?;
-        (epoch, digest)
-    };
+        Ok((signature_verification_status, proof_verification_status))
+    }
 
-    if *signature.epoch() < root_epoch {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Failed("epoch cannot be before root".to_string()),
-        );
+    fn verify_signature(&self, signature: &SignatureResponse) -> VerificationStatus {
+        verify_signature_with_keys(
+            signature,
+            self.verifying_key,
+            self.config.keys(),
+            self.migration_policy,
+        )
     }
 
-    if *signature.epoch() == root_epoch {
-        if signature.digest() == root_digest {
-            return format_audit_response(
-                long,
-                &signature,
-                &VerificationStatus::Success,
-                &VerificationStatus::Success,
-            );
-        } else {
-            return format_audit_response(
-                long,
-                &signature,
-                &VerificationStatus::Success,
-                &VerificationStatus::Failed(
-                    "epoch is at root height but does not match root digest".to_string(),
-                ),
-            );
+    async fn verify_proof(
+        &self,
+        signature: &SignatureResponse,
+        previous: Option<&SignatureResponse>,
+        dots_handle: Option<&JoinHandle<()>>,
+    ) -> Result<VerificationStatus> {
+        let namespace = self.namespace;
+        let Some(namespace_info) = &self.namespace_info else {
+            return Ok(VerificationStatus::Failed(format!(
+                "namespace {namespace} does not exist"
+            )));
+        };
+        // given Cloudflare does not expose the proof at the time of writing, uses the log directory and assume it's formatted like what WhatsApp provides
+        // proofs are retrieved from the auditor first, then from the log directory if the namespace has one
+        let log_directory = self.log_directory.or(namespace_info.log_directory());
+        let proofs = AuditorProofs {
+            client: self.client,
+            directory: log_directory
+                .map(|directory| self.client.proof_store(directory))
+                .transpose()?,
+            dots_handle,
+        };
+
+        let mut options = VerifyOptions::new();
+        if dots_handle.is_some() {
+            options = options.progress(print_progress);
         }
+        let outcome = EpochChainVerifier::new(namespace_info, self.client, &proofs)
+            .require_proofs(log_directory.is_some())
+            .verify_with_options(signature, previous, options)
+            .await?;
+
+        if self.progress && tracing::enabled!(Level::ERROR) {
+            eprintln!();
+        }
+        Ok(outcome.into())
     }
+}
 
-    let previous_signature = client
-        .signature(namespace, &(*signature.epoch() - 1))
-        .await?
-        .expect("Epoch is not the root, there should be a previous signature");
+struct AuditorProofs<'a> {
+    client: &'a PlexiClient,
+    directory: Option<Box<dyn ProofStore>>,
+    dots_handle: Option<&'a JoinHandle<()>>,
+}
 
-    let Ok(current_hash) = signature.digest().try_into() else {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Failed("digest length invalid".to_string()),
+#[async_trait::async_trait]
+impl AuditProofSource for AuditorProofs<'_> {
+    async fn proof(&self, namespace: &str, blob: &ProofId) -> Result<Option<Vec<u8>>> {
+        let Some(proof) = self
+            .client
+            .proof(namespace, blob, self.directory.as_deref())
+            .await?
+        else {
+            return Ok(None);
+        };
+        tracing::info!(
+            "retrieved audit proof from {source} at {url}",
+            source = proof.source(),
+            url = proof.url()
         );
-    };
+        if let Some(dots_handle) = self.dots_handle {
+            dots_handle.abort();
+            if tracing::enabled!(Level::ERROR) {
+                eprintln!();
+            }
+        }
+        Ok(Some(proof.into_proof()))
+    }
+}
 
-    let Ok(previous_hash) = previous_signature.digest().try_into() else {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Failed("digest length invalid".to_string()),
-        );
+impl From<ChainOutcome> for VerificationStatus {
+    fn from(outcome: ChainOutcome) -> Self {
+        match outcome {
+            ChainOutcome::Root | ChainOutcome::Verified => Self::Success,
+            ChainOutcome::Unavailable => Self::Disabled,
+            ChainOutcome::Failed(e) => Self::Failed(e),
+        }
+    }
+}
+
+fn verify_signature_with_keys(
+    signature: &SignatureResponse,
+    verifying_key: Option<&str>,
+    keys: &[KeyInfo],
+    migration_policy: MigrationPolicy,
+) -> VerificationStatus {
+    let verifying_key = match verifying_key {
+        Some(key) => key,
+        None => {
+            let Some(key_id) = signature.key_id() else {
+                return VerificationStatus::Failed(
+                    "don't want to implement random key validation".to_string(),
+                );
+            };
+            let Some(key) = keys.iter().find(|key_info| key_info.key_id() == key_id) else {
+                return VerificationStatus::Failed(
+                    "auditor does not have key with key_id".to_string(),
+                );
+            };
+
+            key.public_key().as_str()
+        }
     };
 
-    let blob = AuditBlobName {
-        epoch: signature.epoch().into(),
-        previous_hash,
-        current_hash,
+    let Ok(verifying_key) = hex::decode(verifying_key) else {
+        return VerificationStatus::Failed("auditor key is not valid hex".to_string());
     };
-    let Some(raw_proof) = client.proof(&blob, Some(log_directory)).await? else {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Failed("cannot retrieve audit proof".to_string()),
+
+    if signature
+        .verify_with(&verifying_key, migration_policy)
+        .is_err()
+    {
+        return VerificationStatus::Failed(
+            "signature does not verify for the auditor key".to_string(),
         );
+    }
+    VerificationStatus::Success
+}
+
+fn decode_expected_digest(expected_digest: Option<&str>) -> Result<Option<Vec<u8>>> {
+    expected_digest
+        .map(|digest| {
+            hex::decode(digest).map_err(|_| usage(anyhow!("expected digest is not valid hex")))
+        })
+        .transpose()
+}
+
+fn check_expected_digest(
+    signature: &SignatureResponse,
+    expected_digest: Option<&[u8]>,
+) -> Option<VerificationStatus> {
+    let expected_digest = expected_digest?;
+    (!constant_time_eq(signature.digest(), expected_digest)).then(|| {
+        VerificationStatus::Failed(format!(
+            "digest {digest} is not the expected {expected}",
+            digest = hex::encode(signature.digest()),
+            expected = hex::encode(expected_digest)
+        ))
+    })
+}
+
+async fn canonical_namespace(client: &PlexiClient, namespace: &str) -> Result<String> {
+    // the auditor knows namespaces by their canonical name, not by their aliases
+    Ok(match client.namespace(namespace).await? {
+        Some(info) => info.name().to_string(),
+        None => namespace.to_string(),
+    })
+}
+
+async fn last_verified_epoch(
+    client: &PlexiClient,
+    namespace: &str,
+    verifying_key: Option<&str>,
+) -> Result<Option<LastVerifiedEpoch>> {
+    let Some(verifying_key) = verifying_key else {
+        return client.last_verified_epoch(namespace).await;
     };
+    let verifying_key = hex::decode(verifying_key)
+        .context("verifying key is not hex encoded")
+        .map_err(usage)?;
+    client
+        .verified_last_verified_epoch(namespace, &verifying_key)
+        .await
+}
 
-    let verification = auditor::verify_raw_proof(&blob, &raw_proof).await;
+pub struct AuditOptions {
+    pub long: bool,
+    pub output: OutputFormat,
+    pub verify: bool,
+    pub verifying_key: Option<String>,
+    pub migration_policy: MigrationPolicy,
+    pub log_directory: Option<String>,
+    pub force: bool,
+    pub freshness: FreshnessArgs,
+    pub expected_digest: Option<String>,
+    pub max_timestamp_gap: Option<Duration>,
+}
 
-    if log_enabled!(log::Level::Error) {
-        eprintln!();
+impl AuditOptions {
+    fn format(&self) -> AuditFormat {
+        AuditFormat::new(self.long, self.output)
     }
-    dots_handle.abort();
+}
 
-    if let Err(e) = verification {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Failed(e.to_string()),
-        );
-    }
+pub async fn audit(
+    namespace: &str,
+    remote: &Remote,
+    epoch: Option<&Epoch>,
+    options: &AuditOptions,
+) -> Result<String> {
+    let format = options.format();
+    let expected_digest = decode_expected_digest(options.expected_digest.as_deref())?;
+    let client = remote.client()?;
+    let (signature, signature_verification_status, proof_verification_status, freshness_status) =
+        audit_namespace(
+            &client,
+            namespace,
+            epoch,
+            options,
+            expected_digest.as_deref(),
+        )
+        .await?;
     format_audit_response(
-        long,
+        format,
         &signature,
-        &VerificationStatus::Success,
-        &VerificationStatus::Success,
+        &signature_verification_status,
+        &proof_verification_status,
+        &freshness_status,
     )
 }
 
-pub async fn audit_local(
-    verifying_key: Option<&str>,
-    long: bool,
-    verify: bool,
-    proof_path: Option<PathBuf>,
-    input: Option<PathBuf>,
+pub async fn audit_remotes(
+    namespace: &str,
+    remotes: &[Remote],
+    epoch: Option<&Epoch>,
+    options: &AuditOptions,
 ) -> Result<String> {
-    let src = file_or_stdin(input)?;
-    let signature: SignatureResponse = serde_json::from_reader(src)?;
+    if options.verifying_key.is_some() {
+        return Err(usage(anyhow!(
+            "a verifying key cannot be used with several auditors"
+        )));
+    }
+    let format = options.format();
+    let expected_digest = decode_expected_digest(options.expected_digest.as_deref())?;
+    let clients = remotes
+        .iter()
+        .map(|remote| Ok((remote.url(), remote.client()?)))
+        .collect::<Result<Vec<_>>>()?;
 
-    // no verification requested, we can stop here
-    if !verify {
-        return format_audit_response(
-            long,
+    let latest = epoch.is_none();
+    let epoch = match epoch {
+        Some(epoch) => *epoch,
+        None => {
+            let mut latest = u64::MAX;
+            for (url, client) in &clients {
+                let namespace = canonical_namespace(client, namespace).await?;
+                let Some(last_verified_epoch) = client.last_verified_epoch(&namespace).await?
+                else {
+                    return Err(usage(anyhow!(
+                        "{url} does not have a latest epoch for {namespace}. Please specify one"
+                    )));
+                };
+                latest = latest.min(u64::from(&last_verified_epoch.epoch()));
+            }
+            Epoch::from(latest)
+        }
+    };
+
+    let mut audits = vec![];
+    for (url, client) in &clients {
+        // each auditor signs with its own keys
+        let (signature, signature_verification_status, proof_verification_status, _) =
+            audit_namespace(
+                client,
+                namespace,
+                Some(&epoch),
+                options,
+                expected_digest.as_deref(),
+            )
+            .await?;
+        let freshness_status = if latest {
+            verify_freshness(client, &signature, &options.freshness).await?
+        } else {
+            VerificationStatus::Disabled
+        };
+        audits.push((
+            url,
+            signature,
+            signature_verification_status,
+            proof_verification_status,
+            freshness_status,
+        ));
+    }
+
+    let [(_, first, _, _, _), others @ ..] = audits.as_slice() else {
+        return Err(usage(anyhow!("at least one --remote-url is required")));
+    };
+    let diverging = others
+        .iter()
+        .any(|(_, signature, _, _, _)| !constant_time_eq(signature.digest(), first.digest()));
+
+    let output = match format {
+        AuditFormat::Structured(output) => serialize_output(
+            output,
+            &serde_json::json!({
+                "epoch": epoch,
+                "auditors": audits
+                    .iter()
+                    .map(|(url, signature, signature_status, proof_status, freshness_status)| {
+                        let mut audit = audit_to_json(signature, signature_status, proof_status);
+                        audit["freshness"] = freshness_status.to_json();
+                        audit["remote_url"] = serde_json::json!(url);
+                        audit
+                    })
+                    .collect::<Vec<_>>(),
+                "split_view": diverging,
+            }),
+        )?,
+        AuditFormat::Short | AuditFormat::Long => {
+            let mut sections = audits
+                .iter()
+                .map(
+                    |(url, signature, signature_status, proof_status, freshness_status)| {
+                        Ok(match format {
+                            AuditFormat::Long => format!(
+                                "{url}\n{details}",
+                                url = url.bold(),
+                                details = with_freshness(
+                                    format_audit_details(
+                                        signature,
+                                        signature_status,
+                                        proof_status
+                                    )?,
+                                    freshness_status
+                                )
+                            ),
+                            _ => format!(
+                                "{url}: {status}",
+                                status =
+                                    short_status(signature_status, proof_status, freshness_status)
+                            ),
+                        })
+                    },
+                )
+                .collect::<Result<Vec<_>>>()?;
+            sections.push(if diverging {
+                format!(
+                    "{}: auditors signed different digests for epoch {epoch}",
+                    "SPLIT VIEW".red().bold()
+                )
+            } else {
+                format!("{: <22}: agree", "Digests".bold())
+            });
+            sections.join(if let AuditFormat::Long = format {
+                "\n\n"
+            } else {
+                "\n"
+            })
+        }
+    };
+
+    if diverging {
+        return Err(VerificationFailure::divergence(output).into());
+    }
+    check_fresh(
+        check_verification(
+            output,
+            audits
+                .iter()
+                .any(|(_, _, signature_status, _, _)| signature_status.is_failed()),
+            audits
+                .iter()
+                .any(|(_, _, _, proof_status, _)| proof_status.is_failed()),
+        ),
+        audits
+            .iter()
+            .any(|(_, _, _, _, freshness_status)| freshness_status.is_failed()),
+    )
+}
+
+async fn audit_namespace(
+    client: &PlexiClient,
+    namespace: &str,
+    epoch: Option<&Epoch>,
+    options: &AuditOptions,
+    expected_digest: Option<&[u8]>,
+) -> Result<(
+    SignatureResponse,
+    VerificationStatus,
+    VerificationStatus,
+    VerificationStatus,
+)> {
+    let verifying_key = options.verifying_key.as_deref();
+    let namespace = &canonical_namespace(client, namespace).await?;
+    let latest = epoch.is_none();
+    let epoch = match epoch {
+        Some(epoch) => epoch,
+        None => {
+            let Some(last_verified_epoch) =
+                last_verified_epoch(client, namespace, verifying_key).await?
+            else {
+                return Err(usage(anyhow!(
+                    "namespace {namespace} does not have a latest epoch. Please specify one"
+                )));
+            };
+            &last_verified_epoch.epoch()
+        }
+    };
+    let Some(signature) = client.signature(namespace, epoch).await? else {
+        return Err(anyhow!(
+            "Signature not found for {namespace} at epoch {epoch}"
+        ));
+    };
+    let freshness_status = if latest {
+        verify_freshness(client, &signature, &options.freshness).await?
+    } else {
+        VerificationStatus::Disabled
+    };
+    if let Some(status) = check_expected_digest(&signature, expected_digest) {
+        record_verification(
+            &AuditTrail::open_default(),
             &signature,
-            &VerificationStatus::Disabled,
+            &status,
             &VerificationStatus::Disabled,
         );
+        return Ok((
+            signature,
+            status,
+            VerificationStatus::Disabled,
+            freshness_status,
+        ));
     }
 
-    // verify the signature against the log signature
-    let verifying_key = match verifying_key {
-        Some(key) => key,
-        None => {
-            return format_audit_response(
-                long,
-                &signature,
-                &VerificationStatus::Failed("auditor does not have key with key_id".to_string()),
-                &VerificationStatus::Disabled,
-            );
+    // no verification requested, we can stop here
+    if !options.verify {
+        return Ok((
+            signature,
+            VerificationStatus::Disabled,
+            VerificationStatus::Disabled,
+            freshness_status,
+        ));
+    }
+
+    let verifier = EpochVerifier::new(
+        client,
+        namespace,
+        verifying_key,
+        options.migration_policy,
+        options.log_directory.as_deref(),
+        options.force,
+    )
+    .await?;
+    let (signature_verification_status, proof_verification_status) =
+        verifier.verify(&signature, None).await?;
+    Ok((
+        signature,
+        signature_verification_status,
+        proof_verification_status,
+        freshness_status,
+    ))
+}
+
+pub async fn audit_batch(
+    input: impl io::BufRead,
+    remote: &Remote,
+    epoch: Option<&Epoch>,
+    options: &AuditOptions,
+) -> Result<String> {
+    let output = options.output;
+    let client = remote.client()?;
+    let (mut signature_failed, mut proof_failed, mut stale, mut errors, mut total) =
+        (false, false, false, 0, 0);
+    for namespace in input.lines() {
+        let namespace = namespace?;
+        let namespace = namespace.trim();
+        if namespace.is_empty() {
+            continue;
+        }
+        total += 1;
+
+        let line = match audit_namespace(&client, namespace, epoch, options, None).await {
+            Ok((
+                signature,
+                signature_verification_status,
+                proof_verification_status,
+                freshness_status,
+            )) => {
+                signature_failed |= signature_verification_status.is_failed();
+                proof_failed |= proof_verification_status.is_failed();
+                stale |= freshness_status.is_failed();
+                match output {
+                    OutputFormat::Json | OutputFormat::Yaml => {
+                        let mut audit = audit_to_json(
+                            &signature,
+                            &signature_verification_status,
+                            &proof_verification_status,
+                        );
+                        audit["freshness"] = freshness_status.to_json();
+                        audit["namespace"] = namespace.into();
+                        serialize_line(output, &audit)?
+                    }
+                    OutputFormat::Text => format!(
+                        "{namespace}: {status}",
+                        status = short_status(
+                            &signature_verification_status,
+                            &proof_verification_status,
+                            &freshness_status
+                        )
+                    ),
+                }
+            }
+            Err(e) => {
+                errors += 1;
+                match output {
+                    OutputFormat::Json | OutputFormat::Yaml => serialize_line(
+                        output,
+                        &serde_json::json!({ "namespace": namespace, "error": e.to_string() }),
+                    )?,
+                    OutputFormat::Text => format!("{namespace}: error - {e}"),
+                }
+            }
+        };
+        print_output(&line);
+    }
+
+    // lines are already output
+    let output = check_verification(String::new(), signature_failed, proof_failed)?;
+    if errors > 0 {
+        return Err(anyhow!("cannot audit {errors} of {total} namespaces"));
+    }
+    check_fresh(Ok(output), stale)
+}
+
+pub async fn audit_all(
+    remote: &Remote,
+    filter: &NamespaceFilter,
+    options: &AuditOptions,
+) -> Result<String> {
+    const HEADERS: [&str; 5] = ["Namespace", "Epoch", "Signature", "Proof", "Freshness"];
+
+    let output = options.output;
+    let client = remote.client()?;
+    let namespaces = client.namespaces_matching(filter).await?;
+    let (mut signature_failed, mut proof_failed, mut stale, mut errors) = (false, false, false, 0);
+    let mut audits = vec![];
+    for info in namespaces.iter() {
+        let namespace = info.name();
+        let audit = audit_namespace(&client, namespace, None, options, None).await;
+        match &audit {
+            Ok((_, signature_verification_status, proof_verification_status, freshness_status)) => {
+                signature_failed |= signature_verification_status.is_failed();
+                proof_failed |= proof_verification_status.is_failed();
+                stale |= freshness_status.is_failed();
+            }
+            Err(e) => {
+                errors += 1;
+                tracing::error!("cannot audit {namespace}: {e:#}");
+            }
+        }
+        audits.push((namespace, audit));
+    }
+
+    let output = match output {
+        OutputFormat::Json | OutputFormat::Yaml => serialize_output(
+            output,
+            &audits
+                .iter()
+                .map(|(namespace, audit)| match audit {
+                    Ok((
+                        signature,
+                        signature_verification_status,
+                        proof_verification_status,
+                        freshness_status,
+                    )) => {
+                        let mut audit = audit_to_json(
+                            signature,
+                            signature_verification_status,
+                            proof_verification_status,
+                        );
+                        audit["freshness"] = freshness_status.to_json();
+                        audit["namespace"] = (*namespace).into();
+                        audit
+                    }
+                    Err(e) => serde_json::json!({ "namespace": namespace, "error": e.to_string() }),
+                })
+                .collect::<Vec<_>>(),
+        )?,
+        OutputFormat::Text => {
+            let rows = audits
+                .iter()
+                .map(|(namespace, audit)| match audit {
+                    Ok((
+                        signature,
+                        signature_verification_status,
+                        proof_verification_status,
+                        freshness_status,
+                    )) => [
+                        namespace.to_string(),
+                        signature.epoch().to_string(),
+                        signature_verification_status.to_string(),
+                        proof_verification_status.to_string(),
+                        freshness_status.to_string(),
+                    ],
+                    Err(e) => [
+                        namespace.to_string(),
+                        "-".to_string(),
+                        format!("error - {e}"),
+                        "-".to_string(),
+                        "-".to_string(),
+                    ],
+                })
+                .collect::<Vec<_>>();
+            format_table(HEADERS, &rows)
         }
     };
 
-    let Ok(verifying_key) = hex::decode(verifying_key) else {
-        return format_audit_response(
-            long,
+    if errors > 0 && !signature_failed && !proof_failed {
+        // the namespaces that could be audited are output nonetheless
+        print_output(&output);
+        return Err(anyhow!(
+            "cannot audit {errors} of {total} namespaces",
+            total = audits.len()
+        ));
+    }
+    check_fresh(
+        check_verification(output, signature_failed, proof_failed),
+        stale,
+    )
+}
+
+pub async fn audit_range(
+    namespace: &str,
+    remote: &Remote,
+    from: &Epoch,
+    to: &Epoch,
+    workers: usize,
+    options: &AuditOptions,
+) -> Result<String> {
+    if from > to {
+        return Err(usage(anyhow!("range start {from} is after range end {to}")));
+    }
+    let format = options.format();
+    let client = remote.client()?;
+    let namespace = &canonical_namespace(&client, namespace).await?;
+    let verifier = if options.verify {
+        Some(
+            EpochVerifier::new(
+                &client,
+                namespace,
+                options.verifying_key.as_deref(),
+                options.migration_policy,
+                options.log_directory.as_deref(),
+                options.force,
+            )
+            .await?
+            .without_progress(),
+        )
+    } else {
+        None
+    };
+
+    // signatures are fetched first, for each proof to start from the digest of the epoch before
+    let mut signatures = vec![];
+    for epoch in u64::from(from)..=u64::from(to) {
+        let epoch = Epoch::from(epoch);
+        let Some(signature) = client.signature(namespace, &epoch).await? else {
+            return Err(anyhow!(
+                "Signature not found for {namespace} at epoch {epoch}"
+            ));
+        };
+        signatures.push(signature);
+    }
+
+    // up to `workers` proofs are downloaded and verified at once, their results kept in epoch order
+    let progress = &RangeProgress::new(signatures.len());
+    let verifier = verifier.as_ref();
+    let statuses: Vec<_> = stream::iter(signatures.iter().enumerate())
+        .map(|(i, signature)| {
+            let previous = i.checked_sub(1).map(|i| &signatures[i]);
+            async move {
+                let statuses = match verifier {
+                    Some(verifier) => verifier.verify(signature, previous).await?,
+                    None => (VerificationStatus::Disabled, VerificationStatus::Disabled),
+                };
+                progress.inc();
+                anyhow::Ok(statuses)
+            }
+        })
+        .buffered(workers.max(1))
+        .try_collect()
+        .await?;
+    progress.finish();
+
+    // a log or an auditor rewriting history is unlikely to keep timestamps consistent from one epoch to the next
+    let mut statuses = statuses;
+    if options.verify {
+        for (i, pair) in signatures.windows(2).enumerate() {
+            let status = &mut statuses[i + 1].0;
+            if status.is_failed() {
+                continue;
+            }
+            if let Err(e) = pair[1].verify_timestamp_after(&pair[0], options.max_timestamp_gap) {
+                *status = VerificationStatus::Failed(e.to_string());
+            }
+        }
+    }
+
+    let audits: Vec<_> = signatures
+        .into_iter()
+        .zip(statuses)
+        .map(
+            |(signature, (signature_verification_status, proof_verification_status))| {
+                (
+                    signature,
+                    signature_verification_status,
+                    proof_verification_status,
+                )
+            },
+        )
+        .collect();
+    format_range_response(format, from, to, &audits)
+}
+
+trait ProofReader: io::Read + io::Seek + Send {}
+
+impl<T: io::Read + io::Seek + Send> ProofReader for T {}
+
+pub async fn audit_local(
+    input: Input,
+    proof: Option<Input>,
+    akd_config: AkdConfig,
+    options: &AuditOptions,
+) -> Result<String> {
+    let format = options.format();
+    let expected_digest = decode_expected_digest(options.expected_digest.as_deref())?;
+    let src = input.reader().await?;
+    let signature: SignatureResponse = serde_json::from_reader(src)
+        .context("cannot parse signature")
+        .map_err(usage)?;
+
+    if let Some(status) = check_expected_digest(&signature, expected_digest.as_deref()) {
+        record_verification(
+            &AuditTrail::open_default(),
             &signature,
-            &VerificationStatus::Failed("auditor key is not valid hex".to_string()),
+            &status,
             &VerificationStatus::Disabled,
         );
-    };
-
-    if signature.verify(&verifying_key).is_err() {
         return format_audit_response(
-            long,
+            format,
             &signature,
-            &VerificationStatus::Failed(
-                "signature does not verify for the auditor key".to_string(),
-            ),
+            &status,
+            &VerificationStatus::Disabled,
             &VerificationStatus::Disabled,
         );
     }
 
-    let Some(proof_path) = proof_path else {
+    // no verification requested, we can stop here
+    if !options.verify {
         return format_audit_response(
-            long,
+            format,
             &signature,
-            &VerificationStatus::Success,
+            &VerificationStatus::Disabled,
+            &VerificationStatus::Disabled,
             &VerificationStatus::Disabled,
         );
+    }
+
+    let (signature_verification_status, proof_verification_status) = verify_local(
+        &signature,
+        options.verifying_key.as_deref(),
+        options.migration_policy,
+        proof,
+        akd_config,
+    )
+    .await?;
+    record_verification(
+        &AuditTrail::open_default(),
+        &signature,
+        &signature_verification_status,
+        &proof_verification_status,
+    );
+    format_audit_response(
+        format,
+        &signature,
+        &signature_verification_status,
+        &proof_verification_status,
+        &VerificationStatus::Disabled,
+    )
+}
+
+async fn verify_local(
+    signature: &SignatureResponse,
+    verifying_key: Option<&str>,
+    migration_policy: MigrationPolicy,
+    proof: Option<Input>,
+    akd_config: AkdConfig,
+) -> Result<(VerificationStatus, VerificationStatus)> {
+    // verify the signature against the log signature
+    let Some(verifying_key) = verifying_key else {
+        return Ok((
+            VerificationStatus::Failed("auditor does not have key with key_id".to_string()),
+            VerificationStatus::Disabled,
+        ));
     };
 
-    let mut src = fs::File::open(proof_path).context("cannot read input file")?;
+    let Ok(verifying_key) = hex::decode(verifying_key) else {
+        return Ok((
+            VerificationStatus::Failed("auditor key is not valid hex".to_string()),
+            VerificationStatus::Disabled,
+        ));
+    };
 
-    let mut raw_proof = vec![];
-    if let Err(e) = src.read_to_end(&mut raw_proof) {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Failed(e.to_string()),
-        );
+    if signature
+        .verify_with(&verifying_key, migration_policy)
+        .is_err()
+    {
+        return Ok((
+            VerificationStatus::Failed("signature does not verify for the auditor key".to_string()),
+            VerificationStatus::Disabled,
+        ));
+    }
+
+    let Some(proof) = proof else {
+        return Ok((VerificationStatus::Success, VerificationStatus::Disabled));
     };
-    let raw_proof = raw_proof;
-    let blob = AuditBlobName {
-        epoch: signature.epoch().into(),
-        previous_hash: auditor::compute_start_root_hash(&raw_proof).await?,
-        current_hash: signature.digest().as_slice().try_into()?,
+
+    let src: Box<dyn ProofReader> = match proof {
+        Input::Path(path) => Box::new(
+            fs::File::open(path)
+                .context("cannot read input file")
+                .map_err(usage)?,
+        ),
+        // verification seeks through the proof, so it is downloaded first
+        Input::Url(remote) => Box::new(io::Cursor::new(fetch(&remote).await?)),
+        Input::Stdin => return Err(usage(anyhow!("the proof cannot be read from stdin"))),
     };
 
-    if log_enabled!(log::Level::Error) {
+    if tracing::enabled!(Level::ERROR) {
         eprintln!("Audit proof verification enabled. It can take a few seconds");
     }
-    let dots_handle = print_dots();
-
-    let verification = auditor::verify_raw_proof(&blob, &raw_proof).await;
+    // the proof is read from the file as it is verified, so that large epochs do not have to fit in memory
+    let verification = auditor::compute_root_hashes_from_reader(
+        akd_config,
+        signature.epoch().into(),
+        src,
+        VerifyOptions::new()
+            .progress(print_progress)
+            .limits(local_proof_limits()),
+    )
+    .await
+    .and_then(|(_, end_hash)| {
+        if !constant_time_eq(&end_hash, signature.digest()) {
+            return Err(anyhow!("proof does not end at the digest of the signature"));
+        }
+        Ok(())
+    });
 
-    if log_enabled!(log::Level::Error) {
+    if tracing::enabled!(Level::ERROR) {
         eprintln!();
     }
-    dots_handle.abort();
 
-    if let Err(e) = verification {
-        return format_audit_response(
-            long,
-            &signature,
-            &VerificationStatus::Success,
-            &VerificationStatus::Failed(e.to_string()),
-        );
+    match verification {
+        Ok(()) => Ok((VerificationStatus::Success, VerificationStatus::Success)),
+        Err(e) => Ok((
+            VerificationStatus::Success,
+            VerificationStatus::Failed(e.to_string()),
+        )),
     }
+}
+
+fn blob_from_path(path: &Path) -> Option<ProofId> {
+    let components: Vec<_> = path
+        .components()
+        .rev()
+        .take(3)
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    if components.len() != 3 {
+        return None;
+    }
+    let name = [components[2], components[1], components[0]].join("/");
+    name.parse().ok()
+}
+
+pub async fn bundle_create(
+    namespace: &str,
+    remote: &Remote,
+    epoch: Option<&Epoch>,
+    log_directory: Option<&str>,
+    bundle_path: &Path,
+) -> Result<String> {
+    let client = remote.client()?;
+    let namespace = &canonical_namespace(&client, namespace).await?;
+    let Some(namespace_info) = client.namespace(namespace).await? else {
+        return Err(usage(anyhow!("namespace {namespace} does not exist")));
+    };
+    let epoch = match epoch {
+        Some(epoch) => *epoch,
+        None => {
+            let Some(last_verified_epoch) = client.last_verified_epoch(namespace).await? else {
+                return Err(usage(anyhow!(
+                    "namespace {namespace} does not have a latest epoch. Please specify one"
+                )));
+            };
+            last_verified_epoch.epoch()
+        }
+    };
+    let Some(signature) = client.signature(namespace, &epoch).await? else {
+        return Err(anyhow!(
+            "Signature not found for {namespace} at epoch {epoch}"
+        ));
+    };
+
+    // the proof starts from the digest of the epoch before, which the root of the namespace does not need
+    let previous_signature = if epoch.is_first() {
+        None
+    } else {
+        client.signature(namespace, &(epoch - 1)).await?
+    };
+    let proof = match &previous_signature {
+        Some(previous_signature) => {
+            let blob = ProofId {
+                epoch: epoch.into(),
+                previous_hash: previous_signature.digest().try_into()?,
+                current_hash: signature.digest().try_into()?,
+            };
+            let directory = log_directory
+                .or(namespace_info.log_directory())
+                .map(|directory| client.proof_store(directory))
+                .transpose()?;
+            client
+                .proof(namespace, &blob, directory.as_deref())
+                .await?
+                .map(ProofResponse::into_proof)
+        }
+        None => None,
+    };
+    if proof.is_none() {
+        tracing::warn!("cannot retrieve the audit proof of {namespace} epoch {epoch}, the bundle only verifies at the root");
+    }
+
+    let keys = client.auditor_config().await?.keys().clone();
+    Bundle::new(signature, previous_signature, proof, keys, namespace_info).write(bundle_path)?;
+    Ok(format!(
+        "Bundle of {namespace} epoch {epoch} written to {path}",
+        path = bundle_path.display()
+    ))
+}
+
+pub async fn bundle_verify(
+    verifying_key: Option<&str>,
+    migration_policy: MigrationPolicy,
+    long: bool,
+    output: OutputFormat,
+    bundle_path: &Path,
+) -> Result<String> {
+    let format = AuditFormat::new(long, output);
+    let bundle = Bundle::read(bundle_path)?;
+    let signature = bundle.signature();
+
+    let signature_verification_status =
+        verify_signature_with_keys(signature, verifying_key, bundle.keys(), migration_policy);
+    let proof_verification_status = if signature_verification_status.is_failed() {
+        VerificationStatus::Disabled
+    } else {
+        verify_bundle_proof(&bundle, verifying_key, migration_policy).await?
+    };
+    record_verification(
+        &AuditTrail::open_default(),
+        signature,
+        &signature_verification_status,
+        &proof_verification_status,
+    );
+
     format_audit_response(
-        long,
-        &signature,
-        &VerificationStatus::Success,
-        &VerificationStatus::Success,
+        format,
+        signature,
+        &signature_verification_status,
+        &proof_verification_status,
+        &VerificationStatus::Disabled,
+    )
+}
+
+async fn verify_bundle_proof(
+    bundle: &Bundle,
+    verifying_key: Option<&str>,
+    migration_policy: MigrationPolicy,
+) -> Result<VerificationStatus> {
+    let namespace_info = bundle.namespace();
+    let namespace = namespace_info.name();
+    let signature = bundle.signature();
+    let Some(root) = namespace_info
+        .root_for_epoch(signature.epoch())
+        .or(namespace_info.root())
+    else {
+        return Ok(VerificationStatus::Failed(format!(
+            "namespace {namespace} does not have a root"
+        )));
+    };
+    if let Some(outcome) = auditor::verify_against_root(root, signature) {
+        return Ok(outcome.into());
+    }
+
+    // the digest the proof starts from is only trusted once the auditor signature on it verifies
+    let Some(previous_signature) = bundle.previous_signature() else {
+        return Ok(VerificationStatus::Failed(
+            "bundle does not contain the signature of the epoch before".to_string(),
+        ));
+    };
+    if previous_signature.namespace() != signature.namespace()
+        || *previous_signature.epoch() != *signature.epoch() - 1
+    {
+        return Ok(VerificationStatus::Failed(
+            "bundle previous signature is not of the epoch before".to_string(),
+        ));
+    }
+    if let VerificationStatus::Failed(e) = verify_signature_with_keys(
+        previous_signature,
+        verifying_key,
+        bundle.keys(),
+        migration_policy,
+    ) {
+        return Ok(VerificationStatus::Failed(format!(
+            "signature of the epoch before: {e}"
+        )));
+    }
+
+    let Some(raw_proof) = bundle.proof() else {
+        return Ok(VerificationStatus::Failed(
+            "bundle does not contain the audit proof".to_string(),
+        ));
+    };
+    let (Ok(previous_hash), Ok(current_hash)) = (
+        previous_signature.digest().try_into(),
+        signature.digest().try_into(),
+    ) else {
+        return Ok(VerificationStatus::Failed(
+            "digest length invalid".to_string(),
+        ));
+    };
+    let blob = ProofId {
+        epoch: signature.epoch().into(),
+        previous_hash,
+        current_hash,
+    };
+
+    if tracing::enabled!(Level::ERROR) {
+        eprintln!("Audit proof verification enabled. It can take a few seconds");
+    }
+    let verification = auditor::verify_raw_proof_with_options(
+        namespace_info.akd_config(),
+        &blob,
+        raw_proof,
+        VerifyOptions::new().progress(print_progress),
+    )
+    .await;
+    if tracing::enabled!(Level::ERROR) {
+        eprintln!();
+    }
+
+    if let Err(e) = verification {
+        return Ok(VerificationStatus::Failed(e.to_string()));
+    }
+    Ok(VerificationStatus::Success)
+}
+
+fn local_proof_limits() -> ProofLimits {
+    ProofLimits::new().max_size(usize::MAX)
+}
+
+pub fn proof_inspect(
+    akd_config: AkdConfig,
+    blob: Option<&str>,
+    output: OutputFormat,
+    input: Option<PathBuf>,
+) -> Result<String> {
+    let blob = match blob {
+        Some(blob) => Some(
+            blob.parse::<ProofId>()
+                .map_err(|e| usage(anyhow!("invalid blob name {blob}: {e}")))?,
+        ),
+        None => input.as_deref().and_then(blob_from_path),
+    };
+
+    let summary = auditor::inspect_proof_from_reader(
+        akd_config,
+        blob.as_ref(),
+        file_or_stdin(input)?,
+        &local_proof_limits(),
+    )?;
+    if output != OutputFormat::Text {
+        return serialize_output(output, &summary);
+    }
+
+    let optional_digest = |digest: Option<[u8; 32]>| digest.map_or("-".to_string(), hex::encode);
+    Ok([
+        "Proof".to_string(),
+        format!(
+            "  {: <15}: {epoch}",
+            "Epoch height".bold(),
+            epoch = summary
+                .epoch()
+                .map_or("-".to_string(), |epoch| epoch.to_string())
+        ),
+        format!(
+            "  {: <15}: {digest}",
+            "Previous digest".bold(),
+            digest = optional_digest(summary.previous_hash())
+        ),
+        format!(
+            "  {: <15}: {digest}",
+            "Current digest".bold(),
+            digest = optional_digest(summary.current_hash())
+        ),
+        format!(
+            "  {: <15}: {size} bytes",
+            "Size".bold(),
+            size = summary.size()
+        ),
+        format!(
+            "  {: <15}: {inserted}",
+            "Inserted nodes".bold(),
+            inserted = summary.inserted()
+        ),
+        format!(
+            "  {: <15}: {unchanged}",
+            "Unchanged nodes".bold(),
+            unchanged = summary.unchanged()
+        ),
+        format!(
+            "  {: <15}: {akd_config}",
+            "AKD config".bold(),
+            akd_config = summary.akd_config()
+        ),
+        format!(
+            "  {: <15}: {algorithm}",
+            "Hash algorithm".bold(),
+            algorithm = summary
+                .hash_algorithm()
+                .map_or("-", |algorithm| algorithm.name())
+        ),
+    ]
+    .join("\n"))
+}
+
+pub fn log_verify(path: Option<&Path>, output: OutputFormat) -> Result<String> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => AuditTrail::default_path()
+            .ok_or_else(|| usage(anyhow!("no default audit trail, provide its path")))?,
+    };
+    let summary = trail::verify(&path)?;
+    if output != OutputFormat::Text {
+        return serialize_line(output, &summary);
+    }
+    Ok(format!(
+        "{path}: {entries} entries verified, last hash {last_hash}",
+        path = path.display(),
+        entries = summary.entries,
+        last_hash = hex::encode(&summary.last_hash)
+    ))
+}
+
+pub async fn report_submit(
+    remote: &Remote,
+    dry_run: bool,
+    provider_key: Option<&str>,
+    output: OutputFormat,
+    input: Option<PathBuf>,
+) -> Result<String> {
+    let report: Report = serde_json::from_reader(file_or_stdin(input)?)
+        .context("cannot parse report")
+        .map_err(usage)?;
+    let (namespace, epoch) = (report.namespace(), report.epoch());
+    let client = remote.client()?;
+
+    if !dry_run {
+        let response = client.submit_report(&report).await?;
+        return Ok(match output {
+            OutputFormat::Json | OutputFormat::Yaml => serialize_output(output, &response)?,
+            OutputFormat::Text => format!(
+                "submitted report {id} for {namespace} epoch {epoch}",
+                id = response.id()
+            ),
+        });
+    }
+
+    let provider_key = provider_key
+        .map(hex::decode)
+        .transpose()
+        .map_err(|_| usage(anyhow!("provider key is not valid hex")))?;
+    client
+        .validate_report(&report, provider_key.as_deref())
+        .await?;
+    Ok(match output {
+        OutputFormat::Json | OutputFormat::Yaml => serialize_output(
+            output,
+            &serde_json::json!({
+                "namespace": namespace,
+                "epoch": epoch,
+                "valid": true,
+                "submitted": false,
+            }),
+        )?,
+        OutputFormat::Text => {
+            format!("report for {namespace} epoch {epoch} is valid, it was not submitted")
+        }
+    })
+}
+
+pub fn trust_reset(namespace: Option<&str>) -> Result<String> {
+    let path = TrustStore::default_path()
+        .ok_or_else(|| anyhow!("no trusted roots, as there is no state directory"))?;
+    let forgotten = TrustStore::open(&path)?.reset(namespace)?;
+    Ok(match namespace {
+        Some(namespace) if forgotten == 0 => format!("no trusted root for {namespace}"),
+        Some(namespace) => format!("forgot the trusted root of {namespace}"),
+        None => format!("forgot {forgotten} trusted roots"),
+    })
+}
+
+async fn signer(args: &SignerArgs) -> Result<Box<dyn Signer>> {
+    if let Some(path) = &args.signing_key_path {
+        return Ok(Box::new(read_signing_key(path, &args.passphrase)?));
+    }
+
+    #[cfg(feature = "pkcs11")]
+    if let Some(module) = &args.pkcs11_module {
+        let (Some(token), Some(key)) = (&args.pkcs11_token, &args.pkcs11_key) else {
+            return Err(anyhow!("--pkcs11-token and --pkcs11-key are required"));
+        };
+        let pin = args
+            .pkcs11_pin
+            .as_deref()
+            .ok_or_else(|| anyhow!("--pkcs11-pin or PLEXI_PKCS11_PIN is required"))?;
+        return Ok(Box::new(plexi_core::signer::Pkcs11Signer::new(
+            module, token, pin, key,
+        )?));
+    }
+
+    #[cfg(feature = "kms")]
+    if let Some(key_id) = &args.aws_kms_key_id {
+        return Ok(Box::new(
+            plexi_core::signer::AwsKmsSigner::from_env(reqwest::Client::new(), key_id).await?,
+        ));
+    }
+
+    #[cfg(feature = "kms")]
+    if let Some(key_version) = &args.gcp_kms_key_version {
+        return Ok(Box::new(
+            plexi_core::signer::GcpKmsSigner::from_env(reqwest::Client::new(), key_version).await?,
+        ));
+    }
+
+    #[cfg(feature = "yubikey")]
+    if let Some(slot) = args.yubikey_slot {
+        let pin = args
+            .yubikey_pin
+            .as_deref()
+            .ok_or_else(|| anyhow!("--yubikey-pin or PLEXI_YUBIKEY_PIN is required"))?;
+        let signer = plexi_core::signer::YubiKeySigner::new(&args.ykcs11_module, slot, pin)?
+            .on_touch(|| eprintln!("Touch your YubiKey to sign"));
+        return Ok(Box::new(signer));
+    }
+
+    Err(anyhow!("a signing key is required, see plexi sign --help"))
+}
+
+pub fn gen_vectors(ciphersuite: &Ciphersuite, count: usize, seed: u64) -> Result<String> {
+    let vectors = testing::generate_vectors(ciphersuite, count, seed).map_err(usage)?;
+    Ok(serde_json::to_string_pretty(&vectors)?)
+}
+
+pub fn keygen(path: &Path, force: bool, passphrase: Option<&PassphraseArgs>) -> Result<String> {
+    let mut secret_key = [0u8; SECRET_KEY_LENGTH];
+    getrandom::getrandom(&mut secret_key).map_err(|e| anyhow!("cannot generate key: {e}"))?;
+    let signer = Ed25519Signer::new(&secret_key);
+    let contents = match passphrase {
+        Some(passphrase) => encrypt_signing_key(&secret_key, passphrase)?,
+        None => hex::encode(secret_key).into_bytes(),
+    };
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("cannot create {path}", path = path.display()))
+        .map_err(usage)?;
+    file.write_all(&contents)?;
+
+    Ok([
+        format!(
+            "{: <13}: {verifying_key}",
+            "Verifying key".bold(),
+            verifying_key = hex::encode(signer.verifying_key())
+        ),
+        format!(
+            "{: <13}: {key_id}",
+            "Key ID".bold(),
+            key_id = signer.key_id()
+        ),
+    ]
+    .join("\n"))
+}
+
+pub async fn sign(
+    namespace: &str,
+    epoch: &Epoch,
+    digest: &str,
+    ciphersuite: &Ciphersuite,
+    timestamp: Option<u64>,
+    signer_args: &SignerArgs,
+) -> Result<String> {
+    let digest = hex::decode(digest).map_err(|_| usage(anyhow!("digest is not valid hex")))?;
+    let timestamp = match timestamp {
+        Some(timestamp) => timestamp,
+        None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64,
+    };
+    let message =
+        SignatureMessage::new(ciphersuite, namespace.to_string(), timestamp, epoch, digest)?;
+    let signer = signer(signer_args).await?;
+    let signature = sign_message(signer.as_ref(), &message).await?;
+    Ok(serde_json::to_string_pretty(&signature)?)
+}
+
+pub fn verify_signature(
+    key: &str,
+    message: Option<(&Path, &str)>,
+    migration_policy: MigrationPolicy,
+    output: OutputFormat,
+    input: Option<PathBuf>,
+) -> Result<String> {
+    let key_bytes: [u8; PUBLIC_KEY_LENGTH] = hex::decode(key)
+        .map_err(|_| usage(anyhow!("key is not valid hex")))?
+        .try_into()
+        .map_err(|_| usage(anyhow!("key should have length {PUBLIC_KEY_LENGTH}")))?;
+    let verifying_key = VerifyingKey::from_bytes(&key_bytes)
+        .map_err(|_| usage(anyhow!("key is not a valid Ed25519 public key")))?;
+
+    let (message, status) = match message {
+        Some((path, signature)) => {
+            let message = fs::read(path)
+                .with_context(|| format!("reading {path}", path = path.display()))
+                .map_err(usage)?;
+            let signature = hex::decode(signature)
+                .ok()
+                .and_then(|signature| Signature::from_slice(&signature).ok())
+                .ok_or_else(|| usage(anyhow!("signature is not a valid Ed25519 signature")))?;
+            let status = match verifying_key.verify_strict(&message, &signature) {
+                Ok(()) => VerificationStatus::Success,
+                Err(_) => {
+                    VerificationStatus::Failed("signature does not verify for the key".to_string())
+                }
+            };
+            (message, status)
+        }
+        None => {
+            let signature: SignatureResponse = serde_json::from_reader(file_or_stdin(input)?)
+                .context("cannot parse signature")
+                .map_err(usage)?;
+            // the message is output as the verifier encodes it, to compare with the one the signer encoded
+            let message = SignatureMessage::from(&signature)
+                .to_vec()
+                .context("encoding the signature message")
+                .map_err(usage)?;
+            let status = match signature.verify_with(&key_bytes, migration_policy) {
+                Ok(()) => VerificationStatus::Success,
+                Err(e) => VerificationStatus::Failed(format!("{e:#}")),
+            };
+            (message, status)
+        }
+    };
+
+    let key_id = ed25519_public_key_to_key_id(&key_bytes);
+    let output = match output {
+        OutputFormat::Json | OutputFormat::Yaml => serialize_output(
+            output,
+            &serde_json::json!({
+                "key_id": key_id,
+                "message": hex::encode(&message),
+                "signature_verification": status.to_json(),
+            }),
+        )?,
+        OutputFormat::Text => [
+            format!("{: <22}: {key_id}", "Key ID".bold()),
+            format!(
+                "{: <22}: {message}",
+                "Message".bold(),
+                message = hex::encode(&message)
+            ),
+            format!("{: <22}: {status}", "Signature verification".bold()),
+        ]
+        .join("\n"),
+    };
+    check_verification(output, status.is_failed(), false)
+}
+
+pub async fn verify_keys(remote: &Remote, root_key: &str, output: OutputFormat) -> Result<String> {
+    let client = remote.client()?;
+    let config = client.auditor_config().await?;
+    if config.keys().is_empty() {
+        return Err(anyhow!("auditor does not advertise any key"));
+    }
+
+    let chains: Vec<_> = config
+        .keys()
+        .iter()
+        .map(|key| (key, key.chain_to(root_key, config.keys())))
+        .collect();
+    let statuses: Vec<_> = chains
+        .iter()
+        .map(|(_, chain)| match chain {
+            Ok(_) => VerificationStatus::Success,
+            Err(e) => VerificationStatus::Failed(format!("{e:#}")),
+        })
+        .collect();
+
+    let output = match output {
+        OutputFormat::Json | OutputFormat::Yaml => serialize_output(
+            output,
+            &serde_json::json!({
+                "root_key": root_key,
+                "keys": chains
+                    .iter()
+                    .zip(&statuses)
+                    .map(|((key, chain), status)| serde_json::json!({
+                        "key": key,
+                        "key_id": key.key_id(),
+                        "chain": chain.as_ref().ok(),
+                        "verification": status.to_json(),
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+        )?,
+        OutputFormat::Text => chains
+            .iter()
+            .zip(&statuses)
+            .map(|((key, _), status)| {
+                format!(
+                    "{public_key} (key ID {key_id}): {status}",
+                    public_key = key.public_key(),
+                    key_id = key.key_id()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    check_verification(
+        output,
+        statuses.iter().any(VerificationStatus::is_failed),
+        false,
     )
 }
+
+enum Finding {
+    Ok(String),
+    Warning(String),
+    Error(String),
+}
+
+impl Finding {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Finding::Ok(message) => serde_json::json!({ "status": "ok", "message": message }),
+            Finding::Warning(message) => {
+                serde_json::json!({ "status": "warning", "message": message })
+            }
+            Finding::Error(message) => serde_json::json!({ "status": "error", "message": message }),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::Ok(message) => write!(f, "{} - {message}", "ok".green()),
+            Finding::Warning(message) => write!(f, "{} - {message}", "warning".yellow()),
+            Finding::Error(message) => write!(f, "{} - {message}", "error".red()),
+        }
+    }
+}
+
+pub async fn doctor(
+    remote: Result<Remote>,
+    verifying_key: Option<&str>,
+    cache_directory: Option<&Path>,
+    output: OutputFormat,
+) -> Result<String> {
+    let mut findings = Vec::new();
+    let config = match remote {
+        Ok(remote) => diagnose_auditor(&remote, &mut findings).await,
+        Err(e) => {
+            findings.push(("Remote URL", Finding::Error(format!("{e:#}"))));
+            None
+        }
+    };
+    findings.push((
+        "Verifying key",
+        diagnose_verifying_key(verifying_key, config.as_ref()),
+    ));
+    findings.push((
+        "State directory",
+        match TrustStore::default_path().as_deref().and_then(Path::parent) {
+            Some(directory) => diagnose_directory(directory),
+            None => Finding::Warning(
+                "neither XDG_STATE_HOME nor HOME is set, so verified epochs, trusted roots, and the audit trail are not persisted"
+                    .to_string(),
+            ),
+        },
+    ));
+    if let Some(directory) = cache_directory {
+        findings.push(("Cache directory", diagnose_directory(directory)));
+    }
+
+    let output = match output {
+        OutputFormat::Json | OutputFormat::Yaml => serialize_output(
+            output,
+            &findings
+                .iter()
+                .map(|(check, finding)| {
+                    let mut finding = finding.to_json();
+                    finding["check"] = (*check).into();
+                    finding
+                })
+                .collect::<Vec<_>>(),
+        )?,
+        OutputFormat::Text => findings
+            .iter()
+            .map(|(check, finding)| format!("{: <22}: {finding}", check.bold()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    let errors = findings
+        .iter()
+        .filter(|(_, finding)| matches!(finding, Finding::Error(_)))
+        .count();
+    if errors > 0 {
+        // findings are what helps fixing the errors, so they are output nonetheless
+        print_output(&output);
+        return Err(anyhow!("{errors} checks failed"));
+    }
+    Ok(output)
+}
+
+async fn diagnose_auditor(
+    remote: &Remote,
+    findings: &mut Vec<(&'static str, Finding)>,
+) -> Option<auditor::Configuration> {
+    let url = match reqwest::Url::parse(remote.url()) {
+        Ok(url) => url,
+        Err(e) => {
+            findings.push((
+                "Remote URL",
+                Finding::Error(format!("{url} is not a valid URL: {e}", url = remote.url())),
+            ));
+            return None;
+        }
+    };
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        findings.push(("Remote URL", Finding::Error(format!("{url} has no host"))));
+        return None;
+    };
+    if url.scheme() != "https" {
+        findings.push((
+            "Remote URL",
+            Finding::Warning(format!(
+                "{url} is not served over TLS, so the auditor can be impersonated. Use an https:// URL"
+            )),
+        ));
+    }
+
+    match tokio::net::lookup_host((host, port)).await {
+        Ok(addresses) => findings.push((
+            "DNS",
+            Finding::Ok(format!(
+                "{host} resolves to {count} addresses",
+                count = addresses.count()
+            )),
+        )),
+        Err(e) => {
+            findings.push((
+                "DNS",
+                Finding::Error(format!(
+                    "cannot resolve {host}: {e}. Check the remote URL, and the DNS configuration of this machine"
+                )),
+            ));
+            return None;
+        }
+    }
+
+    let config = match remote.client() {
+        Ok(client) => client.auditor_config().await,
+        Err(e) => Err(e),
+    };
+    match config {
+        Ok(config) => {
+            findings.push((
+                "Connection",
+                Finding::Ok(format!(
+                    "{url} serves its configuration, advertising {count} keys",
+                    count = config.keys().len()
+                )),
+            ));
+            Some(config)
+        }
+        Err(e) => {
+            let hint = match e
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<reqwest::Error>())
+            {
+                Some(e) if e.is_timeout() => "Raise --timeout, or check no proxy holds the connection",
+                Some(e) if e.is_connect() => {
+                    "Check firewalls and proxies, that the auditor certificate is signed by a trusted CA, and the client certificate if it requires one"
+                }
+                _ => "Check the remote URL is the one of a plexi auditor",
+            };
+            findings.push((
+                "Connection",
+                Finding::Error(format!(
+                    "cannot fetch the configuration of the auditor: {e:#}. {hint}"
+                )),
+            ));
+            None
+        }
+    }
+}
+
+fn diagnose_verifying_key(
+    verifying_key: Option<&str>,
+    config: Option<&auditor::Configuration>,
+) -> Finding {
+    let Some(verifying_key) = verifying_key else {
+        return Finding::Warning(
+            "none is configured, so signatures are verified against the keys the auditor advertises. Pin the auditor key with --verifying-key or a profile verifying_key"
+                .to_string(),
+        );
+    };
+    let Some(key) = hex::decode(verifying_key)
+        .ok()
+        .and_then(|key| <[u8; PUBLIC_KEY_LENGTH]>::try_from(key).ok())
+    else {
+        return Finding::Error(format!(
+            "{verifying_key} is not an Ed25519 public key in hex format"
+        ));
+    };
+    let Some(config) = config else {
+        return Finding::Warning(
+            "cannot check the auditor advertises it, as its configuration could not be fetched"
+                .to_string(),
+        );
+    };
+    let advertised = config
+        .keys()
+        .iter()
+        .any(|info| hex::decode(info.public_key()).is_ok_and(|public_key| public_key == key));
+    if advertised {
+        Finding::Ok(format!(
+            "key ID {key_id} is advertised by the auditor",
+            key_id = ed25519_public_key_to_key_id(&key)
+        ))
+    } else {
+        Finding::Error(format!(
+            "the auditor does not advertise {verifying_key}, so its signatures will not verify. If it rotated its key, check the new one descends from yours with plexi verify-keys --root-key {verifying_key}"
+        ))
+    }
+}
+
+fn diagnose_directory(directory: &Path) -> Finding {
+    let display = directory.display();
+    if let Err(e) = fs::create_dir_all(directory) {
+        return Finding::Error(format!(
+            "cannot create {display}: {e}. Check the permissions of its parent directories"
+        ));
+    }
+    let probe = directory.join(".plexi-doctor");
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            Finding::Ok(format!("{display} is writable"))
+        }
+        Err(e) => Finding::Error(format!(
+            "cannot write to {display}: {e}. Check it is owned by the user running plexi"
+        )),
+    }
+}
+
+pub async fn diff(
+    namespace: &str,
+    remotes: &[Remote],
+    output: OutputFormat,
+    migration_policy: MigrationPolicy,
+    epoch: Option<&Epoch>,
+) -> Result<String> {
+    let [first, second] = remotes else {
+        return Err(usage(anyhow!("exactly two --remote-url are required")));
+    };
+
+    let mut auditors = vec![];
+    for remote in [first, second] {
+        let client = remote.client()?;
+        let namespace = canonical_namespace(&client, namespace).await?;
+        auditors.push((remote, client, namespace));
+    }
+
+    let epoch = match epoch {
+        Some(epoch) => *epoch,
+        None => {
+            let mut latest = u64::MAX;
+            for (remote, client, namespace) in &auditors {
+                let Some(last_verified_epoch) = client.last_verified_epoch(namespace).await? else {
+                    return Err(usage(anyhow!(
+                        "{url} does not have a latest epoch for {namespace}. Please specify one",
+                        url = remote.url()
+                    )));
+                };
+                latest = latest.min(u64::from(&last_verified_epoch.epoch()));
+            }
+            Epoch::from(latest)
+        }
+    };
+
+    let mut signatures = vec![];
+    for (remote, client, namespace) in &auditors {
+        let Some(signature) = client.signature(namespace, &epoch).await? else {
+            return Err(anyhow!(
+                "Signature not found for {namespace} at epoch {epoch} on {url}",
+                url = remote.url()
+            ));
+        };
+        // each auditor signs with its own keys
+        let verifier =
+            EpochVerifier::new(client, namespace, None, migration_policy, None, false).await?;
+        let status = verifier.verify_signature(&signature);
+        signatures.push((remote.url(), signature, status));
+    }
+
+    let [(_, first, first_status), (_, second, second_status)] = signatures.as_slice() else {
+        unreachable!("two auditors are compared");
+    };
+    let mut divergences = vec![];
+    if !constant_time_eq(first.digest(), second.digest()) {
+        divergences.push("digest");
+    }
+    if first.timestamp() != second.timestamp() {
+        divergences.push("timestamp");
+    }
+    if first_status.is_failed() != second_status.is_failed() {
+        divergences.push("signature validity");
+    }
+
+    let output = match output {
+        OutputFormat::Json | OutputFormat::Yaml => serialize_output(
+            output,
+            &serde_json::json!({
+                "epoch": epoch,
+                "auditors": signatures
+                    .iter()
+                    .map(|(url, signature, status)| serde_json::json!({
+                        "remote_url": url,
+                        "signature": signature,
+                        "signature_verification": status.to_json(),
+                    }))
+                    .collect::<Vec<_>>(),
+                "divergences": divergences,
+            }),
+        )?,
+        OutputFormat::Text => {
+            let mut lines = vec![format!("{: <22}: {epoch}", "Epoch height".bold())];
+            for (url, signature, status) in &signatures {
+                lines.extend([
+                    format!("\n{url}"),
+                    format!(
+                        "  {: <22}: {digest}",
+                        "Epoch digest".bold(),
+                        digest = hex::encode(signature.digest())
+                    ),
+                    format!(
+                        "  {: <22}: {formatted} ({timestamp} ms)",
+                        "Timestamp".bold(),
+                        formatted = format_timestamp(signature.timestamp())?,
+                        timestamp = signature.timestamp()
+                    ),
+                    format!("  {: <22}: {status}", "Signature verification".bold()),
+                ]);
+            }
+            lines.push(format!(
+                "\n{: <22}: {divergences}",
+                "Divergences".bold(),
+                divergences = if divergences.is_empty() {
+                    "-".to_string()
+                } else {
+                    divergences.join(", ")
+                }
+            ));
+            lines.join("\n")
+        }
+    };
+
+    if !constant_time_eq(first.digest(), second.digest()) {
+        return Err(VerificationFailure::divergence(output).into());
+    }
+    check_verification(
+        output,
+        first_status.is_failed() || second_status.is_failed(),
+        false,
+    )
+}
+
+const EXPORT_CSV_HEADER: &str = "namespace,ciphersuite,epoch,timestamp,digest,signature,key_id,serialized_message,migration_ciphersuite,migration_signature";
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn signature_to_csv(signature: &SignatureResponse) -> String {
+    let migration = signature.migration_signature();
+    [
+        csv_field(signature.namespace()),
+        signature.ciphersuite().to_string(),
+        signature.epoch().to_string(),
+        signature.timestamp().to_string(),
+        hex::encode(signature.digest()),
+        hex::encode(signature.signature()),
+        signature
+            .key_id()
+            .map(|key_id| key_id.to_string())
+            .unwrap_or_default(),
+        signature
+            .serialized_message()
+            .map(hex::encode)
+            .unwrap_or_default(),
+        migration
+            .as_ref()
+            .map(|migration| migration.ciphersuite().to_string())
+            .unwrap_or_default(),
+        migration
+            .as_ref()
+            .map(|migration| hex::encode(migration.signature()))
+            .unwrap_or_default(),
+    ]
+    .join(",")
+}
+
+pub async fn export(
+    namespace: &str,
+    remote: &Remote,
+    from: &Epoch,
+    to: &Epoch,
+    format: ExportFormat,
+) -> Result<String> {
+    if from > to {
+        return Err(usage(anyhow!("range start {from} is after range end {to}")));
+    }
+    let client = remote.client()?;
+    let namespace = &canonical_namespace(&client, namespace).await?;
+
+    let mut lines = match format {
+        ExportFormat::Jsonl | ExportFormat::Sth => vec![],
+        ExportFormat::Csv => vec![EXPORT_CSV_HEADER.to_string()],
+    };
+    for epoch in u64::from(from)..=u64::from(to) {
+        let epoch = Epoch::from(epoch);
+        let Some(signature) = client.signature(namespace, &epoch).await? else {
+            return Err(anyhow!(
+                "Signature not found for {namespace} at epoch {epoch}"
+            ));
+        };
+        lines.push(match format {
+            ExportFormat::Jsonl => serde_json::to_string(&signature)?,
+            ExportFormat::Csv => signature_to_csv(&signature),
+            ExportFormat::Sth => serde_json::to_string(&SignedTreeHead::from(&signature))?,
+        });
+    }
+    Ok(lines.join("\n"))
+}
+
+pub async fn watch(
+    namespace: &str,
+    remote: &Remote,
+    interval: Duration,
+    state_path: Option<&Path>,
+    alerts: &Alerts,
+    metrics: Option<(&WatchMetrics, &Path)>,
+    options: &AuditOptions,
+) -> Result<String> {
+    let client = remote.client()?;
+    let namespace = &canonical_namespace(&client, namespace).await?;
+    let mut last_verified = match state_path {
+        Some(path) if path.exists() => Some(
+            serde_json::from_str::<SignatureResponse>(&fs::read_to_string(path)?)
+                .with_context(|| format!("cannot parse {path}", path = path.display()))
+                .map_err(usage)?,
+        ),
+        _ => None,
+    };
+    if let (Some((metrics, _)), Some(signature)) = (metrics, &last_verified) {
+        metrics.record_verified(signature);
+    }
+
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+        // failures are reported, and the epochs retried on the next tick
+        if let Err(e) = watch_epochs(
+            &client,
+            namespace,
+            &mut last_verified,
+            state_path,
+            alerts,
+            metrics.map(|(metrics, _)| metrics),
+            options,
+        )
+        .await
+        {
+            tracing::error!("cannot watch {namespace}: {e:#}");
+        }
+        if let Some((metrics, path)) = metrics {
+            if let Err(e) = metrics.write(path) {
+                tracing::error!("{e:#}");
+            }
+        }
+    }
+}
+
+async fn watch_epochs(
+    client: &PlexiClient,
+    namespace: &str,
+    last_verified: &mut Option<SignatureResponse>,
+    state_path: Option<&Path>,
+    alerts: &Alerts,
+    metrics: Option<&WatchMetrics>,
+    options: &AuditOptions,
+) -> Result<()> {
+    let (output, verifying_key) = (options.output, options.verifying_key.as_deref());
+    let Some(latest) = last_verified_epoch(client, namespace, verifying_key).await? else {
+        return Ok(());
+    };
+    let latest = u64::from(&latest.epoch());
+    let first = match last_verified {
+        Some(signature) => u64::from(signature.epoch()) + 1,
+        None => latest,
+    };
+    if first > latest {
+        return Ok(());
+    }
+
+    // namespace roots may change between ticks
+    let verifier = EpochVerifier::new(
+        client,
+        namespace,
+        verifying_key,
+        options.migration_policy,
+        options.log_directory.as_deref(),
+        options.force,
+    )
+    .await?;
+    for epoch in first..=latest {
+        let epoch = Epoch::from(epoch);
+        let Some(signature) = client.signature(namespace, &epoch).await? else {
+            return Err(anyhow!(
+                "Signature not found for {namespace} at epoch {epoch}"
+            ));
+        };
+        let start = Instant::now();
+        let (mut signature_verification_status, proof_verification_status) =
+            verifier.verify(&signature, last_verified.as_ref()).await?;
+        if let Some(metrics) = metrics {
+            metrics.record_verification(&signature, start.elapsed());
+        }
+        if let (Some(previous), false) = (
+            last_verified.as_ref(),
+            signature_verification_status.is_failed(),
+        ) {
+            if let Err(e) = signature.verify_timestamp_after(previous, options.max_timestamp_gap) {
+                signature_verification_status = VerificationStatus::Failed(e.to_string());
+            }
+        }
+
+        print_output(&match output {
+            OutputFormat::Json | OutputFormat::Yaml => serialize_line(
+                output,
+                &audit_to_json(
+                    &signature,
+                    &signature_verification_status,
+                    &proof_verification_status,
+                ),
+            )?,
+            OutputFormat::Text => format!(
+                "{epoch}: {status}",
+                status = summary_status(&signature_verification_status, &proof_verification_status)
+            ),
+        });
+        if signature_verification_status.is_failed() || proof_verification_status.is_failed() {
+            tracing::error!(
+                "{namespace} epoch {epoch} does not verify: {status}",
+                status = summary_status(&signature_verification_status, &proof_verification_status)
+            );
+            let (kind, message) = match (&signature_verification_status, &proof_verification_status)
+            {
+                (VerificationStatus::Failed(e), _) => (FailureKind::Signature, e),
+                (_, VerificationStatus::Failed(e)) => (FailureKind::Proof, e),
+                _ => unreachable!("one of the verifications failed"),
+            };
+            if let Some(metrics) = metrics {
+                metrics.record_failure(namespace, kind);
+            }
+            alerts
+                .notify(&AlertEvent::new(&signature, kind, message))
+                .await;
+            return Ok(());
+        }
+
+        if let Some(path) = state_path {
+            fs::write(path, serde_json::to_string(&signature)?)
+                .with_context(|| format!("cannot write {path}", path = path.display()))?;
+        }
+        if let Some(metrics) = metrics {
+            metrics.record_verified(&signature);
+        }
+        *last_verified = Some(signature);
+    }
+
+    if let Some(signature) = last_verified {
+        if let Some(stall) = check_freshness(client, signature, &options.freshness).await? {
+            if let Some(metrics) = metrics {
+                metrics.record_failure(namespace, FailureKind::Stalled);
+            }
+            alerts
+                .notify(&AlertEvent::new(signature, FailureKind::Stalled, &stall))
+                .await;
+        }
+    }
+    Ok(())
+}
+
+pub fn man(out_dir: Option<&Path>) -> Result<String> {
+    let Some(out_dir) = out_dir else {
+        let mut page = vec![];
+        clap_mangen::Man::new(cli::command()).render(&mut page)?;
+        return Ok(String::from_utf8(page)?);
+    };
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("creating {out_dir}", out_dir = out_dir.display()))?;
+    clap_mangen::generate_to(cli::command(), out_dir)?;
+    Ok(format!(
+        "Man pages written to {out_dir}",
+        out_dir = out_dir.display()
+    ))
+}
@@ -0,0 +1,257 @@
+use std::{
+    collections::HashMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use plexi_core::client::{ClientMtls, PlexiClient};
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{
+    alert::{Alerts, NotifierConfig, Webhook},
+    cli::{AdminArgs, NetworkArgs},
+    cmd::APP_USER_AGENT,
+    exit::usage,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: HashMap<String, Profile>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    remote_url: Option<String>,
+    namespace: Option<String>,
+    verifying_key: Option<String>,
+    log_directory: Option<String>,
+    mtls_cert_path: Option<PathBuf>,
+    mtls_key_path: Option<PathBuf>,
+    #[serde(default)]
+    notifiers: Vec<NotifierConfig>,
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("plexi").join("config.toml"))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let config = fs::read_to_string(path)
+            .with_context(|| format!("reading {path}", path = path.display()))?;
+        toml::from_str(&config)
+            .with_context(|| format!("parsing {path}", path = path.display()))
+            .map_err(usage)
+    }
+
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile> {
+        let Some(name) = name.or(self.default_profile.as_deref()) else {
+            return Ok(Profile::default());
+        };
+        self.profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| usage(anyhow!("profile {name} does not exist")))
+    }
+}
+
+impl Profile {
+    pub fn remote(&self, remote_url: Option<String>) -> Result<Remote> {
+        let url = remote_url
+            .or_else(|| self.remote_url.clone())
+            .ok_or_else(|| {
+                usage(anyhow!(
+                    "--remote-url, PLEXI_REMOTE_URL, or a profile remote_url is required"
+                ))
+            })?;
+        let mtls = match (&self.mtls_cert_path, &self.mtls_key_path) {
+            (Some(cert), Some(key)) => Some(ClientIdentity::Pem {
+                cert: cert.clone(),
+                key: key.clone(),
+            }),
+            (None, None) => None,
+            _ => {
+                return Err(usage(anyhow!(
+                    "profile mtls_cert_path and mtls_key_path have to be set together"
+                )))
+            }
+        };
+        Ok(Remote {
+            url,
+            mtls,
+            timeout: None,
+            retries: 0,
+            cache_ttl: None,
+            cache_directory: None,
+            admin_token: None,
+        })
+    }
+
+    pub fn namespace(&self, namespace: Option<String>) -> Result<String> {
+        namespace.or_else(|| self.namespace.clone()).ok_or_else(|| {
+            usage(anyhow!(
+                "--namespace, PLEXI_NAMESPACE, or a profile namespace is required"
+            ))
+        })
+    }
+
+    pub fn verifying_key(&self, verifying_key: Option<String>) -> Option<String> {
+        verifying_key.or_else(|| self.verifying_key.clone())
+    }
+
+    pub fn log_directory(&self, log_directory: Option<String>) -> Option<String> {
+        log_directory.or_else(|| self.log_directory.clone())
+    }
+
+    pub fn alerts(&self, webhook_url: Option<Url>) -> Result<Alerts> {
+        let mut notifiers = self
+            .notifiers
+            .iter()
+            .map(NotifierConfig::notifier)
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(url) = webhook_url {
+            notifiers.push(Box::new(Webhook::new(url)?));
+        }
+        Ok(Alerts::new(notifiers))
+    }
+}
+
+#[derive(Clone)]
+pub struct Remote {
+    url: String,
+    mtls: Option<ClientIdentity>,
+    timeout: Option<Duration>,
+    retries: u32,
+    cache_ttl: Option<Duration>,
+    cache_directory: Option<PathBuf>,
+    admin_token: Option<String>,
+}
+
+impl fmt::Debug for Remote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Remote")
+            .field("url", &self.url)
+            .field("mtls", &self.mtls)
+            .field("timeout", &self.timeout)
+            .field("retries", &self.retries)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("cache_directory", &self.cache_directory)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Remote {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn network(mut self, network: &NetworkArgs) -> Self {
+        self.timeout = network.timeout.map(Duration::from_secs);
+        self.retries = network.retries;
+        self.cache_ttl = (network.cache_ttl > 0).then(|| Duration::from_secs(network.cache_ttl));
+        self.cache_directory = network.cache_dir.clone();
+        if let (Some(cert), Some(key)) = (&network.client_cert, &network.client_key) {
+            self.mtls = Some(ClientIdentity::Pem {
+                cert: cert.clone(),
+                key: key.clone(),
+            });
+        }
+        #[cfg(feature = "native-tls")]
+        if let Some(path) = &network.client_pkcs12 {
+            self.mtls = Some(ClientIdentity::Pkcs12 {
+                path: path.clone(),
+                password: network.client_pkcs12_password.clone().unwrap_or_default(),
+            });
+        }
+        self
+    }
+
+    pub fn admin(self, admin: &AdminArgs) -> Self {
+        self.admin_token(&admin.admin_token)
+    }
+
+    pub fn admin_token(mut self, token: &str) -> Self {
+        self.admin_token = Some(token.to_string());
+        self
+    }
+
+    pub fn client(&self) -> Result<PlexiClient> {
+        let mut builder = PlexiClient::builder(Url::parse(&self.url).map_err(usage)?)
+            .user_agent(APP_USER_AGENT)
+            .retries(self.retries);
+        if let Some(identity) = &self.mtls {
+            builder = builder.mtls(identity.mtls()?);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(ttl) = self.cache_ttl {
+            builder = builder.cache_ttl(ttl);
+            if let Some(directory) = &self.cache_directory {
+                builder = builder.cache_directory(directory);
+            }
+        }
+        if let Some(token) = &self.admin_token {
+            builder = builder.bearer_token(token);
+        }
+        builder.build()
+    }
+}
+
+#[derive(Clone)]
+enum ClientIdentity {
+    Pem {
+        cert: PathBuf,
+        key: PathBuf,
+    },
+    #[cfg(feature = "native-tls")]
+    Pkcs12 {
+        path: PathBuf,
+        password: String,
+    },
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pem { cert, key } => f
+                .debug_struct("Pem")
+                .field("cert", cert)
+                .field("key", key)
+                .finish(),
+            #[cfg(feature = "native-tls")]
+            Self::Pkcs12 { path, .. } => f.debug_struct("Pkcs12").field("path", path).finish(),
+        }
+    }
+}
+
+impl ClientIdentity {
+    fn mtls(&self) -> Result<ClientMtls> {
+        match self {
+            Self::Pem { cert, key } => ClientMtls::new(
+                &fs::read(cert)
+                    .with_context(|| format!("reading {cert}", cert = cert.display()))?,
+                &fs::read(key).with_context(|| format!("reading {key}", key = key.display()))?,
+            ),
+            #[cfg(feature = "native-tls")]
+            Self::Pkcs12 { path, password } => ClientMtls::from_pkcs12(
+                &fs::read(path)
+                    .with_context(|| format!("reading {path}", path = path.display()))?,
+                password,
+            ),
+        }
+    }
+}
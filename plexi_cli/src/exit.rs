@@ -0,0 +1,97 @@
+use std::{error, fmt};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExitCode {
+    Failure = 1,
+    Usage = 2,
+    Network = 3,
+    SignatureVerification = 4,
+    ProofVerification = 5,
+    Divergence = 6,
+    Stale = 7,
+}
+
+impl ExitCode {
+    pub fn of(err: &anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(failure) = cause.downcast_ref::<VerificationFailure>() {
+                return failure.exit_code;
+            }
+            if cause.is::<UsageError>() {
+                return Self::Usage;
+            }
+            if cause.is::<reqwest::Error>() {
+                return Self::Network;
+            }
+        }
+        Self::Failure
+    }
+}
+
+#[derive(Debug)]
+pub struct VerificationFailure {
+    output: String,
+    exit_code: ExitCode,
+}
+
+impl VerificationFailure {
+    pub fn signature(output: String) -> Self {
+        Self {
+            output,
+            exit_code: ExitCode::SignatureVerification,
+        }
+    }
+
+    pub fn proof(output: String) -> Self {
+        Self {
+            output,
+            exit_code: ExitCode::ProofVerification,
+        }
+    }
+
+    pub fn divergence(output: String) -> Self {
+        Self {
+            output,
+            exit_code: ExitCode::Divergence,
+        }
+    }
+
+    pub fn stale(output: String) -> Self {
+        Self {
+            output,
+            exit_code: ExitCode::Stale,
+        }
+    }
+
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+}
+
+impl fmt::Display for VerificationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.exit_code {
+            ExitCode::ProofVerification => write!(f, "proof verification failed"),
+            ExitCode::Divergence => write!(f, "auditors diverge"),
+            ExitCode::Stale => write!(f, "latest epoch is stale"),
+            _ => write!(f, "signature verification failed"),
+        }
+    }
+}
+
+impl error::Error for VerificationFailure {}
+
+#[derive(Debug)]
+pub struct UsageError(anyhow::Error);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl error::Error for UsageError {}
+
+pub fn usage(err: impl Into<anyhow::Error>) -> anyhow::Error {
+    UsageError(err.into()).into()
+}
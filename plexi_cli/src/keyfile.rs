@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    io::{Read as _, Write as _},
+    path::Path,
+    process::Command,
+};
+
+use age::{
+    armor::{ArmoredReader, ArmoredWriter, Format},
+    secrecy::{ExposeSecret as _, Secret},
+};
+use anyhow::{anyhow, Context, Result};
+use ed25519_dalek::SECRET_KEY_LENGTH;
+use plexi_core::signer::Ed25519Signer;
+
+use crate::{cli::PassphraseArgs, exit::usage};
+
+const ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+pub fn read_signing_key(path: &Path, args: &PassphraseArgs) -> Result<Ed25519Signer> {
+    let signing_key =
+        fs::read(path).with_context(|| format!("reading {path}", path = path.display()))?;
+    if !signing_key.starts_with(ARMOR_BEGIN.as_bytes()) {
+        return Ed25519Signer::from_hex(&String::from_utf8_lossy(&signing_key));
+    }
+
+    let passphrase = passphrase(
+        args,
+        &format!("Passphrase of {path}: ", path = path.display()),
+    )?;
+    let age::Decryptor::Passphrase(decryptor) =
+        age::Decryptor::new(ArmoredReader::new(signing_key.as_slice()))
+            .with_context(|| format!("parsing {path}", path = path.display()))?
+    else {
+        return Err(usage(anyhow!(
+            "{path} is not encrypted with a passphrase",
+            path = path.display()
+        )));
+    };
+    let mut secret_key = vec![];
+    decryptor
+        .decrypt(&passphrase, None)
+        .map_err(|e| usage(anyhow!("decrypting {path}: {e}", path = path.display())))?
+        .read_to_end(&mut secret_key)?;
+    let secret_key: [u8; SECRET_KEY_LENGTH] = secret_key
+        .try_into()
+        .map_err(|_| anyhow!("{path} does not hold an Ed25519 key", path = path.display()))?;
+    Ok(Ed25519Signer::new(&secret_key))
+}
+
+pub fn encrypt_signing_key(
+    secret_key: &[u8; SECRET_KEY_LENGTH],
+    args: &PassphraseArgs,
+) -> Result<Vec<u8>> {
+    let passphrase = new_passphrase(args)?;
+    let mut encrypted = vec![];
+    let armor = ArmoredWriter::wrap_output(&mut encrypted, Format::AsciiArmor)?;
+    let mut writer = age::Encryptor::with_user_passphrase(passphrase).wrap_output(armor)?;
+    writer.write_all(secret_key)?;
+    writer.finish()?.finish()?;
+    Ok(encrypted)
+}
+
+fn passphrase(args: &PassphraseArgs, prompt: &str) -> Result<Secret<String>> {
+    if let Some(passphrase) = &args.passphrase {
+        return Ok(Secret::new(passphrase.clone()));
+    }
+    if let Some(command) = &args.passphrase_command {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output()
+            .with_context(|| format!("running {command}"))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{command} failed with {status}",
+                status = output.status
+            ));
+        }
+        let passphrase = String::from_utf8(output.stdout)
+            .with_context(|| format!("{command} did not print a UTF-8 passphrase"))?;
+        // commands end their output with a newline, which is not part of the passphrase
+        return Ok(Secret::new(
+            passphrase.trim_end_matches(['\r', '\n']).to_string(),
+        ));
+    }
+    rpassword::prompt_password(prompt)
+        .map(Secret::new)
+        .context("reading passphrase, set PLEXI_SIGNING_KEY_PASSPHRASE or PLEXI_PASSPHRASE_COMMAND to run without a terminal")
+}
+
+fn new_passphrase(args: &PassphraseArgs) -> Result<Secret<String>> {
+    let prompted = args.passphrase.is_none() && args.passphrase_command.is_none();
+    let passphrase = passphrase(args, "Passphrase of the new signing key: ")?;
+    if passphrase.expose_secret().is_empty() {
+        return Err(usage(anyhow!(
+            "passphrase is empty, use --unencrypted to write the key without one"
+        )));
+    }
+    if prompted
+        && passphrase.expose_secret() != passphrase(args, "Confirm passphrase: ")?.expose_secret()
+    {
+        return Err(usage(anyhow!("passphrases do not match")));
+    }
+    Ok(passphrase)
+}
@@ -1,69 +1,534 @@
-use std::process;
+use std::{io, process, time::Duration};
 
+use anyhow::anyhow;
+use config::Config;
+use exit::{usage, ExitCode, VerificationFailure};
+use metrics::WatchMetrics;
+use plexi_core::namespaces::NamespaceFilter;
+use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt as _, Layer as _};
+
+mod alert;
+mod bundle;
+mod cache;
 mod cli;
 mod cmd;
+mod config;
+mod exit;
+mod keyfile;
+mod metrics;
+#[cfg(feature = "otel")]
+mod otel;
 mod print;
+mod trail;
+mod trust;
 
 #[tokio::main]
 pub async fn main() -> anyhow::Result<()> {
     let cli = cli::build();
 
-    env_logger::Builder::new()
-        .filter_level(cli.verbose.log_level_filter())
-        .init();
+    let logs = tracing_subscriber::fmt::layer().with_writer(io::stderr);
+    let logs = match cli.log_format {
+        cli::LogFormat::Text => logs.boxed(),
+        cli::LogFormat::Json => logs.json().boxed(),
+    };
+    let subscriber =
+        tracing_subscriber::registry().with(logs.with_filter(cli::log_level(&cli.verbose)));
+    #[cfg(feature = "otel")]
+    let tracer_provider = cli.otlp_endpoint.as_deref().map(otel::init).transpose()?;
+    #[cfg(feature = "otel")]
+    let subscriber = subscriber.with(tracer_provider.as_ref().map(otel::layer));
+    subscriber.init();
+    colored::control::set_override(cli::colored(cli.color));
+
+    let output_format = cli.command.output();
+    // -q silences logs, and the output too, so that only the exit code tells the result
+    let output = match print::init_output(cli.verbose.is_silent(), cli.output_file.as_deref()) {
+        Ok(()) => run(cli).await,
+        Err(e) => Err(e),
+    };
+    // spans are exported in batches, the last ones are flushed before exiting
+    #[cfg(feature = "otel")]
+    if let Some(tracer_provider) = tracer_provider {
+        if let Err(e) = tracer_provider.shutdown() {
+            eprintln!("error: exporting traces: {e}");
+        }
+    }
+
+    match output {
+        Ok(result) => {
+            if !result.is_empty() {
+                print::print_output(&result)
+            }
+        }
+        Err(err) => {
+            if let Some(failure) = err.downcast_ref::<VerificationFailure>() {
+                // the result of a failed audit is output like any other
+                if !failure.output().is_empty() {
+                    print::print_output(failure.output());
+                }
+            } else if output_format == cli::OutputFormat::Json {
+                // errors are part of the output for programs consuming JSON
+                print::print_output(&serde_json::json!({ "error": err.to_string() }).to_string());
+            } else if output_format == cli::OutputFormat::Yaml {
+                // a JSON string is a valid YAML scalar, quoted and escaped
+                print::print_output(&format!("error: {}", serde_json::json!(err.to_string())));
+            } else if !print::quiet() {
+                eprintln!("error: {err}");
+            }
+            process::exit(ExitCode::of(&err) as i32)
+        }
+    };
+    Ok(())
+}
+
+async fn run(cli: cli::Cli) -> anyhow::Result<String> {
+    let profile = match &cli.config {
+        Some(path) => Config::from_file(path)?,
+        None => match Config::default_path() {
+            Some(path) => Config::from_file(&path)?,
+            None => Config::default(),
+        },
+    }
+    .profile(cli.profile.as_deref())?;
 
-    let output = match cli.command {
+    match cli.command {
         cli::Commands::Ls {
             long,
+            output,
             namespace,
+            status,
+            prefix,
+            ciphersuite,
             remote_url,
-        } => cmd::ls(&remote_url, namespace.as_deref(), long).await,
+            network,
+        } => {
+            let mut filter = NamespaceFilter::new();
+            if let Some(status) = status {
+                filter = filter.status(status);
+            }
+            if let Some(prefix) = &prefix {
+                filter = filter.prefix(prefix);
+            }
+            if let Some(ciphersuite) = ciphersuite {
+                filter = filter.ciphersuite(ciphersuite);
+            }
+            cmd::ls(
+                &profile.remote(remote_url)?.network(&network),
+                namespace.as_deref(),
+                &filter,
+                long,
+                output,
+            )
+            .await
+        }
         cli::Commands::Audit {
             epoch,
+            expected_digest,
+            from,
+            to,
+            workers,
+            max_timestamp_gap,
             namespace,
             remote_url,
             long,
+            output,
             no_verify,
             verifying_key,
+            migration_policy,
+            log_directory,
+            force,
+            stdin,
+            all_namespaces,
+            status,
+            freshness,
+            network,
         } => {
-            cmd::audit(
-                &namespace,
-                &remote_url,
+            if remote_url.len() > 1
+                && (stdin || all_namespaces || from.is_some() || verifying_key.is_some())
+            {
+                return Err(usage(anyhow!(
+                    "--stdin, --all-namespaces, --from, and --verifying-key cannot be used with several --remote-url"
+                )));
+            }
+            let mut options = cmd::AuditOptions {
                 long,
-                !no_verify,
-                verifying_key.as_deref(),
-                epoch.as_ref(),
-            )
-            .await
+                output,
+                verify: !no_verify,
+                verifying_key: None,
+                migration_policy,
+                log_directory: profile.log_directory(log_directory),
+                force,
+                freshness,
+                expected_digest,
+                max_timestamp_gap: max_timestamp_gap.map(Duration::from_secs),
+            };
+            if remote_url.len() > 1 {
+                let remotes = remote_url
+                    .into_iter()
+                    .map(|remote_url| Ok(profile.remote(Some(remote_url))?.network(&network)))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                // each auditor signs with its own keys, rather than the one of the profile
+                return cmd::audit_remotes(
+                    &profile.namespace(namespace)?,
+                    &remotes,
+                    epoch.as_ref(),
+                    &options,
+                )
+                .await;
+            }
+            let remote = profile
+                .remote(remote_url.into_iter().next())?
+                .network(&network);
+            options.verifying_key = profile.verifying_key(verifying_key);
+            if all_namespaces {
+                let mut filter = NamespaceFilter::new();
+                if let Some(status) = status {
+                    filter = filter.status(status);
+                }
+                return cmd::audit_all(&remote, &filter, &options).await;
+            }
+            if stdin || namespace.as_deref() == Some("-") {
+                return cmd::audit_batch(io::stdin().lock(), &remote, epoch.as_ref(), &options)
+                    .await;
+            }
+            let namespace = profile.namespace(namespace)?;
+            match (from, to) {
+                (Some(from), Some(to)) => {
+                    cmd::audit_range(&namespace, &remote, &from, &to, workers, &options).await
+                }
+                _ => cmd::audit(&namespace, &remote, epoch.as_ref(), &options).await,
+            }
         }
         cli::Commands::LocalAudit {
             verifying_key,
+            migration_policy,
             long,
+            output,
             no_verify,
             proof_path,
+            akd_config,
+            expected_digest,
             signature_path_or_stdin,
+            network,
         } => {
-            cmd::audit_local(
-                verifying_key.as_deref(),
+            // URLs are fetched like requests to an auditor at that URL, with the client certificate of the profile
+            let input = |location: String| -> anyhow::Result<cmd::Input> {
+                Ok(if cmd::is_url(&location) {
+                    cmd::Input::Url(profile.remote(Some(location))?.network(&network))
+                } else {
+                    cmd::Input::Path(location.into())
+                })
+            };
+            let options = cmd::AuditOptions {
                 long,
-                !no_verify,
-                proof_path,
-                signature_path_or_stdin,
+                output,
+                verify: !no_verify,
+                verifying_key: profile.verifying_key(verifying_key),
+                migration_policy,
+                log_directory: None,
+                force: false,
+                freshness: cli::FreshnessArgs::default(),
+                expected_digest,
+                max_timestamp_gap: None,
+            };
+            cmd::audit_local(
+                signature_path_or_stdin
+                    .map(input)
+                    .transpose()?
+                    .unwrap_or(cmd::Input::Stdin),
+                proof_path.map(input).transpose()?,
+                akd_config,
+                &options,
             )
             .await
         }
-    };
-
-    match output {
-        Ok(result) => {
-            if !result.is_empty() {
-                println!("{result}")
-            }
+        cli::Commands::Watch {
+            remote_url,
+            namespace,
+            verifying_key,
+            migration_policy,
+            log_directory,
+            interval,
+            max_timestamp_gap,
+            state_path,
+            webhook_url,
+            metrics_textfile,
+            output,
+            freshness,
+            network,
+        } => {
+            let alerts = profile.alerts(webhook_url)?;
+            let metrics = WatchMetrics::new();
+            let options = cmd::AuditOptions {
+                long: false,
+                output,
+                verify: true,
+                verifying_key: profile.verifying_key(verifying_key),
+                migration_policy,
+                log_directory: profile.log_directory(log_directory),
+                force: false,
+                freshness,
+                expected_digest: None,
+                max_timestamp_gap: max_timestamp_gap.map(Duration::from_secs),
+            };
+            cmd::watch(
+                &profile.namespace(namespace)?,
+                &profile.remote(remote_url)?.network(&network),
+                Duration::from_secs(interval),
+                state_path.as_deref(),
+                &alerts,
+                metrics_textfile.as_deref().map(|path| (&metrics, path)),
+                &options,
+            )
+            .await
         }
-        Err(err) => {
-            eprintln!("error: {err}");
-            process::exit(1)
+        cli::Commands::VerifyKeys {
+            remote_url,
+            root_key,
+            output,
+            network,
+        } => {
+            cmd::verify_keys(
+                &profile.remote(remote_url)?.network(&network),
+                &root_key,
+                output,
+            )
+            .await
         }
-    };
-    Ok(())
+        cli::Commands::Doctor {
+            remote_url,
+            verifying_key,
+            output,
+            network,
+        } => {
+            // a missing remote is one of the findings, rather than an error
+            let remote = profile
+                .remote(remote_url)
+                .map(|remote| remote.network(&network));
+            cmd::doctor(
+                remote,
+                profile.verifying_key(verifying_key).as_deref(),
+                network.cache_dir.as_deref(),
+                output,
+            )
+            .await
+        }
+        cli::Commands::Diff {
+            remote_url,
+            namespace,
+            epoch,
+            migration_policy,
+            output,
+        } => {
+            let remotes = remote_url
+                .into_iter()
+                .map(|remote_url| profile.remote(Some(remote_url)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            cmd::diff(
+                &profile.namespace(namespace)?,
+                &remotes,
+                output,
+                migration_policy,
+                epoch.as_ref(),
+            )
+            .await
+        }
+        cli::Commands::Export {
+            remote_url,
+            namespace,
+            from,
+            to,
+            format,
+            network,
+        } => {
+            cmd::export(
+                &profile.namespace(namespace)?,
+                &profile.remote(remote_url)?.network(&network),
+                &from,
+                &to,
+                format,
+            )
+            .await
+        }
+        cli::Commands::Proof { command } => match command {
+            cli::ProofCommands::Inspect {
+                akd_config,
+                blob,
+                output,
+                proof_path_or_stdin,
+            } => cmd::proof_inspect(akd_config, blob.as_deref(), output, proof_path_or_stdin),
+        },
+        cli::Commands::Bundle { command } => match command {
+            cli::BundleCommands::Create {
+                remote_url,
+                namespace,
+                epoch,
+                log_directory,
+                network,
+                bundle_path,
+            } => {
+                cmd::bundle_create(
+                    &profile.namespace(namespace)?,
+                    &profile.remote(remote_url)?.network(&network),
+                    epoch.as_ref(),
+                    profile.log_directory(log_directory).as_deref(),
+                    &bundle_path,
+                )
+                .await
+            }
+            cli::BundleCommands::Verify {
+                verifying_key,
+                migration_policy,
+                long,
+                output,
+                bundle_path,
+            } => {
+                cmd::bundle_verify(
+                    profile.verifying_key(verifying_key).as_deref(),
+                    migration_policy,
+                    long,
+                    output,
+                    &bundle_path,
+                )
+                .await
+            }
+        },
+        cli::Commands::Log { command } => match command {
+            cli::LogCommands::Verify { output, trail_path } => {
+                cmd::log_verify(trail_path.as_deref(), output)
+            }
+        },
+        cli::Commands::Namespace { command } => match command {
+            cli::NamespaceCommands::Create {
+                remote_url,
+                namespace,
+                ciphersuite,
+                root,
+                settings,
+                output,
+                admin,
+                network,
+            } => {
+                cmd::namespace_create(
+                    &profile.remote(remote_url)?.network(&network).admin(&admin),
+                    &profile.namespace(namespace)?,
+                    &ciphersuite,
+                    root.as_deref(),
+                    &settings,
+                    output,
+                )
+                .await
+            }
+            cli::NamespaceCommands::Update {
+                remote_url,
+                namespace,
+                settings,
+                output,
+                admin,
+                network,
+            } => {
+                cmd::namespace_update(
+                    &profile.remote(remote_url)?.network(&network).admin(&admin),
+                    &profile.namespace(namespace)?,
+                    &settings,
+                    output,
+                )
+                .await
+            }
+            cli::NamespaceCommands::Disable {
+                remote_url,
+                namespace,
+                yes,
+                output,
+                admin,
+                network,
+            } => {
+                cmd::namespace_disable(
+                    &profile.remote(remote_url)?.network(&network).admin(&admin),
+                    &profile.namespace(namespace)?,
+                    yes,
+                    output,
+                )
+                .await
+            }
+        },
+        cli::Commands::Report { command } => match command {
+            cli::ReportCommands::Submit {
+                remote_url,
+                dry_run,
+                provider_key,
+                admin_token,
+                output,
+                network,
+                report_path_or_stdin,
+            } => {
+                let remote = profile.remote(remote_url)?.network(&network);
+                let remote = match &admin_token {
+                    Some(token) => remote.admin_token(token),
+                    None => remote,
+                };
+                cmd::report_submit(
+                    &remote,
+                    dry_run,
+                    provider_key.as_deref(),
+                    output,
+                    report_path_or_stdin,
+                )
+                .await
+            }
+        },
+        cli::Commands::Trust { command } => match command {
+            cli::TrustCommands::Reset { namespace, all: _ } => {
+                cmd::trust_reset(namespace.as_deref())
+            }
+        },
+        cli::Commands::Sign {
+            namespace,
+            epoch,
+            digest,
+            ciphersuite,
+            timestamp,
+            signer,
+        } => {
+            cmd::sign(
+                &namespace,
+                &epoch,
+                &digest,
+                &ciphersuite,
+                timestamp,
+                &signer,
+            )
+            .await
+        }
+        cli::Commands::VerifySignature {
+            key,
+            message,
+            signature,
+            migration_policy,
+            output,
+            signature_path_or_stdin,
+        } => cmd::verify_signature(
+            &key,
+            message.as_deref().zip(signature.as_deref()),
+            migration_policy,
+            output,
+            signature_path_or_stdin,
+        ),
+        cli::Commands::GenVectors {
+            ciphersuite,
+            count,
+            seed,
+        } => cmd::gen_vectors(&ciphersuite, count, seed),
+        cli::Commands::Keygen {
+            signing_key_path,
+            force,
+            unencrypted,
+            passphrase,
+        } => cmd::keygen(
+            &signing_key_path,
+            force,
+            (!unencrypted).then_some(&passphrase),
+        ),
+        cli::Commands::Man { out_dir } => cmd::man(out_dir.as_deref()),
+    }
 }
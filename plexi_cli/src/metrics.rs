@@ -0,0 +1,115 @@
+use std::{fs, path::Path, time::Duration};
+
+use anyhow::{Context, Result};
+use plexi_core::SignatureResponse;
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+use crate::alert::FailureKind;
+
+#[derive(Clone, Debug)]
+pub struct WatchMetrics {
+    registry: Registry,
+    last_verified_epoch: IntGaugeVec,
+    last_verified_timestamp: IntGaugeVec,
+    verification_duration: HistogramVec,
+    failures: IntCounterVec,
+}
+
+impl Default for WatchMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WatchMetrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("plexi_watch".to_string()), None)
+            .expect("plexi_watch is a valid metric prefix");
+        let last_verified_epoch = IntGaugeVec::new(
+            Opts::new(
+                "last_verified_epoch",
+                "Last epoch whose signature and proof verified",
+            ),
+            &["namespace"],
+        )
+        .expect("metric is valid");
+        let last_verified_timestamp = IntGaugeVec::new(
+            Opts::new(
+                "last_verified_timestamp_seconds",
+                "Time the last verified epoch was signed at, to alert on stalled namespaces",
+            ),
+            &["namespace"],
+        )
+        .expect("metric is valid");
+        let verification_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "verification_duration_seconds",
+                "Time taken to verify the signature and proof of an epoch",
+            )
+            .buckets(prometheus::exponential_buckets(0.01, 2.0, 14).expect("buckets are valid")),
+            &["namespace"],
+        )
+        .expect("metric is valid");
+        let failures = IntCounterVec::new(
+            Opts::new(
+                "failures_total",
+                "Epochs that did not verify, and checks finding the namespace stalled",
+            ),
+            &["namespace", "kind"],
+        )
+        .expect("metric is valid");
+
+        for collector in [
+            Box::new(last_verified_epoch.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(last_verified_timestamp.clone()),
+            Box::new(verification_duration.clone()),
+            Box::new(failures.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metrics are registered once");
+        }
+
+        Self {
+            registry,
+            last_verified_epoch,
+            last_verified_timestamp,
+            verification_duration,
+            failures,
+        }
+    }
+
+    pub fn record_verification(&self, signature: &SignatureResponse, duration: Duration) {
+        let namespace = signature.namespace();
+        self.verification_duration
+            .with_label_values(&[namespace])
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn record_verified(&self, signature: &SignatureResponse) {
+        let namespace = signature.namespace();
+        self.last_verified_epoch
+            .with_label_values(&[namespace])
+            .set(u64::from(signature.epoch()) as i64);
+        self.last_verified_timestamp
+            .with_label_values(&[namespace])
+            .set((signature.timestamp() / 1000) as i64);
+    }
+
+    pub fn record_failure(&self, namespace: &str, kind: FailureKind) {
+        self.failures
+            .with_label_values(&[namespace, kind.label()])
+            .inc();
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let metrics = TextEncoder::new().encode_to_string(&self.registry.gather())?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, metrics)
+            .and_then(|()| fs::rename(&tmp, path))
+            .with_context(|| format!("writing metrics to {path}", path = path.display()))
+    }
+}
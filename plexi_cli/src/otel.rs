@@ -0,0 +1,44 @@
+use anyhow::{Context, Result};
+use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::WithExportConfig as _;
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    runtime,
+    trace::{Tracer, TracerProvider},
+    Resource,
+};
+use tracing::{level_filters::LevelFilter, Subscriber};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::{filter::Targets, registry::LookupSpan, Layer};
+
+const TRACED_TARGETS: [&str; 2] = ["plexi", "plexi_core"];
+
+pub fn init(endpoint: &str) -> Result<TracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()
+        .with_context(|| format!("building OTLP exporter to {endpoint}"))?;
+    let tracer_provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            env!("CARGO_BIN_NAME"),
+        )]))
+        .build();
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    Ok(tracer_provider)
+}
+
+pub fn layer<S>(tracer_provider: &TracerProvider) -> impl Layer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let tracer: Tracer = tracer_provider.tracer(env!("CARGO_BIN_NAME"));
+    let targets = TRACED_TARGETS
+        .into_iter()
+        .fold(Targets::new(), |targets, target| {
+            targets.with_target(target, LevelFilter::DEBUG)
+        });
+    OpenTelemetryLayer::new(tracer).with_filter(targets)
+}
@@ -1,17 +1,62 @@
-use std::io::Write as _;
+use std::{
+    fs::File,
+    io::Write as _,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    time::Instant,
+};
 
-use log::log_enabled;
+use anyhow::{Context, Result};
+use plexi_core::auditor::VerificationProgress;
 use tokio::{
     task::JoinHandle,
     time::{interval, Duration},
 };
+use tracing::Level;
+
+struct OutputSink {
+    quiet: bool,
+    file: Option<Mutex<File>>,
+}
+
+static OUTPUT: OnceLock<OutputSink> = OnceLock::new();
+
+pub fn init_output(quiet: bool, file: Option<&Path>) -> Result<()> {
+    let file = file
+        .map(|path| {
+            File::create(path).with_context(|| format!("creating {path}", path = path.display()))
+        })
+        .transpose()?
+        .map(Mutex::new);
+    let _ = OUTPUT.set(OutputSink { quiet, file });
+    Ok(())
+}
+
+pub fn quiet() -> bool {
+    OUTPUT.get().is_some_and(|output| output.quiet)
+}
+
+pub fn print_output(output: &str) {
+    if let Some(file) = OUTPUT.get().and_then(|output| output.file.as_ref()) {
+        let mut file = file.lock().expect("output file lock poisoned");
+        if let Err(e) = writeln!(file, "{output}") {
+            tracing::error!("cannot write to the output file: {e}");
+        }
+    }
+    if !quiet() {
+        println!("{output}");
+    }
+}
 
 pub fn print_dots() -> JoinHandle<()> {
     async fn print_dots_routine() {
         let mut interval = interval(Duration::from_secs(1));
         loop {
             interval.tick().await;
-            if log_enabled!(log::Level::Error) {
+            if tracing::enabled!(Level::ERROR) {
                 eprint!(".");
             }
             std::io::stderr().flush().unwrap();
@@ -20,3 +65,68 @@ pub fn print_dots() -> JoinHandle<()> {
 
     tokio::spawn(print_dots_routine())
 }
+
+pub fn print_progress(progress: VerificationProgress) {
+    if tracing::enabled!(Level::ERROR) {
+        eprint!(
+            "\rVerifying proof: {percent}% ({processed}/{total} nodes)",
+            percent = progress.processed() * 100 / progress.total().max(1),
+            processed = progress.processed(),
+            total = progress.total()
+        );
+    }
+    std::io::stderr().flush().unwrap();
+}
+
+pub struct RangeProgress {
+    total: usize,
+    verified: AtomicUsize,
+    start: Instant,
+}
+
+impl RangeProgress {
+    const WIDTH: usize = 30;
+
+    pub fn new(total: usize) -> Self {
+        let progress = Self {
+            total,
+            verified: AtomicUsize::new(0),
+            start: Instant::now(),
+        };
+        progress.print(0);
+        progress
+    }
+
+    pub fn inc(&self) {
+        let verified = self.verified.fetch_add(1, Ordering::Relaxed) + 1;
+        self.print(verified);
+    }
+
+    pub fn finish(&self) {
+        if tracing::enabled!(Level::ERROR) {
+            eprintln!();
+        }
+    }
+
+    fn print(&self, verified: usize) {
+        if !tracing::enabled!(Level::ERROR) {
+            return;
+        }
+        let filled = verified * Self::WIDTH / self.total.max(1);
+        let eta = match verified {
+            0 => "-".to_string(),
+            _ => {
+                let remaining = self.start.elapsed().as_secs() * (self.total - verified) as u64
+                    / verified as u64;
+                format!("{}m{:02}s", remaining / 60, remaining % 60)
+            }
+        };
+        eprint!(
+            "\rVerifying epochs: [{bar}{space}] {verified}/{total} ETA {eta}",
+            bar = "#".repeat(filled),
+            space = " ".repeat(Self::WIDTH - filled),
+            total = self.total
+        );
+        std::io::stderr().flush().unwrap();
+    }
+}
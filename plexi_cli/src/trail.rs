@@ -0,0 +1,187 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{BufRead as _, BufReader, Read as _, Seek as _, SeekFrom, Write as _},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context, Result};
+use plexi_core::{crypto::constant_time_eq, SignatureResponse};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+const GENESIS_HASH: [u8; 32] = [0; 32];
+
+const TAIL_CHUNK_SIZE: u64 = 4096;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Outcome {
+    Success,
+    Disabled,
+    Failed { error: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Record {
+    time: u64,
+    namespace: String,
+    epoch: u64,
+    #[serde(with = "hex::serde")]
+    digest: Vec<u8>,
+    signature: Outcome,
+    proof: Outcome,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    #[serde(flatten)]
+    record: Record,
+    #[serde(with = "hex::serde")]
+    previous_hash: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    hash: Vec<u8>,
+}
+
+impl Entry {
+    fn new(record: Record, previous_hash: Vec<u8>) -> Result<Self> {
+        let hash = entry_hash(&record, &previous_hash)?;
+        Ok(Self {
+            record,
+            previous_hash,
+            hash,
+        })
+    }
+}
+
+fn entry_hash(record: &Record, previous_hash: &[u8]) -> Result<Vec<u8>> {
+    let mut hasher = Sha256::new();
+    hasher.update(previous_hash);
+    hasher.update(serde_json::to_vec(record)?);
+    Ok(hasher.finalize().to_vec())
+}
+
+#[derive(Debug, Default)]
+pub struct AuditTrail {
+    path: Option<PathBuf>,
+}
+
+impl AuditTrail {
+    pub fn default_path() -> Option<PathBuf> {
+        let state_home = env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+            })?;
+        Some(state_home.join("plexi").join("audit-trail.jsonl"))
+    }
+
+    pub fn open_default() -> Self {
+        Self {
+            path: Self::default_path(),
+        }
+    }
+
+    pub fn record(
+        &self,
+        signature: &SignatureResponse,
+        signature_outcome: Outcome,
+        proof_outcome: Outcome,
+    ) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if signature_outcome == Outcome::Disabled && proof_outcome == Outcome::Disabled {
+            return Ok(());
+        }
+        let record = Record {
+            time: SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64,
+            namespace: signature.namespace().to_string(),
+            epoch: signature.epoch().into(),
+            digest: signature.digest().to_vec(),
+            signature: signature_outcome,
+            proof: proof_outcome,
+        };
+        append(path, record).with_context(|| format!("appending to {path}", path = path.display()))
+    }
+}
+
+fn append(path: &Path, record: Record) -> Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let mut file = File::options()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?;
+    // other plexi processes may be appending to the same trail
+    file.lock()?;
+
+    let previous_hash = match last_line(&mut file)? {
+        Some(line) => serde_json::from_slice::<Entry>(&line)?.hash,
+        None => GENESIS_HASH.to_vec(),
+    };
+    let mut line = serde_json::to_vec(&Entry::new(record, previous_hash)?)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    file.sync_data()?;
+    Ok(())
+}
+
+fn last_line(file: &mut File) -> Result<Option<Vec<u8>>> {
+    let mut end = file.seek(SeekFrom::End(0))?;
+    let mut tail = vec![];
+    while end > 0 {
+        let start = end.saturating_sub(TAIL_CHUNK_SIZE);
+        let mut chunk = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        end = start;
+
+        let lines = tail.strip_suffix(b"\n").unwrap_or(&tail);
+        if let Some(newline) = lines.iter().rposition(|&b| b == b'\n') {
+            return Ok(Some(lines[newline + 1..].to_vec()));
+        }
+    }
+    let line = tail.strip_suffix(b"\n").unwrap_or(&tail);
+    Ok((!line.is_empty()).then(|| line.to_vec()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrailSummary {
+    pub entries: usize,
+    #[serde(with = "hex::serde")]
+    pub last_hash: Vec<u8>,
+}
+
+pub fn verify(path: &Path) -> Result<TrailSummary> {
+    let file =
+        File::open(path).with_context(|| format!("reading {path}", path = path.display()))?;
+    let mut previous_hash = GENESIS_HASH.to_vec();
+    let mut entries = 0;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let entry_number = index + 1;
+        let entry: Entry = serde_json::from_str(&line?)
+            .with_context(|| format!("parsing entry {entry_number}"))?;
+        if !constant_time_eq(&entry.previous_hash, &previous_hash) {
+            return Err(anyhow!(
+                "entry {entry_number} is not chained to the entry before it"
+            ));
+        }
+        if !constant_time_eq(&entry_hash(&entry.record, &previous_hash)?, &entry.hash) {
+            return Err(anyhow!(
+                "entry {entry_number} does not match its hash, it has been modified"
+            ));
+        }
+        previous_hash = entry.hash;
+        entries = entry_number;
+    }
+    Ok(TrailSummary {
+        entries,
+        last_hash: previous_hash,
+    })
+}
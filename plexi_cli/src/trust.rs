@@ -0,0 +1,167 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    roots: BTreeMap<String, String>,
+}
+
+impl TrustStore {
+    pub fn default_path() -> Option<PathBuf> {
+        let state_home = env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+            })?;
+        Some(state_home.join("plexi").join("trusted-roots.json"))
+    }
+
+    pub fn open_default() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::open(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut store = if path.exists() {
+            let store = fs::read_to_string(path)
+                .with_context(|| format!("reading {path}", path = path.display()))?;
+            serde_json::from_str(&store)
+                .with_context(|| format!("parsing {path}", path = path.display()))?
+        } else {
+            Self::default()
+        };
+        store.path = Some(path.to_path_buf());
+        Ok(store)
+    }
+
+    pub fn check(&mut self, namespace: &str, root: &str) -> Result<()> {
+        if self.path.is_none() {
+            return Ok(());
+        }
+        self.update(|roots| match roots.get(namespace) {
+            Some(trusted) if trusted == root => Ok(()),
+            Some(trusted) => Err(anyhow!(
+                "root of {namespace} changed from {trusted} to {root} since it was first seen. \
+                 If the namespace was re-rooted legitimately, run plexi trust reset --namespace {namespace}"
+            )),
+            None => {
+                tracing::info!("trusting {root} as the root of {namespace} on first use");
+                roots.insert(namespace.to_string(), root.to_string());
+                Ok(())
+            }
+        })
+    }
+
+    pub fn reset(&mut self, namespace: Option<&str>) -> Result<usize> {
+        self.update(|roots| {
+            Ok(match namespace {
+                Some(namespace) => usize::from(roots.remove(namespace).is_some()),
+                None => {
+                    let forgotten = roots.len();
+                    roots.clear();
+                    forgotten
+                }
+            })
+        })
+    }
+
+    fn update<T>(
+        &mut self,
+        f: impl FnOnce(&mut BTreeMap<String, String>) -> Result<T>,
+    ) -> Result<T> {
+        let Some(path) = self.path.clone() else {
+            return f(&mut self.roots);
+        };
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).with_context(|| {
+                format!("creating {directory}", directory = directory.display())
+            })?;
+        }
+        // the lock is taken on a file of its own, as the store is replaced when it is written
+        let lock_path = path.with_extension("json.lock");
+        let lock = fs::File::create(&lock_path)
+            .with_context(|| format!("opening {path}", path = lock_path.display()))?;
+        lock.lock()
+            .with_context(|| format!("locking {path}", path = lock_path.display()))?;
+
+        self.roots = Self::open(&path)?.roots;
+        let result = f(&mut self.roots)?;
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("writing {path}", path = temporary.display()))?;
+        fs::rename(&temporary, &path)
+            .with_context(|| format!("writing {path}", path = path.display()))?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn store_path() -> (TempDir, PathBuf) {
+        let directory = TempDir::with_prefix("plexi-").unwrap();
+        let path = directory.path().join("trusted-roots.json");
+        (directory, path)
+    }
+
+    #[test]
+    fn test_root_change() {
+        let (_directory, path) = store_path();
+        let mut store = TrustStore::open(&path).unwrap();
+        store.check("example", "1/aa").unwrap();
+        store.check("example", "1/aa").unwrap();
+
+        // another process sees the root trusted by the first one
+        let mut other = TrustStore::open(&path).unwrap();
+        assert!(other.check("example", "5/bb").is_err());
+        assert!(store.check("example", "5/bb").is_err());
+    }
+
+    #[test]
+    fn test_reset() {
+        let (_directory, path) = store_path();
+        let mut store = TrustStore::open(&path).unwrap();
+        store.check("example", "1/aa").unwrap();
+        store.check("other", "1/cc").unwrap();
+
+        assert_eq!(store.reset(Some("example")).unwrap(), 1);
+        assert_eq!(store.reset(Some("example")).unwrap(), 0);
+        let mut store = TrustStore::open(&path).unwrap();
+        store.check("example", "5/bb").unwrap();
+        assert!(store.check("other", "5/dd").is_err());
+
+        assert_eq!(store.reset(None).unwrap(), 2);
+        let mut store = TrustStore::open(&path).unwrap();
+        store.check("other", "5/dd").unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_checks() {
+        let (_directory, path) = store_path();
+        // each store is opened before any root is trusted, and only sees the others through the file
+        let stores: Vec<_> = (0..8).map(|_| TrustStore::open(&path).unwrap()).collect();
+        std::thread::scope(|scope| {
+            for (i, mut store) in stores.into_iter().enumerate() {
+                scope.spawn(move || store.check(&format!("namespace-{i}"), "1/aa").unwrap());
+            }
+        });
+
+        let mut store = TrustStore::open(&path).unwrap();
+        for i in 0..8 {
+            assert!(store.check(&format!("namespace-{i}"), "5/bb").is_err());
+        }
+    }
+}
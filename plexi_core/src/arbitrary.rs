@@ -0,0 +1,108 @@
+//! [`Arbitrary`] implementations generating well-formed values, for fuzzers and property tests of integrations.
+//! Signatures are random bytes, so they do not verify.
+
+use std::collections::BTreeMap;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use ed25519_dalek::SIGNATURE_LENGTH;
+
+use crate::{
+    auditor::AkdConfig,
+    namespaces::{Namespace, NamespaceInfo, NamespaceMetadata, NamespaceStatus},
+    Ciphersuite, DigestAlgorithm, Epoch, SignatureMessage, SignatureResponse, DIGEST_ALGORITHMS,
+    DIGEST_LENGTH, SIGNATURE_VERSIONS,
+};
+
+impl<'a> Arbitrary<'a> for Epoch {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Epoch(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Ciphersuite {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&SIGNATURE_VERSIONS).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for DigestAlgorithm {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&DIGEST_ALGORITHMS).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for SignatureMessage {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let ciphersuite: Ciphersuite = u.arbitrary()?;
+        let message = SignatureMessage::new(
+            &ciphersuite,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            &u.arbitrary()?,
+            <[u8; DIGEST_LENGTH]>::arbitrary(u)?.to_vec(),
+        )
+        .expect("ciphersuite is supported");
+        if ciphersuite != Ciphersuite::ProtobufV2Ed25519 {
+            return Ok(message);
+        }
+        let mut message = message.with_metadata(BTreeMap::arbitrary(u)?);
+        if let Some(digest_algorithm) = Option::<DigestAlgorithm>::arbitrary(u)? {
+            message = message.with_digest_algorithm(digest_algorithm);
+        }
+        Ok(match Option::<[u8; DIGEST_LENGTH]>::arbitrary(u)? {
+            Some(previous_digest) => message.with_previous_digest(previous_digest.to_vec()),
+            None => message,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for SignatureResponse {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let message: SignatureMessage = u.arbitrary()?;
+        Ok(SignatureResponse::new(
+            message.ciphersuite(),
+            message.ciphersuite(),
+            message.namespace().to_string(),
+            message.timestamp(),
+            message.epoch(),
+            message.digest_owned(),
+            <[u8; SIGNATURE_LENGTH]>::arbitrary(u)?.to_vec(),
+            u.arbitrary()?,
+            None,
+        )
+        .with_message_fields(&message))
+    }
+}
+
+impl<'a> Arbitrary<'a> for NamespaceInfo {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let root = match Option::<(Epoch, [u8; DIGEST_LENGTH])>::arbitrary(u)? {
+            Some((epoch, digest)) => Some(epoch.as_root_epoch(&hex::encode(digest))),
+            None => None,
+        };
+        let mut namespace = Namespace::new(
+            u.arbitrary()?,
+            u.arbitrary()?,
+            root,
+            None,
+            Some(u.arbitrary()?),
+        );
+        namespace.set_akd_config(*u.choose(&[AkdConfig::WhatsAppV1, AkdConfig::Experimental])?);
+        namespace.set_aliases(u.arbitrary()?);
+        namespace.set_metadata(NamespaceMetadata::new(
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+        ));
+        let status = u
+            .choose(&[
+                NamespaceStatus::Online,
+                NamespaceStatus::Initialization,
+                NamespaceStatus::Disabled,
+            ])?
+            .clone();
+        let mut info = NamespaceInfo::new(&namespace, status);
+        info.set_last_verified_epoch(u.arbitrary()?);
+        Ok(info)
+    }
+}
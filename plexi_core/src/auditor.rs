@@ -1,25 +1,70 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, str::FromStr};
+#[cfg(feature = "auditor")]
+use std::{
+    io::{self, BufReader, Read, Seek},
+    sync::Arc,
+    time::Instant,
+};
 
 #[cfg(feature = "auditor")]
 use akd::{
     append_only_zks::InsertMode,
+    errors::{AkdError, AzksError},
     local_auditing::AuditBlobName,
     storage::{memory::AsyncInMemoryDatabase, StorageManager},
-    Azks, Digest, SingleAppendOnlyProof, WhatsAppV1Configuration,
+    Azks, AzksElement, AzksValue, Digest, ExampleLabel, ExperimentalConfiguration,
+    SingleAppendOnlyProof, WhatsAppV1Configuration,
 };
-#[cfg(feature = "auditor")]
-use anyhow::anyhow;
-use anyhow::Context as _;
+use anyhow::{anyhow, Context as _};
 #[cfg(feature = "auditor")]
 use protobuf::Message as _;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "auditor")]
+pub use tokio_util::sync::CancellationToken;
 #[cfg(feature = "openapi")]
 use utoipa::ToSchema;
 
+use crate::PlexiError;
+#[cfg(feature = "auditor")]
+use crate::{crypto::constant_time_eq, namespaces::NamespaceInfo, SignatureResponse};
+use crate::{DigestAlgorithm, Epoch};
+
+const KEY_ROTATION_CONTEXT: &[u8] = b"plexi key rotation v1\0";
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
 pub struct KeyInfo {
     public_key: String,
     not_before: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotation: Option<KeyRotation>,
+}
+
+/// Statement by which the key an auditor rotates from introduces its successor
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct KeyRotation {
+    /// Hex encoded Ed25519 public key introducing the new key
+    previous_key: String,
+    /// Hex encoded signature of the [`KeyInfo::rotation_message`] of the new key by `previous_key`
+    signature: String,
+}
+
+impl KeyRotation {
+    pub fn new(previous_key: &str, signature: &[u8]) -> Self {
+        Self {
+            previous_key: previous_key.into(),
+            signature: hex::encode(signature),
+        }
+    }
+
+    pub fn previous_key(&self) -> &str {
+        &self.previous_key
+    }
+
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
 }
 
 impl KeyInfo {
@@ -27,9 +72,16 @@ impl KeyInfo {
         Self {
             public_key: public_key.into(),
             not_before,
+            rotation: None,
         }
     }
 
+    /// Attach `rotation`, the statement of the key introducing this one
+    pub fn with_rotation(mut self, rotation: KeyRotation) -> Self {
+        self.rotation = Some(rotation);
+        self
+    }
+
     pub fn public_key(&self) -> &String {
         &self.public_key
     }
@@ -44,6 +96,83 @@ impl KeyInfo {
             .last()
             .expect("fixed size array has a last element")
     }
+
+    pub fn rotation(&self) -> Option<&KeyRotation> {
+        self.rotation.as_ref()
+    }
+
+    /// Message the previous key signs to introduce this key: a context, the public key, and `not_before` in big endian
+    pub fn rotation_message(&self) -> anyhow::Result<Vec<u8>> {
+        let public_key = hex::decode(&self.public_key).context("decoding key public key")?;
+        Ok([
+            KEY_ROTATION_CONTEXT,
+            &public_key,
+            &self.not_before.to_be_bytes(),
+        ]
+        .concat())
+    }
+
+    /// Key introducing this one, once its rotation statement verifies
+    pub fn verify_rotation(&self) -> anyhow::Result<Option<&str>> {
+        let Some(rotation) = &self.rotation else {
+            return Ok(None);
+        };
+        let previous_key: [u8; ed25519_dalek::PUBLIC_KEY_LENGTH] =
+            hex::decode(&rotation.previous_key)
+                .context("decoding previous key")?
+                .try_into()
+                .map_err(|_| anyhow!("previous key is not an Ed25519 public key"))?;
+        let previous_key = ed25519_dalek::VerifyingKey::from_bytes(&previous_key)
+            .context("parsing previous key")?;
+        let signature = ed25519_dalek::Signature::from_slice(
+            &hex::decode(&rotation.signature).context("decoding rotation signature")?,
+        )
+        .context("parsing rotation signature")?;
+        previous_key
+            .verify_strict(&self.rotation_message()?, &signature)
+            .with_context(|| {
+                format!(
+                    "rotation from {previous} to {key} does not verify",
+                    previous = rotation.previous_key,
+                    key = self.public_key
+                )
+            })?;
+        Ok(Some(&rotation.previous_key))
+    }
+
+    /// Public keys from this key back to `root`, each introduced by the next one
+    pub fn chain_to(&self, root: &str, keys: &[KeyInfo]) -> anyhow::Result<Vec<String>> {
+        let mut chain = vec![self.public_key.clone()];
+        let mut key = self;
+        while !key.public_key.eq_ignore_ascii_case(root) {
+            let Some(previous_key) = key.verify_rotation()? else {
+                return Err(anyhow!(
+                    "key {key} is not introduced by another key",
+                    key = key.public_key
+                ));
+            };
+            if chain
+                .iter()
+                .any(|chained| chained.eq_ignore_ascii_case(previous_key))
+            {
+                return Err(anyhow!("key {previous_key} is introduced by itself"));
+            }
+            chain.push(previous_key.to_string());
+            if previous_key.eq_ignore_ascii_case(root) {
+                break;
+            }
+            key = keys
+                .iter()
+                .find(|previous| previous.public_key.eq_ignore_ascii_case(previous_key))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "key {previous_key} introducing {key} is not advertised",
+                        key = key.public_key
+                    )
+                })?;
+        }
+        Ok(chain)
+    }
 }
 
 impl From<KeyInfo> for HashMap<String, String> {
@@ -53,6 +182,10 @@ impl From<KeyInfo> for HashMap<String, String> {
         map.insert("public_key".to_string(), val.public_key.clone());
         // Convert u64 to String for key 'not_before'
         map.insert("not_before".to_string(), val.not_before.to_string());
+        if let Some(rotation) = val.rotation {
+            map.insert("rotation_previous_key".to_string(), rotation.previous_key);
+            map.insert("rotation_signature".to_string(), rotation.signature);
+        }
         map
     }
 }
@@ -70,11 +203,21 @@ impl TryFrom<HashMap<String, String>> for KeyInfo {
                 .get("not_before")
                 .context("getting KeyInfo not_before")?
                 .parse()?,
+            rotation: match (
+                value.get("rotation_previous_key"),
+                value.get("rotation_signature"),
+            ) {
+                (Some(previous_key), Some(signature)) => Some(KeyRotation {
+                    previous_key: previous_key.clone(),
+                    signature: signature.clone(),
+                }),
+                _ => None,
+            },
         })
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
 pub struct Configuration {
     keys: Vec<KeyInfo>,
@@ -98,47 +241,1876 @@ impl Configuration {
     }
 }
 
+/// AKD configuration a log uses, which defines how its tree nodes are hashed
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AkdConfig {
+    /// Configuration used by WhatsApp Key Transparency
+    #[default]
+    #[serde(rename = "whatsapp_v1")]
+    WhatsAppV1,
+    /// akd experimental configuration, with akd example domain label
+    Experimental,
+    /// Configuration this version of plexi does not know about
+    #[serde(other)]
+    Unknown,
+}
+
+impl fmt::Display for AkdConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::WhatsAppV1 => "whatsapp_v1",
+            Self::Experimental => "experimental",
+            Self::Unknown => "unknown",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl AkdConfig {
+    /// Hash function used to compute the tree nodes, if known
+    pub fn digest_algorithm(&self) -> Option<DigestAlgorithm> {
+        match self {
+            Self::WhatsAppV1 | Self::Experimental => Some(DigestAlgorithm::Blake3),
+            Self::Unknown => None,
+        }
+    }
+}
+
+impl FromStr for AkdConfig {
+    type Err = PlexiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "whatsapp_v1" => Ok(Self::WhatsAppV1),
+            "experimental" => Ok(Self::Experimental),
+            _ => Err(PlexiError::BadParameter("akd_config".to_string())),
+        }
+    }
+}
+
+/// Name a log publishes the append-only proof leading to `epoch` under
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProofId {
+    pub epoch: u64,
+    /// Root hash of the tree at `epoch - 1`
+    pub previous_hash: [u8; 32],
+    /// Root hash of the tree at `epoch`
+    pub current_hash: [u8; 32],
+}
+
+impl fmt::Display for ProofId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{epoch}/{previous_hash}/{current_hash}",
+            epoch = self.epoch,
+            previous_hash = hex::encode(self.previous_hash),
+            current_hash = hex::encode(self.current_hash)
+        )
+    }
+}
+
+impl FromStr for ProofId {
+    type Err = PlexiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_hash = |hash: &str| {
+            let mut decoded = [0; 32];
+            hex::decode_to_slice(hash, &mut decoded)
+                .map_err(|_| PlexiError::BadParameter("proof_id".to_string()))?;
+            Ok(decoded)
+        };
+        let mut parts = s.split('/');
+        let (Some(epoch), Some(previous_hash), Some(current_hash), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(PlexiError::BadParameter("proof_id".to_string()));
+        };
+        Ok(Self {
+            epoch: epoch
+                .parse()
+                .map_err(|_| PlexiError::BadParameter("proof_id".to_string()))?,
+            previous_hash: parse_hash(previous_hash)?,
+            current_hash: parse_hash(current_hash)?,
+        })
+    }
+}
+
+#[cfg(feature = "auditor")]
+impl From<AuditBlobName> for ProofId {
+    fn from(blob: AuditBlobName) -> Self {
+        Self {
+            epoch: blob.epoch,
+            previous_hash: blob.previous_hash,
+            current_hash: blob.current_hash,
+        }
+    }
+}
+
+#[cfg(feature = "auditor")]
+impl From<ProofId> for AuditBlobName {
+    fn from(id: ProofId) -> Self {
+        Self {
+            epoch: id.epoch,
+            previous_hash: id.previous_hash,
+            current_hash: id.current_hash,
+        }
+    }
+}
+
+#[cfg(feature = "auditor")]
+macro_rules! dispatch_akd_config {
+    ($config:expr, $f:ident($($args:expr),*)) => {
+        match $config {
+            AkdConfig::WhatsAppV1 => $f::<WhatsAppV1Configuration>($($args),*).await,
+            AkdConfig::Experimental => $f::<ExperimentalConfiguration<ExampleLabel>>($($args),*).await,
+            AkdConfig::Unknown => Err(anyhow!("proof verification is not supported for this AKD configuration")),
+        }
+    };
+}
+
+/// Default maximum size of an encoded proof, in bytes, and of a download from an auditor or log directory
+pub const DEFAULT_MAX_PROOF_SIZE: usize = 1 << 30;
+/// Default maximum number of nodes, inserted and unchanged, in a proof
+#[cfg(feature = "auditor")]
+pub const DEFAULT_MAX_PROOF_NODES: usize = 1 << 24;
+
+/// Bounds on the proofs plexi accepts to parse, so a hostile log cannot exhaust memory
+#[cfg(feature = "auditor")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProofLimits {
+    max_size: usize,
+    max_nodes: usize,
+}
+
+#[cfg(feature = "auditor")]
+impl Default for ProofLimits {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_PROOF_SIZE,
+            max_nodes: DEFAULT_MAX_PROOF_NODES,
+        }
+    }
+}
+
+#[cfg(feature = "auditor")]
+impl ProofLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    fn check(&self, raw_proof: &[u8]) -> Result<(), PlexiError> {
+        if raw_proof.len() > self.max_size {
+            return Err(PlexiError::ProofTooLarge {
+                size: raw_proof.len(),
+                limit: self.max_size,
+            });
+        }
+        let nodes = count_proof_nodes(raw_proof)?;
+        if nodes > self.max_nodes {
+            return Err(PlexiError::TooManyProofNodes {
+                nodes,
+                limit: self.max_nodes,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "auditor")]
+fn read_varint(input: &mut &[u8]) -> Result<u64, PlexiError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (byte, rest) = input.split_first().ok_or(PlexiError::Serialization)?;
+        *input = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(PlexiError::Serialization)
+}
+
+#[cfg(feature = "auditor")]
+const WIRE_VARINT: u64 = 0;
+#[cfg(feature = "auditor")]
+const WIRE_FIXED64: u64 = 1;
+#[cfg(feature = "auditor")]
+const WIRE_LENGTH_DELIMITED: u64 = 2;
+#[cfg(feature = "auditor")]
+const WIRE_FIXED32: u64 = 5;
+
+#[cfg(feature = "auditor")]
+const INSERTED_FIELD: u64 = 1;
+#[cfg(feature = "auditor")]
+const UNCHANGED_FIELD: u64 = 2;
+
+#[cfg(feature = "auditor")]
+fn count_proof_nodes(raw_proof: &[u8]) -> Result<usize, PlexiError> {
+    let mut input = raw_proof;
+    let mut nodes = 0;
+    while !input.is_empty() {
+        let key = read_varint(&mut input)?;
+        let length = match key & 0x7 {
+            WIRE_VARINT => {
+                read_varint(&mut input)?;
+                0
+            }
+            WIRE_FIXED64 => 8,
+            WIRE_LENGTH_DELIMITED => {
+                if matches!(key >> 3, INSERTED_FIELD | UNCHANGED_FIELD) {
+                    nodes += 1;
+                }
+                usize::try_from(read_varint(&mut input)?).map_err(|_| PlexiError::Serialization)?
+            }
+            WIRE_FIXED32 => 4,
+            _ => return Err(PlexiError::Serialization),
+        };
+        input = input.get(length..).ok_or(PlexiError::Serialization)?;
+    }
+    Ok(nodes)
+}
+
 #[cfg(feature = "auditor")]
-pub async fn compute_start_root_hash(raw_proof: &[u8]) -> anyhow::Result<Digest> {
+fn parse_proof(raw_proof: &[u8], limits: &ProofLimits) -> anyhow::Result<SingleAppendOnlyProof> {
+    limits.check(raw_proof)?;
+
     let proto = akd::proto::specs::types::SingleAppendOnlyProof::parse_from_bytes(raw_proof)
         .context("unable to parse proof bytes")?;
 
-    let proof = SingleAppendOnlyProof::try_from(&proto)
+    SingleAppendOnlyProof::try_from(&proto)
         .map_err(|e| anyhow::anyhow!(e.to_string()))
-        .context("converting parsed protobuf proof to `SingleAppendOnlyProof`")?;
+        .context("converting parsed protobuf proof to `SingleAppendOnlyProof`")
+}
+
+#[cfg(feature = "auditor")]
+const MAX_PROOF_NODE_SIZE: usize = 1024;
+
+#[cfg(feature = "auditor")]
+struct ProofNodeReader<'a, R> {
+    reader: &'a mut R,
+    field: Option<u64>,
+    limits: &'a ProofLimits,
+    size: usize,
+    inserted: usize,
+    unchanged: usize,
+    done: bool,
+}
+
+#[cfg(feature = "auditor")]
+impl<'a, R: Read> ProofNodeReader<'a, R> {
+    fn new(reader: &'a mut R, field: Option<u64>, limits: &'a ProofLimits) -> Self {
+        Self {
+            reader,
+            field,
+            limits,
+            size: 0,
+            inserted: 0,
+            unchanged: 0,
+            done: false,
+        }
+    }
+
+    fn walk(mut self) -> anyhow::Result<Self> {
+        self.field = None;
+        while self.next_node()?.is_some() {}
+        Ok(self)
+    }
+
+    fn nodes(&self) -> usize {
+        self.inserted + self.unchanged
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> anyhow::Result<()> {
+        self.add_size(buf.len())?;
+        self.reader.read_exact(buf).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => PlexiError::Serialization.into(),
+            _ => anyhow::Error::from(e),
+        })
+    }
+
+    fn skip(&mut self, length: usize) -> anyhow::Result<()> {
+        self.add_size(length)?;
+        let skipped = io::copy(
+            &mut self.reader.by_ref().take(length as u64),
+            &mut io::sink(),
+        )?;
+        if skipped != length as u64 {
+            return Err(PlexiError::Serialization.into());
+        }
+        Ok(())
+    }
+
+    fn add_size(&mut self, length: usize) -> Result<(), PlexiError> {
+        self.size = self.size.saturating_add(length);
+        if self.size > self.limits.max_size {
+            return Err(PlexiError::ProofTooLarge {
+                size: self.size,
+                limit: self.limits.max_size,
+            });
+        }
+        Ok(())
+    }
+
+    fn read_varint(&mut self, at_key: bool) -> anyhow::Result<Option<u64>> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        while shift < 64 {
+            let mut byte = [0];
+            match self.reader.read(&mut byte) {
+                Ok(0) if at_key && shift == 0 => return Ok(None),
+                Ok(0) => return Err(PlexiError::Serialization.into()),
+                Ok(_) => self.add_size(1)?,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+            value |= u64::from(byte[0] & 0x7f) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(Some(value));
+            }
+            shift += 7;
+        }
+        Err(PlexiError::Serialization.into())
+    }
+
+    fn next_node(&mut self) -> anyhow::Result<Option<AzksElement>> {
+        loop {
+            let Some(key) = self.read_varint(true)? else {
+                return Ok(None);
+            };
+            let length = match key & 0x7 {
+                WIRE_VARINT => {
+                    self.read_varint(false)?;
+                    continue;
+                }
+                WIRE_FIXED64 => 8,
+                WIRE_FIXED32 => 4,
+                WIRE_LENGTH_DELIMITED => {
+                    let length = self.read_varint(false)?.ok_or(PlexiError::Serialization)?;
+                    usize::try_from(length).map_err(|_| PlexiError::Serialization)?
+                }
+                _ => return Err(PlexiError::Serialization.into()),
+            };
+            let field = key >> 3;
+            if key & 0x7 == WIRE_LENGTH_DELIMITED
+                && matches!(field, INSERTED_FIELD | UNCHANGED_FIELD)
+            {
+                match field {
+                    INSERTED_FIELD => self.inserted += 1,
+                    _ => self.unchanged += 1,
+                }
+                if self.nodes() > self.limits.max_nodes {
+                    return Err(PlexiError::TooManyProofNodes {
+                        nodes: self.nodes(),
+                        limit: self.limits.max_nodes,
+                    }
+                    .into());
+                }
+            }
+            if self.field != Some(field) {
+                self.skip(length)?;
+                continue;
+            }
+            if length > MAX_PROOF_NODE_SIZE {
+                return Err(PlexiError::Serialization.into());
+            }
+
+            let mut node = vec![0; length];
+            self.read_exact(&mut node)?;
+            let node = akd::proto::specs::types::AzksElement::parse_from_bytes(&node)
+                .context("unable to parse proof node")?;
+            return AzksElement::try_from(&node)
+                .map(Some)
+                .map_err(|e| anyhow!(e.to_string()))
+                .context("converting parsed protobuf node to `AzksElement`");
+        }
+    }
+}
+
+#[cfg(feature = "auditor")]
+impl<R: Read> Iterator for ProofNodeReader<'_, R> {
+    type Item = anyhow::Result<AzksElement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let node = self.next_node().transpose();
+        self.done = !matches!(node, Some(Ok(_)));
+        node
+    }
+}
+
+/// Structured description of an audit proof, obtained without verifying it
+#[cfg(feature = "auditor")]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProofSummary {
+    epoch: Option<u64>,
+    #[serde(serialize_with = "serialize_optional_digest")]
+    previous_hash: Option<Digest>,
+    #[serde(serialize_with = "serialize_optional_digest")]
+    current_hash: Option<Digest>,
+    size: usize,
+    inserted: usize,
+    unchanged: usize,
+    akd_config: AkdConfig,
+    hash_algorithm: Option<DigestAlgorithm>,
+}
+
+#[cfg(feature = "auditor")]
+fn serialize_optional_digest<S: serde::Serializer>(
+    digest: &Option<Digest>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    digest.map(hex::encode).serialize(serializer)
+}
+
+#[cfg(feature = "auditor")]
+impl ProofSummary {
+    /// Epoch the proof leads to
+    pub fn epoch(&self) -> Option<u64> {
+        self.epoch
+    }
+
+    pub fn previous_hash(&self) -> Option<Digest> {
+        self.previous_hash
+    }
+
+    pub fn current_hash(&self) -> Option<Digest> {
+        self.current_hash
+    }
+
+    /// Size of the encoded proof, in bytes
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Number of leaves inserted between the two epochs
+    pub fn inserted(&self) -> usize {
+        self.inserted
+    }
+
+    /// Number of subtrees left unchanged between the two epochs
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    pub fn akd_config(&self) -> AkdConfig {
+        self.akd_config
+    }
+
+    /// Hash function of [`Self::akd_config`], if known
+    pub fn hash_algorithm(&self) -> Option<DigestAlgorithm> {
+        self.hash_algorithm
+    }
+}
+
+/// Decode `raw_proof` and describe it, without verifying it
+#[cfg(feature = "auditor")]
+pub fn inspect_proof(
+    config: AkdConfig,
+    blob: Option<&ProofId>,
+    raw_proof: &[u8],
+) -> anyhow::Result<ProofSummary> {
+    let proof = parse_proof(raw_proof, &ProofLimits::default())?;
+
+    Ok(ProofSummary {
+        epoch: blob.map(|blob| blob.epoch),
+        previous_hash: blob.map(|blob| blob.previous_hash),
+        current_hash: blob.map(|blob| blob.current_hash),
+        size: raw_proof.len(),
+        inserted: proof.inserted.len(),
+        unchanged: proof.unchanged_nodes.len(),
+        akd_config: config,
+        hash_algorithm: config.digest_algorithm(),
+    })
+}
+
+/// [`inspect_proof`] reading the proof from `reader`
+#[cfg(feature = "auditor")]
+pub fn inspect_proof_from_reader(
+    config: AkdConfig,
+    blob: Option<&ProofId>,
+    reader: impl Read,
+    limits: &ProofLimits,
+) -> anyhow::Result<ProofSummary> {
+    let mut reader = BufReader::new(reader);
+    let proof = ProofNodeReader::new(&mut reader, None, limits).walk()?;
+
+    Ok(ProofSummary {
+        epoch: blob.map(|blob| blob.epoch),
+        previous_hash: blob.map(|blob| blob.previous_hash),
+        current_hash: blob.map(|blob| blob.current_hash),
+        size: proof.size,
+        inserted: proof.inserted,
+        unchanged: proof.unchanged,
+        akd_config: config,
+        hash_algorithm: config.digest_algorithm(),
+    })
+}
+
+#[cfg(feature = "auditor")]
+async fn compute_start_root_hash_for<TC: akd::Configuration>(
+    raw_proof: &[u8],
+) -> anyhow::Result<Digest> {
+    let proof = parse_proof(raw_proof, &ProofLimits::default())?;
 
     let db = AsyncInMemoryDatabase::new();
     let manager = StorageManager::new_no_cache(db);
 
-    let mut azks = Azks::new::<WhatsAppV1Configuration, _>(&manager).await?;
-    azks.batch_insert_nodes::<WhatsAppV1Configuration, _>(
-        &manager,
-        proof.unchanged_nodes.clone(),
-        InsertMode::Auditor,
-    )
-    .await?;
+    let mut azks = Azks::new::<TC, _>(&manager).await?;
+    azks.batch_insert_nodes::<TC, _>(&manager, proof.unchanged_nodes.clone(), InsertMode::Auditor)
+        .await?;
 
-    Ok(azks
-        .get_root_hash::<WhatsAppV1Configuration, _>(&manager)
-        .await?)
+    Ok(azks.get_root_hash::<TC, _>(&manager).await?)
 }
 
+/// Compute the root hash of the tree before `raw_proof` is applied
 #[cfg(feature = "auditor")]
-pub async fn verify_raw_proof(blob: &AuditBlobName, raw_proof: &[u8]) -> anyhow::Result<()> {
-    let proto = akd::proto::specs::types::SingleAppendOnlyProof::parse_from_bytes(raw_proof)
-        .context("unable to parse proof bytes")?;
+#[tracing::instrument(skip(raw_proof), fields(size = raw_proof.len()))]
+pub async fn compute_start_root_hash(
+    config: AkdConfig,
+    raw_proof: &[u8],
+) -> anyhow::Result<Digest> {
+    let raw_proof = raw_proof.to_vec();
+    spawn_verification(move || async move {
+        dispatch_akd_config!(config, compute_start_root_hash_for(&raw_proof))
+    })
+    .await
+}
 
-    let proof = SingleAppendOnlyProof::try_from(&proto)
-        .map_err(|e| anyhow::anyhow!(e.to_string()))
-        .context("converting parsed protobuf proof to `SingleAppendOnlyProof`")?;
+/// Number of nodes inserted in the verification tree so far, out of the number of nodes in the proof
+#[cfg(feature = "auditor")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VerificationProgress {
+    processed: usize,
+    total: usize,
+}
+
+#[cfg(feature = "auditor")]
+impl VerificationProgress {
+    pub fn processed(&self) -> usize {
+        self.processed
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+#[cfg(feature = "auditor")]
+pub type ProgressCallback = Arc<dyn Fn(VerificationProgress) + Send + Sync>;
+
+#[cfg(feature = "auditor")]
+const PROGRESS_STEPS: usize = 100;
+
+/// Options for [`verify_raw_proof_with_options`]
+#[cfg(feature = "auditor")]
+#[derive(Clone, Default)]
+pub struct VerifyOptions {
+    progress: Option<ProgressCallback>,
+    cancellation: Option<CancellationToken>,
+    deadline: Option<Instant>,
+    limits: ProofLimits,
+}
+
+#[cfg(feature = "auditor")]
+impl fmt::Debug for VerifyOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifyOptions")
+            .field("progress", &self.progress.is_some())
+            .field("cancellation", &self.cancellation)
+            .field("deadline", &self.deadline)
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
+
+#[cfg(feature = "auditor")]
+impl VerifyOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `progress` as nodes of the proof are processed
+    pub fn progress(
+        mut self,
+        progress: impl Fn(VerificationProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Abort verification with [`PlexiError::VerificationCancelled`] once `token` is cancelled
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Abort verification with [`PlexiError::VerificationDeadlineExceeded`] if it is still running at `deadline`
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Reject proofs exceeding `limits` before decoding them
+    pub fn limits(mut self, limits: ProofLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    fn is_interruptible(&self) -> bool {
+        self.progress.is_some() || self.cancellation.is_some() || self.deadline.is_some()
+    }
+
+    fn check_interrupted(&self) -> Result<(), PlexiError> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(PlexiError::VerificationCancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(PlexiError::VerificationDeadlineExceeded);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "auditor")]
+struct Verification<'a> {
+    options: &'a VerifyOptions,
+    batch_size: usize,
+    progress: VerificationProgress,
+}
+
+#[cfg(feature = "auditor")]
+impl<'a> Verification<'a> {
+    fn new(options: &'a VerifyOptions, total: usize) -> Self {
+        let batch_size = if options.is_interruptible() {
+            total.div_ceil(PROGRESS_STEPS).max(1)
+        } else {
+            total.max(1)
+        };
+        Self {
+            options,
+            batch_size,
+            progress: VerificationProgress {
+                processed: 0,
+                total,
+            },
+        }
+    }
+
+    async fn insert_nodes<TC: akd::Configuration>(
+        &mut self,
+        azks: &mut Azks,
+        manager: &StorageManager<AsyncInMemoryDatabase>,
+        nodes: impl IntoIterator<Item = anyhow::Result<AzksElement>>,
+        epoch: u64,
+    ) -> anyhow::Result<()> {
+        let mut nodes = nodes.into_iter().peekable();
+        while nodes.peek().is_some() {
+            self.options.check_interrupted()?;
+
+            let batch = nodes
+                .by_ref()
+                .take(self.batch_size)
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let inserted = batch.len();
+            // batch insertion increments the tree epoch, while all nodes must be inserted at the same epoch
+            azks.latest_epoch = epoch - 1;
+            azks.batch_insert_nodes::<TC, _>(manager, batch, InsertMode::Auditor)
+                .await?;
+
+            self.progress.processed += inserted;
+            if let Some(progress) = &self.options.progress {
+                progress(self.progress);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "auditor")]
+async fn compute_root_hashes_for<TC: akd::Configuration>(
+    proof: SingleAppendOnlyProof,
+    epoch: u64,
+    options: &VerifyOptions,
+) -> anyhow::Result<(Digest, Digest)> {
+    if epoch == 0 {
+        return Err(anyhow!("epoch 0 does not have an append-only proof"));
+    }
+
+    let mut verification =
+        Verification::new(options, proof.unchanged_nodes.len() + proof.inserted.len());
+
+    let manager = StorageManager::new_no_cache(AsyncInMemoryDatabase::new());
+    let mut azks = Azks::new::<TC, _>(&manager).await?;
+
+    verification
+        .insert_nodes::<TC>(
+            &mut azks,
+            &manager,
+            proof.unchanged_nodes.into_iter().map(Ok),
+            1,
+        )
+        .await?;
+    let start_hash = azks.get_root_hash::<TC, _>(&manager).await?;
+
+    let inserted = proof
+        .inserted
+        .into_iter()
+        .map(|node| Ok(inserted_leaf::<TC>(node, epoch)));
+    verification
+        .insert_nodes::<TC>(&mut azks, &manager, inserted, epoch)
+        .await?;
+    let end_hash = azks.get_root_hash::<TC, _>(&manager).await?;
+
+    Ok((start_hash, end_hash))
+}
+
+#[cfg(feature = "auditor")]
+fn inserted_leaf<TC: akd::Configuration>(mut node: AzksElement, epoch: u64) -> AzksElement {
+    node.value = AzksValue(TC::hash_leaf_with_commitment(node.value, epoch).0);
+    node
+}
+
+#[cfg(feature = "auditor")]
+const READER_BATCH_SIZE: usize = 1 << 16;
+
+#[cfg(feature = "auditor")]
+async fn compute_root_hashes_from_reader_for<TC: akd::Configuration>(
+    reader: &mut (impl Read + Seek),
+    epoch: u64,
+    options: &VerifyOptions,
+) -> anyhow::Result<(Digest, Digest)> {
+    if epoch == 0 {
+        return Err(anyhow!("epoch 0 does not have an append-only proof"));
+    }
+
+    let limits = &options.limits;
+    let total = ProofNodeReader::new(reader, None, limits).walk()?.nodes();
+    let mut verification = Verification::new(options, total);
+    verification.batch_size = verification.batch_size.min(READER_BATCH_SIZE);
+
+    let manager = StorageManager::new_no_cache(AsyncInMemoryDatabase::new());
+    let mut azks = Azks::new::<TC, _>(&manager).await?;
+
+    reader.rewind()?;
+    let unchanged = ProofNodeReader::new(reader, Some(UNCHANGED_FIELD), limits);
+    verification
+        .insert_nodes::<TC>(&mut azks, &manager, unchanged, 1)
+        .await?;
+    let start_hash = azks.get_root_hash::<TC, _>(&manager).await?;
+
+    reader.rewind()?;
+    let inserted = ProofNodeReader::new(reader, Some(INSERTED_FIELD), limits)
+        .map(|node| node.map(|node| inserted_leaf::<TC>(node, epoch)));
+    verification
+        .insert_nodes::<TC>(&mut azks, &manager, inserted, epoch)
+        .await?;
+    let end_hash = azks.get_root_hash::<TC, _>(&manager).await?;
+
+    Ok((start_hash, end_hash))
+}
+
+/// Compute the root hashes of the tree before and after the proof `reader` holds is applied at `epoch`
+#[cfg(feature = "auditor")]
+#[tracing::instrument(skip(reader, options))]
+pub async fn compute_root_hashes_from_reader<R: Read + Seek + Send + 'static>(
+    config: AkdConfig,
+    epoch: u64,
+    reader: R,
+    options: VerifyOptions,
+) -> anyhow::Result<(Digest, Digest)> {
+    spawn_verification(move || async move {
+        let mut reader = BufReader::new(reader);
+        dispatch_akd_config!(
+            config,
+            compute_root_hashes_from_reader_for(&mut reader, epoch, &options)
+        )
+    })
+    .await
+}
+
+/// Verify the proof `reader` holds is a valid append-only proof for `blob`, without reading it in memory at once
+#[cfg(feature = "auditor")]
+pub async fn verify_proof_from_reader<R: Read + Seek + Send + 'static>(
+    config: AkdConfig,
+    blob: &ProofId,
+    reader: R,
+    options: VerifyOptions,
+) -> anyhow::Result<()> {
+    let (start_hash, end_hash) =
+        compute_root_hashes_from_reader(config, blob.epoch, reader, options).await?;
+    if !constant_time_eq(&start_hash, &blob.previous_hash)
+        || !constant_time_eq(&end_hash, &blob.current_hash)
+    {
+        return Err(anyhow!(AkdError::AzksErr(AzksError::VerifyAppendOnlyProof)))
+            .with_context(|| format!("verifying raw proof: {blob}"));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "auditor")]
+async fn compute_end_root_hash_for<TC: akd::Configuration>(
+    epoch: u64,
+    raw_proof: &[u8],
+) -> anyhow::Result<Digest> {
+    let proof = parse_proof(raw_proof, &ProofLimits::default())?;
+    let (_, end_hash) =
+        compute_root_hashes_for::<TC>(proof, epoch, &VerifyOptions::default()).await?;
+    Ok(end_hash)
+}
+
+/// Compute the root hash of the tree once `raw_proof` is applied at `epoch`
+#[cfg(feature = "auditor")]
+#[tracing::instrument(skip(raw_proof), fields(size = raw_proof.len()))]
+pub async fn compute_end_root_hash(
+    config: AkdConfig,
+    epoch: u64,
+    raw_proof: &[u8],
+) -> anyhow::Result<Digest> {
+    let raw_proof = raw_proof.to_vec();
+    spawn_verification(move || async move {
+        dispatch_akd_config!(config, compute_end_root_hash_for(epoch, &raw_proof))
+    })
+    .await
+}
+
+#[cfg(feature = "auditor")]
+async fn verify_raw_proof_for<TC: akd::Configuration>(
+    blob: &ProofId,
+    raw_proof: &[u8],
+    options: &VerifyOptions,
+) -> anyhow::Result<()> {
+    let proof = parse_proof(raw_proof, &options.limits)?;
+    if !options.is_interruptible() {
+        if blob.epoch == 0 {
+            return Err(anyhow!("epoch 0 does not have an append-only proof"));
+        }
+        return akd::auditor::verify_consecutive_append_only::<TC>(
+            &proof,
+            blob.previous_hash,
+            blob.current_hash,
+            blob.epoch,
+        )
+        .await
+        .with_context(|| format!("verifying raw proof: {blob}"));
+    }
+    let (start_hash, end_hash) = compute_root_hashes_for::<TC>(proof, blob.epoch, options).await?;
 
-    akd::auditor::verify_consecutive_append_only::<WhatsAppV1Configuration>(
-        &proof,
-        blob.previous_hash,
-        blob.current_hash,
-        blob.epoch,
+    if !constant_time_eq(&start_hash, &blob.previous_hash)
+        || !constant_time_eq(&end_hash, &blob.current_hash)
+    {
+        return Err(anyhow!(AkdError::AzksErr(AzksError::VerifyAppendOnlyProof)))
+            .with_context(|| format!("verifying raw proof: {blob}"));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "auditor")]
+fn block_on_verification<F: std::future::Future>(future: F) -> anyhow::Result<F::Output> {
+    thread_local! {
+        static RUNTIME: std::cell::OnceCell<tokio::runtime::Runtime> = const { std::cell::OnceCell::new() };
+    }
+    RUNTIME.with(|runtime| {
+        let runtime = match runtime.get() {
+            Some(runtime) => runtime,
+            None => {
+                let built = tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .context("building proof verification runtime")?;
+                runtime.get_or_init(|| built)
+            }
+        };
+        Ok(runtime.block_on(future))
+    })
+}
+
+#[cfg(feature = "auditor")]
+async fn spawn_verification<T, F, Fut>(verify: F) -> anyhow::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = anyhow::Result<T>>,
+{
+    let handle = tokio::runtime::Handle::current();
+    // the blocking thread does not inherit the span of the caller
+    let span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || span.in_scope(|| handle.block_on(verify())))
+        .await
+        .context("proof verification task failed")?
+}
+
+/// Verify `raw_proof` is a valid append-only proof for `blob`, blocking the current thread
+#[cfg(feature = "auditor")]
+pub fn verify_raw_proof_sync(
+    config: AkdConfig,
+    blob: &ProofId,
+    raw_proof: &[u8],
+) -> anyhow::Result<()> {
+    verify_raw_proof_sync_with_options(config, blob, raw_proof, &VerifyOptions::default())
+}
+
+/// [`verify_raw_proof_sync`] with verification options
+#[cfg(feature = "auditor")]
+pub fn verify_raw_proof_sync_with_options(
+    config: AkdConfig,
+    blob: &ProofId,
+    raw_proof: &[u8],
+    options: &VerifyOptions,
+) -> anyhow::Result<()> {
+    block_on_verification(async {
+        dispatch_akd_config!(config, verify_raw_proof_for(blob, raw_proof, options))
+    })?
+}
+
+/// Verify `raw_proof` is a valid append-only proof for `blob`
+#[cfg(feature = "auditor")]
+pub async fn verify_raw_proof(
+    config: AkdConfig,
+    blob: &ProofId,
+    raw_proof: &[u8],
+) -> anyhow::Result<()> {
+    verify_raw_proof_with_options(config, blob, raw_proof, VerifyOptions::default()).await
+}
+
+/// [`verify_raw_proof`] with verification options
+#[cfg(feature = "auditor")]
+#[tracing::instrument(skip_all, fields(akd_config = %config, epoch = blob.epoch, size = raw_proof.len()))]
+pub async fn verify_raw_proof_with_options(
+    config: AkdConfig,
+    blob: &ProofId,
+    raw_proof: &[u8],
+    options: VerifyOptions,
+) -> anyhow::Result<()> {
+    let blob = *blob;
+    let raw_proof = raw_proof.to_vec();
+    spawn_verification(move || async move {
+        dispatch_akd_config!(config, verify_raw_proof_for(&blob, &raw_proof, &options))
+    })
+    .await
+}
+
+#[cfg(feature = "auditor")]
+async fn verify_chain_for<TC: akd::Configuration>(
+    start_epoch: Epoch,
+    start_digest: Digest,
+    proofs: &[Vec<u8>],
+    options: &VerifyOptions,
+) -> anyhow::Result<(Epoch, Digest)> {
+    let mut epoch = start_epoch;
+    let mut digest = start_digest;
+    for raw_proof in proofs {
+        let proof = parse_proof(raw_proof, &options.limits)?;
+        let (start_hash, end_hash) =
+            compute_root_hashes_for::<TC>(proof, u64::from(epoch) + 1, options).await?;
+        if !constant_time_eq(&start_hash, &digest) {
+            return Err(anyhow!(
+                "proof for epoch {next} does not start from the digest of epoch {epoch}",
+                next = epoch + 1
+            ));
+        }
+        epoch = epoch + 1;
+        digest = end_hash;
+    }
+    Ok((epoch, digest))
+}
+
+/// Verify a sequence of consecutive append-only proofs, starting from `start_digest` at `start_epoch`
+#[cfg(feature = "auditor")]
+pub async fn verify_chain(
+    config: AkdConfig,
+    start_epoch: Epoch,
+    start_digest: Digest,
+    proofs: Vec<Vec<u8>>,
+) -> anyhow::Result<(Epoch, Digest)> {
+    verify_chain_with_options(
+        config,
+        start_epoch,
+        start_digest,
+        proofs,
+        VerifyOptions::default(),
     )
     .await
-    .with_context(|| format!("verifying raw proof: {blob}", blob = blob.to_string()))
-    .map_err(|e| anyhow!(e))
+}
+
+/// [`verify_chain`] with verification options, applied to each proof
+#[cfg(feature = "auditor")]
+#[tracing::instrument(skip_all, fields(akd_config = %config, start_epoch = %start_epoch, proofs = proofs.len()))]
+pub async fn verify_chain_with_options(
+    config: AkdConfig,
+    start_epoch: Epoch,
+    start_digest: Digest,
+    proofs: Vec<Vec<u8>>,
+    options: VerifyOptions,
+) -> anyhow::Result<(Epoch, Digest)> {
+    spawn_verification(move || async move {
+        dispatch_akd_config!(
+            config,
+            verify_chain_for(start_epoch, start_digest, &proofs, &options)
+        )
+    })
+    .await
+}
+
+/// Verify proofs for many epochs on a pool of `parallelism` threads, blocking the current thread
+#[cfg(feature = "auditor")]
+pub fn verify_epochs(
+    config: AkdConfig,
+    proofs: Vec<(ProofId, Vec<u8>)>,
+    parallelism: usize,
+) -> Vec<(ProofId, anyhow::Result<()>)> {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    let next = AtomicUsize::new(0);
+    let outcomes: Mutex<Vec<Option<anyhow::Result<()>>>> =
+        Mutex::new(proofs.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..parallelism.clamp(1, proofs.len().max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some((blob, raw_proof)) = proofs.get(i) else {
+                    break;
+                };
+                let outcome = verify_raw_proof_sync(config, blob, raw_proof);
+                outcomes.lock().expect("verification thread panicked")[i] = Some(outcome);
+            });
+        }
+    });
+
+    let outcomes = outcomes.into_inner().expect("verification thread panicked");
+    proofs
+        .into_iter()
+        .zip(outcomes)
+        .map(|((blob, _), outcome)| {
+            (
+                blob,
+                outcome.expect("every proof is verified before threads exit"),
+            )
+        })
+        .collect()
+}
+
+/// Signatures of the epochs of a namespace, such as those an auditor serves
+#[cfg(feature = "auditor")]
+#[async_trait::async_trait]
+pub trait SignatureSource: Send + Sync {
+    /// Signature of `epoch` of `namespace`
+    async fn signature(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+    ) -> anyhow::Result<Option<SignatureResponse>>;
+}
+
+/// Append-only proofs of the epochs of a namespace, such as those an auditor or a log directory serve
+#[cfg(feature = "auditor")]
+#[async_trait::async_trait]
+pub trait AuditProofSource: Send + Sync {
+    /// Proof for `blob` of `namespace`
+    async fn proof(&self, namespace: &str, blob: &ProofId) -> anyhow::Result<Option<Vec<u8>>>;
+}
+
+/// Outcome of [`EpochChainVerifier::verify`]
+#[cfg(feature = "auditor")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChainOutcome {
+    /// The epoch is the root of the namespace, and has its digest
+    Root,
+    /// The proof from the epoch before verified
+    Verified,
+    /// The namespace does not provide proofs, so the epoch cannot be verified
+    Unavailable,
+    /// The epoch does not descend from the root of the namespace, or its proof cannot be verified
+    Failed(String),
+}
+
+#[cfg(feature = "auditor")]
+impl ChainOutcome {
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+}
+
+/// Check `signature` against `root`, formatted as epoch/digest
+#[cfg(feature = "auditor")]
+pub fn verify_against_root(root: &str, signature: &SignatureResponse) -> Option<ChainOutcome> {
+    let Some((root_epoch, root_digest)) = root.split_once('/').and_then(|(epoch, digest)| {
+        Some((epoch.parse::<Epoch>().ok()?, hex::decode(digest).ok()?))
+    }) else {
+        return Some(ChainOutcome::Failed(format!("root {root} is not valid")));
+    };
+
+    if *signature.epoch() < root_epoch {
+        return Some(ChainOutcome::Failed(
+            "epoch cannot be before root".to_string(),
+        ));
+    }
+    if *signature.epoch() == root_epoch {
+        return Some(if constant_time_eq(signature.digest(), &root_digest) {
+            ChainOutcome::Root
+        } else {
+            ChainOutcome::Failed(
+                "epoch is at root height but does not match root digest".to_string(),
+            )
+        });
+    }
+    None
+}
+
+/// Verifies that epochs of a namespace descend from its root
+#[cfg(feature = "auditor")]
+pub struct EpochChainVerifier<'a> {
+    namespace: &'a NamespaceInfo,
+    signatures: &'a dyn SignatureSource,
+    proofs: &'a dyn AuditProofSource,
+    require_proofs: bool,
+}
+
+#[cfg(feature = "auditor")]
+impl<'a> EpochChainVerifier<'a> {
+    /// Verifier of the epochs of `namespace`
+    pub fn new(
+        namespace: &'a NamespaceInfo,
+        signatures: &'a dyn SignatureSource,
+        proofs: &'a dyn AuditProofSource,
+    ) -> Self {
+        Self {
+            namespace,
+            signatures,
+            proofs,
+            require_proofs: namespace.log_directory().is_some(),
+        }
+    }
+
+    /// Fail epochs whose proof is missing, rather than reporting them [`ChainOutcome::Unavailable`]
+    pub fn require_proofs(mut self, require_proofs: bool) -> Self {
+        self.require_proofs = require_proofs;
+        self
+    }
+
+    /// Verify the epoch `signature` signs
+    pub async fn verify(
+        &self,
+        signature: &SignatureResponse,
+        previous: Option<&SignatureResponse>,
+    ) -> anyhow::Result<ChainOutcome> {
+        self.verify_with_options(signature, previous, VerifyOptions::default())
+            .await
+    }
+
+    /// [`EpochChainVerifier::verify`] with options for the verification of the proof
+    pub async fn verify_with_options(
+        &self,
+        signature: &SignatureResponse,
+        previous: Option<&SignatureResponse>,
+        options: VerifyOptions,
+    ) -> anyhow::Result<ChainOutcome> {
+        let namespace = self.namespace.name();
+        if let Some(digest_algorithm) = self.namespace.digest_algorithm() {
+            if let Err(e) = signature.verify_digest_algorithm(digest_algorithm) {
+                return Ok(ChainOutcome::Failed(e.to_string()));
+            }
+        }
+        // TODO: support namespace in the initialisation phase
+        let Some(root) = self.namespace.root() else {
+            // without a root, the namespace does not provide proofs
+            if self.require_proofs {
+                return Ok(ChainOutcome::Failed(format!(
+                    "namespace {namespace} does not have a root"
+                )));
+            }
+            return Ok(ChainOutcome::Unavailable);
+        };
+        // a namespace that re-rooted verifies each epoch against the root it descends from
+        let root = self
+            .namespace
+            .root_for_epoch(signature.epoch())
+            .unwrap_or(root);
+        if let Some(outcome) = verify_against_root(root, signature) {
+            return Ok(outcome);
+        }
+
+        let previous_epoch = *signature.epoch() - 1;
+        let previous = match previous {
+            Some(previous) => previous.clone(),
+            None => self
+                .signatures
+                .signature(namespace, &previous_epoch)
+                .await?
+                .ok_or_else(|| {
+                    anyhow!("signature of {namespace} epoch {previous_epoch} not found")
+                })?,
+        };
+        if *previous.epoch() != previous_epoch {
+            return Ok(ChainOutcome::Failed(format!(
+                "previous signature is of epoch {epoch}, not {previous_epoch}",
+                epoch = previous.epoch()
+            )));
+        }
+
+        let (Ok(previous_hash), Ok(current_hash)) =
+            (previous.digest().try_into(), signature.digest().try_into())
+        else {
+            return Ok(ChainOutcome::Failed("digest length invalid".to_string()));
+        };
+        let blob = ProofId {
+            epoch: signature.epoch().into(),
+            previous_hash,
+            current_hash,
+        };
+        let Some(raw_proof) = self.proofs.proof(namespace, &blob).await? else {
+            if self.require_proofs {
+                return Ok(ChainOutcome::Failed(
+                    "cannot retrieve audit proof".to_string(),
+                ));
+            }
+            return Ok(ChainOutcome::Unavailable);
+        };
+
+        Ok(
+            match verify_raw_proof_with_options(
+                self.namespace.akd_config(),
+                &blob,
+                &raw_proof,
+                options,
+            )
+            .await
+            {
+                Ok(()) => ChainOutcome::Verified,
+                Err(e) => ChainOutcome::Failed(e.to_string()),
+            },
+        )
+    }
+}
+
+#[cfg(all(test, feature = "auditor"))]
+pub(crate) mod tests {
+    use akd::{ecvrf::HardCodedAkdVRF, AkdLabel, AkdValue, Directory};
+
+    use super::*;
+
+    pub(crate) async fn generate_proofs<TC: akd::Configuration>(
+        epochs: u64,
+    ) -> Vec<(ProofId, Vec<u8>)> {
+        let storage = StorageManager::new_no_cache(AsyncInMemoryDatabase::new());
+        let directory = Directory::<TC, _, _>::new(storage, HardCodedAkdVRF {})
+            .await
+            .unwrap();
+
+        let mut hashes = vec![];
+        for epoch in 1..=epochs {
+            let updates = (0..4)
+                .map(|i| {
+                    (
+                        AkdLabel::from(format!("label-{epoch}-{i}").as_str()),
+                        AkdValue::from("value"),
+                    )
+                })
+                .collect();
+            hashes.push(directory.publish(updates).await.unwrap().hash());
+        }
+
+        let audit = directory.audit(1, epochs).await.unwrap();
+        audit
+            .proofs
+            .iter()
+            .enumerate()
+            .map(|(i, proof)| {
+                let blob = ProofId {
+                    epoch: i as u64 + 2,
+                    previous_hash: hashes[i],
+                    current_hash: hashes[i + 1],
+                };
+                let raw_proof = akd::proto::specs::types::SingleAppendOnlyProof::from(proof)
+                    .write_to_bytes()
+                    .unwrap();
+                (blob, raw_proof)
+            })
+            .collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_verify_raw_proof_akd_config() {
+        let whatsapp = generate_proofs::<WhatsAppV1Configuration>(2).await;
+        let experimental = generate_proofs::<ExperimentalConfiguration<ExampleLabel>>(2).await;
+        let (blob, raw_proof) = &whatsapp[0];
+
+        assert!(verify_raw_proof(AkdConfig::WhatsAppV1, blob, raw_proof)
+            .await
+            .is_ok());
+        assert!(verify_raw_proof(AkdConfig::Experimental, blob, raw_proof)
+            .await
+            .is_err());
+        assert!(verify_raw_proof(AkdConfig::Unknown, blob, raw_proof)
+            .await
+            .is_err());
+        assert_eq!(
+            compute_start_root_hash(AkdConfig::WhatsAppV1, raw_proof)
+                .await
+                .unwrap(),
+            blob.previous_hash
+        );
+
+        assert_eq!(
+            compute_end_root_hash(AkdConfig::WhatsAppV1, blob.epoch, raw_proof)
+                .await
+                .unwrap(),
+            blob.current_hash
+        );
+        assert_ne!(
+            compute_end_root_hash(AkdConfig::WhatsAppV1, blob.epoch + 1, raw_proof)
+                .await
+                .unwrap(),
+            blob.current_hash
+        );
+
+        let (blob, raw_proof) = &experimental[0];
+        assert!(verify_raw_proof(AkdConfig::Experimental, blob, raw_proof)
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_verify_raw_proof_sync() {
+        let proofs = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(generate_proofs::<WhatsAppV1Configuration>(3));
+
+        for (blob, raw_proof) in &proofs {
+            verify_raw_proof_sync(AkdConfig::WhatsAppV1, blob, raw_proof).unwrap();
+        }
+
+        let (blob, _) = &proofs[0];
+        let (_, other_proof) = &proofs[1];
+        assert!(verify_raw_proof_sync(AkdConfig::WhatsAppV1, blob, other_proof).is_err());
+        assert!(verify_raw_proof_sync(AkdConfig::WhatsAppV1, blob, b"not a proof").is_err());
+    }
+
+    #[test]
+    fn test_verify_raw_proof_progress() {
+        let proofs = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(generate_proofs::<WhatsAppV1Configuration>(3));
+
+        for (blob, raw_proof) in &proofs {
+            let reports = Arc::new(std::sync::Mutex::new(vec![]));
+            let options = VerifyOptions::new().progress({
+                let reports = reports.clone();
+                move |progress| reports.lock().unwrap().push(progress)
+            });
+            verify_raw_proof_sync_with_options(AkdConfig::WhatsAppV1, blob, raw_proof, &options)
+                .unwrap();
+
+            let reports = reports.lock().unwrap();
+            let last = reports.last().unwrap();
+            assert_eq!(last.processed(), last.total());
+            assert!(reports
+                .windows(2)
+                .all(|w| w[0].processed() < w[1].processed()));
+        }
+
+        let (blob, _) = &proofs[0];
+        let (_, other_proof) = &proofs[1];
+        let options = VerifyOptions::new().progress(|_| ());
+        assert!(verify_raw_proof_sync_with_options(
+            AkdConfig::WhatsAppV1,
+            blob,
+            other_proof,
+            &options
+        )
+        .is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_batched_verification_matches_akd() {
+        let proofs = generate_proofs::<WhatsAppV1Configuration>(4).await;
+
+        for (blob, raw_proof) in &proofs {
+            let batches = Arc::new(std::sync::Mutex::new(0));
+            let options = VerifyOptions::new().progress({
+                let batches = batches.clone();
+                move |_| *batches.lock().unwrap() += 1
+            });
+            for epoch in [blob.epoch, blob.epoch + 1] {
+                let proof = parse_proof(raw_proof, &options.limits).unwrap();
+                let (start_hash, end_hash) =
+                    compute_root_hashes_for::<WhatsAppV1Configuration>(proof, epoch, &options)
+                        .await
+                        .unwrap();
+                let proof = parse_proof(raw_proof, &options.limits).unwrap();
+                let akd = akd::auditor::verify_consecutive_append_only::<WhatsAppV1Configuration>(
+                    &proof,
+                    blob.previous_hash,
+                    blob.current_hash,
+                    epoch,
+                )
+                .await;
+                assert_eq!(
+                    start_hash == blob.previous_hash && end_hash == blob.current_hash,
+                    akd.is_ok()
+                );
+                assert_eq!(akd.is_ok(), epoch == blob.epoch);
+            }
+            assert!(*batches.lock().unwrap() > 2);
+        }
+    }
+
+    #[test]
+    fn test_verify_raw_proof_interrupted() {
+        let proofs = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(generate_proofs::<WhatsAppV1Configuration>(2));
+        let (blob, raw_proof) = &proofs[0];
+
+        let token = CancellationToken::new();
+        let options = VerifyOptions::new().cancellation(token.clone());
+        verify_raw_proof_sync_with_options(AkdConfig::WhatsAppV1, blob, raw_proof, &options)
+            .unwrap();
+        token.cancel();
+        let err =
+            verify_raw_proof_sync_with_options(AkdConfig::WhatsAppV1, blob, raw_proof, &options)
+                .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlexiError>(),
+            Some(PlexiError::VerificationCancelled)
+        ));
+
+        let options = VerifyOptions::new().deadline(Instant::now());
+        let err =
+            verify_raw_proof_sync_with_options(AkdConfig::WhatsAppV1, blob, raw_proof, &options)
+                .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlexiError>(),
+            Some(PlexiError::VerificationDeadlineExceeded)
+        ));
+    }
+
+    #[test]
+    fn test_inspect_proof() {
+        let proofs = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(generate_proofs::<WhatsAppV1Configuration>(2));
+        let (blob, raw_proof) = &proofs[0];
+
+        let summary = inspect_proof(AkdConfig::WhatsAppV1, Some(blob), raw_proof).unwrap();
+        assert_eq!(summary.epoch(), Some(2));
+        assert_eq!(summary.current_hash(), Some(blob.current_hash));
+        assert_eq!(summary.size(), raw_proof.len());
+        assert_eq!(summary.inserted(), 4);
+        assert!(summary.unchanged() > 0);
+        assert_eq!(summary.hash_algorithm(), Some(DigestAlgorithm::Blake3));
+
+        let summary = inspect_proof(AkdConfig::Unknown, None, raw_proof).unwrap();
+        assert_eq!(summary.epoch(), None);
+        assert_eq!(summary.hash_algorithm(), None);
+
+        assert!(inspect_proof(AkdConfig::WhatsAppV1, None, b"not a proof").is_err());
+    }
+
+    #[test]
+    fn test_proof_limits() {
+        let proofs = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(generate_proofs::<WhatsAppV1Configuration>(2));
+        let (blob, raw_proof) = &proofs[0];
+        let summary = inspect_proof(AkdConfig::WhatsAppV1, None, raw_proof).unwrap();
+        let nodes = summary.inserted() + summary.unchanged();
+        assert_eq!(count_proof_nodes(raw_proof).unwrap(), nodes);
+
+        let verify = |limits: ProofLimits| {
+            verify_raw_proof_sync_with_options(
+                AkdConfig::WhatsAppV1,
+                blob,
+                raw_proof,
+                &VerifyOptions::new().limits(limits),
+            )
+        };
+        verify(
+            ProofLimits::new()
+                .max_size(raw_proof.len())
+                .max_nodes(nodes),
+        )
+        .unwrap();
+
+        let err = verify(ProofLimits::new().max_size(raw_proof.len() - 1)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlexiError>(),
+            Some(PlexiError::ProofTooLarge { .. })
+        ));
+        let err = verify(ProofLimits::new().max_nodes(nodes - 1)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlexiError>(),
+            Some(PlexiError::TooManyProofNodes { .. })
+        ));
+
+        assert!(count_proof_nodes(&raw_proof[..raw_proof.len() - 1]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_proof_from_reader() {
+        let proofs = generate_proofs::<WhatsAppV1Configuration>(3).await;
+        for (blob, raw_proof) in &proofs {
+            let reader = std::io::Cursor::new(raw_proof.clone());
+            let hashes = compute_root_hashes_from_reader(
+                AkdConfig::WhatsAppV1,
+                blob.epoch,
+                reader,
+                VerifyOptions::new(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(hashes, (blob.previous_hash, blob.current_hash));
+        }
+
+        let (blob, raw_proof) = proofs.last().unwrap();
+        let verify = |raw_proof: Vec<u8>, options: VerifyOptions| {
+            verify_proof_from_reader(
+                AkdConfig::WhatsAppV1,
+                blob,
+                std::io::Cursor::new(raw_proof),
+                options,
+            )
+        };
+        verify(raw_proof.clone(), VerifyOptions::new())
+            .await
+            .unwrap();
+        assert!(verify(
+            raw_proof[..raw_proof.len() - 1].to_vec(),
+            VerifyOptions::new()
+        )
+        .await
+        .is_err());
+
+        let nodes = count_proof_nodes(raw_proof).unwrap();
+        assert_eq!(
+            inspect_proof_from_reader(
+                AkdConfig::WhatsAppV1,
+                Some(blob),
+                raw_proof.as_slice(),
+                &ProofLimits::default()
+            )
+            .unwrap(),
+            inspect_proof(AkdConfig::WhatsAppV1, Some(blob), raw_proof).unwrap()
+        );
+        let err = verify(
+            raw_proof.clone(),
+            VerifyOptions::new().limits(ProofLimits::new().max_size(raw_proof.len() - 1)),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlexiError>(),
+            Some(PlexiError::ProofTooLarge { .. })
+        ));
+        let err = verify(
+            raw_proof.clone(),
+            VerifyOptions::new().limits(ProofLimits::new().max_nodes(nodes - 1)),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlexiError>(),
+            Some(PlexiError::TooManyProofNodes { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_chain() {
+        let proofs = generate_proofs::<WhatsAppV1Configuration>(4).await;
+        let (first_blob, _) = &proofs[0];
+        let (last_blob, _) = proofs.last().unwrap();
+        let mut raw_proofs: Vec<_> = proofs.iter().map(|(_, proof)| proof.clone()).collect();
+
+        let (epoch, digest) = verify_chain(
+            AkdConfig::WhatsAppV1,
+            Epoch::from(1),
+            first_blob.previous_hash,
+            raw_proofs.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(epoch, last_blob.epoch);
+        assert_eq!(digest, last_blob.current_hash);
+
+        let (epoch, digest) = verify_chain(
+            AkdConfig::WhatsAppV1,
+            Epoch::from(1),
+            first_blob.previous_hash,
+            vec![],
+        )
+        .await
+        .unwrap();
+        assert_eq!(epoch, 1);
+        assert_eq!(digest, first_blob.previous_hash);
+
+        raw_proofs.swap(1, 2);
+        assert!(verify_chain(
+            AkdConfig::WhatsAppV1,
+            Epoch::from(1),
+            first_blob.previous_hash,
+            raw_proofs,
+        )
+        .await
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_epochs() {
+        let mut proofs = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(generate_proofs::<WhatsAppV1Configuration>(5));
+        proofs[2].1 = proofs[1].1.clone();
+
+        let outcomes = verify_epochs(AkdConfig::WhatsAppV1, proofs.clone(), 3);
+        assert_eq!(outcomes.len(), proofs.len());
+        for (i, (blob, outcome)) in outcomes.iter().enumerate() {
+            assert_eq!(blob, &proofs[i].0);
+            assert_eq!(outcome.is_ok(), i != 2);
+        }
+
+        assert!(verify_epochs(AkdConfig::WhatsAppV1, vec![], 4).is_empty());
+    }
+
+    fn rotated_key(seed: u8, previous_seed: Option<u8>) -> KeyInfo {
+        let public_key = |seed| {
+            hex::encode(
+                ed25519_dalek::SigningKey::from_bytes(&[seed; 32])
+                    .verifying_key()
+                    .to_bytes(),
+            )
+        };
+        let key = KeyInfo::new(&public_key(seed), u64::from(seed));
+        let Some(previous_seed) = previous_seed else {
+            return key;
+        };
+        let signature = ed25519_dalek::Signer::sign(
+            &ed25519_dalek::SigningKey::from_bytes(&[previous_seed; 32]),
+            &key.rotation_message().unwrap(),
+        );
+        key.with_rotation(KeyRotation::new(
+            &public_key(previous_seed),
+            &signature.to_bytes(),
+        ))
+    }
+
+    #[test]
+    fn test_key_chain() {
+        let root = rotated_key(1, None);
+        let keys = vec![
+            root.clone(),
+            rotated_key(2, Some(1)),
+            rotated_key(3, Some(2)),
+        ];
+
+        for (i, key) in keys.iter().enumerate() {
+            let chain = key.chain_to(root.public_key(), &keys).unwrap();
+            assert_eq!(chain.len(), i + 1);
+            assert_eq!(chain.last(), Some(root.public_key()));
+        }
+
+        // a key signed by an unpinned key does not descend from the root
+        let unrelated = rotated_key(5, Some(4));
+        assert!(unrelated.chain_to(root.public_key(), &keys).is_err());
+        assert!(rotated_key(6, None)
+            .chain_to(root.public_key(), &keys)
+            .is_err());
+
+        // a rotation statement signed for another key does not verify
+        let mut forged = rotated_key(7, None);
+        forged.rotation = keys[1].rotation.clone();
+        assert!(forged.verify_rotation().is_err());
+        assert!(forged.chain_to(root.public_key(), &keys).is_err());
+
+        // a key rotation survives storage as a map
+        let stored = KeyInfo::try_from(HashMap::from(keys[2].clone())).unwrap();
+        assert_eq!(stored, keys[2]);
+    }
+
+    #[test]
+    fn test_proof_id() {
+        let blob = AuditBlobName {
+            epoch: 42,
+            previous_hash: [1; 32],
+            current_hash: [2; 32],
+        };
+        let id = ProofId::from(blob);
+        assert_eq!(id.to_string(), blob.to_string());
+        assert_eq!(id.to_string().parse::<ProofId>().unwrap(), id);
+        assert_eq!(AuditBlobName::from(id), blob);
+
+        assert!("42".parse::<ProofId>().is_err());
+        assert!(format!("{id}/extra").parse::<ProofId>().is_err());
+        assert!(format!("42/{hash}/{hash}", hash = hex::encode([1; 31]))
+            .parse::<ProofId>()
+            .is_err());
+        assert!(format!("forty/{hash}/{hash}", hash = hex::encode([1; 32]))
+            .parse::<ProofId>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_akd_config_serialization() {
+        assert_eq!(
+            serde_json::to_string(&AkdConfig::WhatsAppV1).unwrap(),
+            r#""whatsapp_v1""#
+        );
+        assert_eq!(
+            serde_json::from_str::<AkdConfig>(r#""experimental""#).unwrap(),
+            AkdConfig::Experimental
+        );
+        assert_eq!(
+            serde_json::from_str::<AkdConfig>(r#""future_config""#).unwrap(),
+            AkdConfig::Unknown
+        );
+        assert_eq!(
+            "whatsapp_v1".parse::<AkdConfig>().unwrap(),
+            AkdConfig::WhatsAppV1
+        );
+    }
+
+    struct Signatures(HashMap<u64, SignatureResponse>);
+
+    #[async_trait::async_trait]
+    impl SignatureSource for Signatures {
+        async fn signature(
+            &self,
+            _namespace: &str,
+            epoch: &Epoch,
+        ) -> anyhow::Result<Option<SignatureResponse>> {
+            Ok(self.0.get(&epoch.into()).cloned())
+        }
+    }
+
+    struct Proofs(HashMap<u64, Vec<u8>>);
+
+    #[async_trait::async_trait]
+    impl AuditProofSource for Proofs {
+        async fn proof(&self, _namespace: &str, blob: &ProofId) -> anyhow::Result<Option<Vec<u8>>> {
+            Ok(self.0.get(&blob.epoch).cloned())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_epoch_chain_verifier() {
+        use crate::{
+            namespaces::{Namespace, NamespaceStatus},
+            Ciphersuite,
+        };
+
+        let proofs = generate_proofs::<WhatsAppV1Configuration>(3).await;
+        let signature = |epoch: u64, digest: Digest| {
+            SignatureResponse::new(
+                &Ciphersuite::ProtobufEd25519,
+                &Ciphersuite::ProtobufEd25519,
+                "example.key-transparency.v1".to_string(),
+                1717084639921,
+                &Epoch::from(epoch),
+                digest.to_vec(),
+                vec![0xbb; 64],
+                None,
+                None,
+            )
+        };
+        let signatures = Signatures(HashMap::from([
+            (1, signature(1, proofs[0].0.previous_hash)),
+            (2, signature(2, proofs[0].0.current_hash)),
+            (3, signature(3, proofs[1].0.current_hash)),
+        ]));
+        let namespace = Namespace::new(
+            "example.key-transparency.v1".to_string(),
+            None,
+            Some(Epoch::from(1).as_root_epoch(&hex::encode(proofs[0].0.previous_hash))),
+            None,
+            Some(Ciphersuite::ProtobufEd25519),
+        );
+        let info = NamespaceInfo::new(&namespace, NamespaceStatus::Online);
+        let proof_source = Proofs(
+            proofs
+                .iter()
+                .map(|(blob, raw_proof)| (blob.epoch, raw_proof.clone()))
+                .collect(),
+        );
+        let verifier = EpochChainVerifier::new(&info, &signatures, &proof_source);
+
+        assert_eq!(
+            verifier.verify(&signatures.0[&1], None).await.unwrap(),
+            ChainOutcome::Root
+        );
+        for epoch in [2, 3] {
+            assert_eq!(
+                verifier.verify(&signatures.0[&epoch], None).await.unwrap(),
+                ChainOutcome::Verified
+            );
+        }
+
+        // the proof of epoch 3 does not start from the digest of epoch 1
+        assert!(verifier
+            .verify(
+                &signatures.0[&3],
+                Some(&signature(2, proofs[0].0.previous_hash))
+            )
+            .await
+            .unwrap()
+            .is_failed());
+
+        // WhatsApp logs hash with BLAKE3
+        let mut sha256 = signatures.0[&2].clone();
+        sha256.digest_algorithm = Some(DigestAlgorithm::Sha256);
+        assert!(verifier.verify(&sha256, None).await.unwrap().is_failed());
+
+        let no_proofs = Proofs(HashMap::new());
+        let verifier = EpochChainVerifier::new(&info, &signatures, &no_proofs);
+        assert_eq!(
+            verifier.verify(&signatures.0[&2], None).await.unwrap(),
+            ChainOutcome::Unavailable
+        );
+        assert!(verifier
+            .require_proofs(true)
+            .verify(&signatures.0[&2], None)
+            .await
+            .unwrap()
+            .is_failed());
+    }
+
+    #[tokio::test]
+    async fn test_verifications_run_concurrently() {
+        // each verification waits for the other, and would never return if they were serialized
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+        let verify = |barrier: Arc<std::sync::Barrier>| {
+            spawn_verification(move || async move {
+                barrier.wait();
+                Ok(())
+            })
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            tokio::try_join!(verify(barrier.clone()), verify(barrier))
+        })
+        .await
+        .expect("verifications are serialized")
+        .unwrap();
+    }
 }
@@ -0,0 +1,166 @@
+use anyhow::{anyhow, Context as _};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auditor::{self, AkdConfig, ProofId},
+    crypto::constant_time_eq,
+    SignatureResponse,
+};
+
+/// Signature of an epoch, paired with the append-only proof leading to it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignatureBundle {
+    signature: SignatureResponse,
+    #[serde(with = "hex::serde")]
+    proof: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    previous_digest: Vec<u8>,
+}
+
+impl SignatureBundle {
+    pub fn new(signature: SignatureResponse, proof: Vec<u8>, previous_digest: Vec<u8>) -> Self {
+        Self {
+            signature,
+            proof,
+            previous_digest,
+        }
+    }
+
+    pub fn signature(&self) -> &SignatureResponse {
+        &self.signature
+    }
+
+    pub fn proof(&self) -> &[u8] {
+        &self.proof
+    }
+
+    pub fn previous_digest(&self) -> &[u8] {
+        &self.previous_digest
+    }
+
+    /// [`Self::verify_with_config`] for logs using [`AkdConfig::WhatsAppV1`]
+    pub fn verify(&self, verifying_key: &[u8]) -> anyhow::Result<()> {
+        self.verify_with_config(verifying_key, AkdConfig::default())
+    }
+
+    /// Verify the signature against `verifying_key`, and the proof from `previous_digest` to the signed digest
+    pub fn verify_with_config(
+        &self,
+        verifying_key: &[u8],
+        akd_config: AkdConfig,
+    ) -> anyhow::Result<()> {
+        self.signature.verify(verifying_key)?;
+
+        self.signature
+            .ciphersuite()
+            .validate_digest(&self.previous_digest)?;
+        if let Some(signed_previous_digest) = self.signature.previous_digest() {
+            if !constant_time_eq(signed_previous_digest, &self.previous_digest) {
+                return Err(anyhow!(
+                    "signature binds a previous digest other than the one of the proof"
+                ));
+            }
+        }
+
+        let blob = ProofId {
+            epoch: self.signature.epoch().into(),
+            previous_hash: self
+                .previous_digest
+                .as_slice()
+                .try_into()
+                .context("previous digest length")?,
+            current_hash: self
+                .signature
+                .digest()
+                .try_into()
+                .context("digest length")?,
+        };
+        auditor::verify_raw_proof_sync(akd_config, &blob, &self.proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use akd::WhatsAppV1Configuration;
+    use ed25519_dalek::{Signer as _, SECRET_KEY_LENGTH};
+
+    use super::*;
+    use crate::{auditor::tests::generate_proofs, Ciphersuite, Epoch, SignatureMessage};
+
+    fn sign(
+        signing_key: &ed25519_dalek::SigningKey,
+        ciphersuite: &Ciphersuite,
+        blob: &ProofId,
+        previous_digest: [u8; 32],
+    ) -> SignatureResponse {
+        let mut message = SignatureMessage::new(
+            ciphersuite,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch::from(blob.epoch),
+            blob.current_hash.to_vec(),
+        )
+        .unwrap();
+        if *ciphersuite == Ciphersuite::ProtobufV2Ed25519 {
+            message = message.with_previous_digest(previous_digest.to_vec());
+        }
+        SignatureResponse::new(
+            message.ciphersuite(),
+            message.ciphersuite(),
+            message.namespace().to_string(),
+            message.timestamp(),
+            message.epoch(),
+            message.digest_owned(),
+            signing_key.sign(&message.to_vec().unwrap()).to_vec(),
+            None,
+            None,
+        )
+        .with_message_fields(&message)
+    }
+
+    #[test]
+    fn test_signature_bundle() {
+        let proofs = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(generate_proofs::<WhatsAppV1Configuration>(2));
+        let (blob, raw_proof) = &proofs[0];
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[0xd6; SECRET_KEY_LENGTH]);
+        let verifying_key = signing_key.verifying_key().to_bytes();
+
+        for ciphersuite in [Ciphersuite::ProtobufEd25519, Ciphersuite::ProtobufV2Ed25519] {
+            let signature = sign(&signing_key, &ciphersuite, blob, blob.previous_hash);
+            let bundle = SignatureBundle::new(
+                signature.clone(),
+                raw_proof.clone(),
+                blob.previous_hash.to_vec(),
+            );
+            assert!(bundle.verify(&verifying_key).is_ok());
+
+            let serialized = serde_json::to_string(&bundle).unwrap();
+            assert_eq!(
+                serde_json::from_str::<SignatureBundle>(&serialized).unwrap(),
+                bundle
+            );
+
+            let other_key = ed25519_dalek::SigningKey::from_bytes(&[0xd7; SECRET_KEY_LENGTH]);
+            assert!(bundle
+                .verify(&other_key.verifying_key().to_bytes())
+                .is_err());
+
+            let wrong_start =
+                SignatureBundle::new(signature, raw_proof.clone(), blob.current_hash.to_vec());
+            assert!(wrong_start.verify(&verifying_key).is_err());
+        }
+
+        // the signature binds the previous digest the proof starts from
+        let signature = sign(
+            &signing_key,
+            &Ciphersuite::ProtobufV2Ed25519,
+            blob,
+            [0xcc; 32],
+        );
+        let bundle =
+            SignatureBundle::new(signature, raw_proof.clone(), blob.previous_hash.to_vec());
+        assert!(bundle.verify(&verifying_key).is_err());
+    }
+}
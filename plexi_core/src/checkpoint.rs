@@ -0,0 +1,339 @@
+//! Epochs as checkpoints in the [signed note](https://github.com/C2SP/C2SP/blob/main/signed-note.md) format of the Go checksum database,
+//! so that witnesses and gossip infrastructure built around it can consume them.
+//!
+//! The checkpoint of an epoch has the namespace as origin, the epoch as tree size, and the digest as root hash:
+//!
+//! ```text
+//! example.key-transparency.v1
+//! 489193
+//! qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqo=
+//!
+//! — example.key-transparency.v1 x08go/ZJkuBS9UG/SffcvIAQxVBtiFupLLr8pAcElZInNIuGUgYN1FFYC2pZSNXg...
+//! ```
+
+use anyhow::{anyhow, Context as _};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signature, Verifier as _, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+#[cfg(feature = "signer")]
+use crate::signer::Signer;
+use crate::{Epoch, SignatureResponse, DIGEST_LENGTH};
+
+const SIGNATURE_TYPE_ED25519: u8 = 0x01;
+const SIGNATURE_TYPE_COSIGNATURE: u8 = 0x04;
+pub(crate) const KEY_HASH_LENGTH: usize = 4;
+pub(crate) const SIGNATURE_LINE_PREFIX: &str = "\u{2014} ";
+
+/// Head of a namespace at an epoch
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    origin: String,
+    epoch: Epoch,
+    digest: Vec<u8>,
+}
+
+impl Checkpoint {
+    pub fn new(origin: String, epoch: Epoch, digest: Vec<u8>) -> Self {
+        Self {
+            origin,
+            epoch,
+            digest,
+        }
+    }
+
+    /// Checkpoint of the epoch `signature` signs, which should have been verified beforehand
+    pub fn from_signature(signature: &SignatureResponse) -> Self {
+        Self::new(
+            signature.namespace().to_string(),
+            *signature.epoch(),
+            signature.digest_owned(),
+        )
+    }
+
+    /// Namespace of the checkpoint
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Text of the note, which note signatures sign
+    pub fn to_note_text(&self) -> String {
+        format!(
+            "{origin}\n{epoch}\n{digest}\n",
+            origin = self.origin,
+            epoch = self.epoch,
+            digest = BASE64.encode(&self.digest)
+        )
+    }
+
+    /// Parse the text of a checkpoint note
+    pub fn from_note_text(text: &str) -> anyhow::Result<Self> {
+        let mut lines = text
+            .strip_suffix('\n')
+            .ok_or_else(|| anyhow!("checkpoint should end with a newline"))?
+            .split('\n');
+        let (Some(origin), Some(epoch), Some(digest)) = (lines.next(), lines.next(), lines.next())
+        else {
+            return Err(anyhow!(
+                "checkpoint should have an origin, a tree size, and a root hash"
+            ));
+        };
+        if origin.is_empty() {
+            return Err(anyhow!("checkpoint origin is empty"));
+        }
+        let epoch = epoch.parse().context("parsing checkpoint tree size")?;
+        let digest = BASE64
+            .decode(digest)
+            .context("parsing checkpoint root hash")?;
+        if digest.len() != DIGEST_LENGTH {
+            return Err(anyhow!(
+                "checkpoint root hash should have length {DIGEST_LENGTH}"
+            ));
+        }
+        Ok(Self::new(origin.to_string(), epoch, digest))
+    }
+
+    /// Sign the checkpoint as `key_name` with `signer`, producing a signed note
+    #[cfg(feature = "signer")]
+    pub async fn sign(&self, key_name: &str, signer: &dyn Signer) -> anyhow::Result<String> {
+        let text = self.to_note_text();
+        let signature = signer.sign(text.as_bytes()).await?;
+        let mut note_signature = key_hash(key_name, &signer.verifying_key()).to_vec();
+        note_signature.extend_from_slice(&signature);
+        Ok(format!(
+            "{text}\n{SIGNATURE_LINE_PREFIX}{key_name} {signature}\n",
+            signature = BASE64.encode(note_signature)
+        ))
+    }
+
+    /// Checkpoint of the signed note `note`, if it has a valid signature by `key_name` with `verifying_key`
+    pub fn open(note: &str, key_name: &str, verifying_key: &[u8]) -> anyhow::Result<Self> {
+        Self::from_note_text(verify_note(note, key_name, verifying_key)?)
+    }
+}
+
+/// Text of the signed note `note`, if it has a valid signature by `key_name` with the Ed25519 `verifying_key`
+pub fn verify_note<'a>(
+    note: &'a str,
+    key_name: &str,
+    verifying_key: &[u8],
+) -> anyhow::Result<&'a str> {
+    let verifying_key: [u8; PUBLIC_KEY_LENGTH] = verifying_key
+        .try_into()
+        .map_err(|_| anyhow!("verifying_key should have length {PUBLIC_KEY_LENGTH}"))?;
+    let key_hash = key_hash(key_name, &verifying_key);
+    let verifying_key = VerifyingKey::from_bytes(&verifying_key)
+        .map_err(|_| anyhow!("Cannot parse the provided verifying_key."))?;
+
+    // the text ends with a newline, and is separated from signatures by an empty line
+    let split = note
+        .find("\n\n")
+        .ok_or_else(|| anyhow!("note has no signature"))?;
+    let (text, signatures) = (&note[..split + 1], &note[split + 2..]);
+
+    for line in signatures.lines() {
+        let (name, signature) = line
+            .strip_prefix(SIGNATURE_LINE_PREFIX)
+            .and_then(|line| line.split_once(' '))
+            .ok_or_else(|| anyhow!("note signature line is malformed"))?;
+        if name != key_name {
+            continue;
+        }
+        let signature = BASE64.decode(signature).context("parsing note signature")?;
+        if signature.len() != KEY_HASH_LENGTH + SIGNATURE_LENGTH
+            || signature[..KEY_HASH_LENGTH] != key_hash
+        {
+            continue;
+        }
+        let signature = Signature::from_slice(&signature[KEY_HASH_LENGTH..])?;
+        verifying_key
+            .verify(text.as_bytes(), &signature)
+            .map_err(|_| anyhow!("note signature by {key_name} is invalid"))?;
+        return Ok(text);
+    }
+    Err(anyhow!("note is not signed by {key_name}"))
+}
+
+/// Signature of a checkpoint by a witness
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct Cosignature {
+    /// Key name of the witness
+    witness: String,
+    /// Seconds since the Unix epoch at which the witness cosigned
+    timestamp: u64,
+    #[serde(with = "hex::serde")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String, format = "hex"))]
+    signature: Vec<u8>,
+}
+
+impl Cosignature {
+    pub fn new(witness: &str, timestamp: u64, signature: Vec<u8>) -> Self {
+        Self {
+            witness: witness.to_string(),
+            timestamp,
+            signature,
+        }
+    }
+
+    pub fn witness(&self) -> &str {
+        &self.witness
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Verify the cosignature of `checkpoint` against the Ed25519 key of the witness
+    pub fn verify(&self, checkpoint: &Checkpoint, verifying_key: &[u8]) -> anyhow::Result<()> {
+        let verifying_key: [u8; PUBLIC_KEY_LENGTH] = verifying_key
+            .try_into()
+            .map_err(|_| anyhow!("verifying_key should have length {PUBLIC_KEY_LENGTH}"))?;
+        let verifying_key = VerifyingKey::from_bytes(&verifying_key)
+            .map_err(|_| anyhow!("Cannot parse the provided verifying_key."))?;
+        let signature = Signature::from_slice(&self.signature)?;
+        verifying_key
+            .verify(
+                cosigned_message(checkpoint, self.timestamp).as_bytes(),
+                &signature,
+            )
+            .map_err(|_| {
+                anyhow!(
+                    "cosignature by {witness} is invalid",
+                    witness = self.witness
+                )
+            })
+    }
+}
+
+/// Message a witness signs to cosign `checkpoint` at `timestamp`
+pub fn cosigned_message(checkpoint: &Checkpoint, timestamp: u64) -> String {
+    format!(
+        "cosignature/v1\ntime {timestamp}\n{text}",
+        text = checkpoint.to_note_text()
+    )
+}
+
+/// Hash identifying the Ed25519 note key `key_name` in signature lines
+pub fn key_hash(key_name: &str, verifying_key: &[u8; PUBLIC_KEY_LENGTH]) -> [u8; KEY_HASH_LENGTH] {
+    key_hash_of_type(key_name, SIGNATURE_TYPE_ED25519, verifying_key)
+}
+
+/// Hash identifying the cosignature key `key_name` of a witness in signature lines
+pub fn cosignature_key_hash(
+    key_name: &str,
+    verifying_key: &[u8; PUBLIC_KEY_LENGTH],
+) -> [u8; KEY_HASH_LENGTH] {
+    key_hash_of_type(key_name, SIGNATURE_TYPE_COSIGNATURE, verifying_key)
+}
+
+fn key_hash_of_type(
+    key_name: &str,
+    signature_type: u8,
+    verifying_key: &[u8; PUBLIC_KEY_LENGTH],
+) -> [u8; KEY_HASH_LENGTH] {
+    let hash = Sha256::new()
+        .chain_update(key_name)
+        .chain_update(b"\n")
+        .chain_update([signature_type])
+        .chain_update(verifying_key)
+        .finalize();
+    hash[..KEY_HASH_LENGTH]
+        .try_into()
+        .expect("SHA-256 is longer than a key hash")
+}
+
+/// Verifier key of the Ed25519 note key `key_name`, as witnesses are configured with
+pub fn verifier_key(key_name: &str, verifying_key: &[u8; PUBLIC_KEY_LENGTH]) -> String {
+    let mut key = vec![SIGNATURE_TYPE_ED25519];
+    key.extend_from_slice(verifying_key);
+    format!(
+        "{key_name}+{key_hash}+{key}",
+        key_hash = hex::encode(key_hash(key_name, verifying_key)),
+        key = BASE64.encode(key)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // from the documentation of golang.org/x/mod/sumdb/note
+    const KEY_NAME: &str = "PeterNeumann";
+    const VERIFYING_KEY: &str = "1a5cd907143c384c41e830c5bce12aa8817ec5649aaabf2c31388b35b2d744d6";
+    const NOTE: &str = "If you think cryptography is the answer to your problem,\nthen you don't know what your problem is.\n\n\u{2014} PeterNeumann x08go/ZJkuBS9UG/SffcvIAQxVBtiFupLLr8pAcElZInNIuGUgYN1FFYC2pZSNXgKvqfqdngotpRZb6KE6RyyBwJnAM=\n";
+
+    fn verifying_key() -> [u8; PUBLIC_KEY_LENGTH] {
+        hex::decode(VERIFYING_KEY).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_verify_note() {
+        assert_eq!(
+            verifier_key(KEY_NAME, &verifying_key()),
+            "PeterNeumann+c74f20a3+ARpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW"
+        );
+        assert_eq!(
+            verify_note(NOTE, KEY_NAME, &verifying_key()).unwrap(),
+            "If you think cryptography is the answer to your problem,\nthen you don't know what your problem is.\n"
+        );
+        assert!(verify_note(NOTE, "PeterNeumann2", &verifying_key()).is_err());
+        assert!(verify_note(
+            &NOTE.replace("think", "thought"),
+            KEY_NAME,
+            &verifying_key()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_text() {
+        let checkpoint = Checkpoint::new(
+            "example.key-transparency.v1".to_string(),
+            Epoch::from(489193),
+            vec![0xaa; DIGEST_LENGTH],
+        );
+        let text = checkpoint.to_note_text();
+        assert_eq!(
+            text,
+            "example.key-transparency.v1\n489193\nqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqo=\n"
+        );
+        assert_eq!(Checkpoint::from_note_text(&text).unwrap(), checkpoint);
+        assert_eq!(
+            Checkpoint::from_note_text(&format!("{text}extension\n")).unwrap(),
+            checkpoint
+        );
+        assert!(Checkpoint::from_note_text("example\n1\nqqo=\n").is_err());
+    }
+
+    #[cfg(feature = "signer")]
+    #[tokio::test]
+    async fn test_checkpoint_sign() {
+        let signer = crate::signer::Ed25519Signer::new(&[0xd6; 32]);
+        let checkpoint = Checkpoint::new(
+            "example.key-transparency.v1".to_string(),
+            Epoch::from(2),
+            vec![0xaa; DIGEST_LENGTH],
+        );
+        let note = checkpoint.sign("auditor", &signer).await.unwrap();
+        assert_eq!(
+            Checkpoint::open(&note, "auditor", &signer.verifying_key()).unwrap(),
+            checkpoint
+        );
+    }
+}
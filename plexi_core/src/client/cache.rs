@@ -0,0 +1,160 @@
+//! Cache of the auditor configuration and namespace records, which rarely change, so that audits of many epochs
+//! do not fetch them again for each one.
+//!
+//! Lookups are kept in memory, and optionally in a directory so that they outlive the process. They expire after a TTL.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+const ENTRIES_LOCK_POISONED: &str = "lookup cache lock poisoned";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Entry {
+    fetched_at: u64,
+    value: serde_json::Value,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_millis() as u64)
+}
+
+#[derive(Debug)]
+pub(crate) struct LookupCache {
+    ttl: Duration,
+    directory: Option<PathBuf>,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl LookupCache {
+    /// Cache of the lookups of the auditor at `base_url`
+    pub fn new(base_url: &Url, ttl: Duration, directory: Option<PathBuf>) -> Self {
+        Self {
+            ttl,
+            directory: directory
+                .map(|directory| directory.join(hex::encode(Sha256::digest(base_url.as_str())))),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn path(&self, url: &Url) -> Option<PathBuf> {
+        let file_name = format!(
+            "{key}.json",
+            key = hex::encode(Sha256::digest(url.as_str()))
+        );
+        self.directory
+            .as_ref()
+            .map(|directory| directory.join(file_name))
+    }
+
+    /// Value fetched from `url`, unless it expired
+    pub fn get<T: DeserializeOwned>(&self, url: &Url) -> Option<T> {
+        let mut entries = self.entries.lock().expect(ENTRIES_LOCK_POISONED);
+        if !entries.contains_key(url.as_str()) {
+            let entry = fs::read(self.path(url)?)
+                .ok()
+                .and_then(|entry| serde_json::from_slice::<Entry>(&entry).ok())?;
+            entries.insert(url.to_string(), entry);
+        }
+        let entry = entries.get(url.as_str())?;
+        if now().saturating_sub(entry.fetched_at) >= self.ttl.as_millis() as u64 {
+            return None;
+        }
+        serde_json::from_value(entry.value.clone()).ok()
+    }
+
+    /// Record `value` as fetched from `url`
+    pub fn insert<T: Serialize>(&self, url: &Url, value: &T) {
+        let Ok(value) = serde_json::to_value(value) else {
+            return;
+        };
+        let entry = Entry {
+            fetched_at: now(),
+            value,
+        };
+        if let Some(path) = self.path(url) {
+            let persisted = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::write(&path, serde_json::to_vec(&entry)?));
+            if let Err(e) = persisted {
+                tracing::debug!(
+                    "cannot persist lookup of {url} to {path}: {e}",
+                    path = path.display()
+                );
+            }
+        }
+        self.entries
+            .lock()
+            .expect(ENTRIES_LOCK_POISONED)
+            .insert(url.to_string(), entry);
+    }
+
+    /// Forget the value fetched from `url`
+    pub fn invalidate(&self, url: &Url) {
+        self.entries
+            .lock()
+            .expect(ENTRIES_LOCK_POISONED)
+            .remove(url.as_str());
+        if let Some(path) = self.path(url) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// Forget every value fetched from the auditor
+    pub fn clear(&self) {
+        self.entries.lock().expect(ENTRIES_LOCK_POISONED).clear();
+        if let Some(directory) = &self.directory {
+            let _ = fs::remove_dir_all(directory);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_cache() {
+        let base_url = Url::parse("https://auditor.example.com").unwrap();
+        let info = base_url.join("/info").unwrap();
+        let namespace = base_url.join("/namespaces/example").unwrap();
+        let directory = std::env::temp_dir().join(format!("plexi-{id}", id = uuid::Uuid::new_v4()));
+
+        let cache = LookupCache::new(&base_url, Duration::from_secs(60), Some(directory.clone()));
+        assert_eq!(cache.get::<String>(&info), None);
+        cache.insert(&info, &"config");
+        cache.insert(&namespace, &"namespace");
+        assert_eq!(cache.get::<String>(&info).as_deref(), Some("config"));
+
+        // a later process reads the lookups from the directory
+        let persisted =
+            LookupCache::new(&base_url, Duration::from_secs(60), Some(directory.clone()));
+        assert_eq!(persisted.get::<String>(&info).as_deref(), Some("config"));
+        persisted.invalidate(&info);
+        assert_eq!(persisted.get::<String>(&info), None);
+        assert_eq!(
+            LookupCache::new(&base_url, Duration::from_secs(60), Some(directory.clone()))
+                .get::<String>(&info),
+            None
+        );
+        persisted.clear();
+        assert_eq!(persisted.get::<String>(&namespace), None);
+
+        let expired = LookupCache::new(&base_url, Duration::ZERO, None);
+        expired.insert(&info, &"config");
+        assert_eq!(expired.get::<String>(&info), None);
+
+        fs::remove_dir_all(&directory).ok();
+    }
+}
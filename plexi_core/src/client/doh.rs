@@ -0,0 +1,220 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use anyhow::{anyhow, Context as _};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::{ACCEPT, CONTENT_TYPE},
+    Client, Url,
+};
+
+const DNS_MESSAGE_CONTENT_TYPE: &str = "application/dns-message";
+const DNS_HEADER_LENGTH: usize = 12;
+const DNS_CLASS_IN: u16 = 1;
+const DNS_TYPE_A: u16 = 1;
+const DNS_TYPE_AAAA: u16 = 28;
+
+#[derive(Clone, Debug)]
+pub(crate) struct DohResolver {
+    resolver_url: Url,
+    client: Client,
+}
+
+impl DohResolver {
+    pub(crate) fn new(resolver_url: Url, client: Client) -> Self {
+        Self {
+            resolver_url,
+            client,
+        }
+    }
+
+    async fn query(&self, name: &str, record_type: u16) -> anyhow::Result<Vec<IpAddr>> {
+        let response = self
+            .client
+            .post(self.resolver_url.clone())
+            .header(ACCEPT, DNS_MESSAGE_CONTENT_TYPE)
+            .header(CONTENT_TYPE, DNS_MESSAGE_CONTENT_TYPE)
+            .body(encode_query(name, record_type)?)
+            .send()
+            .await
+            .with_context(|| format!("querying {url}", url = self.resolver_url))?
+            .error_for_status()
+            .with_context(|| format!("querying {url}", url = self.resolver_url))?
+            .bytes()
+            .await?;
+
+        decode_response(&response)
+    }
+
+    async fn lookup(&self, name: &str) -> anyhow::Result<Vec<IpAddr>> {
+        let (v4, v6) = futures_util::join!(
+            self.query(name, DNS_TYPE_A),
+            self.query(name, DNS_TYPE_AAAA)
+        );
+
+        let addresses: Vec<IpAddr> = match (v4, v6) {
+            (Err(e), Err(_)) => return Err(e),
+            (v4, v6) => v4
+                .unwrap_or_default()
+                .into_iter()
+                .chain(v6.unwrap_or_default())
+                .collect(),
+        };
+
+        if addresses.is_empty() {
+            return Err(anyhow!("no address found for {name}"));
+        }
+        Ok(addresses)
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addresses = resolver.lookup(name.as_str()).await?;
+            // reqwest overrides the port with the one from the requested URL
+            let addrs: Addrs = Box::new(
+                addresses
+                    .into_iter()
+                    .map(|address| SocketAddr::new(address, 0)),
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+fn encode_query(name: &str, record_type: u16) -> anyhow::Result<Vec<u8>> {
+    // RFC 8484 recommends a zero ID to maximise cache friendliness
+    let mut message = vec![
+        0x00, 0x00, // id
+        0x01, 0x00, // flags: recursion desired
+        0x00, 0x01, // qdcount
+        0x00, 0x00, // ancount
+        0x00, 0x00, // nscount
+        0x00, 0x00, // arcount
+    ];
+
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(anyhow!("invalid DNS name {name}"));
+        }
+        message.push(label.len() as u8);
+        message.extend_from_slice(label.as_bytes());
+    }
+    message.push(0);
+    message.extend_from_slice(&record_type.to_be_bytes());
+    message.extend_from_slice(&DNS_CLASS_IN.to_be_bytes());
+
+    Ok(message)
+}
+
+fn read_u16(message: &[u8], offset: usize) -> anyhow::Result<u16> {
+    message
+        .get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| anyhow!("truncated DNS response"))
+}
+
+fn skip_name(message: &[u8], mut offset: usize) -> anyhow::Result<usize> {
+    loop {
+        let length = *message
+            .get(offset)
+            .ok_or_else(|| anyhow!("truncated DNS response"))?;
+        match length {
+            0 => return Ok(offset + 1),
+            // compression pointer, the name ends here
+            l if l & 0xc0 == 0xc0 => return Ok(offset + 2),
+            l => offset += 1 + l as usize,
+        }
+    }
+}
+
+fn decode_response(message: &[u8]) -> anyhow::Result<Vec<IpAddr>> {
+    if message.len() < DNS_HEADER_LENGTH {
+        return Err(anyhow!("truncated DNS response"));
+    }
+    let rcode = message[3] & 0x0f;
+    if rcode != 0 {
+        return Err(anyhow!("DNS resolver returned error code {rcode}"));
+    }
+    let question_count = read_u16(message, 4)?;
+    let answer_count = read_u16(message, 6)?;
+
+    let mut offset = DNS_HEADER_LENGTH;
+    for _ in 0..question_count {
+        // name, type, class
+        offset = skip_name(message, offset)? + 4;
+    }
+
+    let mut addresses = vec![];
+    for _ in 0..answer_count {
+        offset = skip_name(message, offset)?;
+        let record_type = read_u16(message, offset)?;
+        let length = read_u16(message, offset + 8)? as usize;
+        // type, class, ttl, rdlength
+        offset += 10;
+        let data = message
+            .get(offset..offset + length)
+            .ok_or_else(|| anyhow!("truncated DNS response"))?;
+        match (record_type, length) {
+            (DNS_TYPE_A, 4) => addresses.push(IpAddr::V4(Ipv4Addr::new(
+                data[0], data[1], data[2], data[3],
+            ))),
+            (DNS_TYPE_AAAA, 16) => {
+                let octets: [u8; 16] = data.try_into().expect("length is checked above");
+                addresses.push(IpAddr::V6(Ipv6Addr::from(octets)))
+            }
+            // CNAME and other records are followed by the resolver
+            _ => (),
+        }
+        offset += length;
+    }
+
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_query() {
+        let query = encode_query("plexi.example.", DNS_TYPE_A).unwrap();
+        assert_eq!(
+            &query[..DNS_HEADER_LENGTH],
+            &[0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            &query[DNS_HEADER_LENGTH..],
+            b"\x05plexi\x07example\x00\x00\x01\x00\x01"
+        );
+        assert!(encode_query("plexi..example", DNS_TYPE_A).is_err());
+    }
+
+    #[test]
+    fn test_decode_response() {
+        let mut response = vec![0, 0, 0x81, 0x80, 0, 1, 0, 3, 0, 0, 0, 0];
+        response.extend_from_slice(b"\x05plexi\x07example\x00\x00\x01\x00\x01");
+        // CNAME pointing back to the question name
+        response.extend_from_slice(&[0xc0, 0x0c, 0, 5, 0, 1, 0, 0, 0, 60, 0, 2, 0xc0, 0x0c]);
+        // A record
+        response.extend_from_slice(&[0xc0, 0x0c, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 192, 0, 2, 1]);
+        // AAAA record
+        response.extend_from_slice(&[0xc0, 0x0c, 0, 28, 0, 1, 0, 0, 0, 60, 0, 16]);
+        response.extend_from_slice(&Ipv6Addr::LOCALHOST.octets());
+
+        let addresses = decode_response(&response).unwrap();
+        assert_eq!(
+            addresses,
+            vec![
+                IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+                IpAddr::V6(Ipv6Addr::LOCALHOST)
+            ]
+        );
+
+        // NXDOMAIN
+        let nxdomain = [0, 0, 0x81, 0x83, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(decode_response(&nxdomain).is_err());
+        assert!(decode_response(&response[..20]).is_err());
+    }
+}
@@ -1,18 +1,102 @@
 use core::fmt;
-use std::time::Duration;
-
-use crate::auditor::Configuration as AuditorConfiguration;
-use crate::namespaces::{NamespaceInfo, Namespaces};
-use crate::{Epoch, LastVerifiedEpoch, SignatureResponse};
-use akd::local_auditing::AuditBlobName;
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use crate::auditor::{Configuration as AuditorConfiguration, DEFAULT_MAX_PROOF_SIZE};
+#[cfg(feature = "auditor")]
+use crate::auditor::{ProofId, SignatureSource};
+use crate::crypto::constant_time_eq;
+use crate::gossip::{Divergence, GossipRequest, GossipResponse};
+use crate::namespaces::{Namespace, NamespaceFilter, NamespaceInfo, Namespaces};
+use crate::{
+    Epoch, LastVerifiedEpoch, MigrationPolicy, PlexiError, Report, ReportResponse,
+    SignatureResponse,
+};
 use anyhow::{anyhow, Context as _};
-use reqwest::{Certificate, Client, Identity, StatusCode, Url};
-use serde::de::DeserializeOwned;
+use cache::LookupCache;
+use pinning::{SpkiPins, SPKI_HASH_LENGTH};
+use reqwest::{
+    header::HeaderMap, Certificate, Client, Identity, Method, Proxy, Response, StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "auditor")]
+use store::ProofStore;
+
+pub(crate) mod aws;
+mod cache;
+mod doh;
+mod pinning;
+mod quorum;
+#[cfg(feature = "auditor")]
+pub mod store;
+
+pub use aws::AwsCredentials;
+pub use quorum::{AuditorSignature, MultiAuditorClient, QuorumVerification};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+#[cfg(feature = "otel")]
+fn trace_context() -> HeaderMap {
+    use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+    let mut headers = HeaderMap::new();
+    let context = tracing::Span::current().context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(
+            &context,
+            &mut opentelemetry_http::HeaderInjector(&mut headers),
+        )
+    });
+    headers
+}
+
+#[cfg(not(feature = "otel"))]
+fn trace_context() -> HeaderMap {
+    HeaderMap::new()
+}
+
+pub(crate) async fn read_body(mut response: Response, max_size: usize) -> anyhow::Result<Vec<u8>> {
+    let too_large = |size| PlexiError::ProofTooLarge {
+        size,
+        limit: max_size,
+    };
+    if let Some(length) = response.content_length() {
+        let length = usize::try_from(length).unwrap_or(usize::MAX);
+        if length > max_size {
+            return Err(too_large(length).into());
+        }
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let size = body.len().saturating_add(chunk.len());
+        if size > max_size {
+            return Err(too_large(size).into());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// HTTP client for downloads such as proofs, with a timeout between reads rather than on the whole response
+pub fn download_client() -> anyhow::Result<Client> {
+    Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(DEFAULT_TIMEOUT)
+        .build()
+        .context("building download client")
+}
 
 #[derive(Clone)]
 pub struct PlexiClient {
     base_url: Url,
     client: Client,
+    spki_pins: Option<SpkiPins>,
+    bearer_token: Option<String>,
+    retries: u32,
+    max_response_size: usize,
+    cache: Option<Arc<LookupCache>>,
 }
 
 impl fmt::Debug for PlexiClient {
@@ -27,56 +111,64 @@ impl PlexiClient {
         mtls: Option<ClientMtls>,
         user_agent: Option<&str>,
     ) -> anyhow::Result<Self> {
-        let mut client_builder = Client::builder();
-
-        if let Ok(bundle) = std::env::var("SSL_CERT_FILE") {
-            let certs = std::fs::read(&bundle)
-                .with_context(|| format!("reading cert bundle at: {bundle}"))?;
-
-            for cert in Certificate::from_pem_bundle(&certs)
-                .with_context(|| format!("parsing cert bundle at: {bundle}"))?
-            {
-                client_builder = client_builder.add_root_certificate(cert);
-            }
-        }
-
-        if let Ok("1") = std::env::var("SSL_ACCEPT_INVALID_CERTS").as_deref() {
-            client_builder = client_builder
-                .danger_accept_invalid_certs(true)
-                .danger_accept_invalid_hostnames(true);
-        }
-
+        let mut builder = Self::builder(base_url);
         if let Some(mtls) = mtls {
-            client_builder = client_builder.identity(mtls.identity);
+            builder = builder.mtls(mtls);
         }
-
         if let Some(user_agent) = user_agent {
-            client_builder = client_builder.user_agent(user_agent);
+            builder = builder.user_agent(user_agent);
         }
+        builder.build()
+    }
 
-        Ok(Self {
-            base_url,
-            client: client_builder
-                .connect_timeout(Duration::from_secs(30))
-                .timeout(Duration::from_secs(60))
-                .build()
-                .context("building plexi api client")?,
-        })
+    pub fn builder(base_url: Url) -> PlexiClientBuilder {
+        PlexiClientBuilder::new(base_url)
     }
 
     pub fn base_url(&self) -> &Url {
         &self.base_url
     }
 
+    /// Underlying HTTP client
     pub fn client(&self) -> &Client {
         &self.client
     }
 
+    #[tracing::instrument(level = "debug", skip(self), fields(url = %url, status))]
+    async fn get(&self, url: &Url) -> anyhow::Result<Response> {
+        let mut attempt = 0;
+        let response = loop {
+            let result = self
+                .client
+                .get(url.clone())
+                .headers(trace_context())
+                .send()
+                .await;
+            let retryable = match &result {
+                Ok(response) => response.status().is_server_error(),
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+            if !retryable || attempt >= self.retries {
+                break result?;
+            }
+            tracing::debug!(attempt, "retrying request");
+            tokio::time::sleep(RETRY_BACKOFF * 2u32.pow(attempt.min(6))).await;
+            attempt += 1;
+        };
+        tracing::Span::current().record("status", response.status().as_u16());
+
+        if let Some(pins) = &self.spki_pins {
+            pins.verify(url, &response)?;
+        }
+
+        Ok(response)
+    }
+
     async fn fetch_json<T>(&self, url: &Url) -> anyhow::Result<Option<T>>
     where
         T: DeserializeOwned,
     {
-        let response = self.client.get(url.clone()).send().await?;
+        let response = self.get(url).await?;
 
         if response.status() == StatusCode::NOT_FOUND {
             return Ok(None);
@@ -92,30 +184,80 @@ impl PlexiClient {
         ))
     }
 
+    /// Configuration of the auditor
     pub async fn auditor_config(&self) -> anyhow::Result<AuditorConfiguration> {
         let url = self.base_url.join("/info")?;
+        if let Some(config) = self.cache.as_ref().and_then(|cache| cache.get(&url)) {
+            return Ok(config);
+        }
 
         match self.fetch_json(&url).await? {
-            Some(config) => Ok(config),
+            Some(config) => {
+                if let Some(cache) = &self.cache {
+                    cache.insert(&url, &config);
+                }
+                Ok(config)
+            }
             None => Err(anyhow!("auditor configuration should alwasys be defined")),
         }
     }
 
+    /// Namespace named `namespace`
     pub async fn namespace(&self, namespace: &str) -> anyhow::Result<Option<NamespaceInfo>> {
         let url = self.base_url.join(&format!("/namespaces/{namespace}"))?;
+        if let Some(info) = self.cache.as_ref().and_then(|cache| cache.get(&url)) {
+            return Ok(Some(info));
+        }
 
-        self.fetch_json(&url).await
+        let info = match self.fetch_json(&url).await? {
+            Some(info) => Some(info),
+            None => self.namespaces().await?.find(namespace).cloned(),
+        };
+        if let (Some(cache), Some(info)) = (&self.cache, &info) {
+            cache.insert(&url, info);
+        }
+        Ok(info)
+    }
+
+    /// Fetch the auditor configuration and namespaces again on their next lookup, rather than reading them from the cache
+    pub fn invalidate_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Fetch `namespace` again on its next lookup, rather than reading it from the cache
+    pub fn invalidate_namespace(&self, namespace: &str) -> anyhow::Result<()> {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(&self.base_url.join(&format!("/namespaces/{namespace}"))?);
+        }
+        Ok(())
     }
 
     pub async fn namespaces(&self) -> anyhow::Result<Namespaces> {
-        let url = self.base_url.join("/namespaces")?;
+        self.namespaces_matching(&NamespaceFilter::new()).await
+    }
 
-        match self.fetch_json(&url).await? {
-            Some(namespaces) => Ok(namespaces),
+    /// Namespaces matching `filter`
+    pub async fn namespaces_matching(
+        &self,
+        filter: &NamespaceFilter,
+    ) -> anyhow::Result<Namespaces> {
+        let url = self
+            .client
+            .get(self.base_url.join("/namespaces")?)
+            .query(filter)
+            .build()?
+            .url()
+            .clone();
+
+        match self.fetch_json::<Namespaces>(&url).await? {
+            Some(namespaces) => Ok(namespaces.filter(filter)),
             None => Err(anyhow!("auditor configuration should alwasys be defined")),
         }
     }
 
+    #[tracing::instrument(skip(self, epoch), fields(epoch = %epoch))]
     pub async fn signature(
         &self,
         namespace: &str,
@@ -128,6 +270,7 @@ impl PlexiClient {
         self.fetch_json(&url).await
     }
 
+    /// Last epoch the auditor verified for `namespace`
     pub async fn last_verified_epoch(
         &self,
         namespace: &str,
@@ -139,48 +282,647 @@ impl PlexiClient {
         self.fetch_json(&url).await
     }
 
-    pub async fn proof(
+    /// Last epoch the auditor verified for `namespace`, once its signature verifies with `verifying_key`
+    pub async fn verified_last_verified_epoch(
         &self,
-        blob: &AuditBlobName,
-        directory_url: Option<&str>,
-    ) -> anyhow::Result<Option<Vec<u8>>> {
-        // to be replaced with a default to self.base_url/proofs once available
-        let Some(directory_url) = directory_url else {
+        namespace: &str,
+        verifying_key: &[u8],
+    ) -> anyhow::Result<Option<LastVerifiedEpoch>> {
+        let Some(last_verified_epoch) = self.last_verified_epoch(namespace).await? else {
+            return Ok(None);
+        };
+        if last_verified_epoch.signature().is_none() {
+            tracing::warn!(namespace, "last verified epoch is not signed");
+            return Ok(Some(last_verified_epoch));
+        }
+        last_verified_epoch
+            .verify(namespace, verifying_key)
+            .with_context(|| format!("verifying last verified epoch of {namespace}"))?;
+        Ok(Some(last_verified_epoch))
+    }
+
+    /// Send our heads to the auditor, and receive its own for the same namespaces
+    pub async fn gossip(&self, request: &GossipRequest) -> anyhow::Result<GossipResponse> {
+        let url = self.base_url.join("/gossip")?;
+        if let Some(pins) = &self.spki_pins {
+            pins.verify_before_send()?;
+        }
+        let response = self
+            .client
+            .post(url.clone())
+            .headers(trace_context())
+            .json(request)
+            .send()
+            .await?;
+
+        if let Some(pins) = &self.spki_pins {
+            pins.verify(&url, &response)?;
+        }
+
+        response
+            .error_for_status()
+            .with_context(|| format!("posting {url}"))?
+            .json()
+            .await
+            .context(format!("converting {url} into json"))
+    }
+
+    /// Divergences the auditor found while gossiping with its peers
+    pub async fn divergences(&self) -> anyhow::Result<Vec<Divergence>> {
+        let url = self.base_url.join("/gossip/divergences")?;
+
+        Ok(self.fetch_json(&url).await?.unwrap_or_default())
+    }
+
+    /// Submit `report`, a new epoch of its namespace, for the auditor to verify and sign
+    pub async fn submit_report(&self, report: &Report) -> anyhow::Result<ReportResponse> {
+        let url = self.base_url.join(&format!(
+            "/namespaces/{namespace}/reports",
+            namespace = report.namespace()
+        ))?;
+        let token = self
+            .bearer_token
+            .as_deref()
+            .ok_or_else(|| anyhow!("submitting reports requires a bearer token"))?;
+        if let Some(pins) = &self.spki_pins {
+            pins.verify_before_send()?;
+        }
+        let response = self
+            .client
+            .post(url.clone())
+            .headers(trace_context())
+            .bearer_auth(token)
+            .json(report)
+            .send()
+            .await?;
+
+        if let Some(pins) = &self.spki_pins {
+            pins.verify(&url, &response)?;
+        }
+
+        response
+            .error_for_status()
+            .with_context(|| format!("posting {url}"))?
+            .json()
+            .await
+            .context(format!("converting {url} into json"))
+    }
+
+    /// Check `report` as the auditor does before signing it, without submitting it
+    pub async fn validate_report(
+        &self,
+        report: &Report,
+        provider_key: Option<&[u8]>,
+    ) -> anyhow::Result<()> {
+        let name = report.namespace();
+        let namespace = self
+            .namespace(name)
+            .await?
+            .ok_or_else(|| anyhow!("namespace {name} does not exist"))?;
+        if namespace.name() != name {
             return Err(anyhow!(
-                "plexi does not provide proof retrieval at this time."
+                "{name} is an alias, reports have to be for {namespace}",
+                namespace = namespace.name()
             ));
+        }
+        if !namespace.accepts_ciphersuite(report.ciphersuite()) {
+            return Err(anyhow!(
+                "namespace {name} uses ciphersuite {expected}",
+                expected = namespace.ciphersuite()
+            ));
+        }
+        report.ciphersuite().validate_digest(report.digest())?;
+        if let Some(provider_key) = provider_key {
+            report
+                .verify_with(provider_key, MigrationPolicy::Either)
+                .context("report signature does not verify for the provider key")?;
+        }
+
+        let Some(last_verified_epoch) = self.last_verified_epoch(name).await? else {
+            return Ok(());
         };
+        let (epoch, last_verified) = (*report.epoch(), last_verified_epoch.epoch());
+        if epoch <= last_verified {
+            // the auditor returns its signature when the epoch is reported again with the same digest
+            return match self.signature(name, &epoch).await? {
+                Some(signature) if !constant_time_eq(signature.digest(), report.digest()) => Err(
+                    anyhow!("epoch {epoch} is already signed with a different digest"),
+                ),
+                _ => Ok(()),
+            };
+        }
+        if epoch != last_verified + 1 {
+            return Err(anyhow!(
+                "the auditor last verified epoch {last_verified}, so epoch {epoch} cannot be verified before the epochs in between are reported"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Create `namespace` on the auditor
+    pub async fn create_namespace(&self, namespace: &Namespace) -> anyhow::Result<NamespaceInfo> {
+        let url = self.base_url.join("/namespaces")?;
+
+        self.send_admin(Method::POST, url, Some(namespace)).await
+    }
 
-        let url = Url::parse(directory_url)?;
-        let url = url.join(&format!("/{blob}", blob = blob.to_string()))?;
+    /// Replace the log directory, AKD configuration, and metadata of `namespace`
+    pub async fn update_namespace(&self, namespace: &Namespace) -> anyhow::Result<NamespaceInfo> {
+        let url = self
+            .base_url
+            .join(&format!("/namespaces/{name}", name = namespace.name()))?;
+
+        self.send_admin(Method::PUT, url, Some(namespace)).await
+    }
+
+    /// Stop auditing `namespace`
+    pub async fn disable_namespace(&self, namespace: &str) -> anyhow::Result<NamespaceInfo> {
+        let url = self
+            .base_url
+            .join(&format!("/namespaces/{namespace}/disable"))?;
+
+        self.send_admin::<()>(Method::POST, url, None).await
+    }
 
-        let response = self.client.get(url.clone()).send().await?;
+    async fn send_admin<B>(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<&B>,
+    ) -> anyhow::Result<NamespaceInfo>
+    where
+        B: Serialize + ?Sized,
+    {
+        let token = self
+            .bearer_token
+            .as_deref()
+            .ok_or_else(|| anyhow!("managing namespaces requires a bearer token"))?;
+        if let Some(pins) = &self.spki_pins {
+            pins.verify_before_send()?;
+        }
+        let mut request = self
+            .client
+            .request(method, url.clone())
+            .headers(trace_context())
+            .bearer_auth(token);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send().await?;
+
+        if let Some(pins) = &self.spki_pins {
+            pins.verify(&url, &response)?;
+        }
+        // the namespace changed, and so may have the namespaces it is an alias of
+        self.invalidate_cache();
+
+        response
+            .error_for_status()
+            .with_context(|| format!("sending {url}"))?
+            .json()
+            .await
+            .context(format!("converting {url} into json"))
+    }
+
+    async fn fetch_bytes(&self, url: &Url) -> anyhow::Result<Option<Vec<u8>>> {
+        let response = self.get(url).await?;
 
         if response.status() == StatusCode::NOT_FOUND {
             return Ok(None);
         }
 
+        let response = response
+            .error_for_status()
+            .with_context(|| format!("fetching {url}"))?;
         Ok(Some(
-            response
-                .error_for_status()
-                .with_context(|| format!("fetching {url}"))?
-                .bytes()
-                .await?
-                .to_vec(),
+            read_body(response, self.max_response_size)
+                .await
+                .with_context(|| format!("fetching {url}"))?,
         ))
     }
+
+    /// Content at `url`, with the timeout, retries, and client certificate of this client
+    pub async fn fetch(&self, url: &Url) -> anyhow::Result<Vec<u8>> {
+        self.fetch_bytes(url)
+            .await?
+            .ok_or_else(|| anyhow!("{url} does not exist"))
+    }
+
+    /// Proof store for a log directory URL, sharing this client connection settings
+    #[cfg(feature = "auditor")]
+    pub fn proof_store(&self, directory_url: &str) -> anyhow::Result<Box<dyn ProofStore>> {
+        store::proof_store_from_url(&self.client, directory_url)
+    }
+
+    /// Retrieve the proof for `blob`
+    #[cfg(feature = "auditor")]
+    #[tracing::instrument(skip(self, blob, directory), fields(epoch = blob.epoch))]
+    pub async fn proof(
+        &self,
+        namespace: &str,
+        blob: &ProofId,
+        directory: Option<&dyn ProofStore>,
+    ) -> anyhow::Result<Option<ProofResponse>> {
+        let url = self
+            .base_url
+            .join(&format!("/namespaces/{namespace}/proofs/{blob}"))?;
+        let auditor_proof = self.fetch_bytes(&url).await;
+        let Some(directory) = directory else {
+            return Ok(
+                auditor_proof?.map(|proof| ProofResponse::new(ProofSource::Auditor, url, proof))
+            );
+        };
+        match auditor_proof {
+            Ok(Some(proof)) => {
+                return Ok(Some(ProofResponse::new(ProofSource::Auditor, url, proof)))
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("{url} failed, falling back to the log directory: {e:#}"),
+        }
+
+        let url = directory.url(blob)?;
+        Ok(directory
+            .get(blob)
+            .await?
+            .map(|proof| ProofResponse::new(ProofSource::Directory, url, proof)))
+    }
+}
+
+#[cfg(feature = "auditor")]
+#[async_trait::async_trait]
+impl SignatureSource for PlexiClient {
+    async fn signature(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+    ) -> anyhow::Result<Option<SignatureResponse>> {
+        PlexiClient::signature(self, namespace, epoch).await
+    }
+}
+
+#[cfg(feature = "auditor")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofSource {
+    Auditor,
+    Directory,
+}
+
+#[cfg(feature = "auditor")]
+impl fmt::Display for ProofSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Auditor => "auditor",
+            Self::Directory => "directory",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(feature = "auditor")]
+#[derive(Clone)]
+pub struct ProofResponse {
+    source: ProofSource,
+    url: Url,
+    proof: Vec<u8>,
+}
+
+#[cfg(feature = "auditor")]
+impl fmt::Debug for ProofResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProofResponse")
+            .field("source", &self.source)
+            .field("url", &self.url.as_str())
+            .field("proof_length", &self.proof.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "auditor")]
+impl ProofResponse {
+    pub fn new(source: ProofSource, url: Url, proof: Vec<u8>) -> Self {
+        Self { source, url, proof }
+    }
+
+    pub fn source(&self) -> ProofSource {
+        self.source
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn proof(&self) -> &[u8] {
+        &self.proof
+    }
+
+    pub fn into_proof(self) -> Vec<u8> {
+        self.proof
+    }
+}
+
+#[derive(Clone)]
+pub struct PlexiClientBuilder {
+    base_url: Url,
+    mtls: Option<ClientMtls>,
+    user_agent: Option<String>,
+    doh_resolver: Option<Url>,
+    socks5_proxy: Option<String>,
+    spki_pins: Vec<[u8; SPKI_HASH_LENGTH]>,
+    bearer_token: Option<String>,
+    timeout: Duration,
+    retries: u32,
+    pool_idle_timeout: Option<Option<Duration>>,
+    pool_max_idle_per_host: Option<usize>,
+    tcp_keepalive: Option<Duration>,
+    cache_ttl: Option<Duration>,
+    cache_directory: Option<PathBuf>,
+    max_response_size: usize,
+}
+
+impl fmt::Debug for PlexiClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlexiClientBuilder")
+            .field("base_url", &self.base_url.as_str())
+            .field("mtls", &self.mtls.is_some())
+            .field("user_agent", &self.user_agent)
+            .field("doh_resolver", &self.doh_resolver.as_ref().map(Url::as_str))
+            .field("socks5_proxy", &self.socks5_proxy)
+            .field(
+                "spki_pins",
+                &self.spki_pins.iter().map(hex::encode).collect::<Vec<_>>(),
+            )
+            .field("bearer_token", &self.bearer_token.is_some())
+            .field("timeout", &self.timeout)
+            .field("retries", &self.retries)
+            .field("pool_idle_timeout", &self.pool_idle_timeout)
+            .field("pool_max_idle_per_host", &self.pool_max_idle_per_host)
+            .field("tcp_keepalive", &self.tcp_keepalive)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("cache_directory", &self.cache_directory)
+            .field("max_response_size", &self.max_response_size)
+            .finish()
+    }
+}
+
+impl PlexiClientBuilder {
+    pub fn new(base_url: Url) -> Self {
+        Self {
+            base_url,
+            mtls: None,
+            user_agent: None,
+            doh_resolver: None,
+            socks5_proxy: None,
+            spki_pins: vec![],
+            bearer_token: None,
+            timeout: DEFAULT_TIMEOUT,
+            retries: 0,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            cache_ttl: None,
+            cache_directory: None,
+            max_response_size: DEFAULT_MAX_PROOF_SIZE,
+        }
+    }
+
+    pub fn mtls(mut self, mtls: ClientMtls) -> Self {
+        self.mtls = Some(mtls);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Resolve the auditor and log directory hostnames over DNS-over-HTTPS (RFC 8484) instead of the system resolver
+    pub fn dns_over_https(mut self, resolver_url: Url) -> Self {
+        self.doh_resolver = Some(resolver_url);
+        self
+    }
+
+    /// Route all traffic, including proof downloads from the log directory, through a SOCKS5 proxy such as Tor
+    pub fn socks5(mut self, addr: &str) -> Self {
+        self.socks5_proxy = Some(addr.to_string());
+        self
+    }
+
+    /// Pin the auditor public key
+    pub fn pin_server_spki(mut self, hashes: &[[u8; SPKI_HASH_LENGTH]]) -> Self {
+        self.spki_pins.extend_from_slice(hashes);
+        self
+    }
+
+    /// Authenticate namespace management requests with `token`, the admin token of the auditor
+    pub fn bearer_token(mut self, token: &str) -> Self {
+        self.bearer_token = Some(token.to_string());
+        self
+    }
+
+    /// Time a request, including downloading its response, may take before it times out
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Number of times a GET request is retried, with exponential backoff
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Largest response body, in bytes, that is read from the auditor or a log directory
+    pub fn max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_size = bytes;
+        self
+    }
+
+    /// Time an idle connection is kept open for reuse, `None` to keep it open indefinitely
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum number of idle connections kept open to each host
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Send TCP keepalive probes on connections after they have been idle for `interval`
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Reuse the auditor configuration and namespaces looked up for `ttl`, rather than fetching them for every audit
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Persist cached lookups in `directory`
+    pub fn cache_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.cache_directory = Some(directory.into());
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<PlexiClient> {
+        let mut client_builder = Client::builder();
+
+        #[cfg_attr(not(feature = "rustls"), allow(unused_variables))]
+        let cert_bundle = match std::env::var("SSL_CERT_FILE") {
+            Ok(bundle) => {
+                let certs = std::fs::read(&bundle)
+                    .with_context(|| format!("reading cert bundle at: {bundle}"))?;
+
+                for cert in Certificate::from_pem_bundle(&certs)
+                    .with_context(|| format!("parsing cert bundle at: {bundle}"))?
+                {
+                    client_builder = client_builder.add_root_certificate(cert);
+                }
+                Some(certs)
+            }
+            Err(_) => None,
+        };
+
+        let accept_invalid_certs = matches!(
+            std::env::var("SSL_ACCEPT_INVALID_CERTS").as_deref(),
+            Ok("1")
+        );
+        if accept_invalid_certs {
+            client_builder = client_builder
+                .danger_accept_invalid_certs(true)
+                .danger_accept_invalid_hostnames(true);
+        }
+
+        if let Some(mtls) = &self.mtls {
+            client_builder = client_builder.identity(mtls.identity.clone());
+        }
+
+        if let Some(user_agent) = &self.user_agent {
+            client_builder = client_builder.user_agent(user_agent);
+        }
+
+        if let Some(resolver_url) = self.doh_resolver {
+            let mut resolver_client = Client::builder().timeout(Duration::from_secs(10));
+            if let Some(user_agent) = &self.user_agent {
+                resolver_client = resolver_client.user_agent(user_agent);
+            }
+            let resolver_client = resolver_client
+                .build()
+                .context("building DNS-over-HTTPS client")?;
+            client_builder = client_builder.dns_resolver(Arc::new(doh::DohResolver::new(
+                resolver_url,
+                resolver_client,
+            )));
+        }
+
+        if let Some(addr) = &self.socks5_proxy {
+            let proxy = Proxy::all(format!("socks5h://{addr}"))
+                .with_context(|| format!("configuring socks5 proxy at: {addr}"))?;
+            client_builder = client_builder.proxy(proxy);
+        }
+
+        if let Some(timeout) = self.pool_idle_timeout {
+            client_builder = client_builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            client_builder = client_builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            client_builder = client_builder.tcp_keepalive(interval);
+        }
+
+        let spki_pins = if self.spki_pins.is_empty() {
+            None
+        } else {
+            let pins = SpkiPins::new(&self.base_url, self.spki_pins)?;
+            client_builder = client_builder
+                .tls_info(true)
+                .redirect(pins.redirect_policy());
+            // with rustls, the pins are checked in the handshake as well, before any request is sent
+            #[cfg(feature = "rustls")]
+            {
+                let identity = match &self.mtls {
+                    Some(ClientMtls { pem: Some(pem), .. }) => Some(pem.as_slice()),
+                    Some(ClientMtls { pem: None, .. }) => {
+                        return Err(anyhow!(
+                            "SPKI pinning cannot be combined with a PKCS#12 client identity"
+                        ))
+                    }
+                    None => None,
+                };
+                client_builder = client_builder.use_preconfigured_tls(pinning::tls_config(
+                    &pins,
+                    cert_bundle.as_deref(),
+                    accept_invalid_certs,
+                    identity,
+                )?);
+            }
+            Some(pins)
+        };
+
+        let cache = self
+            .cache_ttl
+            .map(|ttl| Arc::new(LookupCache::new(&self.base_url, ttl, self.cache_directory)));
+
+        Ok(PlexiClient {
+            cache,
+            base_url: self.base_url,
+            spki_pins,
+            bearer_token: self.bearer_token,
+            retries: self.retries,
+            max_response_size: self.max_response_size,
+            client: client_builder
+                .connect_timeout(CONNECT_TIMEOUT.min(self.timeout))
+                .timeout(self.timeout)
+                .build()
+                .context("building plexi api client")?,
+        })
+    }
 }
 
 #[derive(Clone)]
 pub struct ClientMtls {
     identity: Identity,
+    #[cfg_attr(not(feature = "rustls"), allow(dead_code))]
+    pem: Option<Vec<u8>>,
 }
 
 impl ClientMtls {
+    #[cfg(feature = "native-tls")]
     pub fn new(cert: &[u8], key: &[u8]) -> anyhow::Result<Self> {
         let identity =
             Identity::from_pkcs8_pem(cert, key).context("creating identity from pkcs8 pem")?;
 
-        Ok(ClientMtls { identity })
+        Ok(ClientMtls {
+            identity,
+            pem: Some([cert, b"\n", key].concat()),
+        })
+    }
+
+    #[cfg(not(feature = "native-tls"))]
+    pub fn new(cert: &[u8], key: &[u8]) -> anyhow::Result<Self> {
+        // rustls expects the certificate chain and the key in a single pem buffer
+        let pem = [cert, b"\n", key].concat();
+        let identity = Identity::from_pem(&pem).context("creating identity from pem")?;
+
+        Ok(ClientMtls {
+            identity,
+            pem: Some(pem),
+        })
+    }
+
+    /// Identity from a DER encoded PKCS#12 archive holding the client certificate chain and key, encrypted with `password`
+    #[cfg(feature = "native-tls")]
+    pub fn from_pkcs12(der: &[u8], password: &str) -> anyhow::Result<Self> {
+        let identity =
+            Identity::from_pkcs12_der(der, password).context("creating identity from pkcs12")?;
+
+        Ok(ClientMtls {
+            identity,
+            pem: None,
+        })
     }
 }
@@ -0,0 +1,357 @@
+#[cfg(feature = "rustls")]
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _};
+use reqwest::{redirect, tls::TlsInfo, Response, Url};
+#[cfg(feature = "rustls")]
+use rustls::{
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        WebPkiServerVerifier,
+    },
+    crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use sha2::{Digest, Sha256};
+
+pub(crate) const SPKI_HASH_LENGTH: usize = 32;
+
+const MAX_REDIRECTS: usize = 10;
+
+const DER_TAG_SEQUENCE: u8 = 0x30;
+const DER_TAG_VERSION: u8 = 0xa0;
+
+#[derive(Clone, Debug)]
+pub(crate) struct SpkiPins {
+    host: String,
+    hashes: Vec<[u8; SPKI_HASH_LENGTH]>,
+}
+
+impl SpkiPins {
+    pub(crate) fn new(base_url: &Url, hashes: Vec<[u8; SPKI_HASH_LENGTH]>) -> anyhow::Result<Self> {
+        if base_url.scheme() != "https" {
+            return Err(anyhow!("SPKI pinning requires an https auditor URL"));
+        }
+        if hashes.is_empty() {
+            return Err(anyhow!("SPKI pinning requires at least one hash"));
+        }
+        let host = base_url
+            .host_str()
+            .ok_or_else(|| anyhow!("auditor URL does not have a host"))?
+            .to_string();
+        Ok(Self { host, hashes })
+    }
+
+    fn is_pinned(&self, url: &Url) -> bool {
+        url.host_str() == Some(self.host.as_str())
+    }
+
+    pub(crate) fn redirect_policy(&self) -> redirect::Policy {
+        let pins = self.clone();
+        redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("too many redirects")
+            } else if !pins.is_pinned(attempt.url())
+                && attempt.previous().iter().any(|url| pins.is_pinned(url))
+            {
+                let error = format!(
+                    "{host} redirected to {url}, outside of the pinned host",
+                    host = pins.host,
+                    url = attempt.url()
+                );
+                attempt.error(error)
+            } else {
+                attempt.follow()
+            }
+        })
+    }
+
+    pub(crate) fn verify_before_send(&self) -> anyhow::Result<()> {
+        if cfg!(feature = "rustls") {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "sending to {host} requires the rustls feature, which checks the SPKI pins before the request is sent",
+                host = self.host
+            ))
+        }
+    }
+
+    pub(crate) fn verify(&self, url: &Url, response: &Response) -> anyhow::Result<()> {
+        if !self.is_pinned(url) {
+            return Ok(());
+        }
+        // fail closed if a redirect left the pinned host
+        if !self.is_pinned(response.url()) {
+            return Err(anyhow!(
+                "{url} was redirected to {response_url}, outside of the pinned host",
+                response_url = response.url()
+            ));
+        }
+
+        // fail closed when the certificate is not available
+        let certificate = response
+            .extensions()
+            .get::<TlsInfo>()
+            .and_then(TlsInfo::peer_certificate)
+            .ok_or_else(|| anyhow!("{host} did not present a certificate", host = self.host))?;
+        self.verify_certificate(certificate)
+    }
+
+    fn verify_certificate(&self, certificate: &[u8]) -> anyhow::Result<()> {
+        let hash: [u8; SPKI_HASH_LENGTH] = Sha256::digest(
+            spki_from_certificate(certificate).context("parsing auditor certificate")?,
+        )
+        .into();
+
+        if self.hashes.contains(&hash) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{host} public key {hash} does not match any pinned SPKI hash",
+                host = self.host,
+                hash = hex::encode(hash)
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "rustls")]
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    pins: SpkiPins,
+    webpki: Option<Arc<WebPkiServerVerifier>>,
+    provider: Arc<CryptoProvider>,
+}
+
+#[cfg(feature = "rustls")]
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if let Some(webpki) = &self.webpki {
+            webpki.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                ocsp_response,
+                now,
+            )?;
+        }
+        if server_name.to_str() == self.pins.host.as_str() {
+            self.pins
+                .verify_certificate(end_entity)
+                .map_err(|e| rustls::Error::General(format!("{e:#}")))?;
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(feature = "rustls")]
+pub(crate) fn tls_config(
+    pins: &SpkiPins,
+    cert_bundle: Option<&[u8]>,
+    accept_invalid_certs: bool,
+    identity: Option<&[u8]>,
+) -> anyhow::Result<ClientConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let webpki = if accept_invalid_certs {
+        None
+    } else {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        if let Some(mut cert_bundle) = cert_bundle {
+            for certificate in rustls_pemfile::certs(&mut cert_bundle) {
+                roots.add(certificate.context("parsing cert bundle")?)?;
+            }
+        }
+        Some(
+            WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                .build()
+                .context("building certificate verifier")?,
+        )
+    };
+
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier {
+            pins: pins.clone(),
+            webpki,
+            provider,
+        }));
+    let mut config = match identity {
+        Some(identity) => {
+            let certificates = rustls_pemfile::certs(&mut &identity[..])
+                .collect::<Result<Vec<_>, _>>()
+                .context("parsing client certificate")?;
+            let key = rustls_pemfile::private_key(&mut &identity[..])
+                .context("parsing client key")?
+                .ok_or_else(|| anyhow!("client identity does not have a private key"))?;
+            builder.with_client_auth_cert(certificates, key)?
+        }
+        None => builder.with_no_client_auth(),
+    };
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+struct DerElement<'a> {
+    tag: u8,
+    element: &'a [u8],
+    content: &'a [u8],
+    remainder: &'a [u8],
+}
+
+fn read_der_element(input: &[u8]) -> anyhow::Result<DerElement<'_>> {
+    let truncated = || anyhow!("truncated DER element");
+    let tag = *input.first().ok_or_else(truncated)?;
+    let first_length = *input.get(1).ok_or_else(truncated)?;
+
+    let (header_length, content_length) = if first_length & 0x80 == 0 {
+        (2, first_length as usize)
+    } else {
+        let length_bytes = (first_length & 0x7f) as usize;
+        if length_bytes == 0 || length_bytes > std::mem::size_of::<usize>() {
+            return Err(anyhow!("unsupported DER length encoding"));
+        }
+        let length = input
+            .get(2..2 + length_bytes)
+            .ok_or_else(truncated)?
+            .iter()
+            .fold(0usize, |length, b| (length << 8) | *b as usize);
+        (2 + length_bytes, length)
+    };
+
+    let end = header_length
+        .checked_add(content_length)
+        .filter(|end| *end <= input.len())
+        .ok_or_else(truncated)?;
+    Ok(DerElement {
+        tag,
+        element: &input[..end],
+        content: &input[header_length..end],
+        remainder: &input[end..],
+    })
+}
+
+pub(crate) fn spki_from_certificate(certificate: &[u8]) -> anyhow::Result<&[u8]> {
+    let certificate = read_der_element(certificate)?;
+    if certificate.tag != DER_TAG_SEQUENCE {
+        return Err(anyhow!("certificate is not a DER sequence"));
+    }
+    let tbs_certificate = read_der_element(certificate.content)?;
+    if tbs_certificate.tag != DER_TAG_SEQUENCE {
+        return Err(anyhow!("tbsCertificate is not a DER sequence"));
+    }
+
+    // version is optional
+    let mut fields = tbs_certificate.content;
+    let version = read_der_element(fields)?;
+    if version.tag == DER_TAG_VERSION {
+        fields = version.remainder;
+    }
+
+    // serialNumber, signature, issuer, validity, subject
+    for _ in 0..5 {
+        fields = read_der_element(fields)?.remainder;
+    }
+
+    let spki = read_der_element(fields)?;
+    if spki.tag != DER_TAG_SEQUENCE {
+        return Err(anyhow!("subjectPublicKeyInfo is not a DER sequence"));
+    }
+    Ok(spki.element)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // self-signed P-256 certificate for plexi.example
+    const CERTIFICATE: &str = "308201863082012ba003020102021419147e40999ab587510f6c93226595e61db7b763300a06082a8648ce3d04030230183116301406035504030c0d706c6578692e6578616d706c65301e170d3236313031343138303430315a170d3336313031313138303430315a30183116301406035504030c0d706c6578692e6578616d706c653059301306072a8648ce3d020106082a8648ce3d03010703420004645eab490bcee15ad01d44ca45ad1db219ae93917d75379f9038e01c6f2161409dc2465ece278fdffd8f8a15ca1c0b14e538a757f8dced5916e5e045c74485c8a3533051301d0603551d0e041604149534e762ba1d650c9474f0663694db7af28d6803301f0603551d230418301680149534e762ba1d650c9474f0663694db7af28d6803300f0603551d130101ff040530030101ff300a06082a8648ce3d0403020349003046022100cbd7e2f27f11a9ab5794ed322dbcb0ee2d01ffe4d7ac982d0132cbea07f54553022100d4748d531d5dec724b2e2274cb10ced4a83346d4444ad6ad03373fec3de0a3e2";
+    // openssl x509 -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256
+    const SPKI_HASH: &str = "c9461a513f88c5ef1d5baecbeb9139d0b8bea9d68eaf21dc7083a870080718f6";
+
+    #[test]
+    fn test_spki_from_certificate() {
+        let certificate = hex::decode(CERTIFICATE).unwrap();
+        let spki = spki_from_certificate(&certificate).unwrap();
+        assert_eq!(hex::encode(Sha256::digest(spki)), SPKI_HASH);
+
+        assert!(spki_from_certificate(&certificate[..100]).is_err());
+        assert!(spki_from_certificate(&[]).is_err());
+    }
+
+    #[test]
+    fn test_verify_certificate() {
+        let certificate = hex::decode(CERTIFICATE).unwrap();
+        let url = Url::parse("https://plexi.example").unwrap();
+        let hash: [u8; SPKI_HASH_LENGTH] = hex::decode(SPKI_HASH).unwrap().try_into().unwrap();
+
+        let pins = SpkiPins::new(&url, vec![hash]).unwrap();
+        assert!(pins.verify_certificate(&certificate).is_ok());
+        let pins = SpkiPins::new(&url, vec![[0u8; SPKI_HASH_LENGTH]]).unwrap();
+        assert!(pins.verify_certificate(&certificate).is_err());
+
+        assert!(pins.is_pinned(&Url::parse("https://plexi.example/info").unwrap()));
+        assert!(!pins.is_pinned(&Url::parse("https://logs.example/proofs").unwrap()));
+    }
+
+    #[test]
+    fn test_spki_pins_require_https() {
+        let hash = [0u8; SPKI_HASH_LENGTH];
+        assert!(SpkiPins::new(&Url::parse("http://plexi.example").unwrap(), vec![hash]).is_err());
+        assert!(SpkiPins::new(&Url::parse("https://plexi.example").unwrap(), vec![]).is_err());
+        assert!(SpkiPins::new(&Url::parse("https://plexi.example").unwrap(), vec![hash]).is_ok());
+    }
+
+    #[test]
+    fn test_verify_before_send() {
+        let hash = [0u8; SPKI_HASH_LENGTH];
+        let pins =
+            SpkiPins::new(&Url::parse("https://plexi.example").unwrap(), vec![hash]).unwrap();
+        assert_eq!(pins.verify_before_send().is_ok(), cfg!(feature = "rustls"));
+    }
+}
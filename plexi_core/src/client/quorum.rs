@@ -0,0 +1,266 @@
+use std::fmt;
+
+use anyhow::anyhow;
+use futures_util::future::join_all;
+use reqwest::Url;
+
+use super::PlexiClient;
+use crate::{crypto::constant_time_eq, gossip::verify_head, Epoch, SignatureResponse};
+
+/// Client querying several independent auditors
+#[derive(Clone)]
+pub struct MultiAuditorClient {
+    auditors: Vec<PlexiClient>,
+    quorum: usize,
+}
+
+impl fmt::Debug for MultiAuditorClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiAuditorClient")
+            .field("auditors", &self.auditors)
+            .field("quorum", &self.quorum)
+            .finish()
+    }
+}
+
+impl MultiAuditorClient {
+    /// Fails if `quorum` is 0, or greater than the number of auditors
+    pub fn new(auditors: Vec<PlexiClient>, quorum: usize) -> anyhow::Result<Self> {
+        if quorum == 0 || quorum > auditors.len() {
+            return Err(anyhow!(
+                "quorum must be between 1 and the {count} auditors",
+                count = auditors.len()
+            ));
+        }
+        Ok(Self { auditors, quorum })
+    }
+
+    /// Client requiring a strict majority of `auditors` to agree
+    pub fn majority(auditors: Vec<PlexiClient>) -> anyhow::Result<Self> {
+        let quorum = auditors.len() / 2 + 1;
+        Self::new(auditors, quorum)
+    }
+
+    pub fn auditors(&self) -> &[PlexiClient] {
+        &self.auditors
+    }
+
+    pub fn quorum(&self) -> usize {
+        self.quorum
+    }
+
+    /// Signature of each auditor for `namespace` at `epoch`, checked against the keys the auditor advertises
+    pub async fn signatures(&self, namespace: &str, epoch: &Epoch) -> Vec<AuditorSignature> {
+        join_all(self.auditors.iter().map(|auditor| async move {
+            AuditorSignature {
+                url: auditor.base_url().clone(),
+                signature: verified_signature(auditor, namespace, epoch)
+                    .await
+                    .map_err(|e| format!("{e:#}")),
+            }
+        }))
+        .await
+    }
+
+    /// Verify `namespace` at `epoch` is signed with the same digest by a quorum of auditors
+    pub async fn verify(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+    ) -> anyhow::Result<QuorumVerification> {
+        tally(self.quorum, self.signatures(namespace, epoch).await)
+    }
+}
+
+async fn verified_signature(
+    auditor: &PlexiClient,
+    namespace: &str,
+    epoch: &Epoch,
+) -> anyhow::Result<SignatureResponse> {
+    let signature = auditor
+        .signature(namespace, epoch)
+        .await?
+        .ok_or_else(|| anyhow!("{namespace} epoch {epoch} is not signed"))?;
+    let config = auditor.auditor_config().await?;
+    verify_head(&signature, config.keys())?;
+    Ok(signature)
+}
+
+/// Answer of one auditor of a [`MultiAuditorClient`]
+#[derive(Clone, Debug)]
+pub struct AuditorSignature {
+    url: Url,
+    signature: Result<SignatureResponse, String>,
+}
+
+impl AuditorSignature {
+    pub fn new(url: Url, signature: Result<SignatureResponse, String>) -> Self {
+        Self { url, signature }
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Verified signature, or why the auditor did not provide one
+    pub fn signature(&self) -> Result<&SignatureResponse, &str> {
+        self.signature.as_ref().map_err(String::as_str)
+    }
+}
+
+/// Digest a quorum of auditors agreed on
+#[derive(Clone, Debug)]
+pub struct QuorumVerification {
+    digest: Vec<u8>,
+    agreeing: Vec<AuditorSignature>,
+    dissenting: Vec<AuditorSignature>,
+    failed: Vec<AuditorSignature>,
+}
+
+impl QuorumVerification {
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Auditors which signed [`Self::digest`]
+    pub fn agreeing(&self) -> &[AuditorSignature] {
+        &self.agreeing
+    }
+
+    /// Auditors which signed another digest
+    pub fn dissenting(&self) -> &[AuditorSignature] {
+        &self.dissenting
+    }
+
+    /// Auditors which could not be reached, had not signed the epoch, or returned an invalid signature
+    pub fn failed(&self) -> &[AuditorSignature] {
+        &self.failed
+    }
+}
+
+fn tally(quorum: usize, signatures: Vec<AuditorSignature>) -> anyhow::Result<QuorumVerification> {
+    let (signed, failed): (Vec<_>, Vec<_>) = signatures
+        .into_iter()
+        .partition(|signature| signature.signature.is_ok());
+
+    // the most signed digest wins. Two digests cannot both reach a majority quorum
+    let mut counts: Vec<(Vec<u8>, usize)> = vec![];
+    for signature in &signed {
+        let digest = signature.signature().expect("partitioned").digest();
+        match counts.iter_mut().find(|(d, _)| constant_time_eq(d, digest)) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((digest.to_vec(), 1)),
+        }
+    }
+    let Some((digest, count)) = counts.into_iter().max_by_key(|(_, count)| *count) else {
+        return Err(anyhow!(
+            "no auditor provided a valid signature, {quorum} are required"
+        ));
+    };
+    if count < quorum {
+        return Err(anyhow!(
+            "{count} auditors agree on digest {digest}, {quorum} are required. {disagreeing} signed another digest, {failed} failed",
+            digest = hex::encode(&digest),
+            disagreeing = signed.len() - count,
+            failed = failed.len()
+        ));
+    }
+
+    let (agreeing, dissenting) = signed.into_iter().partition(|signature| {
+        constant_time_eq(
+            signature.signature().expect("partitioned").digest(),
+            &digest,
+        )
+    });
+    Ok(QuorumVerification {
+        digest,
+        agreeing,
+        dissenting,
+        failed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ciphersuite;
+
+    fn answer(auditor: u8, digest: Option<u8>) -> AuditorSignature {
+        let url = Url::parse(&format!("https://auditor{auditor}.example.com")).unwrap();
+        let signature = match digest {
+            Some(digest) => Ok(SignatureResponse::new(
+                &Ciphersuite::ProtobufEd25519,
+                &Ciphersuite::ProtobufEd25519,
+                "example.key-transparency.v1".to_string(),
+                1717084639921,
+                &Epoch::from(1),
+                vec![digest; 32],
+                vec![0; 64],
+                None,
+                None,
+            )),
+            None => Err("unreachable".to_string()),
+        };
+        AuditorSignature::new(url, signature)
+    }
+
+    #[test]
+    fn test_tally() {
+        let verification = tally(
+            2,
+            vec![
+                answer(1, Some(0xaa)),
+                answer(2, Some(0xbb)),
+                answer(3, Some(0xaa)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(verification.digest(), &[0xaa; 32]);
+        assert_eq!(verification.agreeing().len(), 2);
+        assert_eq!(
+            verification.dissenting()[0].url().as_str(),
+            "https://auditor2.example.com/"
+        );
+        assert!(verification.failed().is_empty());
+
+        // failed auditors do not count towards the quorum
+        let verification = tally(
+            2,
+            vec![
+                answer(1, Some(0xaa)),
+                answer(2, None),
+                answer(3, Some(0xaa)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            verification.failed()[0].signature().unwrap_err(),
+            "unreachable"
+        );
+        assert!(tally(
+            2,
+            vec![answer(1, Some(0xaa)), answer(2, None), answer(3, None)]
+        )
+        .is_err());
+
+        assert!(tally(2, vec![answer(1, Some(0xaa)), answer(2, Some(0xbb))]).is_err());
+        assert!(tally(1, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_quorum_bounds() {
+        let auditors: Vec<_> = (1..=3)
+            .map(|i| {
+                PlexiClient::new(
+                    Url::parse(&format!("https://auditor{i}.example.com")).unwrap(),
+                    None,
+                    None,
+                )
+                .unwrap()
+            })
+            .collect();
+        assert!(MultiAuditorClient::new(auditors.clone(), 0).is_err());
+        assert!(MultiAuditorClient::new(auditors.clone(), 4).is_err());
+        assert_eq!(MultiAuditorClient::majority(auditors).unwrap().quorum(), 2);
+    }
+}
@@ -0,0 +1,451 @@
+use std::{
+    io::{self, Read as _},
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, Context as _};
+use async_trait::async_trait;
+use reqwest::{header::AUTHORIZATION, Client, StatusCode, Url};
+
+use super::aws::{AwsCredentials, SigV4};
+use super::read_body;
+use crate::{
+    auditor::{ProofId, DEFAULT_MAX_PROOF_SIZE},
+    PlexiError,
+};
+
+const GCS_API_URL: &str = "https://storage.googleapis.com/storage/v1/b/";
+
+/// Suffix of proofs a log directory stores compressed with zstd
+pub const ZSTD_SUFFIX: &str = ".zst";
+
+/// Location where a log publishes its audit proofs
+#[async_trait]
+pub trait ProofStore: Send + Sync {
+    /// URL the object `name` is retrieved from
+    fn object_url(&self, name: &str) -> anyhow::Result<Url>;
+
+    /// Retrieve the object `name`
+    async fn get_object(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// URL the proof for `blob` is retrieved from
+    fn url(&self, blob: &ProofId) -> anyhow::Result<Url> {
+        self.object_url(&blob.to_string())
+    }
+
+    /// Retrieve the proof for `blob`, uncompressed
+    async fn get(&self, blob: &ProofId) -> anyhow::Result<Option<Vec<u8>>> {
+        let name = blob.to_string();
+        if let Some(proof) = self.get_object(&name).await? {
+            return Ok(Some(proof));
+        }
+        let name = format!("{name}{ZSTD_SUFFIX}");
+        self.get_object(&name)
+            .await?
+            .map(|compressed| {
+                decompress_zstd(&compressed).with_context(|| format!("decompressing {name}"))
+            })
+            .transpose()
+    }
+}
+
+fn decompress_zstd(compressed: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut proof = vec![];
+    zstd::stream::read::Decoder::new(compressed)?
+        .take(DEFAULT_MAX_PROOF_SIZE as u64 + 1)
+        .read_to_end(&mut proof)?;
+    if proof.len() > DEFAULT_MAX_PROOF_SIZE {
+        return Err(PlexiError::ProofTooLarge {
+            size: proof.len(),
+            limit: DEFAULT_MAX_PROOF_SIZE,
+        }
+        .into());
+    }
+    Ok(proof)
+}
+
+/// Build the proof store for a log directory URL
+pub fn proof_store_from_url(
+    client: &Client,
+    directory_url: &str,
+) -> anyhow::Result<Box<dyn ProofStore>> {
+    let url = Url::parse(directory_url)
+        .with_context(|| format!("parsing log directory {directory_url}"))?;
+
+    let bucket_and_prefix = || -> anyhow::Result<(String, String)> {
+        let bucket = url
+            .host_str()
+            .ok_or_else(|| anyhow!("{directory_url} does not have a bucket"))?;
+        Ok((bucket.to_string(), url.path().trim_matches('/').to_string()))
+    };
+
+    match url.scheme() {
+        "http" | "https" => Ok(Box::new(HttpProofStore::new(client.clone(), url))),
+        "file" => {
+            let root = url
+                .to_file_path()
+                .map_err(|_| anyhow!("{directory_url} is not a valid local path"))?;
+            Ok(Box::new(FileProofStore::new(root)))
+        }
+        "s3" => {
+            let (bucket, prefix) = bucket_and_prefix()?;
+            let region = std::env::var("AWS_REGION")
+                .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+                .unwrap_or_else(|_| "us-east-1".to_string());
+            let endpoint = match std::env::var("AWS_ENDPOINT_URL") {
+                Ok(endpoint) => Url::parse(&endpoint).context("parsing AWS_ENDPOINT_URL")?,
+                Err(_) => Url::parse(&format!("https://s3.{region}.amazonaws.com"))?,
+            };
+            let credentials = AwsCredentials::from_env().ok();
+            Ok(Box::new(S3ProofStore::new(
+                client.clone(),
+                endpoint,
+                &region,
+                &bucket,
+                &prefix,
+                credentials,
+            )))
+        }
+        "gs" => {
+            let (bucket, prefix) = bucket_and_prefix()?;
+            let token = std::env::var("GOOGLE_OAUTH_ACCESS_TOKEN").ok();
+            Ok(Box::new(GcsProofStore::new(
+                client.clone(),
+                &bucket,
+                &prefix,
+                token.as_deref(),
+            )))
+        }
+        scheme => Err(anyhow!("unsupported log directory scheme {scheme}")),
+    }
+}
+
+fn object_name(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{prefix}/{name}")
+    }
+}
+
+async fn read_response(response: reqwest::Response, url: &Url) -> anyhow::Result<Option<Vec<u8>>> {
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("fetching {url}"))?;
+    Ok(Some(
+        read_body(response, DEFAULT_MAX_PROOF_SIZE)
+            .await
+            .with_context(|| format!("fetching {url}"))?,
+    ))
+}
+
+/// Log directory served over HTTP, as is done by WhatsApp
+#[derive(Clone, Debug)]
+pub struct HttpProofStore {
+    client: Client,
+    base_url: Url,
+}
+
+impl HttpProofStore {
+    pub fn new(client: Client, base_url: Url) -> Self {
+        Self { client, base_url }
+    }
+}
+
+#[async_trait]
+impl ProofStore for HttpProofStore {
+    fn object_url(&self, name: &str) -> anyhow::Result<Url> {
+        Ok(self.base_url.join(&format!("/{name}"))?)
+    }
+
+    async fn get_object(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let url = self.object_url(name)?;
+        let response = self.client.get(url.clone()).send().await?;
+        read_response(response, &url).await
+    }
+}
+
+/// Local mirror of a log directory, for instance to verify proofs on an air-gapped machine
+#[derive(Clone, Debug)]
+pub struct FileProofStore {
+    root: PathBuf,
+}
+
+impl FileProofStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+}
+
+#[async_trait]
+impl ProofStore for FileProofStore {
+    fn object_url(&self, name: &str) -> anyhow::Result<Url> {
+        let path = self.path(name);
+        Url::from_file_path(&path)
+            .map_err(|_| anyhow!("{path} is not an absolute path", path = path.display()))
+    }
+
+    async fn get_object(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self.path(name);
+        match tokio::fs::read(&path).await {
+            Ok(proof) => Ok(Some(proof)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("reading {path}", path = path.display())),
+        }
+    }
+}
+
+/// Log directory stored in an S3-compatible bucket, such as AWS S3 or Cloudflare R2
+#[derive(Clone, Debug)]
+pub struct S3ProofStore {
+    client: Client,
+    endpoint: Url,
+    bucket: String,
+    prefix: String,
+    signer: Option<SigV4>,
+}
+
+impl S3ProofStore {
+    pub fn new(
+        client: Client,
+        endpoint: Url,
+        region: &str,
+        bucket: &str,
+        prefix: &str,
+        credentials: Option<AwsCredentials>,
+    ) -> Self {
+        Self {
+            client,
+            endpoint,
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+            signer: credentials.map(|credentials| SigV4::new(credentials, region, "s3")),
+        }
+    }
+}
+
+#[async_trait]
+impl ProofStore for S3ProofStore {
+    fn object_url(&self, name: &str) -> anyhow::Result<Url> {
+        let mut url = self.endpoint.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("invalid S3 endpoint {endpoint}", endpoint = self.endpoint))?
+            .pop_if_empty()
+            .push(&self.bucket)
+            .extend(object_name(&self.prefix, name).split('/'));
+        Ok(url)
+    }
+
+    async fn get_object(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let url = self.object_url(name)?;
+        let mut request = self.client.get(url.clone());
+        if let Some(signer) = &self.signer {
+            for (name, value) in
+                signer.sign("GET", &url, &[], b"", time::OffsetDateTime::now_utc())?
+            {
+                request = request.header(name, value);
+            }
+        }
+        let response = request.send().await?;
+        read_response(response, &url).await
+    }
+}
+
+/// Log directory stored in a Google Cloud Storage bucket, retrieved with the JSON API
+#[derive(Clone)]
+pub struct GcsProofStore {
+    client: Client,
+    bucket: String,
+    prefix: String,
+    access_token: Option<String>,
+}
+
+impl std::fmt::Debug for GcsProofStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcsProofStore")
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .field("access_token", &self.access_token.is_some())
+            .finish()
+    }
+}
+
+impl GcsProofStore {
+    pub fn new(client: Client, bucket: &str, prefix: &str, access_token: Option<&str>) -> Self {
+        Self {
+            client,
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+            access_token: access_token.map(str::to_string),
+        }
+    }
+}
+
+#[async_trait]
+impl ProofStore for GcsProofStore {
+    fn object_url(&self, name: &str) -> anyhow::Result<Url> {
+        let mut url = Url::parse(GCS_API_URL)?;
+        // the object name is a single, percent-encoded, path segment
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("invalid GCS API URL"))?
+            .pop_if_empty()
+            .extend([self.bucket.as_str(), "o", &object_name(&self.prefix, name)]);
+        url.query_pairs_mut().append_pair("alt", "media");
+        Ok(url)
+    }
+
+    async fn get_object(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let url = self.object_url(name)?;
+        let mut request = self.client.get(url.clone());
+        if let Some(token) = &self.access_token {
+            request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let response = request.send().await?;
+        read_response(response, &url).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob() -> ProofId {
+        ProofId {
+            epoch: 2,
+            previous_hash: [0xaa; 32],
+            current_hash: [0xbb; 32],
+        }
+    }
+
+    #[test]
+    fn test_proof_store_urls() {
+        let client = Client::new();
+        let name = blob().to_string();
+
+        let store = proof_store_from_url(&client, "https://directory.example/").unwrap();
+        assert_eq!(
+            store.url(&blob()).unwrap().as_str(),
+            format!("https://directory.example/{name}")
+        );
+
+        let store = S3ProofStore::new(
+            client.clone(),
+            Url::parse("https://account.r2.cloudflarestorage.com").unwrap(),
+            "auto",
+            "audits",
+            "/whatsapp/",
+            None,
+        );
+        assert_eq!(
+            store.url(&blob()).unwrap().as_str(),
+            format!("https://account.r2.cloudflarestorage.com/audits/whatsapp/{name}")
+        );
+
+        let store = GcsProofStore::new(client.clone(), "audits", "", None);
+        assert_eq!(
+            store.url(&blob()).unwrap().as_str(),
+            format!(
+                "https://storage.googleapis.com/storage/v1/b/audits/o/{name}?alt=media",
+                name = name.replace('/', "%2F")
+            )
+        );
+
+        assert!(proof_store_from_url(&client, "ftp://directory.example/").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_proof_store() {
+        let root = std::env::temp_dir().join(format!("plexi-{id}", id = uuid::Uuid::new_v4()));
+        let store =
+            proof_store_from_url(&Client::new(), Url::from_file_path(&root).unwrap().as_str())
+                .unwrap();
+        assert!(store.get(&blob()).await.unwrap().is_none());
+
+        let path = root.join(blob().to_string());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, b"proof").unwrap();
+        assert_eq!(store.get(&blob()).await.unwrap(), Some(b"proof".to_vec()));
+        assert_eq!(
+            store.url(&blob()).unwrap(),
+            Url::from_file_path(&path).unwrap()
+        );
+
+        // proofs stored compressed are decompressed transparently
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(
+            format!("{path}{ZSTD_SUFFIX}", path = path.display()),
+            zstd::encode_all(b"proof".as_slice(), 0).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(store.get(&blob()).await.unwrap(), Some(b"proof".to_vec()));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    fn serve(response: &'static str, count: usize) -> Url {
+        use std::io::{Read as _, Write as _};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).unwrap();
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_read_response_limit() {
+        let client = Client::new();
+
+        let url = serve(
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nproof",
+            1,
+        );
+        let response = client.get(url.clone()).send().await.unwrap();
+        assert_eq!(
+            read_response(response, &url).await.unwrap(),
+            Some(b"proof".to_vec())
+        );
+
+        // a body announcing a length above the limit is rejected before it is read
+        let url = serve(
+            "HTTP/1.1 200 OK\r\nContent-Length: 1073741825\r\nConnection: close\r\n\r\n",
+            1,
+        );
+        let response = client.get(url.clone()).send().await.unwrap();
+        let err = read_body(response, DEFAULT_MAX_PROOF_SIZE)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlexiError>(),
+            Some(PlexiError::ProofTooLarge { .. })
+        ));
+
+        // so is a chunked body, once it grows past the limit
+        let url = serve(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n5\r\nproof\r\n5\r\nproof\r\n0\r\n\r\n",
+            2,
+        );
+        let response = client.get(url.clone()).send().await.unwrap();
+        assert_eq!(read_body(response, 10).await.unwrap(), b"proofproof");
+        let response = client.get(url).send().await.unwrap();
+        let err = read_body(response, 8).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PlexiError>(),
+            Some(PlexiError::ProofTooLarge { limit: 8, .. })
+        ));
+    }
+}
@@ -1,7 +1,26 @@
 use ed25519_dalek::PUBLIC_KEY_LENGTH;
+use subtle::ConstantTimeEq as _;
 
 pub fn ed25519_public_key_to_key_id(public_key: &[u8; PUBLIC_KEY_LENGTH]) -> u8 {
     *public_key
         .last()
         .expect("fixed size array has a last element")
 }
+
+/// Whether digests or signatures `a` and `b` are equal, in a time that does not depend on their content
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(!constant_time_eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!constant_time_eq(&[1, 2, 3], &[1, 2]));
+        assert!(constant_time_eq(&[], &[]));
+    }
+}
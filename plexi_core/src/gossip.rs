@@ -0,0 +1,190 @@
+//! Gossip between auditors, the defense against a log presenting different views of a namespace to different auditors.
+//!
+//! Auditors monitoring the same namespace exchange their head, the signature of the last epoch they verified.
+//! Each auditor compares the heads it receives with the signature it produced for the same epoch.
+//! Two valid signatures for the same namespace and epoch with different digests are a [`Divergence`], evidence of a split view.
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use crate::{auditor::KeyInfo, crypto::constant_time_eq, Epoch, SignatureResponse};
+
+/// Heads an auditor sends to a peer
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct GossipRequest {
+    heads: Vec<SignatureResponse>,
+}
+
+impl GossipRequest {
+    pub fn new(heads: Vec<SignatureResponse>) -> Self {
+        Self { heads }
+    }
+
+    pub fn heads(&self) -> &[SignatureResponse] {
+        &self.heads
+    }
+}
+
+/// Heads of the peer for the namespaces of the request, and the divergences the request revealed
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct GossipResponse {
+    heads: Vec<SignatureResponse>,
+    divergences: Vec<Divergence>,
+}
+
+impl GossipResponse {
+    pub fn new(heads: Vec<SignatureResponse>, divergences: Vec<Divergence>) -> Self {
+        Self { heads, divergences }
+    }
+
+    pub fn heads(&self) -> &[SignatureResponse] {
+        &self.heads
+    }
+
+    pub fn divergences(&self) -> &[Divergence] {
+        &self.divergences
+    }
+}
+
+/// Two signatures for the same namespace and epoch, over different digests
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct Divergence {
+    namespace: String,
+    epoch: Epoch,
+    heads: Vec<SignatureResponse>,
+}
+
+impl Divergence {
+    /// Divergence between `a` and `b`, if they are for the same namespace and epoch but not the same digest
+    pub fn between(a: &SignatureResponse, b: &SignatureResponse) -> Option<Self> {
+        if a.namespace() != b.namespace()
+            || a.epoch() != b.epoch()
+            || constant_time_eq(a.digest(), b.digest())
+        {
+            return None;
+        }
+        let mut heads = vec![a.clone(), b.clone()];
+        heads.sort_by(|a, b| a.digest().cmp(b.digest()));
+        Some(Self {
+            namespace: a.namespace().to_string(),
+            epoch: *a.epoch(),
+            heads,
+        })
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    pub fn heads(&self) -> &[SignatureResponse] {
+        &self.heads
+    }
+
+    /// Check the divergence is well formed, and both heads are signed by one of `keys`
+    pub fn verify(&self, keys: &[KeyInfo]) -> anyhow::Result<()> {
+        let [a, b] = self.heads.as_slice() else {
+            return Err(anyhow!("a divergence has two heads"));
+        };
+        if Self::between(a, b).as_ref() != Some(self) {
+            return Err(anyhow!(
+                "heads do not diverge on {namespace} epoch {epoch}",
+                namespace = self.namespace,
+                epoch = self.epoch
+            ));
+        }
+        verify_head(a, keys)?;
+        verify_head(b, keys)
+    }
+}
+
+/// Check `head` is signed by one of `keys`
+pub fn verify_head(head: &SignatureResponse, keys: &[KeyInfo]) -> anyhow::Result<()> {
+    let verified = keys.iter().any(|key| {
+        hex::decode(key.public_key()).is_ok_and(|public_key| head.verify(&public_key).is_ok())
+    });
+    if verified {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{namespace} epoch {epoch} head is not signed by a known key",
+            namespace = head.namespace(),
+            epoch = head.epoch()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ciphersuite, SignatureMessage};
+
+    const SIGNING_KEY: [u8; 32] = [0xd6; 32];
+
+    fn head(epoch: u64, digest: [u8; 32]) -> SignatureResponse {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&SIGNING_KEY);
+        let message = SignatureMessage::new(
+            &Ciphersuite::ProtobufEd25519,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch::from(epoch),
+            digest.to_vec(),
+        )
+        .unwrap();
+        let signature = ed25519_dalek::Signer::sign(&signing_key, &message.to_vec().unwrap());
+        SignatureResponse::new(
+            &Ciphersuite::ProtobufEd25519,
+            &Ciphersuite::ProtobufEd25519,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch::from(epoch),
+            digest.to_vec(),
+            signature.to_vec(),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_divergence() {
+        let keys = [KeyInfo::new(
+            &hex::encode(ed25519_dalek::SigningKey::from_bytes(&SIGNING_KEY).verifying_key()),
+            0,
+        )];
+
+        assert!(Divergence::between(&head(1, [0xaa; 32]), &head(1, [0xaa; 32])).is_none());
+        assert!(Divergence::between(&head(1, [0xaa; 32]), &head(2, [0xbb; 32])).is_none());
+
+        let divergence = Divergence::between(&head(1, [0xaa; 32]), &head(1, [0xbb; 32])).unwrap();
+        assert_eq!(divergence.epoch(), 1);
+        assert_eq!(
+            Divergence::between(&head(1, [0xbb; 32]), &head(1, [0xaa; 32])).unwrap(),
+            divergence
+        );
+        divergence.verify(&keys).unwrap();
+        assert!(divergence.verify(&[]).is_err());
+
+        // a forged head does not make a divergence
+        let forged = SignatureResponse::new(
+            &Ciphersuite::ProtobufEd25519,
+            &Ciphersuite::ProtobufEd25519,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch::from(1),
+            vec![0xbb; 32],
+            vec![0; 64],
+            None,
+            None,
+        );
+        let divergence = Divergence::between(&head(1, [0xaa; 32]), &forged).unwrap();
+        assert!(divergence.verify(&keys).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Epoch signatures as [RFC 7515](https://www.rfc-editor.org/rfc/rfc7515) compact JWS, so that web backends can verify them with JOSE libraries.
+//!
+//! The payload is the message the ciphersuite signs, and the algorithm `EdDSA`.
+//! A JWS signs its header along with the payload, so the auditor signs the epoch once more for it, with the same key.
+//! The header carries the signature response in its `plexi` parameter, for [`SignatureResponse::from_jws`] to rebuild it.
+
+use anyhow::{anyhow, Context as _};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL, Engine as _};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "signer")]
+use crate::signer::Signer;
+use crate::{SignatureMessage, SignatureResponse};
+
+const ALGORITHM: &str = "EdDSA";
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+    plexi: SignatureResponse,
+}
+
+impl SignatureResponse {
+    /// Compact JWS of the epoch, signed by `signer`
+    #[cfg(feature = "signer")]
+    pub async fn to_jws(&self, signer: &dyn Signer) -> anyhow::Result<String> {
+        let header = Header {
+            alg: ALGORITHM.to_string(),
+            kid: self.key_id.map(|key_id| key_id.to_string()),
+            plexi: self.clone(),
+        };
+        let payload = SignatureMessage::from(self).to_vec()?;
+        let signing_input = format!(
+            "{header}.{payload}",
+            header = BASE64URL.encode(serde_json::to_vec(&header)?),
+            payload = BASE64URL.encode(payload)
+        );
+        let signature = signer.sign(signing_input.as_bytes()).await?;
+        Ok(format!(
+            "{signing_input}.{signature}",
+            signature = BASE64URL.encode(signature)
+        ))
+    }
+
+    /// Signature response carried by the compact JWS `jws`, once both the JWS and the response verify with `verifying_key`
+    pub fn from_jws(jws: &str, verifying_key: &[u8]) -> anyhow::Result<Self> {
+        let mut parts = jws.split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(anyhow!("JWS does not have three parts"));
+        };
+
+        let verifying_key = verifying_key.try_into().map_err(|_| {
+            anyhow!(
+                "verifying_key should have length {length}",
+                length = ed25519_dalek::PUBLIC_KEY_LENGTH
+            )
+        })?;
+        let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&verifying_key)
+            .context("parsing verifying_key")?;
+        let jws_signature = ed25519_dalek::Signature::from_slice(
+            &BASE64URL
+                .decode(signature)
+                .context("decoding JWS signature")?,
+        )
+        .context("parsing JWS signature")?;
+        verifying_key
+            .verify_strict(format!("{header}.{payload}").as_bytes(), &jws_signature)
+            .context("verifying JWS signature")?;
+
+        let header: Header =
+            serde_json::from_slice(&BASE64URL.decode(header).context("decoding JWS header")?)
+                .context("parsing JWS header")?;
+        if header.alg != ALGORITHM {
+            return Err(anyhow!(
+                "JWS algorithm {alg} is not supported",
+                alg = header.alg
+            ));
+        }
+        let payload = BASE64URL.decode(payload).context("decoding JWS payload")?;
+        if SignatureMessage::from(&header.plexi).to_vec()? != payload {
+            return Err(anyhow!("JWS payload is not the message of its signature"));
+        }
+        header.plexi.verify(verifying_key.as_bytes())?;
+        Ok(header.plexi)
+    }
+}
+
+#[cfg(all(test, feature = "signer"))]
+mod tests {
+    use super::*;
+    use crate::{
+        signer::{sign_message, Ed25519Signer},
+        Ciphersuite, Epoch, DIGEST_LENGTH,
+    };
+
+    const SIGNING_KEY: &str = "0202020202020202020202020202020202020202020202020202020202020202";
+
+    #[tokio::test]
+    async fn test_jws() {
+        let signer = Ed25519Signer::from_hex(SIGNING_KEY).unwrap();
+        let message = SignatureMessage::new(
+            &Ciphersuite::ProtobufEd25519,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch::from(489193),
+            vec![0xaa; DIGEST_LENGTH],
+        )
+        .unwrap();
+        let signature = sign_message(&signer, &message).await.unwrap();
+
+        let jws = signature.to_jws(&signer).await.unwrap();
+        let verifying_key = signer.verifying_key();
+        assert_eq!(
+            SignatureResponse::from_jws(&jws, &verifying_key).unwrap(),
+            signature
+        );
+        let payload = jws.split('.').nth(1).unwrap();
+        assert_eq!(
+            BASE64URL.decode(payload).unwrap(),
+            message.to_vec().unwrap()
+        );
+
+        let other_key = Ed25519Signer::from_hex(&"03".repeat(32))
+            .unwrap()
+            .verifying_key();
+        assert!(SignatureResponse::from_jws(&jws, &other_key).is_err());
+        let (signing_input, _) = jws.rsplit_once('.').unwrap();
+        assert!(SignatureResponse::from_jws(signing_input, &verifying_key).is_err());
+    }
+}
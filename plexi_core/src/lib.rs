@@ -1,9 +1,10 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::{self, Display},
     num::ParseIntError,
     ops::{Add, Sub},
     str::FromStr,
+    time::Duration,
 };
 
 use anyhow::anyhow;
@@ -19,15 +20,66 @@ use utoipa::ToSchema;
 
 pub use uuid::Uuid;
 
+#[cfg(all(
+    any(feature = "client", feature = "workers"),
+    not(any(feature = "native-tls", feature = "rustls"))
+))]
+compile_error!(
+    "the `client` and `workers` features require a TLS backend, enable either `native-tls` or `rustls`"
+);
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
 pub mod auditor;
+#[cfg(feature = "auditor")]
+pub mod bundle;
+#[cfg(feature = "checkpoint")]
+pub mod checkpoint;
 #[cfg(feature = "client")]
 pub mod client;
 pub mod crypto;
+pub mod gossip;
+#[cfg(feature = "jws")]
+mod jws;
 pub mod namespaces;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 pub mod proto;
-
-const SIGNATURE_VERSIONS: [Ciphersuite; 2] =
-    [Ciphersuite::ProtobufEd25519, Ciphersuite::BincodeEd25519];
+#[cfg(feature = "signer")]
+pub mod signer;
+#[cfg(feature = "sth")]
+pub mod sth;
+#[cfg(feature = "storage")]
+pub mod storage;
+#[cfg(feature = "proptest")]
+mod strategy;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "witness")]
+pub mod witness;
+
+const SIGNATURE_VERSIONS: [Ciphersuite; 3] = [
+    Ciphersuite::ProtobufEd25519,
+    Ciphersuite::BincodeEd25519,
+    Ciphersuite::ProtobufV2Ed25519,
+];
+
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+const DIGEST_ALGORITHMS: [DigestAlgorithm; 3] = [
+    DigestAlgorithm::Sha256,
+    DigestAlgorithm::Blake3,
+    DigestAlgorithm::Sha512_256,
+];
+
+/// Length of the digests signed under the ciphersuites plexi supports
+pub const DIGEST_LENGTH: usize = 32;
+
+/// Drift tolerated between the clock checking a timestamp and the auditor one, unless set otherwise
+pub const DEFAULT_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+const SIGNATURE_MESSAGE_V2: u32 = 2;
+
+const LAST_VERIFIED_EPOCH_CONTEXT: &[u8] = b"plexi last verified epoch v1\0";
 
 #[derive(Error, Debug)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
@@ -40,24 +92,96 @@ pub enum PlexiError {
     Serialization,
     #[error("Root is not valid")]
     InvalidRoot,
+    #[error("proof verification was cancelled")]
+    VerificationCancelled,
+    #[error("proof verification did not complete before its deadline")]
+    VerificationDeadlineExceeded,
+    #[error("proof is {size} bytes, above the {limit} bytes limit")]
+    ProofTooLarge { size: usize, limit: usize },
+    #[error("proof has {nodes} nodes, above the {limit} nodes limit")]
+    TooManyProofNodes { nodes: usize, limit: usize },
+    #[error("namespace is {late} seconds behind its epoch cadence")]
+    BehindCadence { late: u64 },
+    #[error("namespace cannot move from {from} to {to}")]
+    InvalidStatusTransition {
+        from: namespaces::NamespaceStatus,
+        to: namespaces::NamespaceStatus,
+    },
+    #[error("digest is {actual} bytes, ciphersuite {ciphersuite} expects {expected} bytes")]
+    InvalidDigestLength {
+        ciphersuite: Ciphersuite,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("signature is {actual} bytes, expected {expected} bytes")]
+    InvalidSignatureLength { expected: usize, actual: usize },
+    #[error("digest is computed with {actual}, namespace expects {expected}")]
+    DigestAlgorithmMismatch {
+        expected: DigestAlgorithm,
+        actual: DigestAlgorithm,
+    },
+    #[error("epoch {epoch} is timestamped {timestamp}, before epoch {previous_epoch} at {previous_timestamp}")]
+    TimestampDecreased {
+        epoch: u64,
+        timestamp: u64,
+        previous_epoch: u64,
+        previous_timestamp: u64,
+    },
+    #[error("epoch {epoch} is timestamped {gap} seconds after epoch {previous_epoch}, above the {limit} seconds bound")]
+    TimestampGapTooLarge {
+        epoch: u64,
+        previous_epoch: u64,
+        gap: u64,
+        limit: u64,
+    },
+    #[error("epoch {epoch} is timestamped {ahead} seconds in the future, above the {tolerance} seconds clock skew tolerance")]
+    TimestampInFuture {
+        epoch: u64,
+        ahead: u64,
+        tolerance: u64,
+    },
+    #[error("epoch {epoch} is {age} seconds old, above the {limit} seconds bound")]
+    SignatureTooOld { epoch: u64, age: u64, limit: u64 },
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
-#[cfg_attr(feature = "openapi", derive(ToSchema))]
 #[serde(into = "u32")]
 #[serde(from = "u32")]
 #[repr(u32)]
 pub enum Ciphersuite {
     ProtobufEd25519 = 0x0001,
     BincodeEd25519 = 0x0002,
+    /// Versioned protobuf message, with an optional previous digest and metadata
+    ProtobufV2Ed25519 = 0x0003,
     Unknown(u32),
 }
 
+// serialized as its u32 value, which the derived schema does not describe
+#[cfg(feature = "openapi")]
+impl<'s> ToSchema<'s> for Ciphersuite {
+    fn schema() -> (&'s str, utoipa::openapi::RefOr<utoipa::openapi::Schema>) {
+        use utoipa::openapi::{KnownFormat, ObjectBuilder, SchemaFormat, SchemaType};
+
+        (
+            "Ciphersuite",
+            ObjectBuilder::new()
+                .schema_type(SchemaType::Integer)
+                .format(Some(SchemaFormat::KnownFormat(KnownFormat::Int32)))
+                .minimum(Some(0.0))
+                .description(Some(
+                    "Signature serialization and algorithm, 1 being protobuf with Ed25519, 2 bincode with Ed25519, and 3 versioned protobuf with Ed25519",
+                ))
+                .into(),
+        )
+    }
+}
+
 impl From<Ciphersuite> for u32 {
     fn from(val: Ciphersuite) -> Self {
         match val {
             Ciphersuite::ProtobufEd25519 => 0x0001,
             Ciphersuite::BincodeEd25519 => 0x0002,
+            Ciphersuite::ProtobufV2Ed25519 => 0x0003,
             Ciphersuite::Unknown(u) => u,
         }
     }
@@ -68,6 +192,7 @@ impl From<u32> for Ciphersuite {
         match u {
             0x0001 => Self::ProtobufEd25519,
             0x0002 => Self::BincodeEd25519,
+            0x0003 => Self::ProtobufV2Ed25519,
             _ => Self::Unknown(u),
         }
     }
@@ -82,11 +207,36 @@ impl FromStr for Ciphersuite {
     }
 }
 
+impl Ciphersuite {
+    /// Length of the digests signed under the ciphersuite
+    pub fn digest_length(&self) -> Option<usize> {
+        match self {
+            Self::ProtobufEd25519 | Self::BincodeEd25519 | Self::ProtobufV2Ed25519 => {
+                Some(DIGEST_LENGTH)
+            }
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Check `digest` has the length the ciphersuite expects
+    pub fn validate_digest(&self, digest: &[u8]) -> Result<(), PlexiError> {
+        match self.digest_length() {
+            Some(expected) if expected != digest.len() => Err(PlexiError::InvalidDigestLength {
+                ciphersuite: *self,
+                expected,
+                actual: digest.len(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl fmt::Display for Ciphersuite {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
             Self::ProtobufEd25519 => "0x0001",
             Self::BincodeEd25519 => "0x0002",
+            Self::ProtobufV2Ed25519 => "0x0003",
             Self::Unknown(_u) => "unknown",
         };
         write!(f, "{}", s)
@@ -124,6 +274,78 @@ impl<'de> BorrowDecode<'de> for Ciphersuite {
     }
 }
 
+/// Hash function a log computes its digests with
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DigestAlgorithm {
+    Sha256,
+    Blake3,
+    #[serde(rename = "sha512_256")]
+    Sha512_256,
+    /// Algorithm this version of plexi does not know about
+    #[serde(other)]
+    Unknown,
+}
+
+impl DigestAlgorithm {
+    /// Identifier of the algorithm in [`Ciphersuite::ProtobufV2Ed25519`] messages
+    pub fn id(&self) -> Option<u32> {
+        match self {
+            Self::Sha256 => Some(1),
+            Self::Blake3 => Some(2),
+            Self::Sha512_256 => Some(3),
+            Self::Unknown => None,
+        }
+    }
+
+    /// Length of the digests the algorithm outputs
+    pub fn digest_length(&self) -> Option<usize> {
+        match self {
+            Self::Sha256 | Self::Blake3 | Self::Sha512_256 => Some(32),
+            Self::Unknown => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Blake3 => "blake3",
+            Self::Sha512_256 => "sha512_256",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Check the algorithm is known, and `digest` has the length it outputs
+    pub fn validate_digest(&self, digest: &[u8]) -> Result<(), PlexiError> {
+        match self.digest_length() {
+            Some(expected) if expected == digest.len() => Ok(()),
+            Some(_) => Err(PlexiError::BadParameter("digest".to_string())),
+            None => Err(PlexiError::BadParameter("digest_algorithm".to_string())),
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for DigestAlgorithm {
+    type Err = PlexiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(Self::Sha256),
+            "blake3" => Ok(Self::Blake3),
+            "sha512_256" => Ok(Self::Sha512_256),
+            _ => Err(PlexiError::BadParameter("digest_algorithm".to_string())),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "bincode", derive(Encode, Decode))]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
@@ -241,6 +463,20 @@ pub struct SignatureMessage {
     epoch: Epoch,
     #[serde(with = "hex::serde")]
     digest: Vec<u8>,
+    /// Digest of the epoch before
+    #[serde(
+        default,
+        with = "optional_hex",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, format = "hex"))]
+    previous_digest: Option<Vec<u8>>,
+    /// Only signed under [`Ciphersuite::ProtobufV2Ed25519`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+    /// Only signed under [`Ciphersuite::ProtobufV2Ed25519`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    digest_algorithm: Option<DigestAlgorithm>,
 }
 
 impl SignatureMessage {
@@ -254,15 +490,37 @@ impl SignatureMessage {
         if !SIGNATURE_VERSIONS.contains(ciphersuite) {
             return Err(PlexiError::BadParameter("version".to_string()));
         }
+        ciphersuite.validate_digest(&digest)?;
         Ok(Self {
             ciphersuite: *ciphersuite,
             namespace,
             timestamp,
             epoch: *epoch,
             digest,
+            previous_digest: None,
+            metadata: BTreeMap::new(),
+            digest_algorithm: None,
         })
     }
 
+    /// Bind the signature to `previous_digest`, the digest of the epoch before
+    pub fn with_previous_digest(mut self, previous_digest: Vec<u8>) -> Self {
+        self.previous_digest = Some(previous_digest);
+        self
+    }
+
+    /// Sign `metadata` along with the epoch
+    pub fn with_metadata(mut self, metadata: BTreeMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Sign the algorithm the digest is computed with
+    pub fn with_digest_algorithm(mut self, digest_algorithm: DigestAlgorithm) -> Self {
+        self.digest_algorithm = Some(digest_algorithm);
+        self
+    }
+
     pub fn ciphersuite(&self) -> &Ciphersuite {
         &self.ciphersuite
     }
@@ -279,14 +537,50 @@ impl SignatureMessage {
         &self.epoch
     }
 
-    pub fn digest(&self) -> Vec<u8> {
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// [`Self::digest`] as an owned vector
+    pub fn digest_owned(&self) -> Vec<u8> {
         self.digest.clone()
     }
 
+    pub fn previous_digest(&self) -> Option<&[u8]> {
+        self.previous_digest.as_deref()
+    }
+
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    pub fn digest_algorithm(&self) -> Option<DigestAlgorithm> {
+        self.digest_algorithm
+    }
+
+    fn without_unsigned_fields(mut self) -> Self {
+        if self.ciphersuite != Ciphersuite::ProtobufV2Ed25519 {
+            self.previous_digest = None;
+            self.metadata.clear();
+            self.digest_algorithm = None;
+        }
+        self
+    }
+
     #[cfg(feature = "bincode")]
     fn to_vec_bincode(&self) -> Result<Vec<u8>, PlexiError> {
-        bincode::encode_to_vec(self, bincode::config::legacy())
-            .map_err(|_e| PlexiError::Serialization)
+        // fields are encoded one after the other, as they were before the message had optional ones
+        bincode::encode_to_vec(
+            (
+                self.ciphersuite,
+                &self.namespace,
+                self.timestamp,
+                self.epoch,
+                &self.digest,
+            ),
+            bincode::config::legacy(),
+        )
+        .map_err(|_e| PlexiError::Serialization)
     }
 
     fn to_vec_proto(&self) -> Result<Vec<u8>, PlexiError> {
@@ -297,17 +591,63 @@ impl SignatureMessage {
             epoch: proto::types::Epoch {
                 inner: self.epoch().into(),
             },
-            digest: self.digest().clone(),
+            digest: self.digest.clone(),
+        };
+
+        Ok(message.encode_to_vec())
+    }
+
+    fn to_vec_proto_v2(&self) -> Result<Vec<u8>, PlexiError> {
+        let message = proto::types::SignatureMessageV2 {
+            version: SIGNATURE_MESSAGE_V2,
+            ciphersuite: (*self.ciphersuite()).into(),
+            namespace: self.namespace().to_string(),
+            timestamp: self.timestamp(),
+            epoch: proto::types::Epoch {
+                inner: self.epoch().into(),
+            },
+            digest: self.digest.clone(),
+            previous_digest: self.previous_digest.clone(),
+            // a BTreeMap iterates in key order
+            metadata: self
+                .metadata
+                .iter()
+                .map(|(key, value)| proto::types::MetadataEntry {
+                    key: key.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+            digest_algorithm: self.digest_algorithm.as_ref().and_then(DigestAlgorithm::id),
         };
 
         Ok(message.encode_to_vec())
     }
 
     pub fn to_vec(&self) -> Result<Vec<u8>, PlexiError> {
+        // ciphersuites before 0x0003 cannot sign the previous digest nor metadata, which would otherwise be left unauthenticated
+        if self.ciphersuite != Ciphersuite::ProtobufV2Ed25519 {
+            if self.previous_digest.is_some() {
+                return Err(PlexiError::BadParameter("previous_digest".to_string()));
+            }
+            if !self.metadata.is_empty() {
+                return Err(PlexiError::BadParameter("metadata".to_string()));
+            }
+            if self.digest_algorithm.is_some() {
+                return Err(PlexiError::BadParameter("digest_algorithm".to_string()));
+            }
+        }
+        self.ciphersuite.validate_digest(&self.digest)?;
+        if let Some(previous_digest) = &self.previous_digest {
+            self.ciphersuite.validate_digest(previous_digest)?;
+        }
+        if let Some(digest_algorithm) = &self.digest_algorithm {
+            digest_algorithm.validate_digest(&self.digest)?;
+        }
         match self.ciphersuite {
             Ciphersuite::ProtobufEd25519 => self.to_vec_proto(),
             #[cfg(feature = "bincode")]
             Ciphersuite::BincodeEd25519 => self.to_vec_bincode(),
+            Ciphersuite::ProtobufV2Ed25519 => self.to_vec_proto_v2(),
             _ => Err(PlexiError::Serialization),
         }
     }
@@ -321,7 +661,11 @@ impl From<SignatureResponse> for SignatureMessage {
             timestamp: val.timestamp,
             epoch: val.epoch,
             digest: val.digest,
+            previous_digest: val.previous_digest,
+            metadata: val.metadata,
+            digest_algorithm: val.digest_algorithm,
         }
+        .without_unsigned_fields()
     }
 }
 
@@ -333,13 +677,17 @@ impl From<&SignatureResponse> for SignatureMessage {
             timestamp: val.timestamp,
             epoch: val.epoch,
             digest: val.digest.clone(),
+            previous_digest: val.previous_digest.clone(),
+            metadata: val.metadata.clone(),
+            digest_algorithm: val.digest_algorithm,
         }
+        .without_unsigned_fields()
     }
 }
 
 impl Display for SignatureMessage {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}/{}", self.epoch, hex::encode(self.digest.clone()))
+        write!(f, "{}/{}", self.epoch, hex::encode(&self.digest))
     }
 }
 
@@ -397,10 +745,18 @@ pub struct SignatureResponse {
     namespace: String,
     timestamp: u64,
     epoch: Epoch,
+    #[cfg_attr(feature = "openapi", schema(value_type = String, format = "hex"))]
     digest: Vec<u8>,
+    #[cfg_attr(feature = "openapi", schema(value_type = String, format = "hex"))]
     signature: Vec<u8>,
     key_id: Option<u8>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, format = "hex"))]
     serialized_message: Option<Vec<u8>>,
+    migration_signature: Option<MigrationSignature>,
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, format = "hex"))]
+    previous_digest: Option<Vec<u8>>,
+    metadata: BTreeMap<String, String>,
+    digest_algorithm: Option<DigestAlgorithm>,
 }
 
 impl fmt::Debug for SignatureResponse {
@@ -415,6 +771,13 @@ impl fmt::Debug for SignatureResponse {
             .field("signature", &hex::encode(&self.signature))
             .field("key_id", &self.key_id)
             .field("serialized_message", &self.serialized_message)
+            .field("migration_signature", &self.migration_signature)
+            .field(
+                "previous_digest",
+                &self.previous_digest.as_ref().map(hex::encode),
+            )
+            .field("metadata", &self.metadata)
+            .field("digest_algorithm", &self.digest_algorithm)
             .finish()
     }
 }
@@ -442,9 +805,69 @@ impl SignatureResponse {
             signature,
             key_id,
             serialized_message,
+            migration_signature: None,
+            previous_digest: None,
+            metadata: BTreeMap::new(),
+            digest_algorithm: None,
         }
     }
 
+    /// Carry the previous digest, metadata, and digest algorithm `message` signs, for verifiers to rebuild it
+    pub fn with_message_fields(mut self, message: &SignatureMessage) -> Self {
+        self.previous_digest = message.previous_digest.clone();
+        self.metadata = message.metadata.clone();
+        self.digest_algorithm = message.digest_algorithm;
+        self
+    }
+
+    /// Attach `migration`, the signature of the same epoch under the ciphersuite the namespace migrates to
+    pub fn with_migration_signature(mut self, migration: &SignatureResponse) -> Self {
+        self.migration_signature = Some(MigrationSignature {
+            ciphersuite: migration.ciphersuite,
+            signature: migration.signature.clone(),
+        });
+        // a migration to the versioned protobuf message may sign fields the current ciphersuite cannot
+        if self.previous_digest.is_none() {
+            self.previous_digest = migration.previous_digest.clone();
+        }
+        if self.metadata.is_empty() {
+            self.metadata = migration.metadata.clone();
+        }
+        if self.digest_algorithm.is_none() {
+            self.digest_algorithm = migration.digest_algorithm;
+        }
+        self
+    }
+
+    /// Signature of the epoch under the ciphersuite the namespace migrates to, if it is migrating
+    pub fn migration_signature(&self) -> Option<SignatureResponse> {
+        let migration = self.migration_signature.as_ref()?;
+        let mut signature = SignatureResponse::new(
+            &migration.ciphersuite,
+            &migration.ciphersuite,
+            self.namespace.clone(),
+            self.timestamp,
+            &self.epoch,
+            self.digest.clone(),
+            migration.signature.clone(),
+            self.key_id,
+            None,
+        );
+        signature.previous_digest = self.previous_digest.clone();
+        signature.metadata = self.metadata.clone();
+        signature.digest_algorithm = self.digest_algorithm;
+        Some(signature)
+    }
+
+    /// Signatures of the epoch, one per ciphersuite it is signed under
+    pub fn signatures(&self) -> Vec<SignatureResponse> {
+        let mut signature = self.clone();
+        signature.migration_signature = None;
+        let mut signatures = vec![signature];
+        signatures.extend(self.migration_signature());
+        signatures
+    }
+
     pub fn version(&self) -> &Ciphersuite {
         &self.version
     }
@@ -463,31 +886,95 @@ impl SignatureResponse {
         &self.epoch
     }
 
-    pub fn digest(&self) -> Vec<u8> {
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// [`Self::digest`] as an owned vector
+    pub fn digest_owned(&self) -> Vec<u8> {
         self.digest.clone()
     }
 
-    pub fn signature(&self) -> [u8; SIGNATURE_LENGTH] {
-        self.signature
-            .as_slice()
-            .try_into()
-            .expect("signature bytes have a known length")
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
     }
 
     pub fn key_id(&self) -> Option<u8> {
         self.key_id
     }
 
-    pub fn serialized_message(&self) -> Option<Vec<u8>> {
+    pub fn serialized_message(&self) -> Option<&[u8]> {
+        self.serialized_message.as_deref()
+    }
+
+    /// [`Self::serialized_message`] as an owned vector
+    pub fn serialized_message_owned(&self) -> Option<Vec<u8>> {
         self.serialized_message.clone()
     }
 
+    /// Digest of the epoch before, signed under [`Ciphersuite::ProtobufV2Ed25519`]
+    pub fn previous_digest(&self) -> Option<&[u8]> {
+        self.previous_digest.as_deref()
+    }
+
+    /// Metadata signed under [`Ciphersuite::ProtobufV2Ed25519`]
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Algorithm the digest is computed with, signed under [`Ciphersuite::ProtobufV2Ed25519`]
+    pub fn digest_algorithm(&self) -> Option<DigestAlgorithm> {
+        self.digest_algorithm
+    }
+
+    /// Check the digest is computed with `expected`, the algorithm of the namespace
+    pub fn verify_digest_algorithm(&self, expected: DigestAlgorithm) -> Result<(), PlexiError> {
+        match self.digest_algorithm {
+            Some(actual) if actual != expected => {
+                Err(PlexiError::DigestAlgorithmMismatch { expected, actual })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the signature, and the migration one if any, are Ed25519 signatures
+    pub fn validate_signature_length(&self) -> Result<(), PlexiError> {
+        let migration = self.migration_signature.as_ref().map(|m| &m.signature);
+        for signature in std::iter::once(&self.signature).chain(migration) {
+            if signature.len() != SIGNATURE_LENGTH {
+                return Err(PlexiError::InvalidSignatureLength {
+                    expected: SIGNATURE_LENGTH,
+                    actual: signature.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify the signature with `verifying_key`
     pub fn verify(&self, verifying_key: &[u8]) -> anyhow::Result<()> {
+        self.verify_with(verifying_key, MigrationPolicy::Either)
+    }
+
+    /// Verify the signature with `verifying_key`, checking the signatures of a ciphersuite migration as `policy` requires
+    pub fn verify_with(&self, verifying_key: &[u8], policy: MigrationPolicy) -> anyhow::Result<()> {
+        let verified = self.verify_signature(verifying_key);
+        let Some(migration) = self.migration_signature() else {
+            return verified;
+        };
+        let migration_verified = migration.verify_signature(verifying_key);
+        match policy {
+            MigrationPolicy::Either => verified.or(migration_verified),
+            MigrationPolicy::Both => verified.and(migration_verified),
+        }
+    }
+
+    fn verify_signature(&self, verifying_key: &[u8]) -> anyhow::Result<()> {
         // at the time of writing, all versions use ed25519 keys. This simplifies parsing of the verifying key.
         match self.version {
             #[cfg(feature = "bincode")]
             Ciphersuite::BincodeEd25519 => (),
-            Ciphersuite::ProtobufEd25519 => (),
+            Ciphersuite::ProtobufEd25519 | Ciphersuite::ProtobufV2Ed25519 => (),
             Ciphersuite::Unknown(_) => {
                 return Err(anyhow!(
                     "Verification is not supported for the given version."
@@ -507,7 +994,7 @@ impl SignatureResponse {
             return Err(anyhow!("Cannot parse the provided verifying_key."));
         };
 
-        let Ok(signature) = ed25519_dalek::Signature::from_slice(&self.signature()) else {
+        let Ok(signature) = ed25519_dalek::Signature::from_slice(self.signature()) else {
             return Err(anyhow!("Cannot construct an Ed25519 signature."));
         };
 
@@ -515,6 +1002,111 @@ impl SignatureResponse {
             .verify_strict(&message, &signature)
             .map_err(Into::into)
     }
+
+    /// Check the timestamp of the epoch is not before the one of `previous`, an earlier epoch, nor more than `max_gap` after it
+    pub fn verify_timestamp_after(
+        &self,
+        previous: &SignatureResponse,
+        max_gap: Option<Duration>,
+    ) -> Result<(), PlexiError> {
+        if self.timestamp < previous.timestamp {
+            return Err(PlexiError::TimestampDecreased {
+                epoch: self.epoch.into(),
+                timestamp: self.timestamp,
+                previous_epoch: previous.epoch.into(),
+                previous_timestamp: previous.timestamp,
+            });
+        }
+        let gap = Duration::from_millis(self.timestamp - previous.timestamp);
+        match max_gap {
+            Some(max_gap) if gap > max_gap => Err(PlexiError::TimestampGapTooLarge {
+                epoch: self.epoch.into(),
+                previous_epoch: previous.epoch.into(),
+                gap: gap.as_secs(),
+                limit: max_gap.as_secs(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the epoch is fresh at `now`: timestamped no later than `now`, and no more than `max_age` before it
+    pub fn verify_fresh(
+        &self,
+        now: u64,
+        max_age: Option<Duration>,
+        clock_skew: Duration,
+    ) -> Result<(), PlexiError> {
+        let skew = clock_skew.as_millis() as u64;
+        if self.timestamp > now.saturating_add(skew) {
+            return Err(PlexiError::TimestampInFuture {
+                epoch: self.epoch.into(),
+                ahead: (self.timestamp - now) / 1000,
+                tolerance: clock_skew.as_secs(),
+            });
+        }
+        let age = Duration::from_millis(now.saturating_sub(self.timestamp));
+        match max_age {
+            Some(max_age) if age > max_age.saturating_add(clock_skew) => {
+                Err(PlexiError::SignatureTooOld {
+                    epoch: self.epoch.into(),
+                    age: age.as_secs(),
+                    limit: max_age.as_secs(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check the timestamps of `signatures`, ordered by epoch, do not decrease nor jump by more than `max_gap`
+pub fn verify_timestamps(
+    signatures: &[SignatureResponse],
+    max_gap: Option<Duration>,
+) -> Result<(), PlexiError> {
+    signatures
+        .windows(2)
+        .try_for_each(|pair| pair[1].verify_timestamp_after(&pair[0], max_gap))
+}
+
+/// Signature of an epoch under the ciphersuite its namespace migrates to, carried by the [`SignatureResponse`] under the current one
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct MigrationSignature {
+    ciphersuite: Ciphersuite,
+    #[serde(with = "hex::serde")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String, format = "hex"))]
+    signature: Vec<u8>,
+}
+
+/// Signatures [`SignatureResponse::verify_with`] requires on epochs signed during a ciphersuite migration
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MigrationPolicy {
+    /// Either signature
+    #[default]
+    Either,
+    /// Both signatures
+    Both,
+}
+
+impl FromStr for MigrationPolicy {
+    type Err = PlexiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "either" => Ok(Self::Either),
+            "both" => Ok(Self::Both),
+            _ => Err(PlexiError::BadParameter("migration policy".to_string())),
+        }
+    }
+}
+
+impl fmt::Display for MigrationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Either => write!(f, "either"),
+            Self::Both => write!(f, "both"),
+        }
+    }
 }
 
 // A report request is a signature reponse, except the signature does not come from the auditor (thought to be offline) but from the log provider
@@ -542,6 +1134,18 @@ impl From<Report> for HashMap<String, String> {
                 hex::encode(serialized_message),
             );
         }
+        if let Some(previous_digest) = val.previous_digest {
+            map.insert("previous_digest".to_string(), hex::encode(previous_digest));
+        }
+        if !val.metadata.is_empty() {
+            map.insert(
+                "metadata".to_string(),
+                serde_json::to_string(&val.metadata).expect("a string map serializes to JSON"),
+            );
+        }
+        if let Some(digest_algorithm) = val.digest_algorithm {
+            map.insert("digest_algorithm".to_string(), digest_algorithm.to_string());
+        }
         map
     }
 }
@@ -596,10 +1200,49 @@ impl TryFrom<HashMap<String, String>> for Report {
                 .map(hex::decode)
                 .transpose()
                 .map_err(|_| PlexiError::BadParameter("serialized_message".to_string()))?,
+            migration_signature: None,
+            previous_digest: value
+                .get("previous_digest")
+                .map(hex::decode)
+                .transpose()
+                .map_err(|_| PlexiError::BadParameter("previous_digest".to_string()))?,
+            metadata: value
+                .get("metadata")
+                .map(|metadata| serde_json::from_str(metadata))
+                .transpose()
+                .map_err(|_| PlexiError::BadParameter("metadata".to_string()))?
+                .unwrap_or_default(),
+            digest_algorithm: value
+                .get("digest_algorithm")
+                .map(|digest_algorithm| digest_algorithm.parse())
+                .transpose()?,
         })
     }
 }
 
+mod optional_hex {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(bytes: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match bytes {
+            Some(bytes) => serializer.serialize_some(&hex::encode(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|bytes| hex::decode(bytes).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 struct TempSignatureResponse {
     version: Option<Ciphersuite>,
@@ -614,6 +1257,18 @@ struct TempSignatureResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     key_id: Option<u8>,
     serialized_message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    migration_signature: Option<MigrationSignature>,
+    #[serde(
+        default,
+        with = "optional_hex",
+        skip_serializing_if = "Option::is_none"
+    )]
+    previous_digest: Option<Vec<u8>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    digest_algorithm: Option<DigestAlgorithm>,
 }
 
 impl Serialize for SignatureResponse {
@@ -632,6 +1287,10 @@ impl Serialize for SignatureResponse {
             signature: self.signature.clone(),
             key_id: self.key_id,
             serialized_message: sm,
+            migration_signature: self.migration_signature.clone(),
+            previous_digest: self.previous_digest.clone(),
+            metadata: self.metadata.clone(),
+            digest_algorithm: self.digest_algorithm,
         };
         tsp.serialize(serializer)
     }
@@ -666,7 +1325,15 @@ where
         .map(hex::decode)
         .transpose()
         .map_err(|_| de::Error::custom("serialized_message should be hex encoded"))?;
-    Ok(SignatureResponse {
+    suite_value
+        .validate_digest(&temp.digest)
+        .map_err(de::Error::custom)?;
+    if let Some(previous_digest) = &temp.previous_digest {
+        suite_value
+            .validate_digest(previous_digest)
+            .map_err(de::Error::custom)?;
+    }
+    let response = SignatureResponse {
         version: suite_value,
         ciphersuite: suite_value,
         namespace: temp.namespace,
@@ -676,12 +1343,22 @@ where
         signature: temp.signature,
         key_id: temp.key_id,
         serialized_message: sm,
-    })
+        migration_signature: temp.migration_signature,
+        previous_digest: temp.previous_digest,
+        metadata: temp.metadata,
+        digest_algorithm: temp.digest_algorithm,
+    };
+    response
+        .validate_signature_length()
+        .map_err(de::Error::custom)?;
+    Ok(response)
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
 pub struct ReportResponse {
     id: Uuid,
+    #[cfg_attr(feature = "openapi", schema(value_type = SignatureResponse))]
     report: Report,
 }
 
@@ -705,10 +1382,22 @@ pub struct LastVerifiedEpoch {
     job_id: Uuid,
     epoch: Epoch,
     #[serde(with = "hex::serde")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String, format = "hex"))]
     start_hash: Vec<u8>,
     #[serde(with = "hex::serde")]
+    #[cfg_attr(feature = "openapi", schema(value_type = String, format = "hex"))]
     end_hash: Vec<u8>,
     timestamp: u64,
+    /// Signature of the auditor over [`LastVerifiedEpoch::signed_message`]
+    #[serde(
+        default,
+        with = "optional_hex",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, format = "hex"))]
+    signature: Option<Vec<u8>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_id: Option<u8>,
 }
 
 impl fmt::Debug for LastVerifiedEpoch {
@@ -719,6 +1408,8 @@ impl fmt::Debug for LastVerifiedEpoch {
             .field("start_hash", &hex::encode(&self.start_hash))
             .field("end_hash", &hex::encode(&self.end_hash))
             .field("timestamp", &self.timestamp)
+            .field("signature", &self.signature.as_ref().map(hex::encode))
+            .field("key_id", &self.key_id)
             .finish()
     }
 }
@@ -737,9 +1428,17 @@ impl LastVerifiedEpoch {
             start_hash,
             end_hash,
             timestamp,
+            signature: None,
+            key_id: None,
         }
     }
 
+    pub fn with_signature(mut self, key_id: u8, signature: &[u8]) -> Self {
+        self.key_id = Some(key_id);
+        self.signature = Some(signature.to_vec());
+        self
+    }
+
     pub fn job_id(&self) -> Uuid {
         self.job_id
     }
@@ -759,6 +1458,46 @@ impl LastVerifiedEpoch {
     pub fn timestamp(&self) -> u64 {
         self.timestamp
     }
+
+    pub fn signature(&self) -> Option<&[u8]> {
+        self.signature.as_deref()
+    }
+
+    pub fn key_id(&self) -> Option<u8> {
+        self.key_id
+    }
+
+    /// Message the auditor signs, binding the epoch and its hashes to `namespace`
+    pub fn signed_message(&self, namespace: &str) -> Vec<u8> {
+        let length_prefixed = |bytes: &[u8]| [&(bytes.len() as u64).to_be_bytes(), bytes].concat();
+        [
+            LAST_VERIFIED_EPOCH_CONTEXT,
+            &length_prefixed(namespace.as_bytes()),
+            &u64::from(&self.epoch).to_be_bytes(),
+            &length_prefixed(&self.start_hash),
+            &length_prefixed(&self.end_hash),
+            &self.timestamp.to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Verify the signature of the auditor for `namespace` with `verifying_key`
+    pub fn verify(&self, namespace: &str, verifying_key: &[u8]) -> anyhow::Result<()> {
+        let Some(signature) = &self.signature else {
+            return Err(anyhow!("last verified epoch is not signed"));
+        };
+        let verifying_key = verifying_key.try_into().map_err(|_| {
+            anyhow!(
+                "verifying_key should have length {length}",
+                length = ed25519_dalek::PUBLIC_KEY_LENGTH
+            )
+        })?;
+        let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&verifying_key)?;
+        let signature = ed25519_dalek::Signature::from_slice(signature)?;
+        verifying_key
+            .verify_strict(&self.signed_message(namespace), &signature)
+            .map_err(|_| anyhow!("last verified epoch signature does not verify"))
+    }
 }
 
 #[cfg(test)]
@@ -824,16 +1563,340 @@ mod tests {
             namespace: "n".to_string(),
             timestamp: 2,
             epoch: Epoch(3),
-            digest: vec![4],
-            signature: vec![5],
+            digest: vec![4; DIGEST_LENGTH],
+            signature: vec![5; SIGNATURE_LENGTH],
             key_id: Some(6),
             serialized_message: Some(vec![7]),
+            migration_signature: None,
+            previous_digest: None,
+            metadata: BTreeMap::new(),
+            digest_algorithm: None,
+        };
+        let test_json = |signature: &str| {
+            format!(
+                r#"{{"version":1,"ciphersuite":1,"namespace":"n","timestamp":2,"epoch":3,"digest":"0404040404040404040404040404040404040404040404040404040404040404","signature":"{signature}","key_id":6,"serialized_message":"07"}}"#
+            )
         };
-        let test_json = r#"{"version":1,"ciphersuite":1,"namespace":"n","timestamp":2,"epoch":3,"digest":"04","signature":"05","key_id":6,"serialized_message":"07"}"#;
         let serialized = serde_json::to_string(&test_response).unwrap();
-        assert_eq!(serialized, test_json.to_string());
-        let deserialized: Result<SignatureResponse, _> = serde_json::from_str(test_json);
+        assert_eq!(serialized, test_json(&"05".repeat(SIGNATURE_LENGTH)));
+        let deserialized: Result<SignatureResponse, _> =
+            serde_json::from_str(&test_json(&"05".repeat(SIGNATURE_LENGTH)));
         assert!(deserialized.is_ok());
         assert_eq!(deserialized.unwrap(), test_response);
+        // signatures are Ed25519 ones
+        assert!(serde_json::from_str::<SignatureResponse>(&test_json("05")).is_err());
+    }
+
+    #[test]
+    fn test_digest_length() {
+        let message = SignatureMessage::new(
+            &Ciphersuite::ProtobufEd25519,
+            "n".to_string(),
+            2,
+            &Epoch(3),
+            vec![4; 31],
+        );
+        assert!(matches!(
+            message,
+            Err(PlexiError::InvalidDigestLength {
+                expected: DIGEST_LENGTH,
+                actual: 31,
+                ..
+            })
+        ));
+
+        let test_json = r#"{"version":1,"ciphersuite":1,"namespace":"n","timestamp":2,"epoch":3,"digest":"04","signature":"05"}"#;
+        assert!(serde_json::from_str::<SignatureResponse>(test_json).is_err());
+    }
+
+    #[test]
+    fn test_migration_signature() {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[0xd6; SECRET_KEY_LENGTH]);
+        let verifying_key = signing_key.verifying_key().to_bytes();
+        let sign = |ciphersuite: &Ciphersuite| {
+            let message = SignatureMessage::new(
+                ciphersuite,
+                "example.key-transparency.v1".to_string(),
+                1717084639921,
+                &Epoch(1),
+                vec![0xaa; 32],
+            )
+            .unwrap();
+            let signature =
+                ed25519_dalek::Signer::sign(&signing_key, &message.to_vec().unwrap()).to_vec();
+            SignatureResponse::new(
+                ciphersuite,
+                ciphersuite,
+                message.namespace().to_string(),
+                message.timestamp(),
+                message.epoch(),
+                message.digest_owned(),
+                signature,
+                None,
+                None,
+            )
+        };
+        let signature = sign(&Ciphersuite::ProtobufEd25519)
+            .with_migration_signature(&sign(&Ciphersuite::BincodeEd25519));
+        let signatures = signature.signatures();
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(*signatures[1].ciphersuite(), Ciphersuite::BincodeEd25519);
+        signatures[1].verify(&verifying_key).unwrap();
+        signature
+            .verify_with(&verifying_key, MigrationPolicy::Both)
+            .unwrap();
+
+        let deserialized: SignatureResponse =
+            serde_json::from_str(&serde_json::to_string(&signature).unwrap()).unwrap();
+        assert_eq!(deserialized, signature);
+
+        // a verifier accepting either signature does not depend on the new ciphersuite
+        let invalid = SignatureResponse::new(
+            &Ciphersuite::BincodeEd25519,
+            &Ciphersuite::BincodeEd25519,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch(1),
+            vec![0xaa; 32],
+            vec![0; SIGNATURE_LENGTH],
+            None,
+            None,
+        );
+        let forged = sign(&Ciphersuite::ProtobufEd25519).with_migration_signature(&invalid);
+        forged.verify(&verifying_key).unwrap();
+        assert!(forged
+            .verify_with(&verifying_key, MigrationPolicy::Both)
+            .is_err());
+        assert_eq!(
+            "Both".parse::<MigrationPolicy>().unwrap(),
+            MigrationPolicy::Both
+        );
+    }
+
+    #[test]
+    fn test_signature_message_v2() {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[0xd6; SECRET_KEY_LENGTH]);
+        let verifying_key = signing_key.verifying_key().to_bytes();
+        let metadata = BTreeMap::from([
+            ("b".to_string(), "2".to_string()),
+            ("a".to_string(), "1".to_string()),
+        ]);
+        let message = SignatureMessage::new(
+            &Ciphersuite::ProtobufV2Ed25519,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch(2),
+            vec![0xaa; 32],
+        )
+        .unwrap()
+        .with_previous_digest(vec![0xbb; 32])
+        .with_metadata(metadata)
+        .with_digest_algorithm(DigestAlgorithm::Blake3);
+
+        let encoded = message.to_vec().unwrap();
+        let decoded = proto::types::SignatureMessageV2::decode(encoded.as_slice()).unwrap();
+        assert_eq!(decoded.version, SIGNATURE_MESSAGE_V2);
+        assert_eq!(decoded.previous_digest, Some(vec![0xbb; 32]));
+        assert_eq!(decoded.digest_algorithm, DigestAlgorithm::Blake3.id());
+        let keys: Vec<_> = decoded
+            .metadata
+            .iter()
+            .map(|entry| entry.key.as_str())
+            .collect();
+        assert_eq!(keys, ["a", "b"]);
+
+        #[cfg(feature = "bincode")]
+        {
+            let (stored, _): (SignatureMessage, _) = bincode::decode_from_slice(
+                &bincode::encode_to_vec(&message, bincode::config::standard()).unwrap(),
+                bincode::config::standard(),
+            )
+            .unwrap();
+            assert_eq!(stored.to_vec().unwrap(), encoded);
+        }
+
+        let signature = SignatureResponse::new(
+            message.ciphersuite(),
+            message.ciphersuite(),
+            message.namespace().to_string(),
+            message.timestamp(),
+            message.epoch(),
+            message.digest_owned(),
+            ed25519_dalek::Signer::sign(&signing_key, &encoded).to_vec(),
+            None,
+            None,
+        )
+        .with_message_fields(&message);
+        let deserialized: SignatureResponse =
+            serde_json::from_str(&serde_json::to_string(&signature).unwrap()).unwrap();
+        assert_eq!(deserialized, signature);
+        deserialized.verify(&verifying_key).unwrap();
+        let stored = SignatureResponse::try_from(HashMap::from(signature.clone())).unwrap();
+        assert_eq!(stored, signature);
+
+        // the previous digest is signed
+        let mut tampered = signature.clone();
+        tampered.previous_digest = Some(vec![0xcc; 32]);
+        assert!(tampered.verify(&verifying_key).is_err());
+
+        // so is the digest algorithm, which a namespace can require
+        let mut tampered = signature.clone();
+        tampered.digest_algorithm = Some(DigestAlgorithm::Sha256);
+        assert!(tampered.verify(&verifying_key).is_err());
+        signature
+            .verify_digest_algorithm(DigestAlgorithm::Blake3)
+            .unwrap();
+        assert!(matches!(
+            signature.verify_digest_algorithm(DigestAlgorithm::Sha256),
+            Err(PlexiError::DigestAlgorithmMismatch {
+                expected: DigestAlgorithm::Sha256,
+                actual: DigestAlgorithm::Blake3,
+            })
+        ));
+        let mut unknown = signature.clone();
+        unknown.digest_algorithm = Some(DigestAlgorithm::Unknown);
+        assert!(unknown.verify(&verifying_key).is_err());
+
+        // earlier ciphersuites cannot sign it
+        let unsigned = SignatureMessage::new(
+            &Ciphersuite::ProtobufEd25519,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch(2),
+            vec![0xaa; 32],
+        )
+        .unwrap()
+        .with_previous_digest(vec![0xbb; 32]);
+        assert!(unsigned.to_vec().is_err());
+    }
+
+    #[test]
+    fn test_verify_timestamps() {
+        let signature = |epoch: u64, timestamp: u64| {
+            SignatureResponse::new(
+                &Ciphersuite::ProtobufEd25519,
+                &Ciphersuite::ProtobufEd25519,
+                "example.key-transparency.v1".to_string(),
+                timestamp,
+                &Epoch(epoch),
+                vec![0xaa; 32],
+                vec![0xbb; 64],
+                None,
+                None,
+            )
+        };
+        let signatures = vec![
+            signature(1, 1717084639921),
+            signature(2, 1717084639921),
+            signature(3, 1717084699921),
+        ];
+        assert!(verify_timestamps(&signatures, None).is_ok());
+        assert!(verify_timestamps(&signatures, Some(Duration::from_secs(60))).is_ok());
+        assert!(matches!(
+            verify_timestamps(&signatures, Some(Duration::from_secs(30))),
+            Err(PlexiError::TimestampGapTooLarge {
+                epoch: 3,
+                gap: 60,
+                ..
+            })
+        ));
+
+        let rewritten = signature(4, 1717084639920);
+        assert!(matches!(
+            rewritten.verify_timestamp_after(&signatures[2], None),
+            Err(PlexiError::TimestampDecreased {
+                epoch: 4,
+                previous_epoch: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_verify_fresh() {
+        let now = 1717084639921;
+        let signature = |timestamp: u64| {
+            SignatureResponse::new(
+                &Ciphersuite::ProtobufEd25519,
+                &Ciphersuite::ProtobufEd25519,
+                "example.key-transparency.v1".to_string(),
+                timestamp,
+                &Epoch(2),
+                vec![0xaa; 32],
+                vec![0xbb; 64],
+                None,
+                None,
+            )
+        };
+        let max_age = Some(Duration::from_secs(3600));
+
+        assert!(signature(now - 1_000)
+            .verify_fresh(now, max_age, Duration::ZERO)
+            .is_ok());
+        assert!(matches!(
+            signature(now + 30_000).verify_fresh(now, max_age, Duration::ZERO),
+            Err(PlexiError::TimestampInFuture {
+                ahead: 30,
+                tolerance: 0,
+                ..
+            })
+        ));
+        assert!(signature(now + 30_000)
+            .verify_fresh(now, max_age, DEFAULT_CLOCK_SKEW)
+            .is_ok());
+        assert!(matches!(
+            signature(now - 3_630_000).verify_fresh(now, max_age, Duration::ZERO),
+            Err(PlexiError::SignatureTooOld {
+                age: 3630,
+                limit: 3600,
+                ..
+            })
+        ));
+        assert!(signature(now - 3_630_000)
+            .verify_fresh(now, max_age, DEFAULT_CLOCK_SKEW)
+            .is_ok());
+        assert!(signature(now - 3_630_000)
+            .verify_fresh(now, None, Duration::ZERO)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_last_verified_epoch_signature() {
+        const NAMESPACE: &str = "example.key-transparency.v1";
+        let mut signing_key = ed25519_dalek::SigningKey::from_bytes(&[0xd6; SECRET_KEY_LENGTH]);
+        let verifying_key = signing_key.verifying_key().to_bytes();
+        let last_verified_epoch = LastVerifiedEpoch::new(
+            Uuid::new_v4(),
+            Epoch::from(2),
+            vec![0xaa; DIGEST_LENGTH],
+            vec![0xbb; DIGEST_LENGTH],
+            1717084639921,
+        );
+        assert!(last_verified_epoch
+            .verify(NAMESPACE, &verifying_key)
+            .is_err());
+
+        let signature = signing_key.sign(&last_verified_epoch.signed_message(NAMESPACE));
+        let signed = last_verified_epoch.with_signature(
+            ed25519_public_key_to_key_id(&verifying_key),
+            &signature.to_bytes(),
+        );
+        let signed: LastVerifiedEpoch =
+            serde_json::from_str(&serde_json::to_string(&signed).unwrap()).unwrap();
+        assert!(signed.verify(NAMESPACE, &verifying_key).is_ok());
+        assert!(signed
+            .verify("other.key-transparency.v1", &verifying_key)
+            .is_err());
+
+        // the signature covers the hashes
+        let tampered = LastVerifiedEpoch::new(
+            signed.job_id(),
+            signed.epoch(),
+            vec![0xbb; DIGEST_LENGTH],
+            signed.end_hash().to_vec(),
+            signed.timestamp(),
+        )
+        .with_signature(signed.key_id().unwrap(), signed.signature().unwrap());
+        assert!(tampered.verify(NAMESPACE, &verifying_key).is_err());
     }
 }
@@ -1,11 +1,12 @@
 use core::fmt;
+use std::{str::FromStr, time::Duration};
 
 use serde::{de, Deserializer};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "openapi")]
 use utoipa::ToSchema;
 
-use crate::{Ciphersuite, Epoch, PlexiError};
+use crate::{auditor::AkdConfig, Ciphersuite, DigestAlgorithm, Epoch, PlexiError};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
@@ -25,6 +26,85 @@ impl Namespaces {
     pub fn iter(&self) -> impl Iterator<Item = &NamespaceInfo> {
         self.namespaces.iter()
     }
+
+    /// Namespaces matching `filter`
+    pub fn filter(self, filter: &NamespaceFilter) -> Self {
+        Self {
+            namespaces: self
+                .namespaces
+                .into_iter()
+                .filter(|info| filter.matches(info))
+                .collect(),
+        }
+    }
+
+    /// Namespace named `name`, or with `name` among its aliases
+    pub fn find(&self, name: &str) -> Option<&NamespaceInfo> {
+        self.namespaces
+            .iter()
+            .find(|info| info.name() == name)
+            .or_else(|| self.namespaces.iter().find(|info| info.is_named(name)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.namespaces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.namespaces.is_empty()
+    }
+}
+
+/// Criteria to list namespaces by
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamespaceFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<NamespaceStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ciphersuite: Option<Ciphersuite>,
+}
+
+impl NamespaceFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(mut self, status: NamespaceStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Only match namespaces whose name starts with `prefix`
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn ciphersuite(mut self, ciphersuite: Ciphersuite) -> Self {
+        self.ciphersuite = Some(ciphersuite);
+        self
+    }
+
+    pub fn matches(&self, info: &NamespaceInfo) -> bool {
+        if let Some(status) = &self.status {
+            if *status != info.status {
+                return false;
+            }
+        }
+        if let Some(prefix) = &self.prefix {
+            if !info.name.starts_with(prefix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(ciphersuite) = self.ciphersuite {
+            if ciphersuite != info.ciphersuite {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 impl Default for Namespaces {
@@ -42,6 +122,127 @@ impl IntoIterator for Namespaces {
     }
 }
 
+/// Who operates a namespace, and under which policy
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct NamespaceMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    /// Contact of the log operator, such as an email address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    contact: Option<String>,
+    /// URL of the policy the log operates under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    policy_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cadence: Option<CadencePolicy>,
+}
+
+impl NamespaceMetadata {
+    pub fn new(
+        description: Option<String>,
+        contact: Option<String>,
+        policy_url: Option<String>,
+    ) -> Self {
+        Self {
+            description,
+            contact,
+            policy_url,
+            cadence: None,
+        }
+    }
+
+    pub fn with_cadence(mut self, cadence: CadencePolicy) -> Self {
+        self.cadence = Some(cadence);
+        self
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn contact(&self) -> Option<&str> {
+        self.contact.as_deref()
+    }
+
+    pub fn policy_url(&self) -> Option<&str> {
+        self.policy_url.as_deref()
+    }
+
+    /// How often the namespace commits to publish epochs
+    pub fn cadence(&self) -> Option<CadencePolicy> {
+        self.cadence
+    }
+}
+
+/// Epoch cadence a namespace commits to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct CadencePolicy {
+    /// Expected seconds between two epochs
+    epoch_interval: u64,
+    /// Maximum merge delay: seconds an epoch may be published past its expected time
+    max_merge_delay: u64,
+}
+
+impl CadencePolicy {
+    pub fn new(epoch_interval: Duration, max_merge_delay: Duration) -> Self {
+        Self {
+            epoch_interval: epoch_interval.as_secs(),
+            max_merge_delay: max_merge_delay.as_secs(),
+        }
+    }
+
+    pub fn epoch_interval(&self) -> Duration {
+        Duration::from_secs(self.epoch_interval)
+    }
+
+    pub fn max_merge_delay(&self) -> Duration {
+        Duration::from_secs(self.max_merge_delay)
+    }
+
+    /// Time by which the epoch following one at `timestamp` is due
+    pub fn deadline(&self, timestamp: u64) -> u64 {
+        timestamp.saturating_add(
+            self.epoch_interval
+                .saturating_add(self.max_merge_delay)
+                .saturating_mul(1000),
+        )
+    }
+
+    /// Check the namespace is not behind at `now`, its latest epoch being at `timestamp`
+    pub fn verify(&self, timestamp: u64, now: u64) -> Result<(), PlexiError> {
+        self.verify_with_skew(timestamp, now, Duration::ZERO)
+    }
+
+    /// Same as [`Self::verify`], tolerating the clock `now` is read from to be up to `clock_skew` ahead of the auditor one
+    pub fn verify_with_skew(
+        &self,
+        timestamp: u64,
+        now: u64,
+        clock_skew: Duration,
+    ) -> Result<(), PlexiError> {
+        let deadline = self.deadline(timestamp);
+        if now > deadline.saturating_add(clock_skew.as_millis() as u64) {
+            return Err(PlexiError::BehindCadence {
+                late: (now - deadline) / 1000,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CadencePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "every {interval}s, {delay}s maximum merge delay",
+            interval = self.epoch_interval,
+            delay = self.max_merge_delay
+        )
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
 pub struct Namespace {
@@ -50,6 +251,18 @@ pub struct Namespace {
     root: Option<String>,
     signature_version: Option<Ciphersuite>,
     ciphersuite: Option<Ciphersuite>,
+    akd_config: Option<AkdConfig>,
+    /// Hash function of the log, signed under [`Ciphersuite::ProtobufV2Ed25519`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    digest_algorithm: Option<DigestAlgorithm>,
+    /// Ciphersuite the namespace migrates to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_ciphersuite: Option<Ciphersuite>,
+    /// Other names the namespace is known by, such as a short one for users
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(flatten)]
+    metadata: NamespaceMetadata,
 }
 
 impl Namespace {
@@ -69,6 +282,31 @@ impl Namespace {
             root,
             signature_version: Some(suite),
             ciphersuite: Some(suite),
+            akd_config: None,
+            digest_algorithm: None,
+            next_ciphersuite: None,
+            aliases: vec![],
+            metadata: NamespaceMetadata::default(),
+        }
+    }
+
+    /// Check a deserialized namespace has a ciphersuite, which [`Self::new`] guarantees, and migrates to another one if any
+    pub fn validate(&self) -> Result<(), PlexiError> {
+        let Some(ciphersuite) = self.ciphersuite.or(self.signature_version) else {
+            return Err(PlexiError::MissingParameter("ciphersuite".to_string()));
+        };
+        if self.next_ciphersuite == Some(ciphersuite) {
+            return Err(PlexiError::BadParameter("next_ciphersuite".to_string()));
+        }
+        // the AKD configuration fixes the hash function, when plexi knows it
+        match (self.digest_algorithm, self.akd_config().digest_algorithm()) {
+            (Some(DigestAlgorithm::Unknown), _) => {
+                Err(PlexiError::BadParameter("digest_algorithm".to_string()))
+            }
+            (Some(declared), Some(expected)) if declared != expected => {
+                Err(PlexiError::BadParameter("digest_algorithm".to_string()))
+            }
+            _ => Ok(()),
         }
     }
 
@@ -91,6 +329,48 @@ impl Namespace {
     pub fn ciphersuite(&self) -> &Ciphersuite {
         self.ciphersuite.as_ref().unwrap()
     }
+
+    pub fn akd_config(&self) -> AkdConfig {
+        self.akd_config.unwrap_or_default()
+    }
+
+    pub fn set_akd_config(&mut self, akd_config: AkdConfig) {
+        self.akd_config = Some(akd_config);
+    }
+
+    /// Hash function declared for the log, which epoch signatures sign
+    pub fn digest_algorithm(&self) -> Option<DigestAlgorithm> {
+        self.digest_algorithm
+    }
+
+    pub fn set_digest_algorithm(&mut self, digest_algorithm: Option<DigestAlgorithm>) {
+        self.digest_algorithm = digest_algorithm;
+    }
+
+    pub fn metadata(&self) -> &NamespaceMetadata {
+        &self.metadata
+    }
+
+    pub fn set_metadata(&mut self, metadata: NamespaceMetadata) {
+        self.metadata = metadata;
+    }
+
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub fn set_aliases(&mut self, aliases: Vec<String>) {
+        self.aliases = aliases;
+    }
+
+    pub fn next_ciphersuite(&self) -> Option<Ciphersuite> {
+        self.next_ciphersuite
+    }
+
+    /// Start migrating to `next_ciphersuite`, or stop if `None`
+    pub fn set_next_ciphersuite(&mut self, next_ciphersuite: Option<Ciphersuite>) {
+        self.next_ciphersuite = next_ciphersuite;
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -99,6 +379,9 @@ pub struct NamespaceInfo {
     name: String,
     log_directory: Option<String>,
     root: Option<String>,
+    /// Roots the namespace had before `root`, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    root_history: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     last_verified_epoch: Option<Epoch>,
     status: NamespaceStatus,
@@ -106,6 +389,15 @@ pub struct NamespaceInfo {
     audits_uri: String,
     signature_version: Ciphersuite,
     ciphersuite: Ciphersuite,
+    akd_config: AkdConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    digest_algorithm: Option<DigestAlgorithm>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_ciphersuite: Option<Ciphersuite>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(flatten)]
+    metadata: NamespaceMetadata,
 }
 
 impl NamespaceInfo {
@@ -117,12 +409,18 @@ impl NamespaceInfo {
             name: namespace.name().to_string(),
             log_directory: namespace.log_directory().map(str::to_string),
             root: namespace.root().map(str::to_string),
+            root_history: vec![],
             last_verified_epoch: None,
             status: status.clone(),
             reports_uri: format!("/namespaces/{}/reports", namespace.name()),
             audits_uri: format!("/namespaces/{}/audits", namespace.name()),
             signature_version: signature_version.unwrap(),
             ciphersuite: ciphersuite.unwrap(),
+            akd_config: namespace.akd_config(),
+            digest_algorithm: namespace.digest_algorithm(),
+            next_ciphersuite: namespace.next_ciphersuite(),
+            aliases: namespace.aliases().to_vec(),
+            metadata: namespace.metadata().clone(),
         }
     }
 
@@ -138,16 +436,49 @@ impl NamespaceInfo {
         self.root.as_deref()
     }
 
+    /// Set the root of the namespace, such as after the log is rebuilt
     pub fn set_root(&mut self, root: &str) {
-        self.root = Some(root.to_string());
+        if let Some(previous) = self.root.replace(root.to_string()) {
+            if previous != root {
+                self.root_history.push(previous);
+            }
+        }
+    }
+
+    /// Roots the namespace had before [`Self::root`], oldest first
+    pub fn root_history(&self) -> &[String] {
+        &self.root_history
+    }
+
+    /// Root `epoch` descends from: the latest root at or before `epoch`
+    pub fn root_for_epoch(&self, epoch: &Epoch) -> Option<&str> {
+        self.root_history
+            .iter()
+            .chain(&self.root)
+            .filter_map(|root| Some((root_epoch(root)?, root.as_str())))
+            .filter(|(root_epoch, _)| root_epoch <= epoch)
+            .max_by_key(|(root_epoch, _)| u64::from(root_epoch))
+            .map(|(_, root)| root)
+    }
+
+    /// Take the log directory, AKD configuration, digest algorithm, next ciphersuite, aliases, and metadata of `namespace`
+    pub fn update_from(&mut self, namespace: &Namespace) {
+        self.log_directory = namespace.log_directory().map(str::to_string);
+        self.akd_config = namespace.akd_config();
+        self.digest_algorithm = namespace.digest_algorithm();
+        self.next_ciphersuite = namespace.next_ciphersuite();
+        self.aliases = namespace.aliases().to_vec();
+        self.metadata = namespace.metadata().clone();
     }
 
     pub fn status(&self) -> NamespaceStatus {
         self.status.clone()
     }
 
-    pub fn set_status(&mut self, status: NamespaceStatus) {
-        self.status = status;
+    /// Move the namespace to `status`, if [`NamespaceStatus::transition_to`] allows it
+    pub fn set_status(&mut self, status: NamespaceStatus) -> Result<(), PlexiError> {
+        self.status = self.status.transition_to(status)?;
+        Ok(())
     }
 
     pub fn reports_uri(&self) -> &str {
@@ -166,6 +497,67 @@ impl NamespaceInfo {
         self.ciphersuite
     }
 
+    pub fn akd_config(&self) -> AkdConfig {
+        self.akd_config
+    }
+
+    /// Hash function of the log: the one declared for the namespace, or else the one of its AKD configuration
+    pub fn digest_algorithm(&self) -> Option<DigestAlgorithm> {
+        self.digest_algorithm
+            .or_else(|| self.akd_config.digest_algorithm())
+    }
+
+    /// Hash function declared for the namespace, which epoch signatures sign under [`Ciphersuite::ProtobufV2Ed25519`]
+    pub fn declared_digest_algorithm(&self) -> Option<DigestAlgorithm> {
+        self.digest_algorithm
+    }
+
+    /// Ciphersuite the namespace migrates to
+    pub fn next_ciphersuite(&self) -> Option<Ciphersuite> {
+        self.next_ciphersuite
+    }
+
+    pub fn set_next_ciphersuite(&mut self, next_ciphersuite: Option<Ciphersuite>) {
+        self.next_ciphersuite = next_ciphersuite;
+    }
+
+    /// Whether reports and signatures may use `ciphersuite`
+    pub fn accepts_ciphersuite(&self, ciphersuite: &Ciphersuite) -> bool {
+        *ciphersuite == self.ciphersuite || Some(*ciphersuite) == self.next_ciphersuite
+    }
+
+    /// Complete the migration to [`Self::next_ciphersuite`], which epochs are then only signed under
+    pub fn complete_migration(&mut self) -> Result<(), PlexiError> {
+        let ciphersuite = self
+            .next_ciphersuite
+            .take()
+            .ok_or_else(|| PlexiError::MissingParameter("next_ciphersuite".to_string()))?;
+        self.ciphersuite = ciphersuite;
+        self.signature_version = ciphersuite;
+        Ok(())
+    }
+
+    pub fn metadata(&self) -> &NamespaceMetadata {
+        &self.metadata
+    }
+
+    pub fn set_metadata(&mut self, metadata: NamespaceMetadata) {
+        self.metadata = metadata;
+    }
+
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub fn set_aliases(&mut self, aliases: Vec<String>) {
+        self.aliases = aliases;
+    }
+
+    /// Whether `name` is the name of the namespace or one of its aliases
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|alias| alias == name)
+    }
+
     pub fn to_string(&self) -> Result<String, PlexiError> {
         serde_json::to_string(self).map_err(|_| PlexiError::Serialization)
     }
@@ -190,6 +582,11 @@ impl NamespaceInfo {
     }
 }
 
+/// Epoch of a root, formatted as `epoch/digest`
+pub fn root_epoch(root: &str) -> Option<Epoch> {
+    root.split_once('/')?.0.parse().ok()
+}
+
 impl<'de> Deserialize<'de> for NamespaceInfo {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -208,12 +605,23 @@ where
         name: String,
         log_directory: Option<String>,
         root: Option<String>,
+        #[serde(default)]
+        root_history: Vec<String>,
         last_verified_epoch: Option<Epoch>,
         status: NamespaceStatus,
         reports_uri: String,
         audits_uri: String,
         signature_version: Option<Ciphersuite>,
         ciphersuite: Option<Ciphersuite>,
+        akd_config: Option<AkdConfig>,
+        #[serde(default)]
+        digest_algorithm: Option<DigestAlgorithm>,
+        #[serde(default)]
+        next_ciphersuite: Option<Ciphersuite>,
+        #[serde(default)]
+        aliases: Vec<String>,
+        #[serde(flatten)]
+        metadata: NamespaceMetadata,
     }
 
     let temp = TempNamespaceInfo::deserialize(deserializer)?;
@@ -232,12 +640,19 @@ where
         name: temp.name,
         log_directory: temp.log_directory,
         root: temp.root,
+        root_history: temp.root_history,
         last_verified_epoch: temp.last_verified_epoch,
         status: temp.status,
         reports_uri: temp.reports_uri,
         audits_uri: temp.audits_uri,
         signature_version: suite_value,
         ciphersuite: suite_value,
+        // namespaces predating configuration selection are all WhatsApp ones
+        akd_config: temp.akd_config.unwrap_or_default(),
+        digest_algorithm: temp.digest_algorithm,
+        next_ciphersuite: temp.next_ciphersuite,
+        aliases: temp.aliases,
+        metadata: temp.metadata,
     })
 }
 
@@ -249,6 +664,44 @@ pub enum NamespaceStatus {
     Disabled,
 }
 
+impl NamespaceStatus {
+    /// Whether a namespace can move from this status to `next`
+    pub fn can_transition_to(&self, next: &NamespaceStatus) -> bool {
+        matches!(
+            (self, next),
+            (Self::Initialization, Self::Online)
+                | (Self::Initialization, Self::Disabled)
+                | (Self::Online, Self::Disabled)
+                | (Self::Disabled, Self::Online)
+        ) || self == next
+    }
+
+    /// Status after moving to `next`, or [`PlexiError::InvalidStatusTransition`]
+    pub fn transition_to(&self, next: NamespaceStatus) -> Result<NamespaceStatus, PlexiError> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(PlexiError::InvalidStatusTransition {
+                from: self.clone(),
+                to: next,
+            })
+        }
+    }
+}
+
+impl FromStr for NamespaceStatus {
+    type Err = PlexiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "online" => Ok(Self::Online),
+            "initialization" => Ok(Self::Initialization),
+            "disabled" => Ok(Self::Disabled),
+            _ => Err(PlexiError::BadParameter("status".to_string())),
+        }
+    }
+}
+
 impl fmt::Display for NamespaceStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
@@ -259,3 +712,219 @@ impl fmt::Display for NamespaceStatus {
         write!(f, "{}", s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cadence() {
+        let cadence = CadencePolicy::new(Duration::from_secs(60), Duration::from_secs(30));
+        let timestamp = 1717084639921;
+        assert_eq!(cadence.deadline(timestamp), timestamp + 90_000);
+        assert!(cadence.verify(timestamp, timestamp + 90_000).is_ok());
+        assert!(matches!(
+            cadence.verify(timestamp, timestamp + 210_000),
+            Err(PlexiError::BehindCadence { late: 120 })
+        ));
+        assert!(cadence
+            .verify_with_skew(timestamp, timestamp + 120_000, Duration::from_secs(30))
+            .is_ok());
+        assert!(matches!(
+            cadence.verify_with_skew(timestamp, timestamp + 210_000, Duration::from_secs(30)),
+            Err(PlexiError::BehindCadence { late: 120 })
+        ));
+
+        let metadata: NamespaceMetadata = serde_json::from_value(serde_json::json!({
+            "cadence": { "epoch_interval": 60, "max_merge_delay": 30 }
+        }))
+        .unwrap();
+        assert_eq!(metadata.cadence(), Some(cadence));
+        assert_eq!(metadata.description(), None);
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut namespaces = Namespaces::new();
+        for (name, status, ciphersuite) in [
+            (
+                "whatsapp.key-transparency.v1",
+                NamespaceStatus::Online,
+                Ciphersuite::ProtobufEd25519,
+            ),
+            (
+                "whatsapp.key-transparency.v0",
+                NamespaceStatus::Disabled,
+                Ciphersuite::BincodeEd25519,
+            ),
+            (
+                "example.key-transparency.v1",
+                NamespaceStatus::Online,
+                Ciphersuite::ProtobufEd25519,
+            ),
+        ] {
+            let namespace = Namespace::new(name.to_string(), None, None, None, Some(ciphersuite));
+            namespaces.push(NamespaceInfo::new(&namespace, status));
+        }
+
+        let online = NamespaceFilter::new().status(NamespaceStatus::Online);
+        assert_eq!(namespaces.clone().filter(&online).len(), 2);
+        let whatsapp = online.prefix("whatsapp");
+        let filtered = namespaces.clone().filter(&whatsapp);
+        assert_eq!(filtered.len(), 1);
+        assert!(filtered.find("whatsapp.key-transparency.v1").is_some());
+        assert!(filtered.find("example.key-transparency.v1").is_none());
+        let bincode = NamespaceFilter::new().ciphersuite(Ciphersuite::BincodeEd25519);
+        assert_eq!(namespaces.clone().filter(&bincode).len(), 1);
+        assert_eq!(namespaces.filter(&NamespaceFilter::new()).len(), 3);
+
+        assert_eq!(
+            "ONLINE".parse::<NamespaceStatus>().unwrap(),
+            NamespaceStatus::Online
+        );
+        assert!("offline".parse::<NamespaceStatus>().is_err());
+    }
+
+    #[test]
+    fn test_root_history() {
+        let namespace = Namespace::new(
+            "example.key-transparency.v1".to_string(),
+            None,
+            Some("5/aa".to_string()),
+            None,
+            Some(Ciphersuite::ProtobufEd25519),
+        );
+        let mut info = NamespaceInfo::new(&namespace, NamespaceStatus::Online);
+        info.set_root("5/aa");
+        assert!(info.root_history().is_empty());
+
+        // the log is rebuilt from epoch 20
+        info.set_root("20/bb");
+        assert_eq!(info.root(), Some("20/bb"));
+        assert_eq!(info.root_history(), ["5/aa"]);
+        assert_eq!(info.root_for_epoch(&Epoch::from(4)), None);
+        assert_eq!(info.root_for_epoch(&Epoch::from(5)), Some("5/aa"));
+        assert_eq!(info.root_for_epoch(&Epoch::from(19)), Some("5/aa"));
+        assert_eq!(info.root_for_epoch(&Epoch::from(25)), Some("20/bb"));
+
+        let info: NamespaceInfo = serde_json::from_str(&info.to_string().unwrap()).unwrap();
+        assert_eq!(info.root_history(), ["5/aa"]);
+    }
+
+    #[test]
+    fn test_aliases() {
+        let mut namespace = Namespace::new(
+            "whatsapp.key-transparency.v1".to_string(),
+            None,
+            None,
+            None,
+            Some(Ciphersuite::ProtobufEd25519),
+        );
+        namespace.set_aliases(vec!["whatsapp".to_string()]);
+        let info = NamespaceInfo::new(&namespace, NamespaceStatus::Online);
+        assert!(info.is_named("whatsapp"));
+        assert!(info.is_named("whatsapp.key-transparency.v1"));
+        assert!(!info.is_named("whatsapp.key-transparency"));
+
+        let mut namespaces = Namespaces::new();
+        namespaces.push(info);
+        assert_eq!(
+            namespaces.find("whatsapp").map(NamespaceInfo::name),
+            Some("whatsapp.key-transparency.v1")
+        );
+        assert!(namespaces.find("signal").is_none());
+
+        let info: NamespaceInfo =
+            serde_json::from_str(&namespaces.find("whatsapp").unwrap().to_string().unwrap())
+                .unwrap();
+        assert_eq!(info.aliases(), ["whatsapp"]);
+    }
+
+    #[test]
+    fn test_ciphersuite_migration() {
+        let mut namespace = Namespace::new(
+            "example.key-transparency.v1".to_string(),
+            None,
+            None,
+            None,
+            Some(Ciphersuite::ProtobufEd25519),
+        );
+        namespace.set_next_ciphersuite(Some(Ciphersuite::ProtobufEd25519));
+        assert!(namespace.validate().is_err());
+        namespace.set_next_ciphersuite(Some(Ciphersuite::BincodeEd25519));
+        namespace.validate().unwrap();
+
+        let mut info = NamespaceInfo::new(&namespace, NamespaceStatus::Online);
+        assert!(info.accepts_ciphersuite(&Ciphersuite::ProtobufEd25519));
+        assert!(info.accepts_ciphersuite(&Ciphersuite::BincodeEd25519));
+        info.complete_migration().unwrap();
+        assert_eq!(info.ciphersuite(), Ciphersuite::BincodeEd25519);
+        assert_eq!(info.next_ciphersuite(), None);
+        assert!(!info.accepts_ciphersuite(&Ciphersuite::ProtobufEd25519));
+        assert!(info.complete_migration().is_err());
+    }
+
+    #[test]
+    fn test_digest_algorithm() {
+        let mut namespace = Namespace::new(
+            "example.key-transparency.v1".to_string(),
+            None,
+            None,
+            None,
+            Some(Ciphersuite::ProtobufV2Ed25519),
+        );
+        let info = NamespaceInfo::new(&namespace, NamespaceStatus::Online);
+        assert_eq!(info.digest_algorithm(), Some(DigestAlgorithm::Blake3));
+        assert_eq!(info.declared_digest_algorithm(), None);
+
+        namespace.set_digest_algorithm(Some(DigestAlgorithm::Sha256));
+        assert!(namespace.validate().is_err());
+        namespace.set_digest_algorithm(Some(DigestAlgorithm::Blake3));
+        namespace.validate().unwrap();
+
+        // an AKD configuration plexi does not know defers to the declared algorithm
+        namespace.set_akd_config(AkdConfig::Unknown);
+        namespace.set_digest_algorithm(Some(DigestAlgorithm::Sha512_256));
+        namespace.validate().unwrap();
+        let info = NamespaceInfo::new(&namespace, NamespaceStatus::Online);
+        assert_eq!(info.digest_algorithm(), Some(DigestAlgorithm::Sha512_256));
+        let info: NamespaceInfo = serde_json::from_str(&info.to_string().unwrap()).unwrap();
+        assert_eq!(
+            info.declared_digest_algorithm(),
+            Some(DigestAlgorithm::Sha512_256)
+        );
+        namespace.set_digest_algorithm(Some(DigestAlgorithm::Unknown));
+        assert!(namespace.validate().is_err());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        use NamespaceStatus::*;
+
+        assert!(Initialization.can_transition_to(&Online));
+        assert!(Online.can_transition_to(&Disabled));
+        assert!(Disabled.can_transition_to(&Online));
+        assert!(Online.can_transition_to(&Online));
+        assert!(matches!(
+            Online.transition_to(Initialization),
+            Err(PlexiError::InvalidStatusTransition {
+                from: Online,
+                to: Initialization
+            })
+        ));
+        assert!(Disabled.transition_to(Initialization).is_err());
+
+        let namespace = Namespace::new(
+            "example.key-transparency.v1".to_string(),
+            None,
+            None,
+            None,
+            Some(Ciphersuite::ProtobufEd25519),
+        );
+        let mut info = NamespaceInfo::new(&namespace, Online);
+        assert!(info.set_status(Initialization).is_err());
+        assert_eq!(info.status(), Online);
+        info.set_status(Disabled).unwrap();
+        assert_eq!(info.status(), Disabled);
+    }
+}
@@ -0,0 +1,368 @@
+//! OpenAPI document of the auditor API, as served by `plexi-server` and consumed by [`crate::client::PlexiClient`].
+
+use serde::Serialize;
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi, ToSchema,
+};
+
+#[cfg(all(feature = "openapi", feature = "checkpoint"))]
+use crate::checkpoint::Cosignature;
+use crate::{
+    auditor::{AkdConfig, Configuration, KeyInfo, KeyRotation},
+    gossip::{Divergence, GossipRequest, GossipResponse},
+    namespaces::{
+        CadencePolicy, Namespace, NamespaceInfo, NamespaceMetadata, NamespaceStatus, Namespaces,
+    },
+    Ciphersuite, DigestAlgorithm, Epoch, LastVerifiedEpoch, MigrationSignature, ReportResponse,
+    SignatureResponse,
+};
+
+/// Error returned by the auditor API
+#[derive(Serialize, ToSchema)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Outcome of the readiness checks, each being `ok` or the reason it failed
+#[derive(Serialize, ToSchema)]
+struct Readiness {
+    storage: String,
+    signer: String,
+}
+
+#[allow(dead_code)]
+mod paths {
+    #[utoipa::path(
+        get,
+        path = "/info",
+        tag = "auditor",
+        responses((status = 200, description = "Auditor keys and the logs it monitors", body = Configuration))
+    )]
+    fn info() {}
+
+    #[utoipa::path(
+        get,
+        path = "/healthz",
+        tag = "operations",
+        responses((status = 200, description = "The server is running", body = Object, example = json!({ "status": "ok" })))
+    )]
+    fn healthz() {}
+
+    #[utoipa::path(
+        get,
+        path = "/readyz",
+        tag = "operations",
+        responses(
+            (status = 200, description = "Storage and signer are available", body = Readiness),
+            (status = 503, description = "Storage or signer is not available", body = Readiness)
+        )
+    )]
+    fn readyz() {}
+
+    #[utoipa::path(
+        get,
+        path = "/metrics",
+        tag = "operations",
+        responses((status = 200, description = "Metrics in the Prometheus text format", body = String, content_type = "text/plain"))
+    )]
+    fn metrics() {}
+
+    #[utoipa::path(
+        get,
+        path = "/namespaces",
+        tag = "namespaces",
+        params(
+            ("status" = Option<NamespaceStatus>, Query, description = "Only list namespaces with this status"),
+            ("prefix" = Option<String>, Query, description = "Only list namespaces whose name starts with this prefix"),
+            ("ciphersuite" = Option<Ciphersuite>, Query, description = "Only list namespaces using this ciphersuite")
+        ),
+        responses((status = 200, description = "Namespaces the auditor monitors", body = Namespaces))
+    )]
+    fn namespaces() {}
+
+    #[utoipa::path(
+        get,
+        path = "/namespaces/{namespace}",
+        tag = "namespaces",
+        params(("namespace" = String, Path, description = "Name of the namespace")),
+        responses(
+            (status = 200, description = "Namespace information", body = NamespaceInfo),
+            (status = 404, description = "Namespace does not exist", body = ErrorResponse)
+        )
+    )]
+    fn namespace() {}
+
+    #[utoipa::path(
+        post,
+        path = "/namespaces",
+        tag = "namespaces",
+        security(("admin_token" = [])),
+        request_body(content = Namespace, description = "Namespace to audit"),
+        responses(
+            (status = 201, description = "Namespace has been created", body = NamespaceInfo),
+            (status = 400, description = "Namespace is not valid", body = ErrorResponse),
+            (status = 401, description = "Admin token is missing or invalid", body = ErrorResponse),
+            (status = 409, description = "Namespace already exists", body = ErrorResponse)
+        )
+    )]
+    fn create_namespace() {}
+
+    #[utoipa::path(
+        put,
+        path = "/namespaces/{namespace}",
+        tag = "namespaces",
+        security(("admin_token" = [])),
+        params(("namespace" = String, Path, description = "Name of the namespace")),
+        request_body(content = Namespace, description = "Log directory, AKD configuration, and metadata of the namespace. Its root and ciphersuite cannot change"),
+        responses(
+            (status = 200, description = "Namespace has been updated", body = NamespaceInfo),
+            (status = 400, description = "Namespace is not valid, or changes its root or ciphersuite", body = ErrorResponse),
+            (status = 401, description = "Admin token is missing or invalid", body = ErrorResponse),
+            (status = 404, description = "Namespace does not exist", body = ErrorResponse)
+        )
+    )]
+    fn update_namespace() {}
+
+    #[utoipa::path(
+        post,
+        path = "/namespaces/{namespace}/disable",
+        tag = "namespaces",
+        security(("admin_token" = [])),
+        params(("namespace" = String, Path, description = "Name of the namespace")),
+        responses(
+            (status = 200, description = "Namespace has been disabled", body = NamespaceInfo),
+            (status = 401, description = "Admin token is missing or invalid", body = ErrorResponse),
+            (status = 404, description = "Namespace does not exist", body = ErrorResponse)
+        )
+    )]
+    fn disable_namespace() {}
+
+    #[utoipa::path(
+        get,
+        path = "/namespaces/{namespace}/audits/{epoch}",
+        tag = "audits",
+        params(
+            ("namespace" = String, Path, description = "Name of the namespace"),
+            ("epoch" = u64, Path, description = "Epoch of the signature")
+        ),
+        responses(
+            (status = 200, description = "Auditor signature for the epoch", body = SignatureResponse),
+            (status = 404, description = "Epoch has not been signed", body = ErrorResponse)
+        )
+    )]
+    fn signature() {}
+
+    #[cfg(all(feature = "openapi", feature = "checkpoint"))]
+    #[utoipa::path(
+        get,
+        path = "/namespaces/{namespace}/cosignatures/{epoch}",
+        tag = "audits",
+        params(
+            ("namespace" = String, Path, description = "Name of the namespace"),
+            ("epoch" = u64, Path, description = "Epoch of the checkpoint")
+        ),
+        responses((status = 200, description = "Cosignatures witnesses returned for the checkpoint of the epoch", body = [Cosignature]))
+    )]
+    fn cosignatures() {}
+
+    #[utoipa::path(
+        get,
+        path = "/namespaces/{namespace}/last-verified-epoch",
+        tag = "audits",
+        params(("namespace" = String, Path, description = "Name of the namespace")),
+        responses(
+            (status = 200, description = "Last epoch the auditor verified", body = LastVerifiedEpoch),
+            (status = 404, description = "No epoch has been verified", body = ErrorResponse)
+        )
+    )]
+    fn last_verified_epoch() {}
+
+    #[utoipa::path(
+        get,
+        path = "/namespaces/{namespace}/proofs/{epoch}/{previous_hash}/{current_hash}",
+        tag = "audits",
+        params(
+            ("namespace" = String, Path, description = "Name of the namespace"),
+            ("epoch" = u64, Path, description = "Epoch the proof leads to"),
+            ("previous_hash" = String, Path, description = "Hex encoded root hash of the previous epoch"),
+            ("current_hash" = String, Path, description = "Hex encoded root hash of the epoch")
+        ),
+        responses(
+            (status = 200, description = "Protobuf encoded audit proof", body = Vec<u8>, content_type = "application/octet-stream"),
+            (status = 400, description = "Invalid proof name", body = ErrorResponse),
+            (status = 404, description = "Proof has not been verified by the auditor", body = ErrorResponse)
+        )
+    )]
+    fn proof() {}
+
+    #[utoipa::path(
+        post,
+        path = "/namespaces/{namespace}/reports",
+        tag = "reports",
+        params(("namespace" = String, Path, description = "Name of the namespace")),
+        request_body(content = SignatureResponse, description = "New epoch, signed by the log"),
+        responses(
+            (status = 200, description = "Report has been verified and the epoch signed", body = ReportResponse),
+            (status = 202, description = "Report cannot be verified yet, such as when its proof is not published, and is retried by the auditor", body = ReportResponse),
+            (status = 400, description = "Report is not valid", body = ErrorResponse),
+            (status = 404, description = "Namespace does not exist", body = ErrorResponse),
+            (status = 409, description = "Report conflicts with an epoch already signed", body = ErrorResponse)
+        )
+    )]
+    fn submit_report() {}
+
+    #[utoipa::path(
+        get,
+        path = "/namespaces/{namespace}/reports/{id}",
+        tag = "reports",
+        params(
+            ("namespace" = String, Path, description = "Name of the namespace"),
+            ("id" = Uuid, Path, description = "Identifier returned when the report was submitted")
+        ),
+        responses(
+            (status = 200, description = "Submitted report", body = ReportResponse),
+            (status = 404, description = "Report does not exist", body = ErrorResponse)
+        )
+    )]
+    fn report() {}
+
+    #[utoipa::path(
+        post,
+        path = "/gossip",
+        tag = "gossip",
+        request_body(content = GossipRequest, description = "Heads of a peer auditor"),
+        responses(
+            (status = 200, description = "Heads of the auditor for the same namespaces, and the divergences found", body = GossipResponse),
+            (status = 400, description = "A head is not signed by a peer", body = ErrorResponse)
+        )
+    )]
+    fn gossip() {}
+
+    #[utoipa::path(
+        get,
+        path = "/gossip/divergences",
+        tag = "gossip",
+        responses((status = 200, description = "Divergences found while gossiping", body = [Divergence]))
+    )]
+    fn divergences() {}
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        paths::info,
+        paths::healthz,
+        paths::readyz,
+        paths::metrics,
+        paths::namespaces,
+        paths::namespace,
+        paths::create_namespace,
+        paths::update_namespace,
+        paths::disable_namespace,
+        paths::signature,
+        paths::last_verified_epoch,
+        paths::proof,
+        paths::submit_report,
+        paths::report,
+        paths::gossip,
+        paths::divergences,
+    ),
+    components(schemas(
+        AkdConfig,
+        CadencePolicy,
+        Ciphersuite,
+        Configuration,
+        DigestAlgorithm,
+        Divergence,
+        Epoch,
+        ErrorResponse,
+        GossipRequest,
+        GossipResponse,
+        KeyInfo,
+        KeyRotation,
+        LastVerifiedEpoch,
+        MigrationSignature,
+        Namespace,
+        NamespaceInfo,
+        NamespaceMetadata,
+        NamespaceStatus,
+        Namespaces,
+        Readiness,
+        ReportResponse,
+        SignatureResponse,
+    )),
+    modifiers(&AdminTokenScheme)
+)]
+struct ApiDoc;
+
+#[cfg(all(feature = "openapi", feature = "checkpoint"))]
+#[derive(OpenApi)]
+#[openapi(paths(paths::cosignatures), components(schemas(Cosignature)))]
+struct CheckpointApiDoc;
+
+struct AdminTokenScheme;
+
+impl Modify for AdminTokenScheme {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme(
+                "admin_token",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+    }
+}
+
+/// OpenAPI document of the auditor API
+pub fn spec() -> utoipa::openapi::OpenApi {
+    #[allow(unused_mut)]
+    let mut spec = ApiDoc::openapi();
+    #[cfg(all(feature = "openapi", feature = "checkpoint"))]
+    spec.merge(CheckpointApiDoc::openapi());
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec() {
+        let spec = serde_json::to_value(spec()).unwrap();
+
+        assert_eq!(spec["info"]["license"]["name"], "Apache-2.0");
+        assert!(spec["paths"]["/namespaces/{namespace}/reports"]["post"].is_object());
+        assert!(spec["paths"]["/namespaces"]["get"].is_object());
+        assert_eq!(
+            spec["paths"]["/namespaces"]["post"]["security"][0]["admin_token"],
+            serde_json::json!([])
+        );
+        assert_eq!(
+            spec["paths"].as_object().unwrap().len(),
+            if cfg!(feature = "checkpoint") { 15 } else { 14 },
+            "every route is documented"
+        );
+
+        // every referenced schema is part of the document
+        let document = spec.to_string();
+        for reference in document.split("\"$ref\":\"#/components/schemas/").skip(1) {
+            let name = &reference[..reference.find('"').unwrap()];
+            assert!(
+                spec["components"]["schemas"][name].is_object(),
+                "{name} is missing"
+            );
+        }
+
+        assert_eq!(
+            spec["components"]["schemas"]["Ciphersuite"]["type"],
+            "integer"
+        );
+        // hex encoded fields are documented as strings
+        assert_eq!(
+            spec["components"]["schemas"]["SignatureResponse"]["properties"]["digest"]["type"],
+            "string"
+        );
+    }
+}
@@ -0,0 +1,377 @@
+use anyhow::{anyhow, Context as _};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{Signature, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use reqwest::{header::AUTHORIZATION, Client, Url};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::Signer;
+use crate::client::{aws::SigV4, AwsCredentials};
+
+const GCP_KMS_API_URL: &str = "https://cloudkms.googleapis.com/v1/";
+const AWS_KMS_CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+const EC_PUBLIC_KEY_OID: [u8; 9] = [0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+fn ed25519_public_key_from_spki(spki: &[u8]) -> anyhow::Result<[u8; PUBLIC_KEY_LENGTH]> {
+    if let Some(key) = spki.strip_prefix(&ED25519_SPKI_PREFIX) {
+        return key
+            .try_into()
+            .map_err(|_| anyhow!("Ed25519 public key has an invalid length"));
+    }
+    if spki
+        .windows(EC_PUBLIC_KEY_OID.len())
+        .any(|window| window == EC_PUBLIC_KEY_OID)
+    {
+        // plexi signatures are Ed25519, clients would not be able to verify ECDSA signatures
+        return Err(anyhow!(
+            "KMS key is an ECDSA key, epochs have to be signed with an Ed25519 key"
+        ));
+    }
+    Err(anyhow!("KMS key is not an Ed25519 key"))
+}
+
+fn pem_to_der(pem: &str) -> anyhow::Result<Vec<u8>> {
+    let body: String = pem
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("-----"))
+        .collect();
+    BASE64.decode(body).context("decoding PEM")
+}
+
+fn verified_signature(
+    verifying_key: &VerifyingKey,
+    message: &[u8],
+    signature: &str,
+) -> anyhow::Result<[u8; SIGNATURE_LENGTH]> {
+    let signature: [u8; SIGNATURE_LENGTH] = BASE64
+        .decode(signature)
+        .context("decoding KMS signature")?
+        .try_into()
+        .map_err(|_| anyhow!("KMS returned a signature of invalid length"))?;
+    verifying_key
+        .verify_strict(message, &Signature::from_bytes(&signature))
+        .context("KMS signature does not verify")?;
+    Ok(signature)
+}
+
+#[derive(Clone, Debug)]
+struct AwsKms {
+    client: Client,
+    endpoint: Url,
+    signer: SigV4,
+}
+
+impl AwsKms {
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        action: &str,
+        body: serde_json::Value,
+    ) -> anyhow::Result<T> {
+        let body = serde_json::to_vec(&body)?;
+        let target = format!("TrentService.{action}");
+        let headers = [
+            ("content-type", AWS_KMS_CONTENT_TYPE),
+            ("x-amz-target", target.as_str()),
+        ];
+        let mut request = self.client.post(self.endpoint.clone());
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        for (name, value) in self.signer.sign(
+            "POST",
+            &self.endpoint,
+            &headers,
+            &body,
+            time::OffsetDateTime::now_utc(),
+        )? {
+            request = request.header(name, value);
+        }
+
+        let response = request.body(body).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let error = response.text().await.unwrap_or_default();
+            return Err(anyhow!("AWS KMS {action} failed with {status}: {error}"));
+        }
+        response
+            .json()
+            .await
+            .with_context(|| format!("decoding AWS KMS {action} response"))
+    }
+}
+
+/// Ed25519 key managed by AWS KMS, with key spec `ECC_NIST_EDWARDS25519`
+#[derive(Clone, Debug)]
+pub struct AwsKmsSigner {
+    kms: AwsKms,
+    key_id: String,
+    verifying_key: VerifyingKey,
+}
+
+impl AwsKmsSigner {
+    /// Fetch the public key of `key_id`, which can be a key ID, key ARN, or alias
+    pub async fn new(
+        client: Client,
+        endpoint: Url,
+        region: &str,
+        key_id: &str,
+        credentials: AwsCredentials,
+    ) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct GetPublicKeyResponse {
+            public_key: String,
+        }
+
+        let kms = AwsKms {
+            client,
+            endpoint,
+            signer: SigV4::new(credentials, region, "kms"),
+        };
+        let response: GetPublicKeyResponse =
+            kms.call("GetPublicKey", json!({ "KeyId": key_id })).await?;
+        let spki = BASE64
+            .decode(response.public_key)
+            .context("decoding AWS KMS public key")?;
+        Ok(Self {
+            kms,
+            key_id: key_id.to_string(),
+            verifying_key: VerifyingKey::from_bytes(&ed25519_public_key_from_spki(&spki)?)?,
+        })
+    }
+
+    /// Use the region and endpoint from `AWS_REGION` and `AWS_ENDPOINT_URL`, and credentials from [`AwsCredentials::from_env`]
+    pub async fn from_env(client: Client, key_id: &str) -> anyhow::Result<Self> {
+        let region = std::env::var("AWS_REGION")
+            .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+            .unwrap_or_else(|_| "us-east-1".to_string());
+        let endpoint = match std::env::var("AWS_ENDPOINT_URL") {
+            Ok(endpoint) => Url::parse(&endpoint).context("parsing AWS_ENDPOINT_URL")?,
+            Err(_) => Url::parse(&format!("https://kms.{region}.amazonaws.com/"))?,
+        };
+        Self::new(
+            client,
+            endpoint,
+            &region,
+            key_id,
+            AwsCredentials::from_env()?,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl Signer for AwsKmsSigner {
+    fn verifying_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.verifying_key.to_bytes()
+    }
+
+    async fn sign(&self, message: &[u8]) -> anyhow::Result<[u8; SIGNATURE_LENGTH]> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct SignResponse {
+            signature: String,
+        }
+
+        let response: SignResponse = self
+            .kms
+            .call(
+                "Sign",
+                json!({
+                    "KeyId": self.key_id,
+                    "Message": BASE64.encode(message),
+                    "MessageType": "RAW",
+                    "SigningAlgorithm": "ED25519_SHA_512",
+                }),
+            )
+            .await?;
+        verified_signature(&self.verifying_key, message, &response.signature)
+    }
+
+    async fn check(&self) -> anyhow::Result<()> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct KeyMetadata {
+            key_state: String,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct DescribeKeyResponse {
+            key_metadata: KeyMetadata,
+        }
+
+        let response: DescribeKeyResponse = self
+            .kms
+            .call("DescribeKey", json!({ "KeyId": self.key_id }))
+            .await?;
+        match response.key_metadata.key_state.as_str() {
+            "Enabled" => Ok(()),
+            state => Err(anyhow!(
+                "AWS KMS key {key_id} is {state}",
+                key_id = self.key_id
+            )),
+        }
+    }
+}
+
+/// Ed25519 key version managed by Google Cloud KMS, with algorithm `EC_SIGN_ED25519`
+#[derive(Clone)]
+pub struct GcpKmsSigner {
+    client: Client,
+    key_version_url: Url,
+    access_token: String,
+    verifying_key: VerifyingKey,
+}
+
+impl std::fmt::Debug for GcpKmsSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcpKmsSigner")
+            .field("key_version_url", &self.key_version_url)
+            .field("verifying_key", &hex::encode(self.verifying_key.as_bytes()))
+            .finish_non_exhaustive()
+    }
+}
+
+async fn gcp_kms_call<T: serde::de::DeserializeOwned>(
+    request: reqwest::RequestBuilder,
+    access_token: &str,
+) -> anyhow::Result<T> {
+    let response = request
+        .header(AUTHORIZATION, format!("Bearer {access_token}"))
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "Google Cloud KMS request failed with {status}: {error}"
+        ));
+    }
+    response
+        .json()
+        .await
+        .context("decoding Google Cloud KMS response")
+}
+
+impl GcpKmsSigner {
+    /// Fetch the public key of the key version `key_version`
+    pub async fn new(
+        client: Client,
+        key_version: &str,
+        access_token: &str,
+    ) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct PublicKeyResponse {
+            pem: String,
+        }
+
+        let key_version_url = Url::parse(GCP_KMS_API_URL)?
+            .join(key_version.trim_matches('/'))
+            .with_context(|| format!("invalid key version {key_version}"))?;
+        let url = Url::parse(&format!("{key_version_url}/publicKey"))?;
+        let response: PublicKeyResponse = gcp_kms_call(client.get(url), access_token).await?;
+        let spki = pem_to_der(&response.pem)?;
+        Ok(Self {
+            client,
+            key_version_url,
+            access_token: access_token.to_string(),
+            verifying_key: VerifyingKey::from_bytes(&ed25519_public_key_from_spki(&spki)?)?,
+        })
+    }
+
+    /// Use `GOOGLE_OAUTH_ACCESS_TOKEN` as the access token
+    pub async fn from_env(client: Client, key_version: &str) -> anyhow::Result<Self> {
+        let access_token = std::env::var("GOOGLE_OAUTH_ACCESS_TOKEN")
+            .context("reading GOOGLE_OAUTH_ACCESS_TOKEN")?;
+        Self::new(client, key_version, &access_token).await
+    }
+}
+
+#[async_trait]
+impl Signer for GcpKmsSigner {
+    fn verifying_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.verifying_key.to_bytes()
+    }
+
+    async fn sign(&self, message: &[u8]) -> anyhow::Result<[u8; SIGNATURE_LENGTH]> {
+        #[derive(Deserialize)]
+        struct AsymmetricSignResponse {
+            signature: String,
+        }
+
+        let url = Url::parse(&format!("{url}:asymmetricSign", url = self.key_version_url))?;
+        let response: AsymmetricSignResponse = gcp_kms_call(
+            self.client
+                .post(url)
+                .json(&json!({ "data": BASE64.encode(message) })),
+            &self.access_token,
+        )
+        .await?;
+        verified_signature(&self.verifying_key, message, &response.signature)
+    }
+
+    async fn check(&self) -> anyhow::Result<()> {
+        #[derive(Deserialize)]
+        struct KeyVersion {
+            state: String,
+        }
+
+        let response: KeyVersion = gcp_kms_call(
+            self.client.get(self.key_version_url.clone()),
+            &self.access_token,
+        )
+        .await?;
+        match response.state.as_str() {
+            "ENABLED" => Ok(()),
+            state => Err(anyhow!(
+                "Google Cloud KMS key version {url} is {state}",
+                url = self.key_version_url
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer as _, SigningKey};
+
+    use super::*;
+
+    // openssl genpkey -algorithm ed25519 | openssl pkey -pubout
+    const ED25519_PEM: &str = "-----BEGIN PUBLIC KEY-----
+MCowBQYDK2VwAyEAeu0exzmWXSMUo08fpjMhMC5j8TH0B+a4pcMC0STDNDQ=
+-----END PUBLIC KEY-----
+";
+    // openssl ecparam -name prime256v1 -genkey | openssl ec -pubout
+    const P256_PEM: &str = "-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAENMqfsK/GBJtaGKDOHuW4fYGdTSL8
+O4kRZFIzHRm/PQkqdDWgFKscJMkx1wtL0FHu0AIFMKOKd3q95iu7/1UClg==
+-----END PUBLIC KEY-----
+";
+
+    #[test]
+    fn test_ed25519_public_key_from_spki() {
+        assert_eq!(
+            hex::encode(ed25519_public_key_from_spki(&pem_to_der(ED25519_PEM).unwrap()).unwrap()),
+            "7aed1ec739965d2314a34f1fa63321302e63f131f407e6b8a5c302d124c33434"
+        );
+        let err = ed25519_public_key_from_spki(&pem_to_der(P256_PEM).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("ECDSA"));
+        assert!(ed25519_public_key_from_spki(&[0x30, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_verified_signature() {
+        let key = SigningKey::from_bytes(&[0x42; 32]);
+        let signature = BASE64.encode(key.sign(b"message").to_bytes());
+        assert!(verified_signature(&key.verifying_key(), b"message", &signature).is_ok());
+        assert!(verified_signature(&key.verifying_key(), b"another message", &signature).is_err());
+        assert!(verified_signature(&key.verifying_key(), b"message", "AAAA").is_err());
+    }
+}
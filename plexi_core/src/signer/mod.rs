@@ -0,0 +1,164 @@
+use std::fmt;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use ed25519_dalek::{
+    Signer as _, SigningKey, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, SIGNATURE_LENGTH,
+};
+
+use crate::{
+    auditor::{KeyInfo, KeyRotation},
+    crypto::ed25519_public_key_to_key_id,
+    LastVerifiedEpoch, SignatureMessage, SignatureResponse,
+};
+
+#[cfg(feature = "kms")]
+mod kms;
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
+#[cfg(feature = "yubikey")]
+mod yubikey;
+#[cfg(feature = "kms")]
+pub use kms::{AwsKmsSigner, GcpKmsSigner};
+#[cfg(feature = "pkcs11")]
+pub use pkcs11::Pkcs11Signer;
+#[cfg(feature = "yubikey")]
+pub use yubikey::{PivSlot, YubiKeySigner, DEFAULT_YKCS11_MODULE};
+
+/// Key an auditor signs epochs with
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Ed25519 public key the signatures verify against
+    fn verifying_key(&self) -> [u8; PUBLIC_KEY_LENGTH];
+
+    /// Sign `message`, which is a serialized [`SignatureMessage`], a [`KeyInfo::rotation_message`], a [`LastVerifiedEpoch::signed_message`], the text of a checkpoint note, or the signing input of a JWS
+    async fn sign(&self, message: &[u8]) -> anyhow::Result<[u8; SIGNATURE_LENGTH]>;
+
+    fn key_id(&self) -> u8 {
+        ed25519_public_key_to_key_id(&self.verifying_key())
+    }
+
+    /// Check the key is available, without producing a signature
+    async fn check(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Sign `message` with `signer`, producing the response an auditor serves for this epoch
+pub async fn sign_message(
+    signer: &dyn Signer,
+    message: &SignatureMessage,
+) -> anyhow::Result<SignatureResponse> {
+    let signature = signer.sign(&message.to_vec()?).await?;
+    Ok(SignatureResponse::new(
+        message.ciphersuite(),
+        message.ciphersuite(),
+        message.namespace().to_string(),
+        message.timestamp(),
+        message.epoch(),
+        message.digest_owned(),
+        signature.to_vec(),
+        Some(signer.key_id()),
+        None,
+    )
+    .with_message_fields(message))
+}
+
+/// Introduce `key` as the successor of the key of `signer`, for verifiers pinning `signer` to trust `key`
+pub async fn sign_key_rotation(signer: &dyn Signer, key: KeyInfo) -> anyhow::Result<KeyInfo> {
+    let signature = signer.sign(&key.rotation_message()?).await?;
+    Ok(key.with_rotation(KeyRotation::new(
+        &hex::encode(signer.verifying_key()),
+        &signature,
+    )))
+}
+
+/// Sign `last_verified_epoch` of `namespace` with `signer`, for clients to trust the epoch they resume auditing from
+pub async fn sign_last_verified_epoch(
+    signer: &dyn Signer,
+    namespace: &str,
+    last_verified_epoch: LastVerifiedEpoch,
+) -> anyhow::Result<LastVerifiedEpoch> {
+    let signature = signer
+        .sign(&last_verified_epoch.signed_message(namespace))
+        .await?;
+    Ok(last_verified_epoch.with_signature(signer.key_id(), &signature))
+}
+
+/// Ed25519 key held in memory
+pub struct Ed25519Signer {
+    key: SigningKey,
+}
+
+impl fmt::Debug for Ed25519Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ed25519Signer")
+            .field("verifying_key", &hex::encode(self.verifying_key()))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Ed25519Signer {
+    pub fn new(secret_key: &[u8; SECRET_KEY_LENGTH]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(secret_key),
+        }
+    }
+
+    /// Parse a hex encoded secret key, surrounding whitespace is ignored
+    pub fn from_hex(secret_key: &str) -> anyhow::Result<Self> {
+        let secret_key: [u8; SECRET_KEY_LENGTH] = hex::decode(secret_key.trim())
+            .map_err(|_| anyhow!("signing key is not valid hex"))?
+            .try_into()
+            .map_err(|_| anyhow!("signing key should have length {SECRET_KEY_LENGTH}"))?;
+        Ok(Self::new(&secret_key))
+    }
+}
+
+#[async_trait]
+impl Signer for Ed25519Signer {
+    fn verifying_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.key.verifying_key().to_bytes()
+    }
+
+    async fn sign(&self, message: &[u8]) -> anyhow::Result<[u8; SIGNATURE_LENGTH]> {
+        Ok(self.key.sign(message).to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ciphersuite, Epoch};
+
+    #[tokio::test]
+    async fn test_sign_message() {
+        // first test vector
+        let signer = Ed25519Signer::from_hex(
+            "d6af1bca3db4fc2766b0c483706c20bf4837a46d54c1d39c2a34a9088572d712\n",
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(signer.verifying_key()),
+            "606a878700158d92b3a14a0fd37ec82e0f05f92fcf23146abfda2e3a2f10a9bc"
+        );
+        assert_eq!(signer.key_id(), 188);
+
+        let message = SignatureMessage::new(
+            &Ciphersuite::ProtobufEd25519,
+            "log1.example.com".to_string(),
+            1717084639921,
+            &Epoch::from(1),
+            vec![0x11; 32],
+        )
+        .unwrap();
+        let response = sign_message(&signer, &message).await.unwrap();
+        assert_eq!(
+            hex::encode(response.signature()),
+            "769d1ae73792dd8da1515793ba29f2f33f2fca84ba7386f9a01d8272e67c4379bdcfd91ea4fa49eafd8f243ac36fe3a7aae90ae92f1729c3c468ddf4e6d2a309"
+        );
+        response.verify(&signer.verifying_key()).unwrap();
+
+        assert!(Ed25519Signer::from_hex("abcd").is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use std::{fmt, path::Path, sync::Mutex};
+
+use anyhow::{anyhow, Context as _};
+use async_trait::async_trait;
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::{
+        eddsa::{EddsaParams, EddsaSignatureScheme},
+        Mechanism,
+    },
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use ed25519_dalek::{Signature, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+
+use super::Signer;
+
+const DER_TAG_OCTET_STRING: u8 = 0x04;
+
+/// Ed25519 key held in an HSM, used through its PKCS#11 module
+pub struct Pkcs11Signer {
+    session: Mutex<Session>,
+    key: ObjectHandle,
+    verifying_key: VerifyingKey,
+}
+
+impl fmt::Debug for Pkcs11Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pkcs11Signer")
+            .field("verifying_key", &hex::encode(self.verifying_key.as_bytes()))
+            .finish_non_exhaustive()
+    }
+}
+
+fn ed25519_public_key_from_ec_point(ec_point: &[u8]) -> anyhow::Result<[u8; PUBLIC_KEY_LENGTH]> {
+    let key = match ec_point {
+        [DER_TAG_OCTET_STRING, length, key @ ..] if *length as usize == PUBLIC_KEY_LENGTH => key,
+        key => key,
+    };
+    key.try_into()
+        .map_err(|_| anyhow!("EC point is not an Ed25519 public key"))
+}
+
+impl Pkcs11Signer {
+    /// Load the PKCS#11 `module`, and use the Ed25519 key pair labelled `key_label` on the token `token_label`
+    pub fn new(
+        module: &Path,
+        token_label: &str,
+        pin: &str,
+        key_label: &str,
+    ) -> anyhow::Result<Self> {
+        Self::open(
+            module,
+            Some(token_label),
+            pin,
+            Attribute::Label(key_label.as_bytes().to_vec()),
+        )
+    }
+
+    /// Same as [`Self::new`], with the key pair identified by its CKA_ID, as is done by PIV tokens
+    pub fn with_id(
+        module: &Path,
+        token_label: Option<&str>,
+        pin: &str,
+        id: &[u8],
+    ) -> anyhow::Result<Self> {
+        Self::open(module, token_label, pin, Attribute::Id(id.to_vec()))
+    }
+
+    fn open(
+        module: &Path,
+        token_label: Option<&str>,
+        pin: &str,
+        key: Attribute,
+    ) -> anyhow::Result<Self> {
+        let key_name = match &key {
+            Attribute::Label(label) => String::from_utf8_lossy(label).to_string(),
+            Attribute::Id(id) => format!("with id {id}", id = hex::encode(id)),
+            _ => "key".to_string(),
+        };
+
+        let pkcs11 = Pkcs11::new(module).with_context(|| {
+            format!("loading PKCS#11 module {module}", module = module.display())
+        })?;
+        pkcs11
+            .initialize(CInitializeArgs::OsThreads)
+            .context("initializing PKCS#11 module")?;
+
+        let mut slot = None;
+        for candidate in pkcs11.get_slots_with_token()? {
+            if token_label.is_none()
+                || token_label == Some(pkcs11.get_token_info(candidate)?.label())
+            {
+                slot = Some(candidate);
+                break;
+            }
+        }
+        let slot = slot.ok_or_else(|| match token_label {
+            Some(token_label) => anyhow!("PKCS#11 token {token_label} not found"),
+            None => anyhow!("no PKCS#11 token found"),
+        })?;
+
+        let session = pkcs11.open_ro_session(slot)?;
+        session
+            .login(UserType::User, Some(&AuthPin::new(pin.into())))
+            .context("logging into PKCS#11 token")?;
+
+        let find = |class: ObjectClass| -> anyhow::Result<ObjectHandle> {
+            let objects = session.find_objects(&[
+                Attribute::Class(class),
+                Attribute::KeyType(KeyType::EC_EDWARDS),
+                key.clone(),
+            ])?;
+            match objects.as_slice() {
+                [object] => Ok(*object),
+                [] => Err(anyhow!("Ed25519 {class} {key_name} not found")),
+                _ => Err(anyhow!("multiple Ed25519 {class} match {key_name}")),
+            }
+        };
+        let private_key = find(ObjectClass::PRIVATE_KEY)?;
+        let public_key = find(ObjectClass::PUBLIC_KEY)?;
+
+        let ec_point = match session
+            .get_attributes(public_key, &[AttributeType::EcPoint])?
+            .as_slice()
+        {
+            [Attribute::EcPoint(ec_point)] => ec_point.clone(),
+            _ => return Err(anyhow!("public key {key_name} does not have an EC point")),
+        };
+        let verifying_key =
+            VerifyingKey::from_bytes(&ed25519_public_key_from_ec_point(&ec_point)?)?;
+
+        Ok(Self {
+            session: Mutex::new(session),
+            key: private_key,
+            verifying_key,
+        })
+    }
+}
+
+#[async_trait]
+impl Signer for Pkcs11Signer {
+    fn verifying_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.verifying_key.to_bytes()
+    }
+
+    async fn sign(&self, message: &[u8]) -> anyhow::Result<[u8; SIGNATURE_LENGTH]> {
+        let signature = self
+            .session
+            .lock()
+            .map_err(|_| anyhow!("PKCS#11 session lock is poisoned"))?
+            .sign(
+                &Mechanism::Eddsa(EddsaParams::new(EddsaSignatureScheme::Ed25519)),
+                self.key,
+                message,
+            )
+            .context("signing with PKCS#11 token")?;
+        let signature: [u8; SIGNATURE_LENGTH] = signature
+            .try_into()
+            .map_err(|_| anyhow!("PKCS#11 token returned a signature of invalid length"))?;
+
+        // a signature with another scheme, such as Ed25519ph, would not verify for clients
+        self.verifying_key
+            .verify_strict(message, &Signature::from_bytes(&signature))
+            .context("PKCS#11 token signature does not verify")?;
+        Ok(signature)
+    }
+
+    async fn check(&self) -> anyhow::Result<()> {
+        self.session
+            .lock()
+            .map_err(|_| anyhow!("PKCS#11 session lock is poisoned"))?
+            .get_session_info()
+            .context("PKCS#11 session is not available")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ed25519_public_key_from_ec_point() {
+        let key = [0xaa; PUBLIC_KEY_LENGTH];
+        let der = [&[DER_TAG_OCTET_STRING, 32][..], &key].concat();
+        assert_eq!(ed25519_public_key_from_ec_point(&der).unwrap(), key);
+        assert_eq!(ed25519_public_key_from_ec_point(&key).unwrap(), key);
+        assert!(ed25519_public_key_from_ec_point(&key[..31]).is_err());
+    }
+}
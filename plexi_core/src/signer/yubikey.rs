@@ -0,0 +1,131 @@
+use std::{fmt, path::Path, str::FromStr};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use ed25519_dalek::{PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+
+use super::{Pkcs11Signer, Signer};
+
+/// Name of the YubiKey PKCS#11 module, ykcs11
+#[cfg(target_os = "macos")]
+pub const DEFAULT_YKCS11_MODULE: &str = "libykcs11.dylib";
+#[cfg(target_os = "windows")]
+pub const DEFAULT_YKCS11_MODULE: &str = "libykcs11.dll";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub const DEFAULT_YKCS11_MODULE: &str = "libykcs11.so";
+
+/// PIV slot holding a key
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PivSlot {
+    /// 9a
+    Authentication,
+    /// 9c, which requires the PIN for every signature
+    Signature,
+    /// 9d
+    KeyManagement,
+    /// 9e
+    CardAuthentication,
+}
+
+impl PivSlot {
+    fn pkcs11_id(&self) -> u8 {
+        match self {
+            Self::Authentication => 1,
+            Self::Signature => 2,
+            Self::KeyManagement => 3,
+            Self::CardAuthentication => 4,
+        }
+    }
+}
+
+impl FromStr for PivSlot {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "9a" => Ok(Self::Authentication),
+            "9c" => Ok(Self::Signature),
+            "9d" => Ok(Self::KeyManagement),
+            "9e" => Ok(Self::CardAuthentication),
+            _ => Err(anyhow!("PIV slot should be one of 9a, 9c, 9d, or 9e")),
+        }
+    }
+}
+
+impl fmt::Display for PivSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Authentication => "9a",
+            Self::Signature => "9c",
+            Self::KeyManagement => "9d",
+            Self::CardAuthentication => "9e",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Ed25519 key in a YubiKey PIV slot, used through ykcs11
+pub struct YubiKeySigner {
+    signer: Pkcs11Signer,
+    slot: PivSlot,
+    on_touch: Option<Box<dyn Fn() + Send + Sync>>,
+}
+
+impl fmt::Debug for YubiKeySigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("YubiKeySigner")
+            .field("slot", &self.slot)
+            .field("verifying_key", &hex::encode(self.verifying_key()))
+            .finish_non_exhaustive()
+    }
+}
+
+impl YubiKeySigner {
+    /// Use the key in `slot` of the first YubiKey found by `module`, after verifying `pin`
+    pub fn new(module: &Path, slot: PivSlot, pin: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            signer: Pkcs11Signer::with_id(module, None, pin, &[slot.pkcs11_id()])?,
+            slot,
+            on_touch: None,
+        })
+    }
+
+    /// Call `f` before every signature, for instance to tell the user to touch the YubiKey
+    pub fn on_touch(mut self, f: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_touch = Some(Box::new(f));
+        self
+    }
+}
+
+#[async_trait]
+impl Signer for YubiKeySigner {
+    fn verifying_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.signer.verifying_key()
+    }
+
+    async fn sign(&self, message: &[u8]) -> anyhow::Result<[u8; SIGNATURE_LENGTH]> {
+        if let Some(on_touch) = &self.on_touch {
+            on_touch();
+        }
+        self.signer.sign(message).await
+    }
+
+    async fn check(&self) -> anyhow::Result<()> {
+        self.signer.check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piv_slot() {
+        for slot in ["9a", "9c", "9d", "9e"] {
+            assert_eq!(slot.parse::<PivSlot>().unwrap().to_string(), slot);
+        }
+        assert_eq!("9C".parse::<PivSlot>().unwrap(), PivSlot::Signature);
+        assert_eq!(PivSlot::Signature.pkcs11_id(), 2);
+        assert!("82".parse::<PivSlot>().is_err());
+    }
+}
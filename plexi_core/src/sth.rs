@@ -0,0 +1,119 @@
+//! Epochs as [RFC 6962](https://www.rfc-editor.org/rfc/rfc6962#section-4.3) signed tree heads, so that Certificate Transparency monitoring pipelines can ingest them.
+//!
+//! The epoch is the tree size, and the digest the root hash. The tree head signature is the auditor signature as a TLS `DigitallySigned`,
+//! with the Ed25519 and intrinsic hash code points of TLS 1.3. It signs the plexi message of the epoch rather than an RFC 6962 `TreeHeadSignature`,
+//! so it has to be verified with [`SignatureResponse::verify`].
+
+use anyhow::anyhow;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::SignatureResponse;
+
+const HASH_ALGORITHM_INTRINSIC: u8 = 8;
+const SIGNATURE_ALGORITHM_ED25519: u8 = 7;
+
+/// Signed tree head, serialized as the JSON response of the `get-sth` endpoint of a CT log
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignedTreeHead {
+    tree_size: u64,
+    timestamp: u64,
+    #[serde(with = "base64_serde")]
+    sha256_root_hash: Vec<u8>,
+    #[serde(with = "base64_serde")]
+    tree_head_signature: Vec<u8>,
+}
+
+impl SignedTreeHead {
+    pub fn tree_size(&self) -> u64 {
+        self.tree_size
+    }
+
+    /// Milliseconds since the Unix epoch
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn sha256_root_hash(&self) -> &[u8] {
+        &self.sha256_root_hash
+    }
+
+    /// `DigitallySigned` structure holding the auditor signature
+    pub fn tree_head_signature(&self) -> &[u8] {
+        &self.tree_head_signature
+    }
+
+    /// Auditor signature held by the tree head signature
+    pub fn signature(&self) -> anyhow::Result<&[u8]> {
+        let [HASH_ALGORITHM_INTRINSIC, SIGNATURE_ALGORITHM_ED25519, length_high, length_low, signature @ ..] =
+            self.tree_head_signature.as_slice()
+        else {
+            return Err(anyhow!("tree head signature is not an Ed25519 signature"));
+        };
+        if usize::from(u16::from_be_bytes([*length_high, *length_low])) != signature.len() {
+            return Err(anyhow!("tree head signature length is invalid"));
+        }
+        Ok(signature)
+    }
+}
+
+impl From<&SignatureResponse> for SignedTreeHead {
+    fn from(signature: &SignatureResponse) -> Self {
+        let auditor_signature = signature.signature();
+        let mut tree_head_signature = vec![HASH_ALGORITHM_INTRINSIC, SIGNATURE_ALGORITHM_ED25519];
+        tree_head_signature.extend_from_slice(&(auditor_signature.len() as u16).to_be_bytes());
+        tree_head_signature.extend_from_slice(auditor_signature);
+        Self {
+            tree_size: signature.epoch().into(),
+            timestamp: signature.timestamp(),
+            sha256_root_hash: signature.digest_owned(),
+            tree_head_signature,
+        }
+    }
+}
+
+mod base64_serde {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        BASE64
+            .decode(String::deserialize(deserializer)?)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ciphersuite, Epoch, DIGEST_LENGTH};
+
+    #[test]
+    fn test_signed_tree_head() {
+        let signature = SignatureResponse::new(
+            &Ciphersuite::ProtobufEd25519,
+            &Ciphersuite::ProtobufEd25519,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch::from(489193),
+            vec![0xaa; DIGEST_LENGTH],
+            vec![0xbb; 64],
+            None,
+            None,
+        );
+        let sth = SignedTreeHead::from(&signature);
+        assert_eq!(sth.tree_size(), 489193);
+        assert_eq!(sth.signature().unwrap(), signature.signature());
+
+        let json = serde_json::to_value(&sth).unwrap();
+        assert_eq!(json["timestamp"], 1717084639921u64);
+        assert_eq!(
+            json["sha256_root_hash"],
+            "qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqo="
+        );
+        assert_eq!(serde_json::from_value::<SignedTreeHead>(json).unwrap(), sth);
+    }
+}
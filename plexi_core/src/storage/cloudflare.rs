@@ -0,0 +1,739 @@
+use anyhow::{anyhow, Context as _};
+use async_trait::async_trait;
+use reqwest::{header::CONTENT_TYPE, Client, RequestBuilder, StatusCode, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use super::{check_inserted_proof, check_inserted_signature, QueuedReport, Storage, SCHEMA};
+use crate::{
+    auditor::ProofId, namespaces::NamespaceInfo, Epoch, LastVerifiedEpoch, ReportResponse,
+    SignatureResponse, Uuid,
+};
+
+const CLOUDFLARE_API_URL: &str = "https://api.cloudflare.com/client/v4/";
+
+/// Key-value store [`KvStorage`] persists state in
+#[async_trait]
+pub trait KvStore: Send + Sync {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>>;
+
+    async fn put(&self, key: &str, value: &[u8]) -> anyhow::Result<()>;
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+
+    /// Keys starting with `prefix`
+    async fn list(&self, prefix: &str) -> anyhow::Result<Vec<String>>;
+}
+
+/// Storage in a key-value store such as Workers KV
+#[derive(Clone, Debug)]
+pub struct KvStorage<S> {
+    store: S,
+}
+
+fn queue_key(namespace: &str, epoch: &Epoch, digest: &[u8]) -> String {
+    format!(
+        "report-queue/{namespace}/{epoch}/{digest}",
+        digest = hex::encode(digest)
+    )
+}
+
+impl<S: KvStore> KvStorage<S> {
+    pub fn new(store: S) -> Self {
+        Self { store }
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, key: &str) -> anyhow::Result<Option<T>> {
+        self.store
+            .get(key)
+            .await?
+            .map(|value| serde_json::from_slice(&value).with_context(|| format!("decoding {key}")))
+            .transpose()
+    }
+
+    async fn put_json<T: serde::Serialize>(&self, key: &str, value: &T) -> anyhow::Result<()> {
+        self.store.put(key, &serde_json::to_vec(value)?).await
+    }
+}
+
+#[async_trait]
+impl<S: KvStore> Storage for KvStorage<S> {
+    async fn namespaces(&self) -> anyhow::Result<Vec<NamespaceInfo>> {
+        let mut namespaces = vec![];
+        for key in self.store.list("namespaces/").await? {
+            if let Some(info) = self.get_json(&key).await? {
+                namespaces.push(info);
+            }
+        }
+        Ok(namespaces)
+    }
+
+    async fn namespace(&self, namespace: &str) -> anyhow::Result<Option<NamespaceInfo>> {
+        self.get_json(&format!("namespaces/{namespace}")).await
+    }
+
+    async fn insert_namespace(&self, info: &NamespaceInfo) -> anyhow::Result<()> {
+        self.put_json(&format!("namespaces/{name}", name = info.name()), info)
+            .await
+    }
+
+    async fn signature(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+    ) -> anyhow::Result<Option<SignatureResponse>> {
+        self.get_json(&format!("signatures/{namespace}/{epoch}"))
+            .await
+    }
+
+    async fn insert_signature(&self, signature: &SignatureResponse) -> anyhow::Result<()> {
+        let (namespace, epoch) = (signature.namespace(), signature.epoch());
+        if let Some(stored) = self.signature(namespace, epoch).await? {
+            return check_inserted_signature(signature, Some(stored));
+        }
+        self.put_json(&format!("signatures/{namespace}/{epoch}"), signature)
+            .await
+    }
+
+    async fn last_verified_epoch(
+        &self,
+        namespace: &str,
+    ) -> anyhow::Result<Option<LastVerifiedEpoch>> {
+        self.get_json(&format!("last-verified-epochs/{namespace}"))
+            .await
+    }
+
+    async fn set_last_verified_epoch(
+        &self,
+        namespace: &str,
+        last_verified_epoch: &LastVerifiedEpoch,
+    ) -> anyhow::Result<()> {
+        self.put_json(
+            &format!("last-verified-epochs/{namespace}"),
+            last_verified_epoch,
+        )
+        .await
+    }
+
+    async fn report(&self, id: &Uuid) -> anyhow::Result<Option<ReportResponse>> {
+        self.get_json(&format!("reports/{id}")).await
+    }
+
+    async fn insert_report(&self, report: &ReportResponse) -> anyhow::Result<()> {
+        self.put_json(&format!("reports/{id}", id = report.id()), report)
+            .await
+    }
+
+    async fn proof(&self, namespace: &str, blob: &ProofId) -> anyhow::Result<Option<Vec<u8>>> {
+        self.store.get(&format!("proofs/{namespace}/{blob}")).await
+    }
+
+    async fn insert_proof(
+        &self,
+        namespace: &str,
+        blob: &ProofId,
+        proof: &[u8],
+    ) -> anyhow::Result<()> {
+        if let Some(stored) = self.proof(namespace, blob).await? {
+            return check_inserted_proof(namespace, blob, proof, Some(stored));
+        }
+        self.store
+            .put(&format!("proofs/{namespace}/{blob}"), proof)
+            .await
+    }
+
+    async fn queued_report(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+        digest: &[u8],
+    ) -> anyhow::Result<Option<QueuedReport>> {
+        self.get_json(&queue_key(namespace, epoch, digest)).await
+    }
+
+    async fn queued_reports(&self) -> anyhow::Result<Vec<QueuedReport>> {
+        let mut reports: Vec<QueuedReport> = vec![];
+        for key in self.store.list("report-queue/").await? {
+            if let Some(report) = self.get_json(&key).await? {
+                reports.push(report);
+            }
+        }
+        // keys are ordered as strings, which does not order epochs
+        reports.sort_by(|a, b| {
+            (a.namespace(), u64::from(a.epoch())).cmp(&(b.namespace(), u64::from(b.epoch())))
+        });
+        Ok(reports)
+    }
+
+    async fn insert_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+        self.put_json(
+            &queue_key(report.namespace(), report.epoch(), report.digest()),
+            report,
+        )
+        .await
+    }
+
+    async fn remove_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+        self.store
+            .delete(&queue_key(
+                report.namespace(),
+                report.epoch(),
+                report.digest(),
+            ))
+            .await
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    success: bool,
+    #[serde(default)]
+    errors: Vec<Value>,
+    result: Option<T>,
+    result_info: Option<ResultInfo>,
+}
+
+#[derive(Deserialize)]
+struct ResultInfo {
+    cursor: Option<String>,
+}
+
+#[derive(Clone)]
+struct CloudflareApi {
+    client: Client,
+    account_url: Url,
+    api_token: String,
+}
+
+impl std::fmt::Debug for CloudflareApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CloudflareApi")
+            .field("account_url", &self.account_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CloudflareApi {
+    fn new(client: Client, account_id: &str, api_token: &str) -> anyhow::Result<Self> {
+        let mut account_url = Url::parse(CLOUDFLARE_API_URL)?;
+        account_url
+            .path_segments_mut()
+            .map_err(|_| anyhow!("invalid Cloudflare API URL"))?
+            .pop_if_empty()
+            .extend(["accounts", account_id, ""]);
+        Ok(Self {
+            client,
+            account_url,
+            api_token: api_token.to_string(),
+        })
+    }
+
+    fn url<'a>(&self, segments: impl IntoIterator<Item = &'a str>) -> anyhow::Result<Url> {
+        let mut url = self.account_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("invalid Cloudflare API URL"))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    fn authenticated(&self, request: RequestBuilder) -> RequestBuilder {
+        request.bearer_auth(&self.api_token)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> anyhow::Result<ApiResponse<T>> {
+        let response: ApiResponse<T> = self
+            .authenticated(request)
+            .send()
+            .await?
+            .json()
+            .await
+            .context("decoding Cloudflare API response")?;
+        if !response.success {
+            return Err(anyhow!(
+                "Cloudflare API request failed: {errors}",
+                errors = Value::from(response.errors)
+            ));
+        }
+        Ok(response)
+    }
+}
+
+/// Workers KV namespace, accessed through the Cloudflare API
+#[derive(Clone, Debug)]
+pub struct WorkersKv {
+    api: CloudflareApi,
+    namespace_id: String,
+}
+
+impl WorkersKv {
+    pub fn new(
+        client: Client,
+        account_id: &str,
+        namespace_id: &str,
+        api_token: &str,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            api: CloudflareApi::new(client, account_id, api_token)?,
+            namespace_id: namespace_id.to_string(),
+        })
+    }
+
+    fn url(&self, endpoint: &str, key: Option<&str>) -> anyhow::Result<Url> {
+        self.api.url(
+            ["storage", "kv", "namespaces", &self.namespace_id, endpoint]
+                .into_iter()
+                .chain(key),
+        )
+    }
+}
+
+#[async_trait]
+impl KvStore for WorkersKv {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let url = self.url("values", Some(key))?;
+        let response = self
+            .api
+            .authenticated(self.api.client.get(url.clone()))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(
+            response
+                .error_for_status()
+                .with_context(|| format!("fetching {url}"))?
+                .bytes()
+                .await?
+                .to_vec(),
+        ))
+    }
+
+    async fn put(&self, key: &str, value: &[u8]) -> anyhow::Result<()> {
+        let request = self
+            .api
+            .client
+            .put(self.url("values", Some(key))?)
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(value.to_vec());
+        self.api.send::<Value>(request).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let request = self.api.client.delete(self.url("values", Some(key))?);
+        self.api.send::<Value>(request).await?;
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct Key {
+            name: String,
+        }
+
+        let mut keys = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut url = self.url("keys", None)?;
+            url.query_pairs_mut().append_pair("prefix", prefix);
+            if let Some(cursor) = &cursor {
+                url.query_pairs_mut().append_pair("cursor", cursor);
+            }
+            let response = self.api.send::<Vec<Key>>(self.api.client.get(url)).await?;
+            keys.extend(
+                response
+                    .result
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|key| key.name),
+            );
+            cursor = response
+                .result_info
+                .and_then(|info| info.cursor)
+                .filter(|cursor| !cursor.is_empty());
+            if cursor.is_none() {
+                return Ok(keys);
+            }
+        }
+    }
+}
+
+/// Storage in a D1 database, accessed through the Cloudflare API
+#[derive(Clone, Debug)]
+pub struct D1Storage {
+    api: CloudflareApi,
+    database_id: String,
+}
+
+#[derive(Deserialize)]
+struct D1Result {
+    results: Vec<serde_json::Map<String, Value>>,
+}
+
+impl D1Storage {
+    /// Connect to the database, creating the tables if they do not exist
+    pub async fn connect(
+        client: Client,
+        account_id: &str,
+        database_id: &str,
+        api_token: &str,
+    ) -> anyhow::Result<Self> {
+        let storage = Self {
+            api: CloudflareApi::new(client, account_id, api_token)?,
+            database_id: database_id.to_string(),
+        };
+        for statement in SCHEMA {
+            storage
+                .query(&statement.replace("{bytes}", "TEXT"), vec![])
+                .await
+                .context("creating storage tables")?;
+        }
+        Ok(storage)
+    }
+
+    async fn query(
+        &self,
+        sql: &str,
+        params: Vec<Value>,
+    ) -> anyhow::Result<Vec<serde_json::Map<String, Value>>> {
+        let request = self
+            .api
+            .client
+            .post(
+                self.api
+                    .url(["d1", "database", &self.database_id, "query"])?,
+            )
+            .json(&json!({ "sql": sql, "params": params }));
+        Ok(self
+            .api
+            .send::<Vec<D1Result>>(request)
+            .await?
+            .result
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|result| result.results)
+            .collect())
+    }
+
+    async fn query_value(
+        &self,
+        sql: &str,
+        params: Vec<Value>,
+        column: &str,
+    ) -> anyhow::Result<Option<String>> {
+        self.query(sql, params)
+            .await?
+            .first()
+            .map(|row| {
+                row.get(column)
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow!("D1 row does not have a text {column} column"))
+            })
+            .transpose()
+    }
+
+    async fn query_json<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        params: Vec<Value>,
+        column: &str,
+    ) -> anyhow::Result<Option<T>> {
+        self.query_value(sql, params, column)
+            .await?
+            .map(|value| serde_json::from_str(&value).context("decoding stored value"))
+            .transpose()
+    }
+}
+
+#[async_trait]
+impl Storage for D1Storage {
+    async fn namespaces(&self) -> anyhow::Result<Vec<NamespaceInfo>> {
+        self.query("SELECT info FROM namespaces ORDER BY name", vec![])
+            .await?
+            .iter()
+            .map(|row| {
+                let info = row
+                    .get("info")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| anyhow!("D1 row does not have a text info column"))?;
+                serde_json::from_str(info).context("decoding stored value")
+            })
+            .collect()
+    }
+
+    async fn namespace(&self, namespace: &str) -> anyhow::Result<Option<NamespaceInfo>> {
+        self.query_json(
+            "SELECT info FROM namespaces WHERE name = ?1",
+            vec![json!(namespace)],
+            "info",
+        )
+        .await
+    }
+
+    async fn insert_namespace(&self, info: &NamespaceInfo) -> anyhow::Result<()> {
+        self.query(
+            "INSERT INTO namespaces (name, info) VALUES (?1, ?2) ON CONFLICT (name) DO UPDATE SET info = excluded.info",
+            vec![json!(info.name()), json!(serde_json::to_string(info)?)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn signature(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+    ) -> anyhow::Result<Option<SignatureResponse>> {
+        self.query_json(
+            "SELECT signature FROM signatures WHERE namespace = ?1 AND epoch = ?2",
+            vec![json!(namespace), json!(u64::from(epoch))],
+            "signature",
+        )
+        .await
+    }
+
+    async fn insert_signature(&self, signature: &SignatureResponse) -> anyhow::Result<()> {
+        self.query(
+            "INSERT INTO signatures (namespace, epoch, signature) VALUES (?1, ?2, ?3) ON CONFLICT (namespace, epoch) DO NOTHING",
+            vec![
+                json!(signature.namespace()),
+                json!(u64::from(signature.epoch())),
+                json!(serde_json::to_string(signature)?),
+            ],
+        )
+        .await?;
+        let stored = self
+            .signature(signature.namespace(), signature.epoch())
+            .await?;
+        check_inserted_signature(signature, stored)
+    }
+
+    async fn last_verified_epoch(
+        &self,
+        namespace: &str,
+    ) -> anyhow::Result<Option<LastVerifiedEpoch>> {
+        self.query_json(
+            "SELECT last_verified_epoch FROM last_verified_epochs WHERE namespace = ?1",
+            vec![json!(namespace)],
+            "last_verified_epoch",
+        )
+        .await
+    }
+
+    async fn set_last_verified_epoch(
+        &self,
+        namespace: &str,
+        last_verified_epoch: &LastVerifiedEpoch,
+    ) -> anyhow::Result<()> {
+        self.query(
+            "INSERT INTO last_verified_epochs (namespace, last_verified_epoch) VALUES (?1, ?2) ON CONFLICT (namespace) DO UPDATE SET last_verified_epoch = excluded.last_verified_epoch",
+            vec![json!(namespace), json!(serde_json::to_string(last_verified_epoch)?)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn report(&self, id: &Uuid) -> anyhow::Result<Option<ReportResponse>> {
+        self.query_json(
+            "SELECT report FROM reports WHERE id = ?1",
+            vec![json!(id.to_string())],
+            "report",
+        )
+        .await
+    }
+
+    async fn insert_report(&self, report: &ReportResponse) -> anyhow::Result<()> {
+        self.query(
+            "INSERT INTO reports (id, namespace, report) VALUES (?1, ?2, ?3) ON CONFLICT (id) DO UPDATE SET report = excluded.report",
+            vec![
+                json!(report.id().to_string()),
+                json!(report.report().namespace()),
+                json!(serde_json::to_string(report)?),
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn proof(&self, namespace: &str, blob: &ProofId) -> anyhow::Result<Option<Vec<u8>>> {
+        self.query_value(
+            "SELECT proof FROM proofs WHERE namespace = ?1 AND blob = ?2",
+            vec![json!(namespace), json!(blob.to_string())],
+            "proof",
+        )
+        .await?
+        .map(|proof| hex::decode(proof).context("decoding stored proof"))
+        .transpose()
+    }
+
+    async fn insert_proof(
+        &self,
+        namespace: &str,
+        blob: &ProofId,
+        proof: &[u8],
+    ) -> anyhow::Result<()> {
+        self.query(
+            "INSERT INTO proofs (namespace, blob, proof) VALUES (?1, ?2, ?3) ON CONFLICT (namespace, blob) DO NOTHING",
+            vec![
+                json!(namespace),
+                json!(blob.to_string()),
+                json!(hex::encode(proof)),
+            ],
+        )
+        .await?;
+        let stored = self.proof(namespace, blob).await?;
+        check_inserted_proof(namespace, blob, proof, stored)
+    }
+
+    async fn queued_report(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+        digest: &[u8],
+    ) -> anyhow::Result<Option<QueuedReport>> {
+        self.query_json(
+            "SELECT queued_report FROM report_queue WHERE namespace = ?1 AND epoch = ?2 AND digest = ?3",
+            vec![
+                json!(namespace),
+                json!(u64::from(epoch)),
+                json!(hex::encode(digest)),
+            ],
+            "queued_report",
+        )
+        .await
+    }
+
+    async fn queued_reports(&self) -> anyhow::Result<Vec<QueuedReport>> {
+        self.query(
+            "SELECT queued_report FROM report_queue ORDER BY namespace, epoch",
+            vec![],
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            let report = row
+                .get("queued_report")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("D1 row does not have a text queued_report column"))?;
+            serde_json::from_str(report).context("decoding stored value")
+        })
+        .collect()
+    }
+
+    async fn insert_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+        self.query(
+            "INSERT INTO report_queue (namespace, epoch, digest, dead_letter, queued_report) VALUES (?1, ?2, ?3, ?4, ?5) ON CONFLICT (namespace, epoch, digest) DO UPDATE SET dead_letter = excluded.dead_letter, queued_report = excluded.queued_report",
+            vec![
+                json!(report.namespace()),
+                json!(u64::from(report.epoch())),
+                json!(hex::encode(report.digest())),
+                json!(report.is_dead_letter()),
+                json!(serde_json::to_string(report)?),
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn enqueue_report(
+        &self,
+        report: &QueuedReport,
+        max_pending: usize,
+    ) -> anyhow::Result<bool> {
+        let inserted = self
+            .query(
+                "INSERT INTO report_queue (namespace, epoch, digest, dead_letter, queued_report) SELECT ?1, ?2, ?3, ?4, ?5 WHERE (SELECT COUNT(*) FROM report_queue WHERE namespace = ?1 AND dead_letter = FALSE) < ?6 ON CONFLICT (namespace, epoch, digest) DO NOTHING RETURNING namespace",
+                vec![
+                    json!(report.namespace()),
+                    json!(u64::from(report.epoch())),
+                    json!(hex::encode(report.digest())),
+                    json!(report.is_dead_letter()),
+                    json!(serde_json::to_string(report)?),
+                    json!(max_pending),
+                ],
+            )
+            .await?;
+        Ok(!inserted.is_empty())
+    }
+
+    async fn remove_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+        self.query(
+            "DELETE FROM report_queue WHERE namespace = ?1 AND epoch = ?2 AND digest = ?3",
+            vec![
+                json!(report.namespace()),
+                json!(u64::from(report.epoch())),
+                json!(hex::encode(report.digest())),
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.query("SELECT 1", vec![]).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::RwLock};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryKv(RwLock<BTreeMap<String, Vec<u8>>>);
+
+    #[async_trait]
+    impl KvStore for MemoryKv {
+        async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+            Ok(self.0.read().unwrap().get(key).cloned())
+        }
+
+        async fn put(&self, key: &str, value: &[u8]) -> anyhow::Result<()> {
+            self.0
+                .write()
+                .unwrap()
+                .insert(key.to_string(), value.to_vec());
+            Ok(())
+        }
+
+        async fn delete(&self, key: &str) -> anyhow::Result<()> {
+            self.0.write().unwrap().remove(key);
+            Ok(())
+        }
+
+        async fn list(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+            Ok(self
+                .0
+                .read()
+                .unwrap()
+                .keys()
+                .filter(|key| key.starts_with(prefix))
+                .cloned()
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kv_storage() {
+        crate::storage::test_storage(&KvStorage::new(MemoryKv::default())).await;
+    }
+
+    #[test]
+    fn test_workers_kv_urls() {
+        let kv = WorkersKv::new(Client::new(), "account", "namespace", "token").unwrap();
+        assert_eq!(
+            kv.url("values", Some("proofs/example/2/aa/bb"))
+                .unwrap()
+                .as_str(),
+            "https://api.cloudflare.com/client/v4/accounts/account/storage/kv/namespaces/namespace/values/proofs%2Fexample%2F2%2Faa%2Fbb"
+        );
+        assert!(!format!("{kv:?}").contains("token"));
+    }
+}
@@ -0,0 +1,674 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auditor::ProofId, namespaces::NamespaceInfo, Epoch, LastVerifiedEpoch, ReportResponse,
+    SignatureResponse, Uuid,
+};
+
+#[cfg(feature = "workers")]
+mod cloudflare;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+mod sql;
+#[cfg(feature = "workers")]
+pub use cloudflare::{D1Storage, KvStorage, KvStore, WorkersKv};
+#[cfg(feature = "postgres")]
+pub use sql::PostgresStorage;
+#[cfg(feature = "sqlite")]
+pub use sql::SqliteStorage;
+
+#[cfg(any(feature = "sqlite", feature = "postgres", feature = "workers"))]
+const SCHEMA: [&str; 6] = [
+    "CREATE TABLE IF NOT EXISTS namespaces (name TEXT PRIMARY KEY, info TEXT NOT NULL)",
+    "CREATE TABLE IF NOT EXISTS signatures (namespace TEXT NOT NULL, epoch BIGINT NOT NULL, signature TEXT NOT NULL, PRIMARY KEY (namespace, epoch))",
+    "CREATE TABLE IF NOT EXISTS last_verified_epochs (namespace TEXT PRIMARY KEY, last_verified_epoch TEXT NOT NULL)",
+    "CREATE TABLE IF NOT EXISTS reports (id TEXT PRIMARY KEY, namespace TEXT NOT NULL, report TEXT NOT NULL)",
+    "CREATE TABLE IF NOT EXISTS proofs (namespace TEXT NOT NULL, blob TEXT NOT NULL, proof {bytes} NOT NULL, PRIMARY KEY (namespace, blob))",
+    "CREATE TABLE IF NOT EXISTS report_queue (namespace TEXT NOT NULL, epoch BIGINT NOT NULL, digest TEXT NOT NULL, dead_letter BOOLEAN NOT NULL, queued_report TEXT NOT NULL, PRIMARY KEY (namespace, epoch, digest))",
+];
+
+/// Report an auditor has not signed yet, identified by its namespace, epoch, and digest
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QueuedReport {
+    report: ReportResponse,
+    attempts: u32,
+    error: Option<String>,
+    dead_letter: bool,
+    dead_lettered_at: Option<u64>,
+}
+
+impl QueuedReport {
+    pub fn new(report: ReportResponse) -> Self {
+        Self {
+            report,
+            attempts: 0,
+            error: None,
+            dead_letter: false,
+            dead_lettered_at: None,
+        }
+    }
+
+    pub fn report(&self) -> &ReportResponse {
+        &self.report
+    }
+
+    pub fn namespace(&self) -> &str {
+        self.report.report.namespace()
+    }
+
+    pub fn epoch(&self) -> &Epoch {
+        self.report.report.epoch()
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        &self.report.report.digest
+    }
+
+    /// Number of failed attempts at signing the report
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn is_dead_letter(&self) -> bool {
+        self.dead_letter
+    }
+
+    pub fn dead_lettered_at(&self) -> Option<u64> {
+        self.dead_lettered_at
+    }
+
+    /// Record a failed attempt, to be retried
+    pub fn record_failure(&mut self, error: &str) {
+        self.attempts += 1;
+        self.error = Some(error.to_string());
+    }
+
+    /// Record a failed attempt at `timestamp`, in milliseconds since UNIX epoch, and stop retrying the report
+    pub fn dead_letter(&mut self, error: &str, timestamp: u64) {
+        self.record_failure(error);
+        self.dead_letter = true;
+        self.dead_lettered_at = Some(timestamp);
+    }
+}
+
+fn check_inserted_signature(
+    signature: &SignatureResponse,
+    stored: Option<SignatureResponse>,
+) -> anyhow::Result<()> {
+    let (namespace, epoch) = (signature.namespace(), signature.epoch());
+    match stored {
+        Some(stored) if stored.digest() == signature.digest() => Ok(()),
+        Some(_) => Err(anyhow!(
+            "epoch {epoch} of {namespace} is already signed with a different digest"
+        )),
+        None => Err(anyhow!(
+            "signature for epoch {epoch} of {namespace} was not stored"
+        )),
+    }
+}
+
+fn check_inserted_proof(
+    namespace: &str,
+    blob: &ProofId,
+    proof: &[u8],
+    stored: Option<Vec<u8>>,
+) -> anyhow::Result<()> {
+    match stored {
+        Some(stored) if stored == proof => Ok(()),
+        Some(_) => Err(anyhow!(
+            "a different proof is already stored for {blob} of {namespace}"
+        )),
+        None => Err(anyhow!("proof {blob} of {namespace} was not stored")),
+    }
+}
+
+/// Persistent state of an auditor, or of a client monitoring one
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn namespaces(&self) -> anyhow::Result<Vec<NamespaceInfo>>;
+
+    async fn namespace(&self, namespace: &str) -> anyhow::Result<Option<NamespaceInfo>>;
+
+    /// Insert `info`, replacing the namespace with the same name if any
+    async fn insert_namespace(&self, info: &NamespaceInfo) -> anyhow::Result<()>;
+
+    async fn signature(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+    ) -> anyhow::Result<Option<SignatureResponse>>;
+
+    /// Insert `signature`. Fails if the epoch is already signed for another digest
+    async fn insert_signature(&self, signature: &SignatureResponse) -> anyhow::Result<()>;
+
+    async fn last_verified_epoch(
+        &self,
+        namespace: &str,
+    ) -> anyhow::Result<Option<LastVerifiedEpoch>>;
+
+    async fn set_last_verified_epoch(
+        &self,
+        namespace: &str,
+        last_verified_epoch: &LastVerifiedEpoch,
+    ) -> anyhow::Result<()>;
+
+    async fn report(&self, id: &Uuid) -> anyhow::Result<Option<ReportResponse>>;
+
+    async fn insert_report(&self, report: &ReportResponse) -> anyhow::Result<()>;
+
+    async fn proof(&self, namespace: &str, blob: &ProofId) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Insert `proof`, keeping the proof already stored for `blob` if any
+    async fn insert_proof(
+        &self,
+        namespace: &str,
+        blob: &ProofId,
+        proof: &[u8],
+    ) -> anyhow::Result<()>;
+
+    /// Report queued for `namespace` at `epoch` with `digest`, if any
+    async fn queued_report(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+        digest: &[u8],
+    ) -> anyhow::Result<Option<QueuedReport>>;
+
+    /// Queued reports, including dead-lettered ones, ordered by namespace and epoch
+    async fn queued_reports(&self) -> anyhow::Result<Vec<QueuedReport>>;
+
+    /// Insert `report`, replacing the one for the same namespace, epoch, and digest if any
+    async fn insert_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()>;
+
+    /// Queue `report` unless it is already queued, or its namespace has `max_pending` pending reports. Returns whether it was queued
+    async fn enqueue_report(
+        &self,
+        report: &QueuedReport,
+        max_pending: usize,
+    ) -> anyhow::Result<bool> {
+        if self
+            .queued_report(report.namespace(), report.epoch(), report.digest())
+            .await?
+            .is_some()
+        {
+            return Ok(false);
+        }
+        let pending = self
+            .queued_reports()
+            .await?
+            .iter()
+            .filter(|queued| queued.namespace() == report.namespace() && !queued.is_dead_letter())
+            .count();
+        if pending >= max_pending {
+            return Ok(false);
+        }
+        self.insert_queued_report(report).await?;
+        Ok(true)
+    }
+
+    /// Remove `report` from the queue, once it is signed
+    async fn remove_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()>;
+
+    /// Check the storage can be reached
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.namespaces().await.map(|_| ())
+    }
+}
+
+/// Open the storage at `url`
+pub async fn storage_from_url(url: &str) -> anyhow::Result<Arc<dyn Storage>> {
+    let scheme = url.split_once(':').map_or(url, |(scheme, _)| scheme);
+    match scheme {
+        "memory" => Ok(Arc::new(MemoryStorage::new())),
+        #[cfg(feature = "sqlite")]
+        "sqlite" => Ok(Arc::new(SqliteStorage::connect(url).await?)),
+        #[cfg(feature = "postgres")]
+        "postgres" | "postgresql" => Ok(Arc::new(PostgresStorage::connect(url).await?)),
+        #[cfg(feature = "workers")]
+        "workers-kv" | "d1" => {
+            let (account_id, id) = url
+                .strip_prefix(&format!("{scheme}://"))
+                .and_then(|path| path.trim_end_matches('/').split_once('/'))
+                .ok_or_else(|| anyhow!("{url} is not {scheme}://{{account_id}}/{{id}}"))?;
+            let api_token = std::env::var("CLOUDFLARE_API_TOKEN")
+                .map_err(|_| anyhow!("{scheme} storage requires CLOUDFLARE_API_TOKEN"))?;
+            let client = reqwest::Client::new();
+            if scheme == "d1" {
+                Ok(Arc::new(
+                    D1Storage::connect(client, account_id, id, &api_token).await?,
+                ))
+            } else {
+                Ok(Arc::new(KvStorage::new(WorkersKv::new(
+                    client, account_id, id, &api_token,
+                )?)))
+            }
+        }
+        scheme => Err(anyhow!("unsupported storage scheme {scheme}")),
+    }
+}
+
+const LOCK_POISONED: &str = "memory storage lock is poisoned";
+
+/// Storage held in memory
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    namespaces: RwLock<BTreeMap<String, NamespaceInfo>>,
+    signatures: RwLock<HashMap<(String, u64), SignatureResponse>>,
+    last_verified_epochs: RwLock<HashMap<String, LastVerifiedEpoch>>,
+    reports: RwLock<HashMap<Uuid, ReportResponse>>,
+    proofs: RwLock<HashMap<(String, ProofId), Vec<u8>>>,
+    report_queue: RwLock<BTreeMap<QueueKey, QueuedReport>>,
+}
+
+type QueueKey = (String, u64, Vec<u8>);
+
+fn queue_key(report: &QueuedReport) -> QueueKey {
+    (
+        report.namespace().to_string(),
+        report.epoch().into(),
+        report.digest().to_vec(),
+    )
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn namespaces(&self) -> anyhow::Result<Vec<NamespaceInfo>> {
+        Ok(self
+            .namespaces
+            .read()
+            .expect(LOCK_POISONED)
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    async fn namespace(&self, namespace: &str) -> anyhow::Result<Option<NamespaceInfo>> {
+        Ok(self
+            .namespaces
+            .read()
+            .expect(LOCK_POISONED)
+            .get(namespace)
+            .cloned())
+    }
+
+    async fn insert_namespace(&self, info: &NamespaceInfo) -> anyhow::Result<()> {
+        self.namespaces
+            .write()
+            .expect(LOCK_POISONED)
+            .insert(info.name().to_string(), info.clone());
+        Ok(())
+    }
+
+    async fn signature(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+    ) -> anyhow::Result<Option<SignatureResponse>> {
+        Ok(self
+            .signatures
+            .read()
+            .expect(LOCK_POISONED)
+            .get(&(namespace.to_string(), epoch.into()))
+            .cloned())
+    }
+
+    async fn insert_signature(&self, signature: &SignatureResponse) -> anyhow::Result<()> {
+        let stored = self
+            .signatures
+            .write()
+            .expect(LOCK_POISONED)
+            .entry((signature.namespace().to_string(), signature.epoch().into()))
+            .or_insert_with(|| signature.clone())
+            .clone();
+        check_inserted_signature(signature, Some(stored))
+    }
+
+    async fn last_verified_epoch(
+        &self,
+        namespace: &str,
+    ) -> anyhow::Result<Option<LastVerifiedEpoch>> {
+        Ok(self
+            .last_verified_epochs
+            .read()
+            .expect(LOCK_POISONED)
+            .get(namespace)
+            .cloned())
+    }
+
+    async fn set_last_verified_epoch(
+        &self,
+        namespace: &str,
+        last_verified_epoch: &LastVerifiedEpoch,
+    ) -> anyhow::Result<()> {
+        self.last_verified_epochs
+            .write()
+            .expect(LOCK_POISONED)
+            .insert(namespace.to_string(), last_verified_epoch.clone());
+        Ok(())
+    }
+
+    async fn report(&self, id: &Uuid) -> anyhow::Result<Option<ReportResponse>> {
+        Ok(self.reports.read().expect(LOCK_POISONED).get(id).cloned())
+    }
+
+    async fn insert_report(&self, report: &ReportResponse) -> anyhow::Result<()> {
+        self.reports
+            .write()
+            .expect(LOCK_POISONED)
+            .insert(report.id(), report.clone());
+        Ok(())
+    }
+
+    async fn proof(&self, namespace: &str, blob: &ProofId) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self
+            .proofs
+            .read()
+            .expect(LOCK_POISONED)
+            .get(&(namespace.to_string(), *blob))
+            .cloned())
+    }
+
+    async fn insert_proof(
+        &self,
+        namespace: &str,
+        blob: &ProofId,
+        proof: &[u8],
+    ) -> anyhow::Result<()> {
+        let stored = self
+            .proofs
+            .write()
+            .expect(LOCK_POISONED)
+            .entry((namespace.to_string(), *blob))
+            .or_insert_with(|| proof.to_vec())
+            .clone();
+        check_inserted_proof(namespace, blob, proof, Some(stored))
+    }
+
+    async fn queued_report(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+        digest: &[u8],
+    ) -> anyhow::Result<Option<QueuedReport>> {
+        Ok(self
+            .report_queue
+            .read()
+            .expect(LOCK_POISONED)
+            .get(&(namespace.to_string(), epoch.into(), digest.to_vec()))
+            .cloned())
+    }
+
+    async fn queued_reports(&self) -> anyhow::Result<Vec<QueuedReport>> {
+        Ok(self
+            .report_queue
+            .read()
+            .expect(LOCK_POISONED)
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    async fn insert_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+        self.report_queue
+            .write()
+            .expect(LOCK_POISONED)
+            .insert(queue_key(report), report.clone());
+        Ok(())
+    }
+
+    async fn remove_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+        self.report_queue
+            .write()
+            .expect(LOCK_POISONED)
+            .remove(&queue_key(report));
+        Ok(())
+    }
+
+    async fn enqueue_report(
+        &self,
+        report: &QueuedReport,
+        max_pending: usize,
+    ) -> anyhow::Result<bool> {
+        let mut queue = self.report_queue.write().expect(LOCK_POISONED);
+        let key = queue_key(report);
+        let pending = queue
+            .range((key.0.clone(), 0, vec![])..)
+            .take_while(|((namespace, _, _), _)| *namespace == key.0)
+            .filter(|(_, queued)| !queued.is_dead_letter())
+            .count();
+        if queue.contains_key(&key) || pending >= max_pending {
+            return Ok(false);
+        }
+        queue.insert(key, report.clone());
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+pub(crate) async fn test_storage(storage: &dyn Storage) {
+    use crate::{
+        namespaces::{Namespace, NamespaceMetadata, NamespaceStatus},
+        Ciphersuite,
+    };
+
+    let mut namespace = Namespace::new(
+        "example.key-transparency.v1".to_string(),
+        Some("https://example.com".to_string()),
+        None,
+        None,
+        Some(Ciphersuite::ProtobufEd25519),
+    );
+    let metadata = NamespaceMetadata::new(
+        Some("Example key transparency log".to_string()),
+        Some("security@example.com".to_string()),
+        None,
+    );
+    namespace.set_metadata(metadata.clone());
+    let mut info = NamespaceInfo::new(&namespace, NamespaceStatus::Initialization);
+    let name = info.name().to_string();
+
+    assert!(storage.namespace(&name).await.unwrap().is_none());
+    storage.insert_namespace(&info).await.unwrap();
+    info.set_status(NamespaceStatus::Online).unwrap();
+    storage.insert_namespace(&info).await.unwrap();
+    let namespaces = storage.namespaces().await.unwrap();
+    assert_eq!(namespaces.len(), 1);
+    assert_eq!(namespaces[0].status(), NamespaceStatus::Online);
+    assert_eq!(namespaces[0].metadata(), &metadata);
+
+    let signature = SignatureResponse::new(
+        &Ciphersuite::ProtobufEd25519,
+        &Ciphersuite::ProtobufEd25519,
+        name.clone(),
+        1,
+        &Epoch::from(2),
+        vec![0xaa; 32],
+        vec![0; 64],
+        Some(1),
+        None,
+    );
+    storage.insert_signature(&signature).await.unwrap();
+    assert_eq!(
+        storage.signature(&name, &Epoch::from(2)).await.unwrap(),
+        Some(signature.clone())
+    );
+    assert!(storage
+        .signature(&name, &Epoch::from(3))
+        .await
+        .unwrap()
+        .is_none());
+    // an epoch is never signed again for another digest
+    storage.insert_signature(&signature).await.unwrap();
+    let conflicting = SignatureResponse::new(
+        &Ciphersuite::ProtobufEd25519,
+        &Ciphersuite::ProtobufEd25519,
+        name.clone(),
+        1,
+        &Epoch::from(2),
+        vec![0xcc; 32],
+        vec![0; 64],
+        Some(1),
+        None,
+    );
+    assert!(storage.insert_signature(&conflicting).await.is_err());
+    assert_eq!(
+        storage.signature(&name, &Epoch::from(2)).await.unwrap(),
+        Some(signature.clone())
+    );
+
+    let job_id = Uuid::new_v4();
+    let last_verified_epoch =
+        LastVerifiedEpoch::new(job_id, Epoch::from(2), vec![0xbb; 32], vec![0xaa; 32], 1);
+    storage
+        .set_last_verified_epoch(&name, &last_verified_epoch)
+        .await
+        .unwrap();
+    assert_eq!(
+        storage
+            .last_verified_epoch(&name)
+            .await
+            .unwrap()
+            .unwrap()
+            .job_id(),
+        job_id
+    );
+
+    let report = ReportResponse::new(job_id, signature.clone());
+    storage.insert_report(&report).await.unwrap();
+    assert_eq!(
+        storage.report(&job_id).await.unwrap().unwrap().report(),
+        report.report()
+    );
+    assert!(storage.report(&Uuid::new_v4()).await.unwrap().is_none());
+
+    let blob = ProofId {
+        epoch: 2,
+        previous_hash: [0xbb; 32],
+        current_hash: [0xaa; 32],
+    };
+    assert!(storage.proof(&name, &blob).await.unwrap().is_none());
+    storage.insert_proof(&name, &blob, b"proof").await.unwrap();
+    assert_eq!(
+        storage.proof(&name, &blob).await.unwrap(),
+        Some(b"proof".to_vec())
+    );
+    storage.insert_proof(&name, &blob, b"proof").await.unwrap();
+    assert!(storage.insert_proof(&name, &blob, b"other").await.is_err());
+    assert_eq!(
+        storage.proof(&name, &blob).await.unwrap(),
+        Some(b"proof".to_vec())
+    );
+
+    let epoch = Epoch::from(2);
+    assert!(storage
+        .queued_report(&name, &epoch, &[0xaa; 32])
+        .await
+        .unwrap()
+        .is_none());
+    let mut queued = QueuedReport::new(report.clone());
+    storage.insert_queued_report(&queued).await.unwrap();
+    queued.record_failure("proof is not available");
+    storage.insert_queued_report(&queued).await.unwrap();
+    let stored = storage
+        .queued_report(&name, &epoch, &[0xaa; 32])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.attempts(), 1);
+    assert_eq!(stored.report().id(), job_id);
+    assert!(storage
+        .queued_report(&name, &epoch, &[0xbb; 32])
+        .await
+        .unwrap()
+        .is_none());
+
+    let later = QueuedReport::new(ReportResponse::new(
+        Uuid::new_v4(),
+        SignatureResponse::new(
+            &Ciphersuite::ProtobufEd25519,
+            &Ciphersuite::ProtobufEd25519,
+            name.clone(),
+            1,
+            &Epoch::from(10),
+            vec![0xcc; 32],
+            vec![0; 64],
+            None,
+            None,
+        ),
+    ));
+    storage.insert_queued_report(&later).await.unwrap();
+    let epochs: Vec<u64> = storage
+        .queued_reports()
+        .await
+        .unwrap()
+        .iter()
+        .map(|queued| queued.epoch().into())
+        .collect();
+    assert_eq!(epochs, vec![2, 10]);
+
+    storage.remove_queued_report(&queued).await.unwrap();
+    assert_eq!(storage.queued_reports().await.unwrap().len(), 1);
+
+    // reports are only enqueued once, and while the namespace has room for them
+    assert!(!storage.enqueue_report(&later, 10).await.unwrap());
+    assert!(!storage.enqueue_report(&queued, 1).await.unwrap());
+    assert!(storage.enqueue_report(&queued, 2).await.unwrap());
+    let mut dead_letter = storage
+        .queued_report(&name, &epoch, &[0xaa; 32])
+        .await
+        .unwrap()
+        .unwrap();
+    dead_letter.dead_letter("proof does not verify", 1);
+    storage.insert_queued_report(&dead_letter).await.unwrap();
+    let stored = storage
+        .queued_report(&name, &epoch, &[0xaa; 32])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.dead_lettered_at(), Some(1));
+    // dead-lettered reports do not count towards the limit
+    let other = QueuedReport::new(ReportResponse::new(
+        Uuid::new_v4(),
+        SignatureResponse::new(
+            &Ciphersuite::ProtobufEd25519,
+            &Ciphersuite::ProtobufEd25519,
+            name.clone(),
+            1,
+            &Epoch::from(11),
+            vec![0xdd; 32],
+            vec![0; 64],
+            None,
+            None,
+        ),
+    ));
+    assert!(storage.enqueue_report(&other, 2).await.unwrap());
+    assert_eq!(storage.queued_reports().await.unwrap().len(), 3);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_storage() {
+        test_storage(&MemoryStorage::new()).await;
+        assert!(storage_from_url("memory://").await.is_ok());
+        assert!(storage_from_url("redis://localhost").await.is_err());
+    }
+}
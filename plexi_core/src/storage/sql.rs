@@ -0,0 +1,327 @@
+use anyhow::Context as _;
+use async_trait::async_trait;
+
+use super::{check_inserted_proof, check_inserted_signature, QueuedReport, Storage, SCHEMA};
+use crate::{
+    auditor::ProofId, namespaces::NamespaceInfo, Epoch, LastVerifiedEpoch, ReportResponse,
+    SignatureResponse, Uuid,
+};
+
+// epochs are u64, while SQL integers are signed
+fn epoch_to_sql(epoch: &Epoch) -> anyhow::Result<i64> {
+    i64::try_from(u64::from(epoch)).with_context(|| format!("epoch {epoch} is too large"))
+}
+
+fn decode<T: serde::de::DeserializeOwned>(value: Option<String>) -> anyhow::Result<Option<T>> {
+    value
+        .map(|value| serde_json::from_str(&value).context("decoding stored value"))
+        .transpose()
+}
+
+macro_rules! sql_storage {
+    ($(#[$meta:meta])* $name:ident, $pool:ty, $bytes:literal) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug)]
+        pub struct $name {
+            pool: $pool,
+        }
+
+        impl $name {
+            /// Wrap an existing pool, creating the tables if they do not exist
+            pub async fn new(pool: $pool) -> anyhow::Result<Self> {
+                for statement in SCHEMA {
+                    sqlx::query(&statement.replace("{bytes}", $bytes))
+                        .execute(&pool)
+                        .await
+                        .context("creating storage tables")?;
+                }
+                Ok(Self { pool })
+            }
+        }
+
+        #[async_trait]
+        impl Storage for $name {
+            async fn namespaces(&self) -> anyhow::Result<Vec<NamespaceInfo>> {
+                sqlx::query_scalar::<_, String>("SELECT info FROM namespaces ORDER BY name")
+                    .fetch_all(&self.pool)
+                    .await?
+                    .into_iter()
+                    .map(|info| serde_json::from_str(&info).context("decoding stored value"))
+                    .collect()
+            }
+
+            async fn namespace(&self, namespace: &str) -> anyhow::Result<Option<NamespaceInfo>> {
+                decode(
+                    sqlx::query_scalar("SELECT info FROM namespaces WHERE name = $1")
+                        .bind(namespace)
+                        .fetch_optional(&self.pool)
+                        .await?,
+                )
+            }
+
+            async fn insert_namespace(&self, info: &NamespaceInfo) -> anyhow::Result<()> {
+                sqlx::query(
+                    "INSERT INTO namespaces (name, info) VALUES ($1, $2) ON CONFLICT (name) DO UPDATE SET info = excluded.info",
+                )
+                .bind(info.name())
+                .bind(serde_json::to_string(info)?)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn signature(
+                &self,
+                namespace: &str,
+                epoch: &Epoch,
+            ) -> anyhow::Result<Option<SignatureResponse>> {
+                decode(
+                    sqlx::query_scalar(
+                        "SELECT signature FROM signatures WHERE namespace = $1 AND epoch = $2",
+                    )
+                    .bind(namespace)
+                    .bind(epoch_to_sql(epoch)?)
+                    .fetch_optional(&self.pool)
+                    .await?,
+                )
+            }
+
+            async fn insert_signature(&self, signature: &SignatureResponse) -> anyhow::Result<()> {
+                sqlx::query(
+                    "INSERT INTO signatures (namespace, epoch, signature) VALUES ($1, $2, $3) ON CONFLICT (namespace, epoch) DO NOTHING",
+                )
+                .bind(signature.namespace())
+                .bind(epoch_to_sql(signature.epoch())?)
+                .bind(serde_json::to_string(signature)?)
+                .execute(&self.pool)
+                .await?;
+                let stored = self.signature(signature.namespace(), signature.epoch()).await?;
+                check_inserted_signature(signature, stored)
+            }
+
+            async fn last_verified_epoch(
+                &self,
+                namespace: &str,
+            ) -> anyhow::Result<Option<LastVerifiedEpoch>> {
+                decode(
+                    sqlx::query_scalar(
+                        "SELECT last_verified_epoch FROM last_verified_epochs WHERE namespace = $1",
+                    )
+                    .bind(namespace)
+                    .fetch_optional(&self.pool)
+                    .await?,
+                )
+            }
+
+            async fn set_last_verified_epoch(
+                &self,
+                namespace: &str,
+                last_verified_epoch: &LastVerifiedEpoch,
+            ) -> anyhow::Result<()> {
+                sqlx::query(
+                    "INSERT INTO last_verified_epochs (namespace, last_verified_epoch) VALUES ($1, $2) ON CONFLICT (namespace) DO UPDATE SET last_verified_epoch = excluded.last_verified_epoch",
+                )
+                .bind(namespace)
+                .bind(serde_json::to_string(last_verified_epoch)?)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn report(&self, id: &Uuid) -> anyhow::Result<Option<ReportResponse>> {
+                decode(
+                    sqlx::query_scalar("SELECT report FROM reports WHERE id = $1")
+                        .bind(id.to_string())
+                        .fetch_optional(&self.pool)
+                        .await?,
+                )
+            }
+
+            async fn insert_report(&self, report: &ReportResponse) -> anyhow::Result<()> {
+                sqlx::query(
+                    "INSERT INTO reports (id, namespace, report) VALUES ($1, $2, $3) ON CONFLICT (id) DO UPDATE SET report = excluded.report",
+                )
+                .bind(report.id().to_string())
+                .bind(report.report().namespace())
+                .bind(serde_json::to_string(report)?)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn proof(
+                &self,
+                namespace: &str,
+                blob: &ProofId,
+            ) -> anyhow::Result<Option<Vec<u8>>> {
+                Ok(sqlx::query_scalar(
+                    "SELECT proof FROM proofs WHERE namespace = $1 AND blob = $2",
+                )
+                .bind(namespace)
+                .bind(blob.to_string())
+                .fetch_optional(&self.pool)
+                .await?)
+            }
+
+            async fn insert_proof(
+                &self,
+                namespace: &str,
+                blob: &ProofId,
+                proof: &[u8],
+            ) -> anyhow::Result<()> {
+                sqlx::query(
+                    "INSERT INTO proofs (namespace, blob, proof) VALUES ($1, $2, $3) ON CONFLICT (namespace, blob) DO NOTHING",
+                )
+                .bind(namespace)
+                .bind(blob.to_string())
+                .bind(proof)
+                .execute(&self.pool)
+                .await?;
+                let stored = self.proof(namespace, blob).await?;
+                check_inserted_proof(namespace, blob, proof, stored)
+            }
+
+            async fn queued_report(
+                &self,
+                namespace: &str,
+                epoch: &Epoch,
+                digest: &[u8],
+            ) -> anyhow::Result<Option<QueuedReport>> {
+                decode(
+                    sqlx::query_scalar(
+                        "SELECT queued_report FROM report_queue WHERE namespace = $1 AND epoch = $2 AND digest = $3",
+                    )
+                    .bind(namespace)
+                    .bind(epoch_to_sql(epoch)?)
+                    .bind(hex::encode(digest))
+                    .fetch_optional(&self.pool)
+                    .await?,
+                )
+            }
+
+            async fn queued_reports(&self) -> anyhow::Result<Vec<QueuedReport>> {
+                sqlx::query_scalar::<_, String>(
+                    "SELECT queued_report FROM report_queue ORDER BY namespace, epoch",
+                )
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .map(|report| serde_json::from_str(&report).context("decoding stored value"))
+                .collect()
+            }
+
+            async fn insert_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+                sqlx::query(
+                    "INSERT INTO report_queue (namespace, epoch, digest, dead_letter, queued_report) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (namespace, epoch, digest) DO UPDATE SET dead_letter = excluded.dead_letter, queued_report = excluded.queued_report",
+                )
+                .bind(report.namespace())
+                .bind(epoch_to_sql(report.epoch())?)
+                .bind(hex::encode(report.digest()))
+                .bind(report.is_dead_letter())
+                .bind(serde_json::to_string(report)?)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn enqueue_report(
+                &self,
+                report: &QueuedReport,
+                max_pending: usize,
+            ) -> anyhow::Result<bool> {
+                let result = sqlx::query(
+                    "INSERT INTO report_queue (namespace, epoch, digest, dead_letter, queued_report) SELECT $1, $2, $3, $4, $5 WHERE (SELECT COUNT(*) FROM report_queue WHERE namespace = $6 AND dead_letter = FALSE) < $7 ON CONFLICT (namespace, epoch, digest) DO NOTHING",
+                )
+                .bind(report.namespace())
+                .bind(epoch_to_sql(report.epoch())?)
+                .bind(hex::encode(report.digest()))
+                .bind(report.is_dead_letter())
+                .bind(serde_json::to_string(report)?)
+                .bind(report.namespace())
+                .bind(i64::try_from(max_pending)?)
+                .execute(&self.pool)
+                .await?;
+                Ok(result.rows_affected() == 1)
+            }
+
+            async fn remove_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+                sqlx::query(
+                    "DELETE FROM report_queue WHERE namespace = $1 AND epoch = $2 AND digest = $3",
+                )
+                .bind(report.namespace())
+                .bind(epoch_to_sql(report.epoch())?)
+                .bind(hex::encode(report.digest()))
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn ping(&self) -> anyhow::Result<()> {
+                sqlx::query("SELECT 1").execute(&self.pool).await?;
+                Ok(())
+            }
+        }
+    };
+}
+
+#[cfg(feature = "sqlite")]
+sql_storage!(
+    /// Storage in a SQLite database, for a single auditor or client
+    SqliteStorage,
+    sqlx::SqlitePool,
+    "BLOB"
+);
+
+#[cfg(feature = "sqlite")]
+impl SqliteStorage {
+    /// Open the database at `url`, such as `sqlite://plexi.db`, creating it if it does not exist
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        use std::str::FromStr as _;
+
+        let options = sqlx::sqlite::SqliteConnectOptions::from_str(url)
+            .with_context(|| format!("parsing {url}"))?
+            .create_if_missing(true);
+        let pool = sqlx::SqlitePool::connect_with(options)
+            .await
+            .with_context(|| format!("opening {url}"))?;
+        Self::new(pool).await
+    }
+}
+
+#[cfg(feature = "postgres")]
+sql_storage!(
+    /// Storage in a Postgres database, which can be shared by multiple auditor instances
+    PostgresStorage,
+    sqlx::PgPool,
+    "BYTEA"
+);
+
+#[cfg(feature = "postgres")]
+impl PostgresStorage {
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        let pool = sqlx::PgPool::connect(url)
+            .await
+            .context("connecting to Postgres")?;
+        Self::new(pool).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_storage() {
+        // each connection to an in-memory database is a different database
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let storage = super::SqliteStorage::new(pool).await.unwrap();
+        crate::storage::test_storage(&storage).await;
+        // tables already exist
+        assert!(super::SqliteStorage::new(storage.pool.clone())
+            .await
+            .is_ok());
+    }
+}
@@ -0,0 +1,190 @@
+//! proptest [`Arbitrary`] implementations generating well-formed values, so that `any::<SignatureResponse>()` and the like
+//! can drive property tests of integrations. Signatures are random bytes, so they do not verify.
+
+use ed25519_dalek::SIGNATURE_LENGTH;
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection, option,
+    sample::select,
+    strategy::{BoxedStrategy, Strategy},
+};
+
+use crate::{
+    auditor::AkdConfig,
+    namespaces::{Namespace, NamespaceInfo, NamespaceMetadata, NamespaceStatus},
+    Ciphersuite, DigestAlgorithm, Epoch, SignatureMessage, SignatureResponse, DIGEST_ALGORITHMS,
+    DIGEST_LENGTH, SIGNATURE_VERSIONS,
+};
+
+fn digest() -> impl Strategy<Value = Vec<u8>> {
+    collection::vec(any::<u8>(), DIGEST_LENGTH)
+}
+
+impl Arbitrary for Epoch {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<u64>().prop_map(Epoch).boxed()
+    }
+}
+
+impl Arbitrary for Ciphersuite {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        select(SIGNATURE_VERSIONS.to_vec()).boxed()
+    }
+}
+
+impl Arbitrary for DigestAlgorithm {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        select(DIGEST_ALGORITHMS.to_vec()).boxed()
+    }
+}
+
+impl Arbitrary for SignatureMessage {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            any::<Ciphersuite>(),
+            any::<String>(),
+            any::<u64>(),
+            any::<Epoch>(),
+            digest(),
+            option::of(digest()),
+            collection::btree_map(any::<String>(), any::<String>(), 0..4),
+            option::of(any::<DigestAlgorithm>()),
+        )
+            .prop_map(
+                |(
+                    ciphersuite,
+                    namespace,
+                    timestamp,
+                    epoch,
+                    digest,
+                    previous_digest,
+                    metadata,
+                    digest_algorithm,
+                )| {
+                    let message =
+                        SignatureMessage::new(&ciphersuite, namespace, timestamp, &epoch, digest)
+                            .expect("ciphersuite is supported");
+                    if ciphersuite != Ciphersuite::ProtobufV2Ed25519 {
+                        return message;
+                    }
+                    let mut message = message.with_metadata(metadata);
+                    if let Some(digest_algorithm) = digest_algorithm {
+                        message = message.with_digest_algorithm(digest_algorithm);
+                    }
+                    match previous_digest {
+                        Some(previous_digest) => message.with_previous_digest(previous_digest),
+                        None => message,
+                    }
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for SignatureResponse {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            any::<SignatureMessage>(),
+            collection::vec(any::<u8>(), SIGNATURE_LENGTH),
+            option::of(any::<u8>()),
+        )
+            .prop_map(|(message, signature, key_id)| {
+                SignatureResponse::new(
+                    message.ciphersuite(),
+                    message.ciphersuite(),
+                    message.namespace().to_string(),
+                    message.timestamp(),
+                    message.epoch(),
+                    message.digest_owned(),
+                    signature,
+                    key_id,
+                    None,
+                )
+                .with_message_fields(&message)
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for NamespaceInfo {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            any::<String>(),
+            option::of(any::<String>()),
+            option::of((any::<Epoch>(), digest())),
+            any::<Ciphersuite>(),
+            select(vec![AkdConfig::WhatsAppV1, AkdConfig::Experimental]),
+            collection::vec(any::<String>(), 0..4),
+            any::<(Option<String>, Option<String>, Option<String>)>(),
+            select(vec![
+                NamespaceStatus::Online,
+                NamespaceStatus::Initialization,
+                NamespaceStatus::Disabled,
+            ]),
+            option::of(any::<Epoch>()),
+        )
+            .prop_map(
+                |(
+                    name,
+                    log_directory,
+                    root,
+                    ciphersuite,
+                    akd_config,
+                    aliases,
+                    (description, contact, policy_url),
+                    status,
+                    last_verified_epoch,
+                )| {
+                    let root =
+                        root.map(|(epoch, digest)| epoch.as_root_epoch(&hex::encode(digest)));
+                    let mut namespace =
+                        Namespace::new(name, log_directory, root, None, Some(ciphersuite));
+                    namespace.set_akd_config(akd_config);
+                    namespace.set_aliases(aliases);
+                    namespace.set_metadata(NamespaceMetadata::new(
+                        description,
+                        contact,
+                        policy_url,
+                    ));
+                    let mut info = NamespaceInfo::new(&namespace, status);
+                    info.set_last_verified_epoch(last_verified_epoch);
+                    info
+                },
+            )
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::SignatureResponse;
+
+    proptest! {
+        #[test]
+        fn test_signature_response_serialization(signature in any::<SignatureResponse>()) {
+            let serialized = serde_json::to_string(&signature).unwrap();
+            let deserialized: SignatureResponse = serde_json::from_str(&serialized).unwrap();
+            prop_assert_eq!(deserialized, signature);
+        }
+    }
+}
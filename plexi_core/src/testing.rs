@@ -0,0 +1,144 @@
+//! Reproducible test vectors for implementations of plexi signature verification, in the format of `tests/test-vectors.json`.
+//!
+//! Signing keys, epochs, timestamps, and digests are derived from a seed with SHA-256, and Ed25519 signatures are deterministic,
+//! so that the same seed always yields the same vectors. Every ciphersuite [`SignatureMessage::new`] accepts can be generated.
+
+use anyhow::anyhow;
+use ed25519_dalek::{
+    Signer as _, SigningKey, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH, SIGNATURE_LENGTH,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+use crate::{crypto::ed25519_public_key_to_key_id, Ciphersuite, Epoch, SignatureMessage};
+
+const VECTOR_CONTEXT: &[u8] = b"plexi test vectors v1\0";
+const BASE_TIMESTAMP: u64 = 1717084639921;
+
+/// Epoch signed with a known key, and the signature an implementation has to compute or verify
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestVector {
+    #[serde(with = "hex::serde")]
+    pub signing_key: [u8; SECRET_KEY_LENGTH],
+    #[serde(with = "hex::serde")]
+    pub verifying_key: [u8; PUBLIC_KEY_LENGTH],
+    pub key_id: u8,
+    pub namespace: String,
+    pub timestamp: u64,
+    pub epoch: Epoch,
+    #[serde(with = "hex::serde")]
+    pub digest: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub signature: [u8; SIGNATURE_LENGTH],
+    pub ciphersuite: Ciphersuite,
+}
+
+impl TestVector {
+    /// Check the keys match, and the signature verifies over the message of the epoch
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let verifying_key = SigningKey::from_bytes(&self.signing_key).verifying_key();
+        if verifying_key.to_bytes() != self.verifying_key {
+            return Err(anyhow!("verifying_key is not the one of signing_key"));
+        }
+        if ed25519_public_key_to_key_id(&self.verifying_key) != self.key_id {
+            return Err(anyhow!("key_id is not the one of verifying_key"));
+        }
+        let message = SignatureMessage::new(
+            &self.ciphersuite,
+            self.namespace.clone(),
+            self.timestamp,
+            &self.epoch,
+            self.digest.clone(),
+        )?;
+        verifying_key
+            .verify_strict(
+                &message.to_vec()?,
+                &ed25519_dalek::Signature::from_bytes(&self.signature),
+            )
+            .map_err(|_| anyhow!("signature does not verify"))
+    }
+}
+
+fn derive(seed: u64, index: usize, label: &str) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(VECTOR_CONTEXT)
+        .chain_update(seed.to_be_bytes())
+        .chain_update((index as u64).to_be_bytes())
+        .chain_update(label.as_bytes())
+        .finalize()
+        .into()
+}
+
+fn derive_u64(seed: u64, index: usize, label: &str) -> u64 {
+    let value = derive(seed, index, label);
+    u64::from_be_bytes(value[..8].try_into().expect("derived values are 32 bytes"))
+}
+
+/// `count` vectors signed under `ciphersuite`, derived from `seed`
+pub fn generate_vectors(
+    ciphersuite: &Ciphersuite,
+    count: usize,
+    seed: u64,
+) -> anyhow::Result<Vec<TestVector>> {
+    (0..count)
+        .map(|index| {
+            let signing_key = SigningKey::from_bytes(&derive(seed, index, "signing_key"));
+            let verifying_key = signing_key.verifying_key().to_bytes();
+            // epochs of every magnitude, so that variable length encodings are exercised
+            let epoch = Epoch::from(derive_u64(seed, index, "epoch") >> (index % 64));
+            let timestamp = BASE_TIMESTAMP + derive_u64(seed, index, "timestamp") % (1 << 32);
+            let message = SignatureMessage::new(
+                ciphersuite,
+                format!("log{n}.example.com", n = index + 1),
+                timestamp,
+                &epoch,
+                derive(seed, index, "digest").to_vec(),
+            )?;
+            let signature = signing_key.sign(&message.to_vec()?);
+            Ok(TestVector {
+                signing_key: signing_key.to_bytes(),
+                verifying_key,
+                key_id: ed25519_public_key_to_key_id(&verifying_key),
+                namespace: message.namespace().to_string(),
+                timestamp,
+                epoch,
+                digest: message.digest_owned(),
+                signature: signature.to_bytes(),
+                ciphersuite: *ciphersuite,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SIGNATURE_VERSIONS;
+
+    #[test]
+    fn test_generate_vectors() {
+        for ciphersuite in SIGNATURE_VERSIONS {
+            let vectors = generate_vectors(&ciphersuite, 8, 42).unwrap();
+            assert_eq!(vectors.len(), 8);
+            for vector in &vectors {
+                vector.verify().unwrap();
+            }
+            assert_eq!(generate_vectors(&ciphersuite, 8, 42).unwrap(), vectors);
+            assert_ne!(generate_vectors(&ciphersuite, 8, 43).unwrap(), vectors);
+
+            let json = serde_json::to_string(&vectors).unwrap();
+            let parsed: Vec<TestVector> = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, vectors);
+        }
+        assert!(generate_vectors(&Ciphersuite::Unknown(0xffff), 1, 42).is_err());
+    }
+
+    #[test]
+    fn test_checked_in_vectors() {
+        let vectors: Vec<TestVector> =
+            serde_json::from_str(include_str!("../tests/test-vectors.json")).unwrap();
+        for vector in vectors {
+            vector.verify().unwrap();
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Submission of checkpoints to witnesses speaking the [tlog-witness](https://github.com/C2SP/C2SP/blob/main/tlog-witness.md) protocol, such as Sigsum witnesses.
+//! A witness cosigns a head once it checked it does not conflict with the heads it saw before, so that a log cannot show different heads to different parties.
+//!
+//! akd append-only proofs are not Merkle consistency proofs, so checkpoints are submitted without them.
+//! Witnesses have to be configured to accept plexi origins on the checkpoint signature alone.
+
+use anyhow::{anyhow, Context as _};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ed25519_dalek::{PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use reqwest::{StatusCode, Url};
+
+use crate::checkpoint::{
+    cosignature_key_hash, Checkpoint, Cosignature, KEY_HASH_LENGTH, SIGNATURE_LINE_PREFIX,
+};
+
+const TIMESTAMP_LENGTH: usize = 8;
+
+/// Witness checkpoints are submitted to
+#[derive(Clone, Debug)]
+pub struct Witness {
+    name: String,
+    url: Url,
+    verifying_key: [u8; PUBLIC_KEY_LENGTH],
+    client: reqwest::Client,
+}
+
+impl Witness {
+    /// Witness at `url`, whose cosignature key is named `name`
+    pub fn new(name: &str, url: Url, verifying_key: [u8; PUBLIC_KEY_LENGTH]) -> Self {
+        Self {
+            name: name.to_string(),
+            url,
+            verifying_key,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Submit the signed checkpoint `note` of `checkpoint`, and return the cosignature of the witness
+    pub async fn add_checkpoint(
+        &self,
+        checkpoint: &Checkpoint,
+        note: &str,
+        old_size: u64,
+    ) -> anyhow::Result<Cosignature> {
+        let url = self
+            .url
+            .join("add-checkpoint")
+            .context("building add-checkpoint URL")?;
+        let mut old_size = old_size;
+        let mut retried = false;
+        loop {
+            let response = self
+                .client
+                .post(url.clone())
+                .body(format!("old {old_size}\n\n{note}"))
+                .send()
+                .await?;
+            let status = response.status();
+            let body = response.text().await?;
+            match status {
+                StatusCode::OK => return self.cosignature(checkpoint, &body),
+                // the witness answers with the size it knows the origin at
+                StatusCode::CONFLICT if !retried => {
+                    old_size = body
+                        .trim()
+                        .parse()
+                        .context("parsing the tree size the witness knows")?;
+                    retried = true;
+                }
+                _ => {
+                    return Err(anyhow!(
+                        "{name} rejected the checkpoint with {status}: {body}",
+                        name = self.name,
+                        body = body.trim()
+                    ))
+                }
+            }
+        }
+    }
+
+    fn cosignature(&self, checkpoint: &Checkpoint, response: &str) -> anyhow::Result<Cosignature> {
+        let key_hash = cosignature_key_hash(&self.name, &self.verifying_key);
+        for line in response.lines() {
+            let Some((name, signature)) = line
+                .strip_prefix(SIGNATURE_LINE_PREFIX)
+                .and_then(|line| line.split_once(' '))
+            else {
+                continue;
+            };
+            if name != self.name {
+                continue;
+            }
+            let signature = BASE64.decode(signature).context("parsing cosignature")?;
+            if signature.len() != KEY_HASH_LENGTH + TIMESTAMP_LENGTH + SIGNATURE_LENGTH
+                || signature[..KEY_HASH_LENGTH] != key_hash
+            {
+                continue;
+            }
+            let (timestamp, signature) = signature[KEY_HASH_LENGTH..].split_at(TIMESTAMP_LENGTH);
+            let cosignature = Cosignature::new(
+                &self.name,
+                u64::from_be_bytes(timestamp.try_into().expect("split at its length")),
+                signature.to_vec(),
+            );
+            cosignature.verify(checkpoint, &self.verifying_key)?;
+            return Ok(cosignature);
+        }
+        Err(anyhow!(
+            "{name} did not cosign the checkpoint",
+            name = self.name
+        ))
+    }
+}
@@ -0,0 +1,50 @@
+/*
+ * C bindings to verify plexi signatures and audit proofs.
+ *
+ * Functions return PLEXI_OK when verification succeeds, and a negative error code otherwise.
+ */
+
+#ifndef PLEXI_H
+#define PLEXI_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Verification succeeded */
+#define PLEXI_OK 0
+/* A pointer is null, or an argument is malformed */
+#define PLEXI_ERR_INVALID_ARGUMENT -1
+/* The signature or the proof does not verify */
+#define PLEXI_ERR_VERIFICATION_FAILED -2
+/* Verification failed unexpectedly */
+#define PLEXI_ERR_INTERNAL -3
+
+/* AKD configuration used by WhatsApp Key Transparency */
+#define PLEXI_AKD_CONFIG_WHATSAPP_V1 1
+/* akd experimental configuration */
+#define PLEXI_AKD_CONFIG_EXPERIMENTAL 2
+
+/*
+ * Verify a signature, JSON encoded as the auditor serves it, against a 32 byte Ed25519 public key.
+ * During a ciphersuite migration, either signature is accepted.
+ */
+int plexi_verify_signature(const uint8_t *signature_json, size_t signature_json_len,
+                           const uint8_t *verifying_key, size_t verifying_key_len);
+
+/*
+ * Verify an append-only proof of epoch `epoch`, from `previous_digest` to `current_digest`, both 32 bytes.
+ * This blocks the calling thread until verification completes.
+ */
+int plexi_verify_proof(uint32_t akd_config, uint64_t epoch,
+                       const uint8_t *previous_digest, const uint8_t *current_digest,
+                       const uint8_t *proof, size_t proof_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PLEXI_H */
@@ -0,0 +1,239 @@
+//! C bindings to verify plexi signatures and audit proofs, so that C and C++ clients do not reimplement the message encodings.
+//!
+//! Functions return [`PLEXI_OK`] when verification succeeds, and a negative error code otherwise. They never unwind into C.
+//! Declarations are in `include/plexi.h`.
+//!
+//! With the `uniffi` feature, the same checks are exported to Kotlin and Swift, see [`mobile`].
+
+use std::{ffi::c_int, panic, slice};
+
+use plexi_core::{
+    auditor::{self, AkdConfig, ProofId},
+    SignatureResponse, DIGEST_LENGTH,
+};
+
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+/// Verification succeeded
+pub const PLEXI_OK: c_int = 0;
+/// A pointer is null, or an argument is malformed
+pub const PLEXI_ERR_INVALID_ARGUMENT: c_int = -1;
+/// The signature or the proof does not verify
+pub const PLEXI_ERR_VERIFICATION_FAILED: c_int = -2;
+/// Verification failed unexpectedly
+pub const PLEXI_ERR_INTERNAL: c_int = -3;
+
+/// AKD configuration used by WhatsApp Key Transparency
+pub const PLEXI_AKD_CONFIG_WHATSAPP_V1: u32 = 1;
+/// akd experimental configuration
+pub const PLEXI_AKD_CONFIG_EXPERIMENTAL: u32 = 2;
+
+const PUBLIC_KEY_LENGTH: usize = 32;
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error))]
+pub enum Error {
+    #[error("invalid argument")]
+    InvalidArgument,
+    #[error("verification failed")]
+    VerificationFailed,
+}
+
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        return Some(&[]);
+    }
+    if ptr.is_null() {
+        return None;
+    }
+    Some(slice::from_raw_parts(ptr, len))
+}
+
+fn status(verify: impl FnOnce() -> Result<(), Error> + panic::UnwindSafe) -> c_int {
+    match panic::catch_unwind(verify) {
+        Ok(Ok(())) => PLEXI_OK,
+        Ok(Err(Error::InvalidArgument)) => PLEXI_ERR_INVALID_ARGUMENT,
+        Ok(Err(Error::VerificationFailed)) => PLEXI_ERR_VERIFICATION_FAILED,
+        Err(_) => PLEXI_ERR_INTERNAL,
+    }
+}
+
+/// Verify a signature, JSON encoded as the auditor serves it, against an Ed25519 public key
+#[no_mangle]
+pub unsafe extern "C" fn plexi_verify_signature(
+    signature_json: *const u8,
+    signature_json_len: usize,
+    verifying_key: *const u8,
+    verifying_key_len: usize,
+) -> c_int {
+    let (Some(signature_json), Some(verifying_key)) = (
+        bytes(signature_json, signature_json_len),
+        bytes(verifying_key, verifying_key_len),
+    ) else {
+        return PLEXI_ERR_INVALID_ARGUMENT;
+    };
+    status(|| verify_signature(signature_json, verifying_key))
+}
+
+/// Verify an append-only proof of an epoch, from the digest of the epoch before to the digest of the epoch
+#[no_mangle]
+pub unsafe extern "C" fn plexi_verify_proof(
+    akd_config: u32,
+    epoch: u64,
+    previous_digest: *const u8,
+    current_digest: *const u8,
+    proof: *const u8,
+    proof_len: usize,
+) -> c_int {
+    let (Some(previous_digest), Some(current_digest), Some(proof)) = (
+        bytes(previous_digest, DIGEST_LENGTH),
+        bytes(current_digest, DIGEST_LENGTH),
+        bytes(proof, proof_len),
+    ) else {
+        return PLEXI_ERR_INVALID_ARGUMENT;
+    };
+    status(|| verify_proof(akd_config, epoch, previous_digest, current_digest, proof))
+}
+
+fn verify_signature(signature_json: &[u8], verifying_key: &[u8]) -> Result<(), Error> {
+    if verifying_key.len() != PUBLIC_KEY_LENGTH {
+        return Err(Error::InvalidArgument);
+    }
+    let signature: SignatureResponse =
+        serde_json::from_slice(signature_json).map_err(|_| Error::InvalidArgument)?;
+    signature
+        .verify(verifying_key)
+        .map_err(|_| Error::VerificationFailed)
+}
+
+fn verify_proof(
+    akd_config: u32,
+    epoch: u64,
+    previous_digest: &[u8],
+    current_digest: &[u8],
+    proof: &[u8],
+) -> Result<(), Error> {
+    let akd_config = match akd_config {
+        PLEXI_AKD_CONFIG_WHATSAPP_V1 => AkdConfig::WhatsAppV1,
+        PLEXI_AKD_CONFIG_EXPERIMENTAL => AkdConfig::Experimental,
+        _ => return Err(Error::InvalidArgument),
+    };
+    let blob = ProofId {
+        epoch,
+        previous_hash: previous_digest
+            .try_into()
+            .map_err(|_| Error::InvalidArgument)?,
+        current_hash: current_digest
+            .try_into()
+            .map_err(|_| Error::InvalidArgument)?,
+    };
+    auditor::verify_raw_proof_sync(akd_config, &blob, proof).map_err(|_| Error::VerificationFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer as _, SigningKey, SECRET_KEY_LENGTH};
+    use plexi_core::{Ciphersuite, Epoch, SignatureMessage};
+
+    use super::*;
+
+    fn signed(signing_key: &SigningKey) -> Vec<u8> {
+        let message = SignatureMessage::new(
+            &Ciphersuite::BincodeEd25519,
+            "example.key-transparency.v1".to_string(),
+            1717084639921,
+            &Epoch::from(2),
+            vec![0xaa; DIGEST_LENGTH],
+        )
+        .unwrap();
+        let signature = SignatureResponse::new(
+            message.ciphersuite(),
+            message.ciphersuite(),
+            message.namespace().to_string(),
+            message.timestamp(),
+            message.epoch(),
+            message.digest_owned(),
+            signing_key.sign(&message.to_vec().unwrap()).to_vec(),
+            None,
+            None,
+        );
+        serde_json::to_vec(&signature).unwrap()
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let signing_key = SigningKey::from_bytes(&[0x42; SECRET_KEY_LENGTH]);
+        let verifying_key = signing_key.verifying_key().to_bytes();
+        let other_key = SigningKey::from_bytes(&[0x43; SECRET_KEY_LENGTH])
+            .verifying_key()
+            .to_bytes();
+        let signature = signed(&signing_key);
+
+        let verify = |signature: &[u8], key: &[u8]| unsafe {
+            plexi_verify_signature(signature.as_ptr(), signature.len(), key.as_ptr(), key.len())
+        };
+        assert_eq!(verify(&signature, &verifying_key), PLEXI_OK);
+        assert_eq!(
+            verify(&signature, &other_key),
+            PLEXI_ERR_VERIFICATION_FAILED
+        );
+        assert_eq!(
+            verify(&signature, &verifying_key[1..]),
+            PLEXI_ERR_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            verify(b"not json", &verifying_key),
+            PLEXI_ERR_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            unsafe {
+                plexi_verify_signature(
+                    std::ptr::null(),
+                    signature.len(),
+                    verifying_key.as_ptr(),
+                    verifying_key.len(),
+                )
+            },
+            PLEXI_ERR_INVALID_ARGUMENT
+        );
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let digest = [0xaa; DIGEST_LENGTH];
+        let verify = |akd_config: u32, previous_digest: *const u8, proof: &[u8]| unsafe {
+            plexi_verify_proof(
+                akd_config,
+                2,
+                previous_digest,
+                digest.as_ptr(),
+                proof.as_ptr(),
+                proof.len(),
+            )
+        };
+        assert_eq!(
+            verify(
+                PLEXI_AKD_CONFIG_WHATSAPP_V1,
+                digest.as_ptr(),
+                b"not a proof"
+            ),
+            PLEXI_ERR_VERIFICATION_FAILED
+        );
+        assert_eq!(
+            verify(0, digest.as_ptr(), b"not a proof"),
+            PLEXI_ERR_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            verify(
+                PLEXI_AKD_CONFIG_WHATSAPP_V1,
+                std::ptr::null(),
+                b"not a proof"
+            ),
+            PLEXI_ERR_INVALID_ARGUMENT
+        );
+    }
+}
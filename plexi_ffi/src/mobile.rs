@@ -0,0 +1,63 @@
+//! Verification core exported to Kotlin and Swift with [UniFFI](https://mozilla.github.io/uniffi-rs/).
+//!
+//! Bindings are generated from the built library with the `uniffi-bindgen` binary of this crate.
+
+use std::collections::{BTreeMap, HashMap};
+
+use plexi_core::{crypto::ed25519_public_key_to_key_id, Ciphersuite, Epoch, SignatureMessage};
+
+use crate::Error;
+
+/// Message an auditor signs for `epoch` of `namespace` under `ciphersuite`
+#[uniffi::export]
+pub fn signature_message(
+    ciphersuite: u32,
+    namespace: String,
+    timestamp: u64,
+    epoch: u64,
+    digest: Vec<u8>,
+    previous_digest: Option<Vec<u8>>,
+    metadata: HashMap<String, String>,
+) -> Result<Vec<u8>, Error> {
+    let mut message = SignatureMessage::new(
+        &Ciphersuite::from(ciphersuite),
+        namespace,
+        timestamp,
+        &Epoch::from(epoch),
+        digest,
+    )
+    .map_err(|_| Error::InvalidArgument)?
+    .with_metadata(BTreeMap::from_iter(metadata));
+    if let Some(previous_digest) = previous_digest {
+        message = message.with_previous_digest(previous_digest);
+    }
+    message.to_vec().map_err(|_| Error::InvalidArgument)
+}
+
+/// Verify a signature, JSON encoded as the auditor serves it, against an Ed25519 public key
+#[uniffi::export]
+pub fn verify_signature(signature_json: String, verifying_key: Vec<u8>) -> Result<(), Error> {
+    crate::verify_signature(signature_json.as_bytes(), &verifying_key)
+}
+
+/// Verify an append-only proof of `epoch`, from the digest of the epoch before to the digest of the epoch
+#[uniffi::export]
+pub fn verify_proof(
+    akd_config: u32,
+    epoch: u64,
+    previous_digest: Vec<u8>,
+    current_digest: Vec<u8>,
+    proof: Vec<u8>,
+) -> Result<(), Error> {
+    crate::verify_proof(akd_config, epoch, &previous_digest, &current_digest, &proof)
+}
+
+/// ID of the auditor key `verifying_key`, as set in the `key_id` of its signatures
+#[uniffi::export]
+pub fn key_id(verifying_key: Vec<u8>) -> Result<u8, Error> {
+    let verifying_key = verifying_key
+        .as_slice()
+        .try_into()
+        .map_err(|_| Error::InvalidArgument)?;
+    Ok(ed25519_public_key_to_key_id(verifying_key))
+}
@@ -0,0 +1,293 @@
+use plexi_core::{
+    namespaces::{root_epoch, Namespace, NamespaceInfo, NamespaceStatus},
+    Epoch,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{ApiError, Auditor};
+
+#[derive(Debug, Default)]
+pub(crate) struct AdminToken(Option<[u8; 32]>);
+
+impl AdminToken {
+    pub(crate) fn new(token: &str) -> Self {
+        Self(Some(Sha256::digest(token).into()))
+    }
+}
+
+impl Auditor {
+    /// Enable namespace management, for requests bearing `token`
+    pub fn with_admin_token(mut self, token: &str) -> Self {
+        self.admin_token = AdminToken::new(token);
+        self
+    }
+
+    /// Check `token` is the admin token
+    pub fn authorize(&self, token: Option<&str>) -> Result<(), ApiError> {
+        let AdminToken(Some(expected)) = &self.admin_token else {
+            return Err(ApiError::Unauthorized(
+                "the admin API is disabled".to_string(),
+            ));
+        };
+        match token {
+            Some(token) if Sha256::digest(token).as_slice() == expected => Ok(()),
+            Some(_) => Err(ApiError::Unauthorized("invalid admin token".to_string())),
+            None => Err(ApiError::Unauthorized("missing admin token".to_string())),
+        }
+    }
+
+    /// Start auditing `namespace`
+    pub async fn create_namespace(&self, namespace: &Namespace) -> Result<NamespaceInfo, ApiError> {
+        namespace
+            .validate()
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let name = namespace.name();
+        let _processing = self.processing.lock(name).await;
+        if self.state().namespace(name).await?.is_some() {
+            return Err(ApiError::Conflict(format!(
+                "namespace {name} already exists"
+            )));
+        }
+        self.check_aliases(namespace).await?;
+        self.state().add_namespace(namespace).await?;
+        self.namespace_info(name).await
+    }
+
+    /// Replace the log directory, AKD configuration, next ciphersuite, aliases, and metadata of `namespace`
+    pub async fn update_namespace(&self, namespace: &Namespace) -> Result<NamespaceInfo, ApiError> {
+        namespace
+            .validate()
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        let name = namespace.name();
+        // reports may be signed against the current root in the meantime
+        let _processing = self.processing.lock(name).await;
+
+        let mut info = self.namespace_info(name).await?;
+        if *namespace.ciphersuite() != info.ciphersuite() {
+            if info.next_ciphersuite() != Some(*namespace.ciphersuite()) {
+                return Err(ApiError::BadRequest(format!(
+                    "namespace {name} uses ciphersuite {ciphersuite}, which can only change to its next ciphersuite",
+                    ciphersuite = info.ciphersuite()
+                )));
+            }
+            info.complete_migration()
+                .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        }
+        self.check_aliases(namespace).await?;
+        info.update_from(namespace);
+        match (info.root(), namespace.root()) {
+            (_, None) => {}
+            (None, Some(root)) => {
+                info.set_root(root);
+                if info.status() == NamespaceStatus::Initialization {
+                    info.set_status(NamespaceStatus::Online)
+                        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+                }
+            }
+            (Some(current), Some(root)) if current == root => {}
+            (Some(current), Some(root)) => {
+                let verified = match self.state().last_verified_epoch(name).await? {
+                    Some(last_verified_epoch) => last_verified_epoch.epoch(),
+                    None => root_epoch(current).unwrap_or(Epoch::from(0)),
+                };
+                if !root_epoch(root).is_some_and(|epoch| epoch > verified) {
+                    return Err(ApiError::BadRequest(format!(
+                        "namespace {name} root is {current}, a new root must come after epoch {verified}"
+                    )));
+                }
+                info.set_root(root);
+            }
+        }
+        self.state().update_namespace(&info).await?;
+        self.namespace_info(name).await
+    }
+
+    /// Stop signing epochs for the namespace `name`
+    pub async fn disable_namespace(&self, name: &str) -> Result<NamespaceInfo, ApiError> {
+        let _processing = self.processing.lock(name).await;
+
+        let mut info = self.namespace_info(name).await?;
+        info.set_status(NamespaceStatus::Disabled)
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        info.set_last_verified_epoch(None);
+        self.state().update_namespace(&info).await?;
+        self.namespace_info(name).await
+    }
+
+    async fn check_aliases(&self, namespace: &Namespace) -> Result<(), ApiError> {
+        let namespaces = self.state().namespaces().await?;
+        for alias in
+            std::iter::once(namespace.name()).chain(namespace.aliases().iter().map(String::as_str))
+        {
+            if let Some(other) = namespaces
+                .iter()
+                .find(|info| info.name() != namespace.name() && info.is_named(alias))
+            {
+                return Err(ApiError::Conflict(format!(
+                    "{alias} already names namespace {other}",
+                    other = other.name()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn namespace_info(&self, name: &str) -> Result<NamespaceInfo, ApiError> {
+        self.state()
+            .namespace(name)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("namespace {name}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::IntoFuture as _, sync::Arc};
+
+    use plexi_core::{client::PlexiClient, namespaces::NamespaceMetadata, Ciphersuite};
+    use reqwest::Url;
+
+    use super::*;
+    use crate::tests::{auditor, report, root};
+
+    const NAMESPACE: &str = "example.key-transparency.v1";
+    const ADMIN_TOKEN: &str = "admin-token";
+
+    fn namespace(root: Option<&str>, ciphersuite: Ciphersuite) -> Namespace {
+        Namespace::new(
+            NAMESPACE.to_string(),
+            Some("https://example.com".to_string()),
+            root.map(str::to_string),
+            None,
+            Some(ciphersuite),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_admin() {
+        let auditor = Arc::new(
+            auditor(serde_json::json!([]))
+                .await
+                .with_admin_token(ADMIN_TOKEN),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!(
+            "http://{addr}",
+            addr = listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        tokio::spawn(axum::serve(listener, crate::router(auditor.clone())).into_future());
+
+        let client = PlexiClient::builder(url.clone())
+            .bearer_token(ADMIN_TOKEN)
+            .build()
+            .unwrap();
+        let unauthorized = PlexiClient::builder(url.clone())
+            .bearer_token("not-the-token")
+            .build()
+            .unwrap();
+        let anonymous = PlexiClient::new(url, None, None).unwrap();
+
+        let mut created = namespace(None, Ciphersuite::ProtobufEd25519);
+        created.set_aliases(vec!["example".to_string()]);
+        assert!(unauthorized.create_namespace(&created).await.is_err());
+        assert!(anonymous.create_namespace(&created).await.is_err());
+        let info = client.create_namespace(&created).await.unwrap();
+        assert_eq!(info.status(), NamespaceStatus::Initialization);
+        assert!(client.create_namespace(&created).await.is_err());
+        assert_eq!(
+            client.namespace("example").await.unwrap().unwrap().name(),
+            NAMESPACE
+        );
+        let mut other = Namespace::new(
+            "other.key-transparency.v1".to_string(),
+            None,
+            None,
+            None,
+            Some(Ciphersuite::ProtobufEd25519),
+        );
+        other.set_aliases(vec!["example".to_string()]);
+        assert!(client.create_namespace(&other).await.is_err());
+
+        // reports are rejected until the root is set, which updates keep
+        assert!(matches!(
+            auditor
+                .submit_report(report(NAMESPACE, 1, [0xaa; 32]))
+                .await,
+            Err(ApiError::BadRequest(_))
+        ));
+        let info = client
+            .update_namespace(&namespace(
+                Some(&root(1, [0xaa; 32])),
+                Ciphersuite::ProtobufEd25519,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(info.status(), NamespaceStatus::Online);
+        auditor
+            .submit_report(report(NAMESPACE, 1, [0xaa; 32]))
+            .await
+            .unwrap();
+        let mut updated = namespace(None, Ciphersuite::ProtobufEd25519);
+        updated.set_metadata(NamespaceMetadata::new(
+            Some("Example log".to_string()),
+            None,
+            None,
+        ));
+        let info = client.update_namespace(&updated).await.unwrap();
+        assert_eq!(info.metadata().description(), Some("Example log"));
+        assert_eq!(info.status(), NamespaceStatus::Online);
+        assert!(info.root().is_some());
+        assert!(client
+            .update_namespace(&namespace(Some("1/bb"), Ciphersuite::ProtobufEd25519))
+            .await
+            .is_err());
+
+        // the log is rebuilt: epochs after the new root are verified against it
+        let rebuilt = root(5, [0xcc; 32]);
+        let info = client
+            .update_namespace(&namespace(Some(&rebuilt), Ciphersuite::ProtobufEd25519))
+            .await
+            .unwrap();
+        assert_eq!(info.root(), Some(rebuilt.as_str()));
+        assert_eq!(info.root_history().len(), 1);
+        assert_eq!(
+            info.root_for_epoch(&Epoch::from(1)),
+            info.root_history().first().map(String::as_str)
+        );
+        auditor
+            .submit_report(report(NAMESPACE, 5, [0xcc; 32]))
+            .await
+            .unwrap();
+        assert!(client
+            .update_namespace(&namespace(Some("3/dd"), Ciphersuite::ProtobufEd25519))
+            .await
+            .is_err());
+        assert!(client
+            .update_namespace(&namespace(None, Ciphersuite::BincodeEd25519))
+            .await
+            .is_err());
+
+        let info = client.disable_namespace(NAMESPACE).await.unwrap();
+        assert_eq!(info.status(), NamespaceStatus::Disabled);
+        assert!(matches!(
+            auditor
+                .submit_report(report(NAMESPACE, 2, [0xbb; 32]))
+                .await,
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(client.disable_namespace("unknown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_admin_disabled() {
+        let auditor = auditor(serde_json::json!([])).await;
+        assert!(matches!(
+            auditor.authorize(Some(ADMIN_TOKEN)),
+            Err(ApiError::Unauthorized(_))
+        ));
+        let auditor = auditor.with_admin_token(ADMIN_TOKEN);
+        assert!(auditor.authorize(Some(ADMIN_TOKEN)).is_ok());
+        assert!(auditor.authorize(None).is_err());
+    }
+}
@@ -0,0 +1,586 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::anyhow;
+use plexi_core::{
+    auditor::{self, ProofId},
+    client::{download_client, store::proof_store_from_url},
+    crypto::constant_time_eq,
+    namespaces::{NamespaceInfo, NamespaceStatus},
+    signer::{sign_last_verified_epoch, sign_message, Signer},
+    Ciphersuite, Epoch, LastVerifiedEpoch, Report, SignatureMessage, SignatureResponse, Uuid,
+};
+use serde::Serialize;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::{
+    admin::AdminToken, gossip::Gossip, queue::ReportQueue, witness::Witnessing, ApiError,
+    AuditorState, QueueConfig,
+};
+
+type Digest = [u8; 32];
+
+/// Result of [`Auditor::ready`], each check being `ok` or the reason it failed
+#[derive(Clone, Debug, Serialize)]
+pub struct Readiness {
+    pub storage: String,
+    pub signer: String,
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.storage == "ok" && self.signer == "ok"
+    }
+}
+
+/// Auditor signing the epochs logs report, once their proof is verified
+pub struct Auditor {
+    state: AuditorState,
+    pub(crate) signer: Arc<dyn Signer>,
+    client: reqwest::Client,
+    pub(crate) processing: NamespaceLocks,
+    pub(crate) queue: ReportQueue,
+    pub(crate) gossip: Gossip,
+    pub(crate) witnessing: Witnessing,
+    pub(crate) admin_token: AdminToken,
+}
+
+impl Auditor {
+    /// Fails if the signer key is not part of the keys the auditor advertises
+    pub fn new(state: AuditorState, signer: Arc<dyn Signer>) -> anyhow::Result<Self> {
+        let verifying_key = hex::encode(signer.verifying_key());
+        if !state
+            .configuration()
+            .keys()
+            .iter()
+            .any(|key| key.public_key() == &verifying_key)
+        {
+            return Err(anyhow!(
+                "signing key {verifying_key} is not in the auditor configuration"
+            ));
+        }
+        Ok(Self {
+            state,
+            signer,
+            client: download_client()?,
+            processing: NamespaceLocks::default(),
+            queue: ReportQueue::new(QueueConfig::default()),
+            gossip: Gossip::default(),
+            witnessing: Witnessing::default(),
+            admin_token: AdminToken::default(),
+        })
+    }
+
+    /// Retry queued reports according to `config`
+    pub fn with_queue_config(mut self, config: QueueConfig) -> Self {
+        self.queue = ReportQueue::new(config);
+        self
+    }
+
+    pub fn state(&self) -> &AuditorState {
+        &self.state
+    }
+
+    /// Check the auditor can sign epochs: its storage can be reached and its key is available
+    pub async fn ready(&self) -> Readiness {
+        let status = |result: anyhow::Result<()>| match result {
+            Ok(()) => "ok".to_string(),
+            Err(e) => {
+                tracing::warn!("readiness check failed: {e:#}");
+                format!("{e:#}")
+            }
+        };
+        Readiness {
+            storage: status(self.state.ping().await),
+            signer: status(self.signer.check().await),
+        }
+    }
+
+    /// Validate `report` against the namespace history, verify the proof it refers to, then sign and store the epoch
+    #[tracing::instrument(skip_all, fields(namespace = report.namespace(), epoch = %report.epoch()))]
+    pub async fn process_report(
+        &self,
+        job_id: Uuid,
+        report: &Report,
+    ) -> Result<SignatureResponse, ApiError> {
+        let result = self.sign_report(job_id, report).await;
+        if let Err(e) = &result {
+            // unknown namespaces are not labelled, so that clients cannot create arbitrary series
+            let namespace = match e {
+                ApiError::NotFound(_) => "unknown",
+                _ => report.namespace(),
+            };
+            self.state.metrics().record_rejection(namespace, e);
+        }
+        result
+    }
+
+    async fn sign_report(
+        &self,
+        job_id: Uuid,
+        report: &Report,
+    ) -> Result<SignatureResponse, ApiError> {
+        let name = report.namespace();
+        // locks are only taken for the namespaces the auditor knows, so that reports cannot grow them
+        if self.state.namespace(name).await?.is_none() {
+            return Err(ApiError::NotFound(format!("namespace {name}")));
+        }
+        let (namespace, _, step) = {
+            let _processing = self.processing.lock(name).await;
+            self.check_report(report).await?
+        };
+
+        // the proof is downloaded and verified without the lock, so that it does not hold up the other reports and updates
+        // of the namespace. The report is checked again once the lock is taken back, as the namespace may have changed since
+        let raw_proof = match &step {
+            Step::Signed(signature) => return Ok(signature.clone()),
+            Step::Root => None,
+            Step::Verify(blob) => Some(self.verify_proof(&namespace, blob).await?),
+        };
+
+        let _processing = self.processing.lock(name).await;
+        let (namespace, digest, checked) = self.check_report(report).await?;
+        let epoch = *report.epoch();
+        let previous_digest = match (step, checked, raw_proof) {
+            (_, Step::Signed(signature), _) => return Ok(signature),
+            (Step::Root, Step::Root, _) => None,
+            (Step::Verify(verified), Step::Verify(blob), Some(raw_proof)) if verified == blob => {
+                self.state.insert_proof(name, &blob, &raw_proof).await?;
+                Some(blob.previous_hash)
+            }
+            _ => {
+                return Err(ApiError::Unavailable(format!(
+                    "namespace {name} changed while epoch {epoch} was verified"
+                )))
+            }
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(anyhow::Error::from)?
+            .as_millis() as u64;
+        let message = |ciphersuite: Ciphersuite| {
+            let message = SignatureMessage::new(
+                &ciphersuite,
+                name.to_string(),
+                timestamp,
+                &epoch,
+                digest.to_vec(),
+            )
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+            if ciphersuite != Ciphersuite::ProtobufV2Ed25519 {
+                return Ok(message);
+            }
+            // the versioned message also signs the digest the proof starts from, and the hash function of the log if the namespace declares it
+            let message = match previous_digest {
+                Some(previous_digest) => message.with_previous_digest(previous_digest.to_vec()),
+                None => message,
+            };
+            Ok::<_, ApiError>(match namespace.declared_digest_algorithm() {
+                Some(digest_algorithm) => message.with_digest_algorithm(digest_algorithm),
+                None => message,
+            })
+        };
+        let mut signature =
+            sign_message(self.signer.as_ref(), &message(namespace.ciphersuite())?).await?;
+        // while migrating, verifiers may support either ciphersuite
+        if let Some(next_ciphersuite) = namespace.next_ciphersuite() {
+            let migration = sign_message(self.signer.as_ref(), &message(next_ciphersuite)?).await?;
+            signature = signature.with_migration_signature(&migration);
+        }
+
+        if let Err(e) = self.state.insert_signature(&signature).await {
+            // an auditor sharing the storage may have signed the epoch since it was checked above
+            return match self.state.signature(name, &epoch).await? {
+                Some(stored) if !constant_time_eq(stored.digest(), &digest) => {
+                    Err(ApiError::Conflict(format!(
+                        "epoch {epoch} is already signed with a different digest"
+                    )))
+                }
+                _ => Err(e.into()),
+            };
+        }
+        let last_verified_epoch = sign_last_verified_epoch(
+            self.signer.as_ref(),
+            name,
+            LastVerifiedEpoch::new(
+                job_id,
+                epoch,
+                previous_digest.unwrap_or(digest).to_vec(),
+                digest.to_vec(),
+                timestamp,
+            ),
+        )
+        .await?;
+        self.state
+            .set_last_verified_epoch(name, &last_verified_epoch)
+            .await?;
+        self.state
+            .metrics()
+            .record_signature(name, epoch.into(), timestamp);
+        tracing::info!("signed {name} epoch {epoch}");
+
+        Ok(signature)
+    }
+
+    async fn check_report(
+        &self,
+        report: &Report,
+    ) -> Result<(NamespaceInfo, Digest, Step), ApiError> {
+        let name = report.namespace();
+        let namespace = self
+            .state
+            .namespace(name)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("namespace {name}")))?;
+        if namespace.status() == NamespaceStatus::Disabled {
+            return Err(ApiError::BadRequest(format!(
+                "namespace {name} is disabled"
+            )));
+        }
+        if !namespace.accepts_ciphersuite(report.ciphersuite()) {
+            return Err(ApiError::BadRequest(format!(
+                "namespace {name} uses ciphersuite {expected}",
+                expected = namespace.ciphersuite()
+            )));
+        }
+        if let Some(digest_algorithm) = namespace.digest_algorithm() {
+            report
+                .verify_digest_algorithm(digest_algorithm)
+                .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        }
+        let digest: Digest = report
+            .digest()
+            .try_into()
+            .map_err(|_| ApiError::BadRequest("digest length invalid".to_string()))?;
+        let epoch = *report.epoch();
+
+        if let Some(signature) = self.state.signature(name, &epoch).await? {
+            return if constant_time_eq(signature.digest(), &digest) {
+                Ok((namespace, digest, Step::Signed(signature)))
+            } else {
+                Err(ApiError::Conflict(format!(
+                    "epoch {epoch} is already signed with a different digest"
+                )))
+            };
+        }
+
+        // a report never sets the root, as anyone able to submit one could then choose the history the auditor vouches for
+        if namespace.root().is_none() {
+            return Err(ApiError::BadRequest(format!(
+                "namespace {name} has no root, set it in the configuration or with the admin API"
+            )));
+        }
+        let root = epoch.as_root_epoch(&hex::encode(digest));
+        let step = match namespace.is_first_epoch(&root) {
+            Ok(true) => Step::Root,
+            Ok(false) => Step::Verify(self.proof_blob(&namespace, epoch, digest).await?),
+            Err(_) => {
+                return Err(ApiError::BadRequest(format!(
+                    "epoch {epoch} does not match namespace {name} root"
+                )))
+            }
+        };
+        Ok((namespace, digest, step))
+    }
+
+    async fn proof_blob(
+        &self,
+        namespace: &NamespaceInfo,
+        epoch: Epoch,
+        digest: Digest,
+    ) -> Result<ProofId, ApiError> {
+        let name = namespace.name();
+        let (root_epoch, root_digest) = namespace
+            .root()
+            .and_then(|root| root.split_once('/'))
+            .ok_or_else(|| anyhow!("namespace {name} has an invalid root"))?;
+        let root_epoch: Epoch = root_epoch.parse().map_err(anyhow::Error::from)?;
+        if epoch <= root_epoch {
+            return Err(ApiError::BadRequest(format!(
+                "epoch {epoch} is not after namespace {name} root"
+            )));
+        }
+
+        let previous_epoch = epoch - 1;
+        let previous_digest = if previous_epoch == root_epoch {
+            hex::decode(root_digest).map_err(anyhow::Error::from)?
+        } else {
+            self.state
+                .signature(name, &previous_epoch)
+                .await?
+                .ok_or_else(|| {
+                    ApiError::Unavailable(format!("epoch {previous_epoch} has not been audited"))
+                })?
+                .digest_owned()
+        };
+        let previous_digest: Digest = previous_digest
+            .try_into()
+            .map_err(|_| anyhow!("epoch {previous_epoch} digest length invalid"))?;
+
+        Ok(ProofId {
+            epoch: epoch.into(),
+            previous_hash: previous_digest,
+            current_hash: digest,
+        })
+    }
+
+    async fn verify_proof(
+        &self,
+        namespace: &NamespaceInfo,
+        blob: &ProofId,
+    ) -> Result<Vec<u8>, ApiError> {
+        let name = namespace.name();
+        let start = Instant::now();
+        let log_directory = namespace.log_directory().ok_or_else(|| {
+            ApiError::BadRequest(format!("namespace {name} does not have a log directory"))
+        })?;
+        let raw_proof = proof_store_from_url(&self.client, log_directory)?
+            .get(blob)
+            .await?
+            .ok_or_else(|| {
+                ApiError::Unavailable(format!("proof {blob} is not available in {log_directory}"))
+            })?;
+
+        let verification =
+            auditor::verify_raw_proof(namespace.akd_config(), blob, &raw_proof).await;
+        self.state.metrics().observe_verification(name, start);
+        verification.map_err(|e| ApiError::BadRequest(format!("invalid proof: {e:#}")))?;
+
+        Ok(raw_proof)
+    }
+}
+
+enum Step {
+    Signed(SignatureResponse),
+    Root,
+    Verify(ProofId),
+}
+
+#[derive(Default)]
+pub(crate) struct NamespaceLocks(std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>);
+
+impl NamespaceLocks {
+    pub(crate) async fn lock(&self, namespace: &str) -> OwnedMutexGuard<()> {
+        let lock = self
+            .0
+            .lock()
+            .expect("namespace locks poisoned")
+            .entry(namespace.to_string())
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use akd::{
+        ecvrf::HardCodedAkdVRF,
+        storage::{memory::AsyncInMemoryDatabase, StorageManager},
+        AkdLabel, AkdValue, Directory, WhatsAppV1Configuration,
+    };
+    use plexi_core::Uuid;
+    use protobuf::Message as _;
+
+    use super::*;
+    use crate::tests::{auditor, report, root};
+
+    const NAMESPACE: &str = "example.key-transparency.v1";
+
+    pub(crate) async fn publish(root: &std::path::Path) -> (Digest, Digest) {
+        let storage = StorageManager::new_no_cache(AsyncInMemoryDatabase::new());
+        let directory =
+            Directory::<WhatsAppV1Configuration, _, _>::new(storage, HardCodedAkdVRF {})
+                .await
+                .unwrap();
+        let mut hashes = vec![];
+        for epoch in 1..=2 {
+            let updates = vec![(
+                AkdLabel::from(format!("label-{epoch}").as_str()),
+                AkdValue::from("value"),
+            )];
+            hashes.push(directory.publish(updates).await.unwrap().hash());
+        }
+
+        let audit = directory.audit(1, 2).await.unwrap();
+        let blob = ProofId {
+            epoch: 2,
+            previous_hash: hashes[0],
+            current_hash: hashes[1],
+        };
+        let path = root.join(blob.to_string());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            path,
+            akd::proto::specs::types::SingleAppendOnlyProof::from(&audit.proofs[0])
+                .write_to_bytes()
+                .unwrap(),
+        )
+        .unwrap();
+        (hashes[0], hashes[1])
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_process_report() {
+        let directory = tempfile::TempDir::with_prefix("plexi-server-").unwrap();
+        let (first, second) = publish(directory.path()).await;
+        let auditor = auditor(serde_json::json!([{
+        "name": NAMESPACE,
+        "ciphersuite": 0x0001,
+        "log_directory": reqwest::Url::from_file_path(directory.path()).unwrap().to_string(),
+        "root": root(1, first),
+        }]))
+        .await;
+
+        // the root epoch is signed without a proof
+        auditor
+            .process_report(Uuid::new_v4(), &report(NAMESPACE, 1, first))
+            .await
+            .unwrap();
+        let namespace = auditor.state().namespace(NAMESPACE).await.unwrap().unwrap();
+        assert_eq!(namespace.status(), NamespaceStatus::Online);
+
+        // epoch 2 proof is verified from the log directory
+        let signature = auditor
+            .process_report(Uuid::new_v4(), &report(NAMESPACE, 2, second))
+            .await
+            .unwrap();
+        assert_eq!(signature.digest(), second);
+        let last_verified_epoch = auditor
+            .state()
+            .last_verified_epoch(NAMESPACE)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(last_verified_epoch.epoch(), 2);
+        assert_eq!(last_verified_epoch.start_hash(), first);
+        assert!(last_verified_epoch
+            .verify(NAMESPACE, &auditor.signer.verifying_key())
+            .is_ok());
+
+        // resubmission returns the existing signature
+        assert_eq!(
+            auditor
+                .process_report(Uuid::new_v4(), &report(NAMESPACE, 2, second))
+                .await
+                .unwrap(),
+            signature
+        );
+        assert!(matches!(
+            auditor
+                .process_report(Uuid::new_v4(), &report(NAMESPACE, 2, first))
+                .await,
+            Err(ApiError::Conflict(_))
+        ));
+        // there is no proof for epoch 3 yet
+        assert!(matches!(
+            auditor
+                .process_report(Uuid::new_v4(), &report(NAMESPACE, 3, [0; 32]))
+                .await,
+            Err(ApiError::Unavailable(_))
+        ));
+        // epochs cannot be skipped
+        assert!(matches!(
+            auditor
+                .process_report(Uuid::new_v4(), &report(NAMESPACE, 5, [0; 32]))
+                .await,
+            Err(ApiError::Unavailable(_))
+        ));
+
+        let metrics = auditor.state().metrics().encode().unwrap();
+        assert!(metrics.contains(&format!(
+            "plexi_signed_epochs_total{{namespace=\"{NAMESPACE}\"}} 2"
+        )));
+        assert!(metrics.contains(&format!(
+            "plexi_rejected_reports_total{{namespace=\"{NAMESPACE}\",reason=\"conflict\"}} 1"
+        )));
+        assert!(metrics.contains(&format!(
+            "plexi_verification_duration_seconds_count{{namespace=\"{NAMESPACE}\"}} 1"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_namespace_locks() {
+        let locks = NamespaceLocks::default();
+        let held = locks.lock(NAMESPACE).await;
+        // other namespaces are not held up
+        let _other = locks.lock("other.key-transparency.v1").await;
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(50), locks.lock(NAMESPACE))
+                .await
+                .is_err()
+        );
+        drop(held);
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(50), locks.lock(NAMESPACE))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_report_without_root() {
+        let auditor =
+            auditor(serde_json::json!([{ "name": NAMESPACE, "ciphersuite": 0x0001 }])).await;
+
+        // the first report does not become the root
+        assert!(matches!(
+            auditor
+                .process_report(Uuid::new_v4(), &report(NAMESPACE, 1, [0xaa; 32]))
+                .await,
+            Err(ApiError::BadRequest(_))
+        ));
+        let namespace = auditor.state().namespace(NAMESPACE).await.unwrap().unwrap();
+        assert_eq!(namespace.status(), NamespaceStatus::Initialization);
+        assert_eq!(namespace.root(), None);
+        assert!(auditor
+            .state()
+            .signature(NAMESPACE, &Epoch::from(1))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ciphersuite_migration() {
+        let auditor = auditor(serde_json::json!([{
+            "name": NAMESPACE,
+            "ciphersuite": 0x0001,
+            "next_ciphersuite": 0x0002,
+            "root": root(1, [0xaa; 32]),
+        }]))
+        .await;
+        let verifying_key = auditor.signer.verifying_key();
+
+        // epochs are signed under both ciphersuites
+        let signature = auditor
+            .process_report(Uuid::new_v4(), &report(NAMESPACE, 1, [0xaa; 32]))
+            .await
+            .unwrap();
+        assert_eq!(
+            *signature.ciphersuite(),
+            plexi_core::Ciphersuite::ProtobufEd25519
+        );
+        let migration = signature.migration_signature().unwrap();
+        assert_eq!(
+            *migration.ciphersuite(),
+            plexi_core::Ciphersuite::BincodeEd25519
+        );
+        signature
+            .verify_with(&verifying_key, plexi_core::MigrationPolicy::Both)
+            .unwrap();
+
+        let mut namespace = auditor.state().namespace(NAMESPACE).await.unwrap().unwrap();
+        namespace.complete_migration().unwrap();
+        auditor.state().update_namespace(&namespace).await.unwrap();
+        let signature = auditor
+            .process_report(Uuid::new_v4(), &report(NAMESPACE, 2, [0xbb; 32]))
+            .await;
+        // reports still using the previous ciphersuite are rejected once the migration completes
+        assert!(matches!(signature, Err(ApiError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,155 @@
+use std::{collections::HashSet, fs, path::Path, sync::Arc};
+
+use anyhow::{anyhow, Context as _};
+use plexi_core::{
+    auditor::{AkdConfig, Configuration, KeyInfo},
+    namespaces::{Namespace, NamespaceMetadata},
+    storage::Storage,
+    witness::Witness,
+    Ciphersuite,
+};
+use serde::Deserialize;
+
+use crate::AuditorState;
+
+/// Namespace an auditor monitors
+#[derive(Clone, Debug, Deserialize)]
+pub struct NamespaceConfig {
+    pub name: String,
+    pub log_directory: Option<String>,
+    /// Root epoch, formatted as `epoch/digest`
+    pub root: Option<String>,
+    pub ciphersuite: Ciphersuite,
+    /// Ciphersuite to migrate to
+    pub next_ciphersuite: Option<Ciphersuite>,
+    #[serde(default)]
+    pub akd_config: AkdConfig,
+    /// Other names the namespace is known by
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Description, operator contact, and policy URL, published with the namespace
+    #[serde(flatten)]
+    pub metadata: NamespaceMetadata,
+}
+
+impl From<&NamespaceConfig> for Namespace {
+    fn from(config: &NamespaceConfig) -> Self {
+        let mut namespace = Namespace::new(
+            config.name.clone(),
+            config.log_directory.clone(),
+            config.root.clone(),
+            None,
+            Some(config.ciphersuite),
+        );
+        namespace.set_akd_config(config.akd_config);
+        namespace.set_next_ciphersuite(config.next_ciphersuite);
+        namespace.set_aliases(config.aliases.clone());
+        namespace.set_metadata(config.metadata.clone());
+        namespace
+    }
+}
+
+/// Auditor this auditor gossips with
+#[derive(Clone, Debug, Deserialize)]
+pub struct PeerConfig {
+    /// Base URL of the peer API
+    pub url: String,
+    /// Keys the peer signs epochs with
+    pub keys: Vec<KeyInfo>,
+}
+
+/// Witness the heads of the auditor are submitted to, with the tlog-witness protocol
+#[derive(Clone, Debug, Deserialize)]
+pub struct WitnessConfig {
+    /// Name of the witness cosignature key
+    pub name: String,
+    /// Base URL of the witness, `add-checkpoint` being relative to it
+    pub url: String,
+    /// Hex encoded Ed25519 key the witness cosigns with
+    pub key: String,
+}
+
+impl WitnessConfig {
+    pub fn witness(&self) -> anyhow::Result<Witness> {
+        let url = reqwest::Url::parse(&self.url)
+            .with_context(|| format!("parsing witness URL {url}", url = self.url))?;
+        let key = hex::decode(&self.key)
+            .ok()
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "witness {name} key is not a hex encoded Ed25519 key",
+                    name = self.name
+                )
+            })?;
+        Ok(Witness::new(&self.name, url, key))
+    }
+}
+
+/// Configuration file of `plexi-server`, in JSON
+#[derive(Clone, Debug, Deserialize)]
+pub struct ServerConfig {
+    pub keys: Vec<KeyInfo>,
+    #[serde(default)]
+    pub namespaces: Vec<NamespaceConfig>,
+    #[serde(default)]
+    pub peers: Vec<PeerConfig>,
+    /// Name of the key checkpoints submitted to witnesses are signed with, such as the domain of the auditor
+    pub checkpoint_key_name: Option<String>,
+    #[serde(default)]
+    pub witnesses: Vec<WitnessConfig>,
+}
+
+impl ServerConfig {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let config = fs::read_to_string(path)
+            .with_context(|| format!("reading {path}", path = path.display()))?;
+        serde_json::from_str(&config)
+            .with_context(|| format!("parsing {path}", path = path.display()))
+    }
+
+    /// State of an auditor persisted in `storage`
+    pub async fn into_state(self, storage: Arc<dyn Storage>) -> anyhow::Result<AuditorState> {
+        for namespace in &self.namespaces {
+            Namespace::from(namespace)
+                .validate()
+                .with_context(|| format!("namespace {name}", name = namespace.name))?;
+        }
+        let mut names = HashSet::new();
+        for name in self
+            .namespaces
+            .iter()
+            .flat_map(|ns| std::iter::once(&ns.name).chain(&ns.aliases))
+        {
+            if !names.insert(name) {
+                return Err(anyhow!("{name} names more than one namespace"));
+            }
+        }
+
+        let logs: Vec<String> = self.namespaces.iter().map(|ns| ns.name.clone()).collect();
+        let state = AuditorState::new(Configuration::new(&self.keys, &logs), storage);
+        for namespace in &self.namespaces {
+            match state.namespace(&namespace.name).await? {
+                None => state.add_namespace(&namespace.into()).await?,
+                Some(mut info) => {
+                    let migrated = info.next_ciphersuite() == Some(namespace.ciphersuite);
+                    if !migrated
+                        && info.next_ciphersuite() == namespace.next_ciphersuite
+                        && info.aliases() == namespace.aliases.as_slice()
+                        && info.metadata() == &namespace.metadata
+                    {
+                        continue;
+                    }
+                    if migrated {
+                        info.complete_migration()?;
+                    }
+                    info.set_next_ciphersuite(namespace.next_ciphersuite);
+                    info.set_aliases(namespace.aliases.clone());
+                    info.set_metadata(namespace.metadata.clone());
+                    state.update_namespace(&info).await?
+                }
+            }
+        }
+        Ok(state)
+    }
+}
@@ -0,0 +1,282 @@
+use std::{sync::RwLock, time::Duration};
+
+use anyhow::Context as _;
+use plexi_core::{
+    auditor::KeyInfo,
+    client::PlexiClient,
+    gossip::{verify_head, Divergence, GossipRequest, GossipResponse},
+    SignatureResponse,
+};
+
+use crate::{ApiError, Auditor, PeerConfig};
+
+const LOCK_POISONED: &str = "divergences lock is poisoned";
+
+#[derive(Debug, Default)]
+pub(crate) struct Gossip {
+    peers: Vec<PeerConfig>,
+    divergences: RwLock<Vec<Divergence>>,
+}
+
+impl Gossip {
+    pub(crate) fn new(peers: Vec<PeerConfig>) -> Self {
+        Self {
+            peers,
+            divergences: RwLock::new(vec![]),
+        }
+    }
+
+    fn peer_keys(&self) -> Vec<KeyInfo> {
+        self.peers
+            .iter()
+            .flat_map(|peer| peer.keys.iter().cloned())
+            .collect()
+    }
+}
+
+impl Auditor {
+    /// Gossip with `peers`, exchanging heads for the namespaces both monitor
+    pub fn with_peers(mut self, peers: Vec<PeerConfig>) -> Self {
+        self.gossip = Gossip::new(peers);
+        self
+    }
+
+    /// Divergences found while gossiping
+    pub fn divergences(&self) -> Vec<Divergence> {
+        self.gossip.divergences.read().expect(LOCK_POISONED).clone()
+    }
+
+    pub(crate) async fn heads<'a>(
+        &self,
+        namespaces: impl IntoIterator<Item = &'a str>,
+    ) -> anyhow::Result<Vec<SignatureResponse>> {
+        let mut heads = vec![];
+        for namespace in namespaces {
+            let Some(last_verified_epoch) = self.state().last_verified_epoch(namespace).await?
+            else {
+                continue;
+            };
+            let epoch = last_verified_epoch.epoch();
+            if let Some(head) = self.state().signature(namespace, &epoch).await? {
+                heads.push(head);
+            }
+        }
+        Ok(heads)
+    }
+
+    async fn compare(&self, head: &SignatureResponse) -> anyhow::Result<Option<Divergence>> {
+        Ok(self
+            .state()
+            .signature(head.namespace(), head.epoch())
+            .await?
+            .and_then(|signature| Divergence::between(&signature, head)))
+    }
+
+    fn record_divergence(&self, divergence: &Divergence) -> bool {
+        let mut divergences = self.gossip.divergences.write().expect(LOCK_POISONED);
+        if divergences.contains(divergence) {
+            return false;
+        }
+        tracing::error!(
+            "{namespace} epoch {epoch} diverges between auditors, the log may present a split view: {evidence}",
+            namespace = divergence.namespace(),
+            epoch = divergence.epoch(),
+            evidence = serde_json::to_string(divergence).unwrap_or_default()
+        );
+        self.state()
+            .metrics()
+            .record_divergence(divergence.namespace());
+        divergences.push(divergence.clone());
+        true
+    }
+
+    /// Handle heads sent by a peer: compare them with our signatures, and answer with our own heads for the same namespaces
+    pub async fn receive_gossip(
+        &self,
+        request: &GossipRequest,
+    ) -> Result<GossipResponse, ApiError> {
+        let peer_keys = self.gossip.peer_keys();
+        let mut namespaces = vec![];
+        let mut divergences = vec![];
+        for head in request.heads() {
+            if self.state().namespace(head.namespace()).await?.is_none() {
+                continue;
+            }
+            head.validate_signature_length()
+                .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+            verify_head(head, &peer_keys).map_err(|e| ApiError::BadRequest(format!("{e:#}")))?;
+            namespaces.push(head.namespace());
+            if let Some(divergence) = self.compare(head).await? {
+                self.record_divergence(&divergence);
+                divergences.push(divergence);
+            }
+        }
+        Ok(GossipResponse::new(
+            self.heads(namespaces).await?,
+            divergences,
+        ))
+    }
+
+    /// Exchange heads with every peer once
+    pub async fn gossip(&self) -> anyhow::Result<Vec<Divergence>> {
+        let configuration = self.state().configuration();
+        let namespaces: Vec<String> = self
+            .state()
+            .namespaces()
+            .await?
+            .into_iter()
+            .map(|info| info.name().to_string())
+            .collect();
+        let request = GossipRequest::new(self.heads(namespaces.iter().map(String::as_str)).await?);
+
+        let mut divergences = vec![];
+        for peer in &self.gossip.peers {
+            let url = reqwest::Url::parse(&peer.url)
+                .with_context(|| format!("parsing peer URL {url}", url = peer.url))?;
+            let response = match PlexiClient::new(url, None, None)?.gossip(&request).await {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::warn!("gossiping with {url}: {e:#}", url = peer.url);
+                    continue;
+                }
+            };
+
+            let mut found = vec![];
+            for head in response.heads() {
+                match verify_head(head, &peer.keys) {
+                    Ok(()) => found.extend(self.compare(head).await?),
+                    Err(e) => tracing::warn!("{url} sent an invalid head: {e:#}", url = peer.url),
+                }
+            }
+            // divergences the peer found are between its heads and ours
+            let keys: Vec<KeyInfo> = configuration
+                .keys()
+                .iter()
+                .chain(&peer.keys)
+                .cloned()
+                .collect();
+            for divergence in response.divergences() {
+                match divergence.verify(&keys) {
+                    Ok(()) => found.push(divergence.clone()),
+                    Err(e) => {
+                        tracing::warn!("{url} sent an invalid divergence: {e:#}", url = peer.url)
+                    }
+                }
+            }
+
+            for divergence in found {
+                if self.record_divergence(&divergence) {
+                    divergences.push(divergence);
+                }
+            }
+        }
+        Ok(divergences)
+    }
+
+    /// Gossip with the peers every `interval`
+    pub async fn run_gossip(&self, interval: Duration) {
+        loop {
+            if let Err(e) = self.gossip().await {
+                tracing::error!("gossiping: {e:#}");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::IntoFuture as _, sync::Arc};
+
+    use plexi_core::signer::{Ed25519Signer, Signer as _};
+
+    use super::*;
+    use crate::tests::{auditor_with_key, report, root, SIGNING_KEY};
+
+    const NAMESPACE: &str = "example.key-transparency.v1";
+    const SPLIT_NAMESPACE: &str = "split.key-transparency.v1";
+    const PEER_SIGNING_KEY: &str =
+        "0101010101010101010101010101010101010101010101010101010101010101";
+
+    fn keys(signing_key: &str) -> Vec<KeyInfo> {
+        let verifying_key = Ed25519Signer::from_hex(signing_key)
+            .unwrap()
+            .verifying_key();
+        vec![KeyInfo::new(&hex::encode(verifying_key), 0)]
+    }
+
+    #[tokio::test]
+    async fn test_gossip() {
+        // the log shows a different first epoch to each auditor
+        let namespaces = |split_digest: [u8; 32]| {
+            serde_json::json!([
+                { "name": NAMESPACE, "ciphersuite": 0x0001, "root": root(1, [0xaa; 32]) },
+                { "name": SPLIT_NAMESPACE, "ciphersuite": 0x0001, "root": root(1, split_digest) },
+            ])
+        };
+        let peer = Arc::new(
+            auditor_with_key(PEER_SIGNING_KEY, namespaces([0xbb; 32]))
+                .await
+                .with_peers(vec![PeerConfig {
+                    url: "http://127.0.0.1".to_string(),
+                    keys: keys(SIGNING_KEY),
+                }]),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{addr}", addr = listener.local_addr().unwrap());
+        tokio::spawn(axum::serve(listener, crate::router(peer.clone())).into_future());
+
+        let auditor = auditor_with_key(SIGNING_KEY, namespaces([0xaa; 32]))
+            .await
+            .with_peers(vec![PeerConfig {
+                url,
+                keys: keys(PEER_SIGNING_KEY),
+            }]);
+
+        for auditor in [&auditor, peer.as_ref()] {
+            auditor
+                .submit_report(report(NAMESPACE, 1, [0xaa; 32]))
+                .await
+                .unwrap();
+        }
+        assert!(auditor.gossip().await.unwrap().is_empty());
+
+        auditor
+            .submit_report(report(SPLIT_NAMESPACE, 1, [0xaa; 32]))
+            .await
+            .unwrap();
+        peer.submit_report(report(SPLIT_NAMESPACE, 1, [0xbb; 32]))
+            .await
+            .unwrap();
+        let divergences = auditor.gossip().await.unwrap();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].namespace(), SPLIT_NAMESPACE);
+        assert_eq!(peer.divergences(), divergences);
+        assert!(auditor
+            .state()
+            .metrics()
+            .encode()
+            .unwrap()
+            .contains(&format!(
+                "plexi_gossip_divergences_total{{namespace=\"{SPLIT_NAMESPACE}\"}} 1"
+            )));
+
+        // divergences are only reported once
+        assert!(auditor.gossip().await.unwrap().is_empty());
+        assert_eq!(auditor.divergences().len(), 1);
+
+        // heads must be signed by a peer
+        let own_head = auditor
+            .state()
+            .signature(NAMESPACE, &plexi_core::Epoch::from(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            auditor
+                .receive_gossip(&GossipRequest::new(vec![own_head]))
+                .await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,604 @@
+//! Auditor server speaking the API [`plexi_core::client::PlexiClient`] consumes.
+//!
+//! The server exposes
+//! * `GET /info`, the auditor keys and the logs it monitors
+//! * `GET /healthz` and `GET /readyz`, the latter checking the storage and signer are available
+//! * `GET /metrics`, in the Prometheus text format
+//! * `GET /namespaces`, filtered by the `status`, `prefix`, and `ciphersuite` query parameters, and `GET /namespaces/{namespace}`
+//! * `POST /namespaces`, `PUT /namespaces/{namespace}`, and `POST /namespaces/{namespace}/disable`, for log operators to manage their namespaces.
+//!   They require the admin token set with [`Auditor::with_admin_token`] as a bearer token
+//! * `GET /namespaces/{namespace}/audits/{epoch}`, the signature for an epoch
+//! * `GET /namespaces/{namespace}/cosignatures/{epoch}`, the cosignatures witnesses returned for an epoch
+//! * `GET /namespaces/{namespace}/last-verified-epoch`
+//! * `GET /namespaces/{namespace}/proofs/{epoch}/{previous_hash}/{current_hash}`, the proofs the auditor verified
+//! * `POST /namespaces/{namespace}/reports` and `GET /namespaces/{namespace}/reports/{id}`, for logs to submit new epochs.
+//!   Submitting a report requires the admin token as well
+//! * `POST /gossip`, for peer auditors to exchange their heads, and `GET /gossip/divergences`, the split views found doing so
+//!
+//! With the `openapi` feature, [`openapi_router`] serves the OpenAPI document of this API at `GET /openapi.json`.
+//!
+//! Submitted reports are queued, then verified and signed by [`Auditor::submit_report`].
+//! Epochs are only signed once verified from the namespace root, which comes from the configuration or the admin API, never from a report.
+//! Reports that cannot be signed yet, for instance because their proof is not published, are answered with `202 Accepted` and retried by [`Auditor::run_report_queue`].
+//!
+//! Auditors configured with `peers` gossip with them through [`Auditor::run_gossip`], sending the signature of the last epoch they verified for each namespace.
+//! A peer signature for the same epoch over a different digest is a [`plexi_core::gossip::Divergence`]: it is logged, counted in the metrics, and listed by `GET /gossip/divergences`.
+//!
+//! Auditors configured with `witnesses` submit the same heads to them through [`Auditor::run_witnessing`], as checkpoints signed under `checkpoint_key_name`.
+//! The cosignatures they return are kept in memory, and served by `GET /namespaces/{namespace}/cosignatures/{epoch}`.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{rejection::JsonRejection, Path, Query, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use plexi_core::{
+    auditor::{Configuration, ProofId},
+    checkpoint::Cosignature,
+    gossip::{Divergence, GossipRequest, GossipResponse},
+    namespaces::{Namespace, NamespaceFilter, NamespaceInfo, Namespaces},
+    Epoch, LastVerifiedEpoch, Report, ReportResponse, SignatureResponse,
+};
+use uuid::Uuid;
+
+mod admin;
+mod auditor;
+mod config;
+mod error;
+mod gossip;
+mod metrics;
+mod queue;
+mod state;
+mod witness;
+
+pub use auditor::{Auditor, Readiness};
+pub use config::{NamespaceConfig, PeerConfig, ServerConfig, WitnessConfig};
+pub use error::ApiError;
+pub use metrics::Metrics;
+pub use queue::{QueueConfig, Submission};
+pub use state::AuditorState;
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Routes of the auditor API, backed by `auditor`
+pub fn router(auditor: Arc<Auditor>) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/info", get(info))
+        .route("/metrics", get(metrics))
+        .route("/namespaces", get(namespaces).post(create_namespace))
+        .route(
+            "/namespaces/{namespace}",
+            get(namespace).put(update_namespace),
+        )
+        .route("/namespaces/{namespace}/disable", post(disable_namespace))
+        .route("/namespaces/{namespace}/audits/{epoch}", get(signature))
+        .route(
+            "/namespaces/{namespace}/cosignatures/{epoch}",
+            get(cosignatures),
+        )
+        .route(
+            "/namespaces/{namespace}/last-verified-epoch",
+            get(last_verified_epoch),
+        )
+        .route(
+            "/namespaces/{namespace}/proofs/{epoch}/{previous_hash}/{current_hash}",
+            get(proof),
+        )
+        .route("/namespaces/{namespace}/reports", post(submit_report))
+        .route("/namespaces/{namespace}/reports/{id}", get(report))
+        .route("/gossip", post(receive_gossip))
+        .route("/gossip/divergences", get(divergences))
+        .with_state(auditor)
+}
+
+/// Route serving [`plexi_core::openapi::spec`] at `/openapi.json`, to be merged with [`router`]
+#[cfg(feature = "openapi")]
+pub fn openapi_router() -> Router {
+    Router::new().route(
+        "/openapi.json",
+        get(|| async { Json(plexi_core::openapi::spec()) }),
+    )
+}
+
+async fn info(State(auditor): State<Arc<Auditor>>) -> Json<Configuration> {
+    Json(auditor.state().configuration().clone())
+}
+
+async fn healthz() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+async fn readyz(State(auditor): State<Arc<Auditor>>) -> impl IntoResponse {
+    let readiness = auditor.ready().await;
+    let status = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
+
+async fn metrics(State(auditor): State<Arc<Auditor>>) -> Result<impl IntoResponse, ApiError> {
+    Ok((
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        auditor.state().metrics().encode()?,
+    ))
+}
+
+async fn namespaces(
+    State(auditor): State<Arc<Auditor>>,
+    Query(filter): Query<NamespaceFilter>,
+) -> ApiResult<Namespaces> {
+    Ok(Json(auditor.state().namespaces().await?.filter(&filter)))
+}
+
+async fn namespace(
+    State(auditor): State<Arc<Auditor>>,
+    Path(namespace): Path<String>,
+) -> ApiResult<NamespaceInfo> {
+    auditor
+        .state()
+        .namespace(&namespace)
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound(format!("namespace {namespace}")))
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+async fn create_namespace(
+    State(auditor): State<Arc<Auditor>>,
+    headers: HeaderMap,
+    Json(namespace): Json<Namespace>,
+) -> Result<(StatusCode, Json<NamespaceInfo>), ApiError> {
+    auditor.authorize(bearer_token(&headers))?;
+    Ok((
+        StatusCode::CREATED,
+        Json(auditor.create_namespace(&namespace).await?),
+    ))
+}
+
+async fn update_namespace(
+    State(auditor): State<Arc<Auditor>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(namespace): Json<Namespace>,
+) -> ApiResult<NamespaceInfo> {
+    auditor.authorize(bearer_token(&headers))?;
+    if namespace.name() != name {
+        return Err(ApiError::BadRequest(format!(
+            "namespace is {namespace_name}, not {name}",
+            namespace_name = namespace.name()
+        )));
+    }
+    Ok(Json(auditor.update_namespace(&namespace).await?))
+}
+
+async fn disable_namespace(
+    State(auditor): State<Arc<Auditor>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> ApiResult<NamespaceInfo> {
+    auditor.authorize(bearer_token(&headers))?;
+    Ok(Json(auditor.disable_namespace(&name).await?))
+}
+
+async fn signature(
+    State(auditor): State<Arc<Auditor>>,
+    Path((namespace, epoch)): Path<(String, u64)>,
+) -> ApiResult<SignatureResponse> {
+    auditor
+        .state()
+        .signature(&namespace, &Epoch::from(epoch))
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound(format!(
+            "signature for {namespace} at epoch {epoch}"
+        )))
+}
+
+async fn cosignatures(
+    State(auditor): State<Arc<Auditor>>,
+    Path((namespace, epoch)): Path<(String, u64)>,
+) -> Json<Vec<Cosignature>> {
+    Json(auditor.cosignatures(&namespace, &Epoch::from(epoch)))
+}
+
+async fn last_verified_epoch(
+    State(auditor): State<Arc<Auditor>>,
+    Path(namespace): Path<String>,
+) -> ApiResult<LastVerifiedEpoch> {
+    auditor
+        .state()
+        .last_verified_epoch(&namespace)
+        .await?
+        .map(Json)
+        .ok_or(ApiError::NotFound(format!(
+            "last verified epoch for {namespace}"
+        )))
+}
+
+async fn proof(
+    State(auditor): State<Arc<Auditor>>,
+    Path((namespace, epoch, previous_hash, current_hash)): Path<(String, u64, String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let name = format!("{epoch}/{previous_hash}/{current_hash}");
+    let blob = name
+        .parse::<ProofId>()
+        .map_err(|_| ApiError::BadRequest(format!("invalid proof name {name}")))?;
+    let proof = auditor
+        .state()
+        .proof(&namespace, &blob)
+        .await?
+        .ok_or(ApiError::NotFound(format!("proof {name}")))?;
+    Ok(([(CONTENT_TYPE, "application/octet-stream")], proof))
+}
+
+async fn submit_report(
+    State(auditor): State<Arc<Auditor>>,
+    Path(namespace): Path<String>,
+    headers: HeaderMap,
+    Json(report): Json<Report>,
+) -> Result<(StatusCode, Json<ReportResponse>), ApiError> {
+    auditor.authorize(bearer_token(&headers))?;
+    if auditor.state().namespace(&namespace).await?.is_none() {
+        return Err(ApiError::NotFound(format!("namespace {namespace}")));
+    }
+    if report.namespace() != namespace {
+        return Err(ApiError::BadRequest(format!(
+            "report is for namespace {report_namespace}, not {namespace}",
+            report_namespace = report.namespace()
+        )));
+    }
+    match auditor.submit_report(report).await? {
+        Submission::Signed(response) => Ok((StatusCode::OK, Json(response))),
+        Submission::Queued(response) => Ok((StatusCode::ACCEPTED, Json(response))),
+    }
+}
+
+async fn report(
+    State(auditor): State<Arc<Auditor>>,
+    Path((namespace, id)): Path<(String, Uuid)>,
+) -> ApiResult<ReportResponse> {
+    auditor
+        .state()
+        .report(&id)
+        .await?
+        .filter(|report| report.report().namespace() == namespace)
+        .map(Json)
+        .ok_or(ApiError::NotFound(format!("report {id}")))
+}
+
+async fn receive_gossip(
+    State(auditor): State<Arc<Auditor>>,
+    request: Result<Json<GossipRequest>, JsonRejection>,
+) -> ApiResult<GossipResponse> {
+    // heads come from peers, malformed ones are their error
+    let Json(request) = request.map_err(|e| ApiError::BadRequest(e.body_text()))?;
+    Ok(Json(auditor.receive_gossip(&request).await?))
+}
+
+async fn divergences(State(auditor): State<Arc<Auditor>>) -> Json<Vec<Divergence>> {
+    Json(auditor.divergences())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+    };
+    use plexi_core::{
+        auditor::KeyInfo,
+        namespaces::NamespaceStatus,
+        signer::{Ed25519Signer, Signer as _},
+        storage::MemoryStorage,
+        Ciphersuite,
+    };
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    const NAMESPACE: &str = "example.key-transparency.v1";
+    const ADMIN_TOKEN: &str = "admin-token";
+    pub(crate) const SIGNING_KEY: &str =
+        "d6af1bca3db4fc2766b0c483706c20bf4837a46d54c1d39c2a34a9088572d712";
+
+    pub(crate) async fn auditor(namespaces: serde_json::Value) -> Auditor {
+        auditor_with_key(SIGNING_KEY, namespaces).await
+    }
+
+    pub(crate) async fn auditor_with_key(
+        signing_key: &str,
+        namespaces: serde_json::Value,
+    ) -> Auditor {
+        let signer = Ed25519Signer::from_hex(signing_key).unwrap();
+        let config: ServerConfig = serde_json::from_value(serde_json::json!({
+            "keys": [KeyInfo::new(&hex::encode(signer.verifying_key()), 0)],
+            "namespaces": namespaces,
+        }))
+        .unwrap();
+        let state = config
+            .into_state(Arc::new(MemoryStorage::new()))
+            .await
+            .unwrap();
+        Auditor::new(state, Arc::new(signer)).unwrap()
+    }
+
+    pub(crate) fn root(epoch: u64, digest: [u8; 32]) -> String {
+        format!("{epoch}/{digest}", digest = hex::encode(digest))
+    }
+
+    pub(crate) fn report(namespace: &str, epoch: u64, digest: [u8; 32]) -> Report {
+        Report::new(
+            &Ciphersuite::ProtobufEd25519,
+            &Ciphersuite::ProtobufEd25519,
+            namespace.to_string(),
+            1_700_000_000_000,
+            &Epoch::from(epoch),
+            digest.to_vec(),
+            vec![0; 64],
+            None,
+            None,
+        )
+    }
+
+    async fn app() -> (Arc<Auditor>, Router) {
+        let auditor = Arc::new(
+            auditor(serde_json::json!([
+                { "name": NAMESPACE, "ciphersuite": 0x0001 }
+            ]))
+            .await
+            .with_admin_token(ADMIN_TOKEN),
+        );
+        (auditor.clone(), router(auditor))
+    }
+
+    async fn send(app: &Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_namespaces() {
+        let (_, app) = app().await;
+
+        let (status, info) = send(&app, get("/info")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(info["logs"][0], NAMESPACE);
+
+        let (status, namespace) = send(&app, get(&format!("/namespaces/{NAMESPACE}"))).await;
+        assert_eq!(status, StatusCode::OK);
+        let namespace: NamespaceInfo = serde_json::from_value(namespace).unwrap();
+        assert_eq!(namespace.status(), NamespaceStatus::Initialization);
+
+        let (status, namespaces) = send(&app, get("/namespaces?status=Online")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(namespaces["namespaces"].as_array().unwrap().is_empty());
+        let (_, namespaces) = send(&app, get("/namespaces?prefix=example.&ciphersuite=1")).await;
+        assert_eq!(namespaces["namespaces"][0]["name"], NAMESPACE);
+        let response = app
+            .clone()
+            .oneshot(get("/namespaces?status=unknown"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let (status, _) = send(&app, get("/namespaces/unknown")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(&app, get(&format!("/namespaces/{NAMESPACE}/audits/1"))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_metadata() {
+        let storage: Arc<dyn plexi_core::storage::Storage> = Arc::new(MemoryStorage::new());
+        let config = |contact: &str| -> ServerConfig {
+            serde_json::from_value(serde_json::json!({
+                "keys": [],
+                "namespaces": [{
+                    "name": NAMESPACE,
+                    "ciphersuite": 0x0001,
+                    "description": "Example log",
+                    "contact": contact,
+                }],
+            }))
+            .unwrap()
+        };
+
+        let state = config("security@example.com")
+            .into_state(storage.clone())
+            .await
+            .unwrap();
+        let info = state.namespace(NAMESPACE).await.unwrap().unwrap();
+        assert_eq!(info.metadata().description(), Some("Example log"));
+        assert_eq!(info.metadata().policy_url(), None);
+
+        // metadata follows the configuration, the rest of the namespace is kept
+        state.set_root(NAMESPACE, "1/aa").await.unwrap();
+        let state = config("ops@example.com").into_state(storage).await.unwrap();
+        let info = state.namespace(NAMESPACE).await.unwrap().unwrap();
+        assert_eq!(info.metadata().contact(), Some("ops@example.com"));
+        assert_eq!(info.root(), Some("1/aa"));
+        assert_eq!(
+            serde_json::to_value(&info).unwrap()["contact"],
+            "ops@example.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_namespace_aliases() {
+        let storage: Arc<dyn plexi_core::storage::Storage> = Arc::new(MemoryStorage::new());
+        let config = |namespaces: serde_json::Value| -> ServerConfig {
+            serde_json::from_value(serde_json::json!({ "keys": [], "namespaces": namespaces }))
+                .unwrap()
+        };
+
+        let state = config(serde_json::json!([
+            { "name": NAMESPACE, "ciphersuite": 0x0001, "aliases": ["example"] },
+        ]))
+        .into_state(storage.clone())
+        .await
+        .unwrap();
+        let info = state.namespace(NAMESPACE).await.unwrap().unwrap();
+        assert_eq!(info.aliases(), ["example"]);
+
+        // aliases follow the configuration, and cannot name two namespaces
+        let state = config(serde_json::json!([
+            { "name": NAMESPACE, "ciphersuite": 0x0001, "aliases": ["example", "ex"] },
+        ]))
+        .into_state(storage.clone())
+        .await
+        .unwrap();
+        let namespaces = state.namespaces().await.unwrap();
+        assert_eq!(namespaces.find("ex").unwrap().name(), NAMESPACE);
+        assert!(config(serde_json::json!([
+            { "name": NAMESPACE, "ciphersuite": 0x0001, "aliases": ["example"] },
+            { "name": "other.key-transparency.v1", "ciphersuite": 0x0001, "aliases": ["example"] },
+        ]))
+        .into_state(storage)
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reports() {
+        let (auditor, app) = app().await;
+        let submit_with = |token: &str, report: &Report| {
+            Request::post(format!("/namespaces/{NAMESPACE}/reports"))
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {token}"))
+                .body(Body::from(serde_json::to_vec(report).unwrap()))
+                .unwrap()
+        };
+        let submit = |report: &Report| submit_with(ADMIN_TOKEN, report);
+
+        let (status, _) = send(
+            &app,
+            submit_with("not-the-token", &report(NAMESPACE, 1, [0xaa; 32])),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        // reports cannot set the namespace root
+        let (status, _) = send(&app, submit(&report(NAMESPACE, 1, [0xaa; 32]))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            auditor
+                .state()
+                .namespace(NAMESPACE)
+                .await
+                .unwrap()
+                .unwrap()
+                .root(),
+            None
+        );
+
+        auditor
+            .state()
+            .set_root(NAMESPACE, &root(1, [0xaa; 32]))
+            .await
+            .unwrap();
+        let (status, response) = send(&app, submit(&report(NAMESPACE, 1, [0xaa; 32]))).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: ReportResponse = serde_json::from_value(response).unwrap();
+
+        let (status, stored) = send(
+            &app,
+            get(&format!(
+                "/namespaces/{NAMESPACE}/reports/{id}",
+                id = response.id()
+            )),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(stored["id"], response.id().to_string());
+
+        let (status, signature) =
+            send(&app, get(&format!("/namespaces/{NAMESPACE}/audits/1"))).await;
+        assert_eq!(status, StatusCode::OK);
+        let signature: SignatureResponse = serde_json::from_value(signature).unwrap();
+        signature
+            .verify(
+                &Ed25519Signer::from_hex(SIGNING_KEY)
+                    .unwrap()
+                    .verifying_key(),
+            )
+            .unwrap();
+
+        let (status, _) = send(&app, submit(&report(NAMESPACE, 1, [0xbb; 32]))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = send(&app, submit(&report("another.namespace", 1, [0xaa; 32]))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_gossip_signature_length() {
+        let (_, app) = app().await;
+        let head = SignatureResponse::new(
+            &Ciphersuite::ProtobufEd25519,
+            &Ciphersuite::ProtobufEd25519,
+            NAMESPACE.to_string(),
+            1_700_000_000_000,
+            &Epoch::from(1),
+            vec![0xaa; 32],
+            vec![0; 3],
+            None,
+            None,
+        );
+        let request = Request::post("/gossip")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::to_vec(&GossipRequest::new(vec![head])).unwrap(),
+            ))
+            .unwrap();
+
+        let (status, _) = send(&app, request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let (_, app) = app().await;
+
+        let (status, health) = send(&app, get("/healthz")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(health["status"], "ok");
+
+        let (status, readiness) = send(&app, get("/readyz")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(readiness["storage"], "ok");
+        assert_eq!(readiness["signer"], "ok");
+    }
+
+    #[cfg(feature = "openapi")]
+    #[tokio::test]
+    async fn test_openapi() {
+        let (_, app) = app().await;
+        let app = app.merge(openapi_router());
+
+        let (status, spec) = send(&app, get("/openapi.json")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(spec["paths"]["/namespaces/{namespace}/audits/{epoch}"]["get"].is_object());
+    }
+}
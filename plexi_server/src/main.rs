@@ -0,0 +1,303 @@
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Context as _};
+use clap::{Parser, ValueEnum};
+use plexi_core::{
+    signer::{Ed25519Signer, Signer},
+    storage::storage_from_url,
+};
+use plexi_server::{Auditor, QueueConfig, ServerConfig, WitnessConfig};
+use tracing_subscriber::EnvFilter;
+
+/// A plexi-compatible auditor server
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080", env = "PLEXI_LISTEN")]
+    listen: SocketAddr,
+    /// Path to the server configuration, in JSON
+    #[arg(long, env = "PLEXI_SERVER_CONFIG")]
+    config: PathBuf,
+    /// Path to the hex encoded Ed25519 key epochs are signed with
+    #[arg(long, env = "PLEXI_SIGNING_KEY_PATH")]
+    signing_key_path: Option<PathBuf>,
+    /// PKCS#11 module of the HSM holding the signing key, instead of --signing-key-path
+    #[cfg(feature = "pkcs11")]
+    #[arg(
+        long,
+        env = "PLEXI_PKCS11_MODULE",
+        conflicts_with = "signing_key_path",
+        requires_all = ["pkcs11_token", "pkcs11_pin", "pkcs11_key"]
+    )]
+    pkcs11_module: Option<PathBuf>,
+    /// Label of the PKCS#11 token holding the signing key
+    #[cfg(feature = "pkcs11")]
+    #[arg(long, env = "PLEXI_PKCS11_TOKEN")]
+    pkcs11_token: Option<String>,
+    /// PIN of the PKCS#11 token user
+    #[cfg(feature = "pkcs11")]
+    #[arg(long, env = "PLEXI_PKCS11_PIN", hide_env_values = true)]
+    pkcs11_pin: Option<String>,
+    /// Label of the Ed25519 key pair in the PKCS#11 token
+    #[cfg(feature = "pkcs11")]
+    #[arg(long, env = "PLEXI_PKCS11_KEY")]
+    pkcs11_key: Option<String>,
+    /// AWS KMS Ed25519 key ID, ARN, or alias epochs are signed with. Region and credentials are read from AWS_* environment variables
+    #[cfg(feature = "kms")]
+    #[arg(long, env = "PLEXI_AWS_KMS_KEY_ID", conflicts_with_all = ["signing_key_path", "gcp_kms_key_version"])]
+    aws_kms_key_id: Option<String>,
+    /// Google Cloud KMS Ed25519 key version epochs are signed with. The access token is read from GOOGLE_OAUTH_ACCESS_TOKEN
+    #[cfg(feature = "kms")]
+    #[arg(
+        long,
+        env = "PLEXI_GCP_KMS_KEY_VERSION",
+        conflicts_with = "signing_key_path"
+    )]
+    gcp_kms_key_version: Option<String>,
+    /// Where the auditor state is persisted: memory://, sqlite://, postgres://, workers-kv://, or d1://
+    #[arg(long, default_value = "memory://", env = "PLEXI_STORAGE_URL")]
+    storage: String,
+    /// Attempts at verifying a report, such as while its proof is not published, before it is dead-lettered
+    #[arg(long, default_value_t = 10, env = "PLEXI_REPORT_MAX_ATTEMPTS")]
+    report_max_attempts: u32,
+    /// Seconds between retries of the reports that could not be verified
+    #[arg(long, default_value_t = 30, env = "PLEXI_REPORT_RETRY_INTERVAL")]
+    report_retry_interval: u64,
+    /// Reports of a namespace waiting to be retried, past which new reports are rejected
+    #[arg(long, default_value_t = 1000, env = "PLEXI_REPORT_QUEUE_LIMIT")]
+    report_queue_limit: usize,
+    /// Seconds the reports that were rejected are kept for
+    #[arg(long, default_value_t = 7 * 24 * 60 * 60, env = "PLEXI_DEAD_LETTER_RETENTION")]
+    dead_letter_retention: u64,
+    /// Seconds between gossip rounds with the peers of the configuration
+    #[arg(long, default_value_t = 300, env = "PLEXI_GOSSIP_INTERVAL")]
+    gossip_interval: u64,
+    /// Seconds between submissions of the heads to the witnesses of the configuration
+    #[arg(long, default_value_t = 300, env = "PLEXI_WITNESS_INTERVAL")]
+    witness_interval: u64,
+    /// Bearer token authorizing namespace management requests. They are rejected if it is not set
+    #[arg(long, env = "PLEXI_ADMIN_TOKEN", hide_env_values = true)]
+    admin_token: Option<String>,
+    /// Serve the OpenAPI document of the API at /openapi.json
+    #[cfg(feature = "openapi")]
+    #[arg(long, env = "PLEXI_SERVE_OPENAPI")]
+    openapi: bool,
+    /// Format of the logs written to stderr (text, json). Their level is set with RUST_LOG, info by default
+    #[arg(long, value_enum, default_value_t = LogFormat::Text, env = "PLEXI_LOG_FORMAT")]
+    log_format: LogFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl Cli {
+    async fn signer(&self) -> anyhow::Result<Arc<dyn Signer>> {
+        if let Some(path) = &self.signing_key_path {
+            let signing_key = std::fs::read_to_string(path)
+                .with_context(|| format!("reading {path}", path = path.display()))?;
+            return Ok(Arc::new(Ed25519Signer::from_hex(&signing_key)?));
+        }
+
+        #[cfg(feature = "pkcs11")]
+        if let (Some(module), Some(token), Some(pin), Some(key)) = (
+            &self.pkcs11_module,
+            &self.pkcs11_token,
+            &self.pkcs11_pin,
+            &self.pkcs11_key,
+        ) {
+            return Ok(Arc::new(plexi_core::signer::Pkcs11Signer::new(
+                module, token, pin, key,
+            )?));
+        }
+
+        #[cfg(feature = "kms")]
+        if let Some(key_id) = &self.aws_kms_key_id {
+            return Ok(Arc::new(
+                plexi_core::signer::AwsKmsSigner::from_env(reqwest::Client::new(), key_id).await?,
+            ));
+        }
+
+        #[cfg(feature = "kms")]
+        if let Some(key_version) = &self.gcp_kms_key_version {
+            return Ok(Arc::new(
+                plexi_core::signer::GcpKmsSigner::from_env(reqwest::Client::new(), key_version)
+                    .await?,
+            ));
+        }
+
+        Err(anyhow!(
+            "a signing key is required, see plexi-server --help"
+        ))
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let logs = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        );
+    match cli.log_format {
+        LogFormat::Text => logs.init(),
+        LogFormat::Json => logs.json().init(),
+    }
+
+    let signer = cli.signer().await?;
+    let storage = storage_from_url(&cli.storage).await?;
+    let config = ServerConfig::from_file(&cli.config)?;
+    let peers = config.peers.clone();
+    let witnesses = config
+        .witnesses
+        .iter()
+        .map(WitnessConfig::witness)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let checkpoint_key_name = match (&config.checkpoint_key_name, witnesses.is_empty()) {
+        (Some(key_name), _) => key_name.clone(),
+        (None, true) => String::new(),
+        (None, false) => {
+            return Err(anyhow!(
+                "checkpoint_key_name is required to submit checkpoints to witnesses"
+            ))
+        }
+    };
+    let state = config.into_state(storage).await?;
+    let mut auditor = Auditor::new(state, signer)?
+        .with_queue_config(QueueConfig {
+            max_attempts: cli.report_max_attempts,
+            retry_interval: Duration::from_secs(cli.report_retry_interval),
+            max_queued_reports: cli.report_queue_limit,
+            dead_letter_retention: Duration::from_secs(cli.dead_letter_retention),
+        })
+        .with_peers(peers.clone())
+        .with_witnesses(&checkpoint_key_name, witnesses.clone());
+    if let Some(token) = &cli.admin_token {
+        auditor = auditor.with_admin_token(token);
+    }
+    let auditor = Arc::new(auditor);
+    tokio::spawn({
+        let auditor = auditor.clone();
+        async move { auditor.run_report_queue().await }
+    });
+    if !peers.is_empty() {
+        let auditor = auditor.clone();
+        let interval = Duration::from_secs(cli.gossip_interval);
+        tokio::spawn(async move { auditor.run_gossip(interval).await });
+    }
+    if !witnesses.is_empty() {
+        let auditor = auditor.clone();
+        let interval = Duration::from_secs(cli.witness_interval);
+        tokio::spawn(async move { auditor.run_witnessing(interval).await });
+    }
+    let listener = tokio::net::TcpListener::bind(cli.listen)
+        .await
+        .with_context(|| format!("binding {listen}", listen = cli.listen))?;
+    tracing::info!("listening on {listen}", listen = cli.listen);
+
+    #[allow(unused_mut)]
+    let mut app = plexi_server::router(auditor);
+    #[cfg(feature = "openapi")]
+    if cli.openapi {
+        app = app.merge(plexi_server::openapi_router());
+    }
+
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNING_KEY: &str = "d6af1bca3db4fc2766b0c483706c20bf4837a46d54c1d39c2a34a9088572d712";
+
+    fn cli(args: &[&str]) -> Cli {
+        Cli::try_parse_from(
+            ["plexi-server", "--config", "config.json"]
+                .iter()
+                .chain(args),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_signer() {
+        let directory = tempfile::TempDir::with_prefix("plexi-server-").unwrap();
+        let path = directory.path().join("signing-key");
+        std::fs::write(&path, SIGNING_KEY).unwrap();
+        let signer = cli(&["--signing-key-path", path.to_str().unwrap()])
+            .signer()
+            .await
+            .unwrap();
+        assert_eq!(
+            signer.verifying_key(),
+            Ed25519Signer::from_hex(SIGNING_KEY)
+                .unwrap()
+                .verifying_key()
+        );
+
+        assert!(cli(&[]).signer().await.is_err());
+    }
+
+    #[cfg(feature = "kms")]
+    #[tokio::test]
+    async fn test_kms_signers() {
+        use std::future::IntoFuture as _;
+
+        use axum::{routing::post, Json, Router};
+
+        // openssl genpkey -algorithm ed25519 | openssl pkey -pubout
+        const PUBLIC_KEY: &str = "MCowBQYDK2VwAyEAeu0exzmWXSMUo08fpjMhMC5j8TH0B+a4pcMC0STDNDQ=";
+
+        let kms = Router::new().route(
+            "/",
+            post(|| async { Json(serde_json::json!({ "PublicKey": PUBLIC_KEY })) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{addr}/", addr = listener.local_addr().unwrap());
+        tokio::spawn(axum::serve(listener, kms).into_future());
+
+        // the environment is shared by the tests of this module, which only this test changes
+        std::env::set_var("AWS_ENDPOINT_URL", endpoint);
+        std::env::set_var("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "secret");
+        let signer = cli(&["--aws-kms-key-id", "alias/plexi"])
+            .signer()
+            .await
+            .unwrap();
+        assert_eq!(
+            hex::encode(signer.verifying_key()),
+            "7aed1ec739965d2314a34f1fa63321302e63f131f407e6b8a5c302d124c33434"
+        );
+
+        // the Google Cloud KMS signer is built from the key version, and reads its token from the environment
+        std::env::remove_var("GOOGLE_OAUTH_ACCESS_TOKEN");
+        let err = cli(&[
+            "--gcp-kms-key-version",
+            "projects/p/locations/l/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1",
+        ])
+        .signer()
+        .await
+        .err()
+        .unwrap();
+        assert!(format!("{err:#}").contains("GOOGLE_OAUTH_ACCESS_TOKEN"));
+
+        assert!(Cli::try_parse_from([
+            "plexi-server",
+            "--config",
+            "config.json",
+            "--aws-kms-key-id",
+            "alias/plexi",
+            "--gcp-kms-key-version",
+            "projects/p/locations/l/keyRings/r/cryptoKeys/k/cryptoKeyVersions/1",
+        ])
+        .is_err());
+    }
+}
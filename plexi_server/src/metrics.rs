@@ -0,0 +1,181 @@
+use std::{future::Future, time::Instant};
+
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+use crate::ApiError;
+
+/// Prometheus metrics of an auditor, served at `/metrics`
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    registry: Registry,
+    signed_epochs: IntCounterVec,
+    rejected_reports: IntCounterVec,
+    last_signed_epoch: IntGaugeVec,
+    last_signed_timestamp: IntGaugeVec,
+    verification_duration: HistogramVec,
+    storage_duration: HistogramVec,
+    divergences: IntCounterVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("plexi".to_string()), None)
+            .expect("plexi is a valid metric prefix");
+        let signed_epochs = IntCounterVec::new(
+            Opts::new("signed_epochs_total", "Epochs signed by the auditor"),
+            &["namespace"],
+        )
+        .expect("metric is valid");
+        let rejected_reports = IntCounterVec::new(
+            Opts::new("rejected_reports_total", "Reports the auditor did not sign"),
+            &["namespace", "reason"],
+        )
+        .expect("metric is valid");
+        let last_signed_epoch = IntGaugeVec::new(
+            Opts::new("last_signed_epoch", "Last epoch signed by the auditor"),
+            &["namespace"],
+        )
+        .expect("metric is valid");
+        let last_signed_timestamp = IntGaugeVec::new(
+            Opts::new(
+                "last_signed_timestamp_seconds",
+                "Time the last epoch was signed at, to alert on stalled namespaces",
+            ),
+            &["namespace"],
+        )
+        .expect("metric is valid");
+        let verification_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "verification_duration_seconds",
+                "Time taken to retrieve and verify an epoch proof",
+            )
+            .buckets(prometheus::exponential_buckets(0.01, 2.0, 14).expect("buckets are valid")),
+            &["namespace"],
+        )
+        .expect("metric is valid");
+        let storage_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "storage_duration_seconds",
+                "Latency of auditor storage operations",
+            )
+            .buckets(prometheus::exponential_buckets(0.0005, 2.0, 14).expect("buckets are valid")),
+            &["operation"],
+        )
+        .expect("metric is valid");
+
+        let divergences = IntCounterVec::new(
+            Opts::new(
+                "gossip_divergences_total",
+                "Epochs a peer auditor signed with a different digest, evidence of a split view",
+            ),
+            &["namespace"],
+        )
+        .expect("metric is valid");
+
+        for collector in [
+            Box::new(signed_epochs.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(rejected_reports.clone()),
+            Box::new(last_signed_epoch.clone()),
+            Box::new(last_signed_timestamp.clone()),
+            Box::new(verification_duration.clone()),
+            Box::new(storage_duration.clone()),
+            Box::new(divergences.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metrics are registered once");
+        }
+
+        Self {
+            registry,
+            signed_epochs,
+            rejected_reports,
+            last_signed_epoch,
+            last_signed_timestamp,
+            verification_duration,
+            storage_duration,
+            divergences,
+        }
+    }
+
+    /// Metrics in the Prometheus text format
+    pub fn encode(&self) -> anyhow::Result<String> {
+        Ok(TextEncoder::new().encode_to_string(&self.registry.gather())?)
+    }
+
+    pub(crate) fn record_signature(&self, namespace: &str, epoch: u64, timestamp: u64) {
+        self.signed_epochs.with_label_values(&[namespace]).inc();
+        self.last_signed_epoch
+            .with_label_values(&[namespace])
+            .set(epoch as i64);
+        self.last_signed_timestamp
+            .with_label_values(&[namespace])
+            .set((timestamp / 1000) as i64);
+    }
+
+    pub(crate) fn record_rejection(&self, namespace: &str, error: &ApiError) {
+        let reason = match error {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Unavailable(_) => "unavailable",
+            ApiError::Internal(_) => "internal",
+        };
+        self.rejected_reports
+            .with_label_values(&[namespace, reason])
+            .inc();
+    }
+
+    pub(crate) fn record_divergence(&self, namespace: &str) {
+        self.divergences.with_label_values(&[namespace]).inc();
+    }
+
+    pub(crate) fn observe_verification(&self, namespace: &str, start: Instant) {
+        self.verification_duration
+            .with_label_values(&[namespace])
+            .observe(start.elapsed().as_secs_f64());
+    }
+
+    pub(crate) async fn time_storage<T>(
+        &self,
+        operation: &str,
+        future: impl Future<Output = T>,
+    ) -> T {
+        let start = Instant::now();
+        let output = future.await;
+        self.storage_duration
+            .with_label_values(&[operation])
+            .observe(start.elapsed().as_secs_f64());
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let metrics = Metrics::new();
+        metrics.record_signature("example", 2, 1_700_000_000_000);
+        metrics.record_rejection("example", &ApiError::Conflict("epoch 2".to_string()));
+        metrics.time_storage("signature", async {}).await;
+
+        let encoded = metrics.encode().unwrap();
+        assert!(encoded.contains("plexi_signed_epochs_total{namespace=\"example\"} 1"));
+        assert!(encoded
+            .contains("plexi_last_signed_timestamp_seconds{namespace=\"example\"} 1700000000"));
+        assert!(encoded
+            .contains("plexi_rejected_reports_total{namespace=\"example\",reason=\"conflict\"} 1"));
+        assert!(encoded.contains("plexi_storage_duration_seconds_count{operation=\"signature\"} 1"));
+    }
+}
@@ -0,0 +1,268 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use plexi_core::{storage::QueuedReport, Report, ReportResponse, Uuid};
+use tokio::sync::Notify;
+
+use crate::{ApiError, Auditor};
+
+/// How an auditor retries the reports it could not sign yet
+#[derive(Clone, Debug)]
+pub struct QueueConfig {
+    /// Attempts after which a report is dead-lettered
+    pub max_attempts: u32,
+    /// Delay between retries of the queued reports
+    pub retry_interval: Duration,
+    /// Reports of a namespace waiting to be retried, past which new reports are rejected
+    pub max_queued_reports: usize,
+    /// Time dead-lettered reports are kept for, after which they are removed
+    pub dead_letter_retention: Duration,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            retry_interval: Duration::from_secs(30),
+            max_queued_reports: 1000,
+            dead_letter_retention: Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ReportQueue {
+    config: QueueConfig,
+    signed: Notify,
+}
+
+impl ReportQueue {
+    pub(crate) fn new(config: QueueConfig) -> Self {
+        Self {
+            config,
+            signed: Notify::new(),
+        }
+    }
+}
+
+/// Outcome of [`Auditor::submit_report`]
+#[derive(Clone, Debug)]
+pub enum Submission {
+    /// The epoch is signed
+    Signed(ReportResponse),
+    /// The report could not be signed yet, and is retried in the background
+    Queued(ReportResponse),
+}
+
+fn is_retryable(error: &ApiError) -> bool {
+    matches!(error, ApiError::Unavailable(_) | ApiError::Internal(_))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_millis() as u64)
+}
+
+fn resubmission(queued: QueuedReport) -> Result<Submission, ApiError> {
+    if queued.is_dead_letter() {
+        return Err(ApiError::BadRequest(format!(
+            "report was rejected: {error}",
+            error = queued.error().unwrap_or_default()
+        )));
+    }
+    Ok(Submission::Queued(queued.report().clone()))
+}
+
+impl Auditor {
+    /// Queue `report` and try to sign it
+    pub async fn submit_report(&self, report: Report) -> Result<Submission, ApiError> {
+        let name = report.namespace().to_string();
+        let epoch = *report.epoch();
+        let digest = report.digest().to_vec();
+        if let Some(queued) = self.state().queued_report(&name, &epoch, &digest).await? {
+            return resubmission(queued);
+        }
+
+        let max = self.queue.config.max_queued_reports;
+        let queued = QueuedReport::new(ReportResponse::new(Uuid::new_v4(), report));
+        if !self.state().enqueue_report(&queued, max).await? {
+            return match self.state().queued_report(&name, &epoch, &digest).await? {
+                Some(queued) => resubmission(queued),
+                None => Err(ApiError::Unavailable(format!(
+                    "namespace {name} has {max} queued reports, retry once they are processed"
+                ))),
+            };
+        }
+        self.state().insert_report(queued.report()).await?;
+        self.attempt(queued).await
+    }
+
+    /// Retry the queued reports, and remove the expired dead letters. Returns how many were signed
+    pub async fn retry_reports(&self) -> anyhow::Result<usize> {
+        let retention = self.queue.config.dead_letter_retention.as_millis() as u64;
+        let mut signed = 0;
+        for queued in self.state().queued_reports().await? {
+            if queued.is_dead_letter() {
+                let expired = queued
+                    .dead_lettered_at()
+                    .is_some_and(|at| now().saturating_sub(at) >= retention);
+                if expired {
+                    self.state().remove_queued_report(&queued).await?;
+                }
+                continue;
+            }
+            if let Ok(Submission::Signed(_)) = self.attempt(queued).await {
+                signed += 1;
+            }
+        }
+        Ok(signed)
+    }
+
+    /// Retry the queued reports every [`QueueConfig::retry_interval`], and whenever an epoch is signed
+    pub async fn run_report_queue(&self) {
+        loop {
+            if let Err(e) = self.retry_reports().await {
+                tracing::error!("retrying queued reports: {e:#}");
+            }
+            tokio::select! {
+                _ = self.queue.signed.notified() => {}
+                _ = tokio::time::sleep(self.queue.config.retry_interval) => {}
+            }
+        }
+    }
+
+    async fn attempt(&self, mut queued: QueuedReport) -> Result<Submission, ApiError> {
+        let response = queued.report().clone();
+        match self.process_report(response.id(), &response.report()).await {
+            Ok(_) => {
+                self.state().remove_queued_report(&queued).await?;
+                self.queue.signed.notify_one();
+                Ok(Submission::Signed(response))
+            }
+            Err(e) => {
+                let error = e.to_string();
+                if is_retryable(&e) && queued.attempts() + 1 < self.queue.config.max_attempts {
+                    queued.record_failure(&error);
+                    tracing::info!(
+                        "queued {namespace} epoch {epoch}: {error}",
+                        namespace = queued.namespace(),
+                        epoch = queued.epoch()
+                    );
+                    self.state().insert_queued_report(&queued).await?;
+                    Ok(Submission::Queued(response))
+                } else {
+                    queued.dead_letter(&error, now());
+                    tracing::warn!(
+                        "dead-lettered {namespace} epoch {epoch} after {attempts} attempts: {error}",
+                        namespace = queued.namespace(),
+                        epoch = queued.epoch(),
+                        attempts = queued.attempts()
+                    );
+                    self.state().insert_queued_report(&queued).await?;
+                    Err(e)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auditor::tests::publish,
+        tests::{auditor, report, root},
+    };
+
+    const NAMESPACE: &str = "example.key-transparency.v1";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_report_queue() {
+        let root_directory = tempfile::TempDir::with_prefix("plexi-server-").unwrap();
+        let published = root_directory.path().join("published");
+        let directory = root_directory.path().join("directory");
+        let (first, second) = publish(&published).await;
+        let auditor = auditor(serde_json::json!([{
+        "name": NAMESPACE,
+        "ciphersuite": 0x0001,
+        "log_directory": reqwest::Url::from_file_path(&directory).unwrap().to_string(),
+        "root": root(1, first),
+        }]))
+        .await
+        .with_queue_config(QueueConfig {
+            max_attempts: 2,
+            retry_interval: Duration::from_millis(10),
+            max_queued_reports: 1,
+            dead_letter_retention: Duration::from_secs(60),
+        });
+
+        assert!(matches!(
+            auditor.submit_report(report(NAMESPACE, 1, first)).await,
+            Ok(Submission::Signed(_))
+        ));
+
+        // epoch 2 proof is not published yet
+        let Ok(Submission::Queued(queued)) =
+            auditor.submit_report(report(NAMESPACE, 2, second)).await
+        else {
+            panic!("epoch 2 is not queued");
+        };
+        let Ok(Submission::Queued(duplicate)) =
+            auditor.submit_report(report(NAMESPACE, 2, second)).await
+        else {
+            panic!("epoch 2 is not queued");
+        };
+        assert_eq!(duplicate.id(), queued.id());
+        assert_eq!(auditor.state().queued_reports().await.unwrap().len(), 1);
+        // the namespace queue is full
+        assert!(matches!(
+            auditor.submit_report(report(NAMESPACE, 3, [0; 32])).await,
+            Err(ApiError::Unavailable(_))
+        ));
+        assert_eq!(auditor.state().queued_reports().await.unwrap().len(), 1);
+
+        std::fs::rename(&published, &directory).unwrap();
+        assert_eq!(auditor.retry_reports().await.unwrap(), 1);
+        assert!(auditor
+            .state()
+            .signature(NAMESPACE, &plexi_core::Epoch::from(2))
+            .await
+            .unwrap()
+            .is_some());
+        assert!(auditor.state().queued_reports().await.unwrap().is_empty());
+
+        // conflicting reports are dead-lettered right away
+        assert!(matches!(
+            auditor.submit_report(report(NAMESPACE, 2, first)).await,
+            Err(ApiError::Conflict(_))
+        ));
+        assert!(matches!(
+            auditor.submit_report(report(NAMESPACE, 2, first)).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        // reports are dead-lettered once they fail max_attempts times
+        assert!(matches!(
+            auditor.submit_report(report(NAMESPACE, 3, [0; 32])).await,
+            Ok(Submission::Queued(_))
+        ));
+        assert_eq!(auditor.retry_reports().await.unwrap(), 0);
+        let queue = auditor.state().queued_reports().await.unwrap();
+        assert_eq!(queue.len(), 2);
+        assert!(queue.iter().all(QueuedReport::is_dead_letter));
+        assert_eq!(queue[1].attempts(), 2);
+
+        // dead letters are removed once past their retention
+        let mut expired = queue[0].clone();
+        expired.dead_letter("expired", 0);
+        auditor
+            .state()
+            .insert_queued_report(&expired)
+            .await
+            .unwrap();
+        assert_eq!(auditor.retry_reports().await.unwrap(), 0);
+        let queue = auditor.state().queued_reports().await.unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(u64::from(queue[0].epoch()), 3);
+    }
+}
@@ -0,0 +1,250 @@
+use std::sync::Arc;
+
+use plexi_core::{
+    auditor::{Configuration, ProofId},
+    namespaces::{Namespace, NamespaceInfo, NamespaceStatus, Namespaces},
+    storage::{MemoryStorage, QueuedReport, Storage},
+    Epoch, LastVerifiedEpoch, ReportResponse, SignatureResponse,
+};
+use uuid::Uuid;
+
+use crate::Metrics;
+
+/// State of an auditor: the namespaces it monitors, and the signatures it produced for them
+#[derive(Clone)]
+pub struct AuditorState {
+    configuration: Configuration,
+    storage: Arc<dyn Storage>,
+    metrics: Arc<Metrics>,
+}
+
+impl std::fmt::Debug for AuditorState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditorState")
+            .field("configuration", &self.configuration)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AuditorState {
+    pub fn new(configuration: Configuration, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            configuration,
+            storage,
+            metrics: Arc::new(Metrics::new()),
+        }
+    }
+
+    /// State held in memory, lost when the server stops
+    pub fn in_memory(configuration: Configuration) -> Self {
+        Self::new(configuration, Arc::new(MemoryStorage::new()))
+    }
+
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Start monitoring `namespace`
+    pub async fn add_namespace(&self, namespace: &Namespace) -> anyhow::Result<()> {
+        let status = match namespace.root() {
+            Some(_) => NamespaceStatus::Online,
+            None => NamespaceStatus::Initialization,
+        };
+        self.metrics
+            .time_storage(
+                "insert_namespace",
+                self.storage
+                    .insert_namespace(&NamespaceInfo::new(namespace, status)),
+            )
+            .await
+    }
+
+    pub async fn namespace(&self, namespace: &str) -> anyhow::Result<Option<NamespaceInfo>> {
+        let Some(mut info) = self
+            .metrics
+            .time_storage("namespace", self.storage.namespace(namespace))
+            .await?
+        else {
+            return Ok(None);
+        };
+        info.set_last_verified_epoch(
+            self.last_verified_epoch(namespace)
+                .await?
+                .map(|last_verified_epoch| last_verified_epoch.epoch()),
+        );
+        Ok(Some(info))
+    }
+
+    /// Set the root of a namespace waiting for it, which brings it online
+    pub async fn set_root(&self, namespace: &str, root: &str) -> anyhow::Result<()> {
+        if let Some(mut info) = self
+            .metrics
+            .time_storage("namespace", self.storage.namespace(namespace))
+            .await?
+        {
+            info.set_root(root);
+            info.set_status(NamespaceStatus::Online)?;
+            self.metrics
+                .time_storage("insert_namespace", self.storage.insert_namespace(&info))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Store `info`, replacing the namespace of the same name
+    pub async fn update_namespace(&self, info: &NamespaceInfo) -> anyhow::Result<()> {
+        self.metrics
+            .time_storage("insert_namespace", self.storage.insert_namespace(info))
+            .await
+    }
+
+    pub async fn namespaces(&self) -> anyhow::Result<Namespaces> {
+        let mut namespaces = Namespaces::new();
+        for info in self
+            .metrics
+            .time_storage("namespaces", self.storage.namespaces())
+            .await?
+        {
+            if let Some(info) = self.namespace(info.name()).await? {
+                namespaces.push(info);
+            }
+        }
+        Ok(namespaces)
+    }
+
+    pub async fn signature(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+    ) -> anyhow::Result<Option<SignatureResponse>> {
+        self.metrics
+            .time_storage("signature", self.storage.signature(namespace, epoch))
+            .await
+    }
+
+    pub async fn insert_signature(&self, signature: &SignatureResponse) -> anyhow::Result<()> {
+        self.metrics
+            .time_storage("insert_signature", self.storage.insert_signature(signature))
+            .await
+    }
+
+    pub async fn last_verified_epoch(
+        &self,
+        namespace: &str,
+    ) -> anyhow::Result<Option<LastVerifiedEpoch>> {
+        self.metrics
+            .time_storage(
+                "last_verified_epoch",
+                self.storage.last_verified_epoch(namespace),
+            )
+            .await
+    }
+
+    pub async fn set_last_verified_epoch(
+        &self,
+        namespace: &str,
+        last_verified_epoch: &LastVerifiedEpoch,
+    ) -> anyhow::Result<()> {
+        self.metrics
+            .time_storage(
+                "set_last_verified_epoch",
+                self.storage
+                    .set_last_verified_epoch(namespace, last_verified_epoch),
+            )
+            .await
+    }
+
+    /// Record a report submitted by a log, and return it with its identifier
+    pub async fn insert_report(&self, report: &ReportResponse) -> anyhow::Result<()> {
+        self.metrics
+            .time_storage("insert_report", self.storage.insert_report(report))
+            .await
+    }
+
+    pub async fn report(&self, id: &Uuid) -> anyhow::Result<Option<ReportResponse>> {
+        self.metrics
+            .time_storage("report", self.storage.report(id))
+            .await
+    }
+
+    /// Keep a verified proof, so it can be served to clients
+    pub async fn insert_proof(
+        &self,
+        namespace: &str,
+        blob: &ProofId,
+        proof: &[u8],
+    ) -> anyhow::Result<()> {
+        self.metrics
+            .time_storage(
+                "insert_proof",
+                self.storage.insert_proof(namespace, blob, proof),
+            )
+            .await
+    }
+
+    pub async fn queued_report(
+        &self,
+        namespace: &str,
+        epoch: &Epoch,
+        digest: &[u8],
+    ) -> anyhow::Result<Option<QueuedReport>> {
+        self.metrics
+            .time_storage(
+                "queued_report",
+                self.storage.queued_report(namespace, epoch, digest),
+            )
+            .await
+    }
+
+    pub async fn queued_reports(&self) -> anyhow::Result<Vec<QueuedReport>> {
+        self.metrics
+            .time_storage("queued_reports", self.storage.queued_reports())
+            .await
+    }
+
+    pub async fn insert_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+        self.metrics
+            .time_storage(
+                "insert_queued_report",
+                self.storage.insert_queued_report(report),
+            )
+            .await
+    }
+
+    pub async fn enqueue_report(
+        &self,
+        report: &QueuedReport,
+        max_pending: usize,
+    ) -> anyhow::Result<bool> {
+        self.metrics
+            .time_storage(
+                "enqueue_report",
+                self.storage.enqueue_report(report, max_pending),
+            )
+            .await
+    }
+
+    pub async fn remove_queued_report(&self, report: &QueuedReport) -> anyhow::Result<()> {
+        self.metrics
+            .time_storage(
+                "remove_queued_report",
+                self.storage.remove_queued_report(report),
+            )
+            .await
+    }
+
+    /// Check the storage can be reached
+    pub async fn ping(&self) -> anyhow::Result<()> {
+        self.metrics.time_storage("ping", self.storage.ping()).await
+    }
+
+    pub async fn proof(&self, namespace: &str, blob: &ProofId) -> anyhow::Result<Option<Vec<u8>>> {
+        self.metrics
+            .time_storage("proof", self.storage.proof(namespace, blob))
+            .await
+    }
+}
@@ -0,0 +1,212 @@
+use std::{collections::HashMap, sync::RwLock, time::Duration};
+
+use plexi_core::{
+    checkpoint::{Checkpoint, Cosignature},
+    witness::Witness,
+    Epoch,
+};
+
+use crate::Auditor;
+
+const LOCK_POISONED: &str = "cosignatures lock is poisoned";
+
+#[derive(Debug, Default)]
+pub(crate) struct Witnessing {
+    key_name: String,
+    witnesses: Vec<Witness>,
+    cosignatures: RwLock<HashMap<(String, u64), Vec<Cosignature>>>,
+    sizes: RwLock<HashMap<(String, String), u64>>,
+}
+
+impl Witnessing {
+    pub(crate) fn new(key_name: &str, witnesses: Vec<Witness>) -> Self {
+        Self {
+            key_name: key_name.to_string(),
+            witnesses,
+            ..Default::default()
+        }
+    }
+}
+
+impl Auditor {
+    /// Submit the heads of the auditor to `witnesses`, as checkpoints signed under `key_name`
+    pub fn with_witnesses(mut self, key_name: &str, witnesses: Vec<Witness>) -> Self {
+        self.witnessing = Witnessing::new(key_name, witnesses);
+        self
+    }
+
+    /// Cosignatures witnesses returned for `epoch` of `namespace`
+    pub fn cosignatures(&self, namespace: &str, epoch: &Epoch) -> Vec<Cosignature> {
+        self.witnessing
+            .cosignatures
+            .read()
+            .expect(LOCK_POISONED)
+            .get(&(namespace.to_string(), epoch.into()))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Submit the head of every namespace to the witnesses once
+    pub async fn witness(&self) -> anyhow::Result<Vec<Cosignature>> {
+        if self.witnessing.witnesses.is_empty() {
+            return Ok(vec![]);
+        }
+        let namespaces: Vec<String> = self
+            .state()
+            .namespaces()
+            .await?
+            .into_iter()
+            .map(|info| info.name().to_string())
+            .collect();
+
+        let mut cosignatures = vec![];
+        for head in self.heads(namespaces.iter().map(String::as_str)).await? {
+            let checkpoint = Checkpoint::from_signature(&head);
+            let note = checkpoint
+                .sign(&self.witnessing.key_name, self.signer.as_ref())
+                .await?;
+            let epoch = u64::from(checkpoint.epoch());
+            let witnessed = self.cosignatures(checkpoint.origin(), &checkpoint.epoch());
+            for witness in &self.witnessing.witnesses {
+                if witnessed
+                    .iter()
+                    .any(|cosignature| cosignature.witness() == witness.name())
+                {
+                    continue;
+                }
+                let size_key = (checkpoint.origin().to_string(), witness.name().to_string());
+                let old_size = self
+                    .witnessing
+                    .sizes
+                    .read()
+                    .expect(LOCK_POISONED)
+                    .get(&size_key)
+                    .copied()
+                    .unwrap_or_default();
+                let cosignature = match witness.add_checkpoint(&checkpoint, &note, old_size).await {
+                    Ok(cosignature) => cosignature,
+                    Err(e) => {
+                        tracing::warn!(
+                            "submitting {origin} epoch {epoch} to {name}: {e:#}",
+                            origin = checkpoint.origin(),
+                            name = witness.name()
+                        );
+                        continue;
+                    }
+                };
+                self.witnessing
+                    .sizes
+                    .write()
+                    .expect(LOCK_POISONED)
+                    .insert(size_key, epoch);
+                self.witnessing
+                    .cosignatures
+                    .write()
+                    .expect(LOCK_POISONED)
+                    .entry((checkpoint.origin().to_string(), epoch))
+                    .or_default()
+                    .push(cosignature.clone());
+                cosignatures.push(cosignature);
+            }
+        }
+        Ok(cosignatures)
+    }
+
+    /// Submit the heads to the witnesses every `interval`
+    pub async fn run_witnessing(&self, interval: Duration) {
+        loop {
+            if let Err(e) = self.witness().await {
+                tracing::error!("submitting checkpoints to witnesses: {e:#}");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::IntoFuture as _, sync::Arc};
+
+    use axum::{extract::State, http::StatusCode, routing::post, Router};
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+    use plexi_core::{
+        checkpoint::{cosignature_key_hash, cosigned_message},
+        signer::{Ed25519Signer, Signer as _},
+    };
+
+    use super::*;
+    use crate::tests::{auditor_with_key, report, root, SIGNING_KEY};
+
+    const NAMESPACE: &str = "example.key-transparency.v1";
+    const KEY_NAME: &str = "auditor.example.com";
+    const WITNESS_NAME: &str = "witness.example.com";
+    const WITNESS_SIGNING_KEY: &str =
+        "0202020202020202020202020202020202020202020202020202020202020202";
+    const TIMESTAMP: u64 = 1717084640;
+
+    async fn add_checkpoint(
+        State(signer): State<Arc<Ed25519Signer>>,
+        body: String,
+    ) -> (StatusCode, String) {
+        let Some(("old 0", note)) = body.split_once("\n\n") else {
+            return (StatusCode::CONFLICT, "0\n".to_string());
+        };
+        let verifying_key = Ed25519Signer::from_hex(SIGNING_KEY)
+            .unwrap()
+            .verifying_key();
+        let Ok(checkpoint) = Checkpoint::open(note, KEY_NAME, &verifying_key) else {
+            return (StatusCode::FORBIDDEN, String::new());
+        };
+        let mut cosignature = cosignature_key_hash(WITNESS_NAME, &signer.verifying_key()).to_vec();
+        cosignature.extend(TIMESTAMP.to_be_bytes());
+        cosignature.extend(
+            signer
+                .sign(cosigned_message(&checkpoint, TIMESTAMP).as_bytes())
+                .await
+                .unwrap(),
+        );
+        (
+            StatusCode::OK,
+            format!(
+                "\u{2014} {WITNESS_NAME} {signature}\n",
+                signature = BASE64.encode(cosignature)
+            ),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_witness() {
+        let signer = Arc::new(Ed25519Signer::from_hex(WITNESS_SIGNING_KEY).unwrap());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{addr}/", addr = listener.local_addr().unwrap());
+        let witness_router = Router::new()
+            .route("/add-checkpoint", post(add_checkpoint))
+            .with_state(signer.clone());
+        tokio::spawn(axum::serve(listener, witness_router).into_future());
+
+        let witness = Witness::new(WITNESS_NAME, url.parse().unwrap(), signer.verifying_key());
+        let namespaces = serde_json::json!([
+            { "name": NAMESPACE, "ciphersuite": 0x0001, "root": root(1, [0xaa; 32]) }
+        ]);
+        let auditor = auditor_with_key(SIGNING_KEY, namespaces)
+            .await
+            .with_witnesses(KEY_NAME, vec![witness]);
+        auditor
+            .submit_report(report(NAMESPACE, 1, [0xaa; 32]))
+            .await
+            .unwrap();
+
+        let cosignatures = auditor.witness().await.unwrap();
+        assert_eq!(cosignatures.len(), 1);
+        assert_eq!(cosignatures[0].witness(), WITNESS_NAME);
+        assert_eq!(cosignatures[0].timestamp(), TIMESTAMP);
+        assert_eq!(
+            auditor.cosignatures(NAMESPACE, &Epoch::from(1)),
+            cosignatures
+        );
+
+        // heads are only submitted until they are cosigned
+        assert!(auditor.witness().await.unwrap().is_empty());
+        assert!(auditor.cosignatures(NAMESPACE, &Epoch::from(2)).is_empty());
+    }
+}